@@ -0,0 +1,98 @@
+//! Smoothing a shape again and again, as the animation and the previews do every frame,
+//! with new buffers for each step and with buffers reused through a [`PointPool`].
+//!
+//! Besides the timings, the allocations each run makes are counted and printed first, since
+//! the pool is there to spare those.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chaikin::algorithm::{ChaikinAlgorithm, FourPointScheme, LaneRiesenfeld, PointPool, Smoother};
+use chaikin::types::Point;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+/// The step smoothed to, about as far as the window animates by default
+const STEP: usize = 7;
+
+/// The system allocator, counting the allocations made through it
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// A star of 40 points, a shape of the size drawn by hand
+fn star() -> Vec<Point> {
+    (0..40)
+        .map(|i| {
+            let angle = i as f32 / 40.0 * std::f32::consts::TAU;
+            let radius = if i % 2 == 0 { 200.0 } else { 80.0 };
+            Point::new(300.0 + radius * angle.cos(), 300.0 + radius * angle.sin())
+        })
+        .collect()
+}
+
+/// The schemes compared, by name
+fn schemes() -> Vec<(&'static str, Box<dyn Smoother>)> {
+    vec![
+        ("chaikin", Box::new(ChaikinAlgorithm::new())),
+        ("lr4", Box::new(LaneRiesenfeld::new(4))),
+        ("fourpoint", Box::new(FourPointScheme)),
+    ]
+}
+
+/// How many allocations smoothing the points makes, once the pool has warmed up
+fn allocations(run: impl FnMut()) -> usize {
+    let mut run = run;
+    run();
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    run();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn bench_pool(c: &mut Criterion) {
+    let points = star();
+    for (name, smoother) in schemes() {
+        let mut pool = PointPool::default();
+        let fresh = allocations(|| drop(black_box(smoother.get_step_points(&points, STEP))));
+        let pooled = allocations(|| {
+            let curve = smoother.get_step_points_pooled(&points, STEP, &mut pool);
+            pool.give(black_box(curve));
+        });
+        println!("{}: {} allocations per run with new buffers, {} with the pool", name, fresh, pooled);
+
+        let mut group = c.benchmark_group(name);
+        group.bench_function("new buffers", |b| {
+            b.iter(|| smoother.get_step_points(black_box(&points), STEP));
+        });
+        group.bench_function("pooled", |b| {
+            let mut pool = PointPool::default();
+            b.iter(|| {
+                let curve = smoother.get_step_points_pooled(black_box(&points), STEP, &mut pool);
+                pool.give(black_box(curve));
+            });
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_pool);
+criterion_main!(benches);
@@ -0,0 +1,171 @@
+//! Renders the application icon at build time.
+//!
+//! The icon is drawn procedurally so that there is a single source of truth for it. Two
+//! artifacts are written to `OUT_DIR`:
+//! - `icon.argb`: raw 32-bit ARGB pixels, embedded in the binary and handed to the window
+//!   at runtime (X11 reads them directly, Windows gets a `.ico` made from them)
+//! - `icon.ico` + `icon.rc`: compiled into the executable as a resource on Windows, so that
+//!   Explorer and the taskbar show the icon for the `.exe` itself
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Icon width and height, in pixels
+const ICON_SIZE: usize = 32;
+/// Background of the rounded tile, matching the window's dark canvas
+const BG_COLOR: u32 = 0xFF1E1E1E;
+/// Control polygon color, a dimmed grey
+const CAGE_COLOR: u32 = 0xFF777777;
+/// Same blue-green mix as the curve drawn in the window
+const CURVE_COLOR: u32 = 0xFF55CCAA;
+/// Same shade of red as the points drawn in the window
+const POINT_COLOR: u32 = 0xFFFF5555;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    let out_dir = Path::new(&out_dir);
+    let pixels = render_icon();
+
+    let argb: Vec<u8> = pixels.iter().flat_map(|p| p.to_le_bytes()).collect();
+    fs::write(out_dir.join("icon.argb"), argb).expect("Failed to write icon.argb");
+
+    let ico_path = out_dir.join("icon.ico");
+    fs::write(&ico_path, encode_ico(&pixels)).expect("Failed to write icon.ico");
+
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows") {
+        let rc_path = out_dir.join("icon.rc");
+        let ico = ico_path.display().to_string().replace('\\', "\\\\");
+        fs::write(&rc_path, format!("1 ICON \"{}\"\n", ico)).expect("Failed to write icon.rc");
+        embed_resource::compile(&rc_path, embed_resource::NONE);
+    }
+}
+
+/// Draws a small Chaikin "logo": a control polygon, the smooth curve it converges to,
+/// and the control points on top, all inside a rounded dark tile
+fn render_icon() -> Vec<u32> {
+    let cage = [(5.0, 25.0), (11.0, 6.0), (21.0, 26.0), (27.0, 7.0)];
+    let mut pixels = vec![0u32; ICON_SIZE * ICON_SIZE];
+
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let tile = rounded_rect_coverage(px, py, ICON_SIZE as f32, 6.0);
+            let mut color = blend(0, BG_COLOR, tile);
+
+            let cage_distance = cage
+                .windows(2)
+                .map(|s| segment_distance(px, py, s[0], s[1]))
+                .fold(f32::MAX, f32::min);
+            color = blend(color, CAGE_COLOR, coverage(cage_distance, 0.5) * 0.8);
+
+            let curve_distance = limit_curve(&cage)
+                .windows(2)
+                .map(|s| segment_distance(px, py, s[0], s[1]))
+                .fold(f32::MAX, f32::min);
+            color = blend(color, CURVE_COLOR, coverage(curve_distance, 1.2));
+
+            for &(cx, cy) in &cage {
+                let distance = ((px - cx).powi(2) + (py - cy).powi(2)).sqrt();
+                color = blend(color, POINT_COLOR, coverage(distance, 2.2));
+            }
+
+            pixels[y * ICON_SIZE + x] = color;
+        }
+    }
+
+    pixels
+}
+
+/// Samples the quadratic B-spline that Chaikin's algorithm converges to
+fn limit_curve(cage: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut samples = vec![cage[0]];
+    for i in 0..cage.len() - 2 {
+        let start = if i == 0 { cage[0] } else { midpoint(cage[i], cage[i + 1]) };
+        let end = if i == cage.len() - 3 { cage[i + 2] } else { midpoint(cage[i + 1], cage[i + 2]) };
+        for step in 1..=16 {
+            let t = step as f32 / 16.0;
+            let u = 1.0 - t;
+            samples.push((
+                u * u * start.0 + 2.0 * u * t * cage[i + 1].0 + t * t * end.0,
+                u * u * start.1 + 2.0 * u * t * cage[i + 1].1 + t * t * end.1,
+            ));
+        }
+    }
+    samples
+}
+
+fn midpoint(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+fn segment_distance(px: f32, py: f32, a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        (((px - a.0) * dx + (py - a.1) * dy) / length_squared).clamp(0.0, 1.0)
+    };
+    ((px - a.0 - t * dx).powi(2) + (py - a.1 - t * dy).powi(2)).sqrt()
+}
+
+/// Antialiased coverage of a shape whose edge lies at `radius` from the sampled pixel
+fn coverage(distance: f32, radius: f32) -> f32 {
+    (radius + 0.5 - distance).clamp(0.0, 1.0)
+}
+
+fn rounded_rect_coverage(px: f32, py: f32, size: f32, corner: f32) -> f32 {
+    let cx = px.clamp(corner, size - corner);
+    let cy = py.clamp(corner, size - corner);
+    let distance = ((px - cx).powi(2) + (py - cy).powi(2)).sqrt();
+    coverage(distance, corner - 0.5)
+}
+
+/// Blends `color` over `bg` (both ARGB) with the given opacity
+fn blend(bg: u32, color: u32, alpha: f32) -> u32 {
+    let channel = |shift: u32| {
+        let a = ((color >> shift) & 0xFF) as f32;
+        let b = ((bg >> shift) & 0xFF) as f32;
+        ((a * alpha + b * (1.0 - alpha)).round() as u32) << shift
+    };
+    channel(24) | channel(16) | channel(8) | channel(0)
+}
+
+/// Wraps the pixels into a single-image `.ico` file holding a 32-bit BMP
+fn encode_ico(pixels: &[u32]) -> Vec<u8> {
+    let size = ICON_SIZE as u32;
+    let mask_row_bytes = size.div_ceil(32) * 4;
+    let image_bytes = 40 + size * size * 4 + mask_row_bytes * size;
+
+    let mut ico = Vec::new();
+    // ICONDIR: reserved, type (1 = icon), image count
+    ico.extend_from_slice(&[0, 0, 1, 0, 1, 0]);
+    // ICONDIRENTRY
+    ico.extend_from_slice(&[size as u8, size as u8, 0, 0]);
+    ico.extend_from_slice(&1u16.to_le_bytes());
+    ico.extend_from_slice(&32u16.to_le_bytes());
+    ico.extend_from_slice(&image_bytes.to_le_bytes());
+    ico.extend_from_slice(&22u32.to_le_bytes());
+
+    // BITMAPINFOHEADER; the height covers both the color and the mask bitmaps
+    ico.extend_from_slice(&40u32.to_le_bytes());
+    ico.extend_from_slice(&size.to_le_bytes());
+    ico.extend_from_slice(&(size * 2).to_le_bytes());
+    ico.extend_from_slice(&1u16.to_le_bytes());
+    ico.extend_from_slice(&32u16.to_le_bytes());
+    ico.extend_from_slice(&[0; 24]);
+
+    // Pixel rows are stored bottom-up as BGRA, which is ARGB in little endian
+    for row in pixels.chunks(ICON_SIZE).rev() {
+        for pixel in row {
+            ico.extend_from_slice(&pixel.to_le_bytes());
+        }
+    }
+    // The alpha channel is used, so the AND mask is left empty
+    ico.extend(std::iter::repeat_n(0, (mask_row_bytes * size) as usize));
+
+    ico
+}
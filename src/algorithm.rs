@@ -0,0 +1,726 @@
+//! Subdivision schemes that smooth polylines.
+//!
+//! # Determinism
+//!
+//! Every new point is computed from its neighbours with a fixed expression, evaluated left
+//! to right in single precision. There are no fused multiply-adds, which Rust never
+//! introduces on its own, and no sums whose order depends on threads or on the length of
+//! the input. The same points therefore give bit-identical curves on every run and every
+//! platform with IEEE 754 floats, whether a step is computed on the UI thread or on a
+//! worker, so curves can be compared in golden tests or cached by hash. The tests pin the
+//! bits of a few curves: a change to the math that changes them has to be deliberate.
+//!
+//! With the `fixed-point` feature, Chaikin's scheme can also be computed in fixed point,
+//! which doesn't depend on floating point hardware at all, see [`FixedChaikin`].
+
+use nalgebra::Point2;
+use crate::geometry;
+use crate::types::Point;
+use std::fmt;
+use std::str::FromStr;
+
+mod bezier;
+mod bspline;
+mod catmull_rom;
+#[cfg(feature = "fixed-point")]
+mod fixed;
+mod four_point;
+mod lane_riesenfeld;
+mod pool;
+mod resample;
+mod step_cache;
+
+pub use bezier::Bezier;
+pub use bspline::{chaikin_limit, uniform_bspline, LIMIT_SAMPLES};
+pub use catmull_rom::{CatmullRom, Parameterization};
+#[cfg(feature = "fixed-point")]
+pub use fixed::{Fixed, FixedChaikin};
+pub use four_point::FourPointScheme;
+pub use lane_riesenfeld::{CubicBSpline, LaneRiesenfeld, MAX_DEGREE};
+pub use pool::PointPool;
+pub use resample::resample_by_arclength;
+pub use step_cache::StepCache;
+
+/// A subdivision scheme that refines a polyline, one step at a time
+pub trait Smoother: Send + Sync {
+    /// Does one round of smoothing, returning the refined points. Each point should be
+    /// computed in a fixed order, see the module documentation
+    fn calculate_step(&self, points: &[Point]) -> Vec<Point>;
+
+    /// Like [`Self::calculate_step`], writing the refined points into the given buffer
+    /// instead, after clearing it. Schemes that override this reuse the buffer's memory,
+    /// which spares an allocation per step when the buffers come from a [`PointPool`]
+    fn calculate_step_into(&self, points: &[Point], out: &mut Vec<Point>) {
+        *out = self.calculate_step(points);
+    }
+
+    /// The fewest points the scheme refines; fewer are left as they are, and aren't worth
+    /// animating
+    fn min_points(&self) -> usize {
+        2
+    }
+
+    /// The same scheme for closed shapes, where the last point connects back to the first,
+    /// or for open ones. None if the scheme only supports open polylines
+    fn with_closed(&self, _closed: bool) -> Option<Box<dyn Smoother>> {
+        None
+    }
+
+    /// How each new point is computed from the points of the step before, one formula per
+    /// line, e.g. to show them while teaching. Subscripts follow an underscore, in braces if
+    /// they are longer than a character, as in `P_{i+1}`. Empty if the scheme doesn't say
+    fn formulas(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The point of the curve at the step closest to the query point, with the segment it lies
+    /// on and how far along it, e.g. to snap to the curve. With `closed`, the curve goes
+    /// back from its last point to its first, and the scheme should be the one for closed
+    /// shapes, see [`Self::with_closed`]. None if there are no points
+    fn nearest_point(&self, initial_points: &[Point], step: usize, closed: bool, query: Point) -> Option<geometry::Projection> {
+        geometry::nearest_on_polyline(&self.get_step_points(initial_points, step), query, closed)
+    }
+
+    /// The curve the steps converge to, sampled with `samples` points along each of its
+    /// spans, e.g. [`LIMIT_SAMPLES`], to show where the steps are heading. None if the scheme
+    /// doesn't know it
+    fn limit_curve(&self, _initial_points: &[Point], _samples: usize) -> Option<Vec<Point>> {
+        None
+    }
+
+    /// Smooth the curve over several rounds
+    ///
+    /// Input:
+    /// - A list of points (the original shape)
+    /// - Number of smoothing steps to apply
+    ///
+    /// Output:
+    /// - The final smoothed points after the steps
+    fn get_step_points(&self, initial_points: &[Point], step: usize) -> Vec<Point> {
+        self.get_step_points_pooled(initial_points, step, &mut PointPool::default())
+    }
+
+    /// Like [`Self::get_step_points`], with the buffers of the steps taken from the pool.
+    /// The steps go back and forth between two buffers, and the one that isn't returned
+    /// goes back into the pool
+    fn get_step_points_pooled(&self, initial_points: &[Point], step: usize, pool: &mut PointPool) -> Vec<Point> {
+        let largest = pool.take();
+
+        // If step is 0 or not enough points, just return the original points
+        if step == 0 || initial_points.len() <= 2 {
+            let mut points = largest;
+            points.extend_from_slice(initial_points);
+            return points;
+        }
+
+        // The last step goes into the largest buffer, which needs the least growing: the
+        // buffers swap at each step, so that is the first one after an even number of steps
+        let other = pool.take();
+        let (mut current_points, mut next_points) = if step.is_multiple_of(2) { (largest, other) } else { (other, largest) };
+        current_points.extend_from_slice(initial_points);
+        for _ in 0..step {
+            // Smooth one step at a time
+            self.calculate_step_into(&current_points, &mut next_points);
+            std::mem::swap(&mut current_points, &mut next_points);
+        }
+        pool.give(next_points);
+
+        current_points // Return the final smoothed points
+    }
+}
+
+/// The points at each step of smoothing, starting with the points themselves at step 0.
+/// The steps never end, so the caller takes as many as it needs, e.g. with
+/// [`Iterator::nth`] or [`Iterator::take`]
+pub struct Steps<'a, S: Smoother + ?Sized> {
+    smoother: &'a S,
+    /// The points of the next step
+    next: Vec<Point>,
+}
+
+impl<'a, S: Smoother + ?Sized> Steps<'a, S> {
+    pub fn new(smoother: &'a S, points: &[Point]) -> Self {
+        Self { smoother, next: points.to_vec() }
+    }
+}
+
+impl<S: Smoother + ?Sized> Iterator for Steps<'_, S> {
+    type Item = Vec<Point>;
+
+    fn next(&mut self) -> Option<Vec<Point>> {
+        let refined = self.smoother.calculate_step(&self.next);
+        Some(std::mem::replace(&mut self.next, refined))
+    }
+}
+
+/// The available subdivision schemes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scheme {
+    /// Chaikin's corner cutting, converging to a quadratic B-spline
+    Chaikin,
+    /// Chaikin's corner cutting in 16.16 fixed point
+    #[cfg(feature = "fixed-point")]
+    ChaikinFixed,
+    /// Lane-Riesenfeld subdivision of the degree, from 3 to [`MAX_DEGREE`], converging to a
+    /// B-spline of that degree. Degree 2 is Chaikin's scheme
+    LaneRiesenfeld(usize),
+    /// The interpolating four-point scheme of Dyn, Levin and Gregory
+    FourPoint,
+    /// Centripetal Catmull-Rom splines, interpolating like the four-point scheme
+    CatmullRom,
+}
+
+impl Scheme {
+    /// Builds the smoother for this scheme. The ratios are only used by Chaikin's scheme,
+    /// the others have fixed weights
+    pub fn smoother(self, ratios: (f32, f32)) -> Box<dyn Smoother> {
+        match self {
+            Scheme::Chaikin => Box::new(ChaikinAlgorithm::with_ratios(ratios.0, ratios.1)),
+            #[cfg(feature = "fixed-point")]
+            Scheme::ChaikinFixed => Box::new(FixedChaikin::with_ratios(ratios.0, ratios.1)),
+            Scheme::LaneRiesenfeld(3) => Box::new(CubicBSpline),
+            Scheme::LaneRiesenfeld(degree) => Box::new(LaneRiesenfeld::new(degree)),
+            Scheme::FourPoint => Box::new(FourPointScheme),
+            Scheme::CatmullRom => Box::new(CatmullRom::new()),
+        }
+    }
+
+    /// Whether the scheme is built with the ratios, which only Chaikin's scheme is
+    pub fn uses_ratios(self) -> bool {
+        match self {
+            Scheme::Chaikin => true,
+            #[cfg(feature = "fixed-point")]
+            Scheme::ChaikinFixed => true,
+            Scheme::LaneRiesenfeld(_) | Scheme::FourPoint | Scheme::CatmullRom => false,
+        }
+    }
+
+    /// The degree of the B-spline the scheme converges to, if it is of the Lane-Riesenfeld
+    /// family, as Chaikin's scheme is with degree 2
+    pub fn degree(self) -> Option<usize> {
+        match self {
+            Scheme::Chaikin => Some(2),
+            #[cfg(feature = "fixed-point")]
+            Scheme::ChaikinFixed => Some(2),
+            Scheme::LaneRiesenfeld(degree) => Some(degree),
+            Scheme::FourPoint | Scheme::CatmullRom => None,
+        }
+    }
+
+    /// The scheme of the Lane-Riesenfeld family one degree higher, going back to Chaikin's
+    /// after [`MAX_DEGREE`]. Schemes of other families go to Chaikin's, where the degrees
+    /// start
+    pub fn with_next_degree(self) -> Scheme {
+        match self.degree() {
+            Some(degree) if degree < MAX_DEGREE => Scheme::LaneRiesenfeld(degree + 1),
+            _ => Scheme::Chaikin,
+        }
+    }
+
+    /// The name of the scheme, to show to the user
+    pub fn title(self) -> &'static str {
+        match self {
+            Scheme::Chaikin => "Chaikin",
+            #[cfg(feature = "fixed-point")]
+            Scheme::ChaikinFixed => "Chaikin (fixed point)",
+            Scheme::LaneRiesenfeld(3) => "Cubic B-spline",
+            Scheme::LaneRiesenfeld(4) => "Quartic B-spline",
+            Scheme::LaneRiesenfeld(5) => "Quintic B-spline",
+            Scheme::LaneRiesenfeld(6) => "Sextic B-spline",
+            Scheme::LaneRiesenfeld(7) => "Septic B-spline",
+            Scheme::LaneRiesenfeld(_) => "Lane-Riesenfeld",
+            Scheme::FourPoint => "Four-point",
+            Scheme::CatmullRom => "Catmull-Rom",
+        }
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chaikin" => Ok(Scheme::Chaikin),
+            #[cfg(feature = "fixed-point")]
+            "chaikin-fixed" => Ok(Scheme::ChaikinFixed),
+            "fourpoint" => Ok(Scheme::FourPoint),
+            "catmull-rom" => Ok(Scheme::CatmullRom),
+            _ => match s.strip_prefix("lr").and_then(|degree| degree.parse().ok()) {
+                Some(degree) if (3..=MAX_DEGREE).contains(&degree) => Ok(Scheme::LaneRiesenfeld(degree)),
+                _ => {
+                    #[cfg(feature = "fixed-point")]
+                    let chaikin = "chaikin, chaikin-fixed";
+                    #[cfg(not(feature = "fixed-point"))]
+                    let chaikin = "chaikin";
+                    Err(format!(
+                        "unknown scheme {:?}, expected {}, lr3 to lr{}, fourpoint or catmull-rom",
+                        s, chaikin, MAX_DEGREE
+                    ))
+                }
+            },
+        }
+    }
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Scheme::Chaikin => f.write_str("chaikin"),
+            #[cfg(feature = "fixed-point")]
+            Scheme::ChaikinFixed => f.write_str("chaikin-fixed"),
+            Scheme::LaneRiesenfeld(degree) => write!(f, "lr{}", degree),
+            Scheme::FourPoint => f.write_str("fourpoint"),
+            Scheme::CatmullRom => f.write_str("catmull-rom"),
+        }
+    }
+}
+
+/// The most steps [`ChaikinAlgorithm::calculate_adaptive`] takes, however sharp the corners
+/// still are, e.g. with no tolerance
+pub const MAX_ADAPTIVE_STEPS: usize = 12;
+
+/// Smooths out a series of points to create a nice curve
+pub struct ChaikinAlgorithm {
+    /// First point ratio (how far the new point is along the line)
+    q_ratio: f32,
+    /// Second point ratio (how far the other new point is along the line)
+    r_ratio: f32,
+    /// Whether the last point connects back to the first, making a closed shape
+    closed: bool,
+    /// How far from flat a corner has to be for its steps to cut it, see [`Self::adaptive`]
+    tolerance: f32,
+}
+
+impl ChaikinAlgorithm {
+    /// Creates a new smoothing tool with standard settings
+    pub fn new() -> Self {
+        Self {
+            q_ratio: 0.25, // Place first point 25% along each line segment
+            r_ratio: 0.75, // Place second point 75% along each line segment
+            closed: false,
+            tolerance: 0.0,
+        }
+    }
+
+    /// Creates a smoothing tool that places the new points at the given ratios along each
+    /// line segment, instead of the standard 25% and 75%
+    pub fn with_ratios(q_ratio: f32, r_ratio: f32) -> Self {
+        Self { q_ratio, r_ratio, closed: false, tolerance: 0.0 }
+    }
+
+    /// Whether the new points can be placed at the ratios: in order, and strictly between
+    /// the ends of the segments, so that each step cuts every corner
+    pub fn valid_ratios(q_ratio: f32, r_ratio: f32) -> bool {
+        0.0 < q_ratio && q_ratio < r_ratio && r_ratio < 1.0
+    }
+
+    /// Makes the last point connect back to the first, so that its corner is cut like the
+    /// others and the curve is a closed loop, e.g. to smooth a star into a blob
+    pub fn closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+
+    /// Makes each step only cut the corners that are at least the tolerance away from the
+    /// segment that would replace them, leaving the flatter ones as they are, so that the
+    /// curve has far fewer points where it is already almost straight. With no tolerance,
+    /// every corner is cut as usual
+    pub fn adaptive(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Subdivides the points adaptively, see [`Self::adaptive`], until no corner is as far
+    /// from flat as the tolerance, or after [`MAX_ADAPTIVE_STEPS`] steps
+    pub fn calculate_adaptive(&self, points: &[Point], tolerance: f32) -> Vec<Point> {
+        let (mut points, mut next) = (points.to_vec(), Vec::new());
+        for _ in 0..MAX_ADAPTIVE_STEPS {
+            if !self.adaptive_step_into(&points, tolerance, &mut next) {
+                break;
+            }
+            std::mem::swap(&mut points, &mut next);
+        }
+        points
+    }
+
+    /// The new points of a step on the segment from p0 to p1: the first closer to p0
+    fn cut(&self, p0: Point, p1: Point) -> (Point, Point) {
+        let q = Point2::new(
+            (1.0 - self.q_ratio) * p0.x + self.q_ratio * p1.x,
+            (1.0 - self.q_ratio) * p0.y + self.q_ratio * p1.y,
+        );
+        let r = Point2::new(
+            (1.0 - self.r_ratio) * p0.x + self.r_ratio * p1.x,
+            (1.0 - self.r_ratio) * p0.y + self.r_ratio * p1.y,
+        );
+        (q, r)
+    }
+
+    /// Does one step, only cutting the corners at least the tolerance away from flat,
+    /// returning whether any was cut. Each segment gives the new point at its start if its
+    /// first corner is cut, or else that corner itself, then the new point at its end if
+    /// its second corner is cut. The ends of an open polyline are kept, and the segments to
+    /// them split where their other corner is cut, as in a uniform step
+    fn adaptive_step_into(&self, points: &[Point], tolerance: f32, new_points: &mut Vec<Point>) -> bool {
+        new_points.clear();
+        let n = points.len();
+        if n <= 2 {
+            new_points.extend_from_slice(points);
+            return false;
+        }
+
+        let cuts: Vec<(Point, Point)> = (0..n).map(|i| self.cut(points[i], points[(i + 1) % n])).collect();
+        let mut sharp: Vec<bool> = (0..n)
+            .map(|i| {
+                let (before, after) = (cuts[(i + n - 1) % n].1, cuts[i].0);
+                geometry::distance_to_segment(points[i], before, after) >= tolerance
+            })
+            .collect();
+        if !self.closed {
+            sharp[0] = sharp[1];
+            sharp[n - 1] = sharp[n - 2];
+        }
+
+        let segments = if self.closed { n } else { n - 1 };
+        for i in 0..segments {
+            let (q, r) = cuts[i];
+            if !self.closed && i == 0 {
+                new_points.push(points[0]);
+                if sharp[0] {
+                    new_points.push(q);
+                }
+            } else {
+                new_points.push(if sharp[i] { q } else { points[i] });
+            }
+            if sharp[(i + 1) % n] {
+                new_points.push(r);
+            }
+        }
+        if !self.closed {
+            new_points.push(points[n - 1]);
+        }
+        sharp.contains(&true)
+    }
+}
+
+impl Default for ChaikinAlgorithm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Smoother for ChaikinAlgorithm {
+    /// Does one round of smoothing to make the curve nicer
+    ///
+    /// Input:
+    /// - A list of points (the original shape)
+    ///
+    /// Output:
+    /// - A new list of points (a smoother shape)
+    ///
+    /// Special cases:
+    /// - No points: returns an empty list
+    /// - One or two points: no changes, just return them
+    fn calculate_step(&self, points: &[Point]) -> Vec<Point> {
+        let mut new_points = Vec::with_capacity(points.len() * 2);
+        self.calculate_step_into(points, &mut new_points);
+        new_points
+    }
+
+    fn calculate_step_into(&self, points: &[Point], new_points: &mut Vec<Point>) {
+        if self.tolerance > 0.0 {
+            self.adaptive_step_into(points, self.tolerance, new_points);
+            return;
+        }
+        new_points.clear();
+        match points.len() {
+            0 => return, // If no points, return an empty list
+            1 | 2 => return new_points.extend_from_slice(points), // If one or two points, no smoothing needed
+            _ => {} // If more than two points, start smoothing
+        }
+        new_points.reserve(points.len() * 2);
+
+        // A closed shape has one more segment, from the last point back to the first, and
+        // no ends to keep
+        let segments = if self.closed { points.len() } else { points.len() - 1 };
+
+        // Keep the first point as is
+        if !self.closed {
+            new_points.push(points[0]);
+        }
+
+        // Go through every pair of points and smooth the curve
+        for i in 0..segments {
+            let p0 = points[i];
+            let p1 = points[(i + 1) % points.len()];
+
+            // Find the new points, the first closer to the first point
+            let (q, r) = self.cut(p0, p1);
+
+            // Add both new points to the list
+            new_points.push(q);
+            new_points.push(r);
+        }
+
+        // Keep the last point as is
+        if !self.closed {
+            new_points.push(*points.last().unwrap());
+        }
+    }
+
+    fn with_closed(&self, closed: bool) -> Option<Box<dyn Smoother>> {
+        Some(Box::new(Self::with_ratios(self.q_ratio, self.r_ratio).closed(closed).adaptive(self.tolerance)))
+    }
+
+    fn limit_curve(&self, initial_points: &[Point], samples: usize) -> Option<Vec<Point>> {
+        // Other ratios don't converge to a B-spline, nor do steps leaving corners uncut
+        let quadratic = self.q_ratio == 0.25 && self.r_ratio == 0.75 && self.tolerance == 0.0;
+        quadratic.then(|| chaikin_limit(initial_points, self.closed, samples))
+    }
+
+    fn formulas(&self) -> Vec<String> {
+        let mut formulas = chaikin_formulas(self.q_ratio, self.r_ratio, self.closed);
+        if self.tolerance > 0.0 {
+            formulas.push(format!("Corners less than {} from flat are kept", self.tolerance));
+        }
+        formulas
+    }
+}
+
+/// The formulas of Chaikin's scheme with the given ratios, see [`Smoother::formulas`]
+fn chaikin_formulas(q_ratio: f32, r_ratio: f32, closed: bool) -> Vec<String> {
+    let mut formulas = vec![
+        format!("Q_i = {}·P_i + {}·P_{{i+1}}", 1.0 - q_ratio, q_ratio),
+        format!("R_i = {}·P_i + {}·P_{{i+1}}", 1.0 - r_ratio, r_ratio),
+    ];
+    if closed {
+        formulas.push("P_n = P_0: the last segment closes the shape".to_string());
+    } else {
+        formulas.push("The first and last points are kept".to_string());
+    }
+    formulas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_points() {
+        let algorithm = ChaikinAlgorithm::new();
+        let empty: Vec<Point> = Vec::new();
+
+        assert_eq!(algorithm.calculate_step(&empty).len(), 0);
+        assert_eq!(algorithm.get_step_points(&empty, 1).len(), 0);
+    }
+
+    #[test]
+    fn test_single_point() {
+        let algorithm = ChaikinAlgorithm::new();
+        let point = Point2::new(100.0, 100.0);
+        let points = vec![point];
+
+        let step_result = algorithm.calculate_step(&points);
+        assert_eq!(step_result.len(), 1);
+        assert_eq!(step_result[0], point);
+
+        let step_points = algorithm.get_step_points(&points, 3);
+        assert_eq!(step_points.len(), 1);
+        assert_eq!(step_points[0], point);
+    }
+
+    #[test]
+    fn test_two_points() {
+        let algorithm = ChaikinAlgorithm::new();
+        let points = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(100.0, 100.0),
+        ];
+
+        let step_result = algorithm.calculate_step(&points);
+        assert_eq!(step_result.len(), 2);
+        assert_eq!(step_result[0], points[0]);
+        assert_eq!(step_result[1], points[1]);
+    }
+
+    #[test]
+    fn test_three_points() {
+        let algorithm = ChaikinAlgorithm::new();
+        let points = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(100.0, 100.0),
+            Point2::new(200.0, 0.0),
+        ];
+
+        let step1 = algorithm.calculate_step(&points);
+        assert_eq!(step1.len(), 6);
+        assert_eq!(step1[0], points[0]);
+        assert_eq!(step1[step1.len() - 1], *points.last().unwrap());
+
+        assert!((step1[1].x - 25.0).abs() < 0.001);
+        assert!((step1[1].y - 25.0).abs() < 0.001);
+
+        assert!((step1[3].x - 125.0).abs() < 0.001);
+        assert!((step1[3].y - 75.0).abs() < 0.001);
+
+        assert!((step1[2].x - 75.0).abs() < 0.001);
+        assert!((step1[2].y - 75.0).abs() < 0.001);
+
+        assert!((step1[4].x - 175.0).abs() < 0.001);
+        assert!((step1[4].y - 25.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_steps() {
+        let algorithm = ChaikinAlgorithm::new();
+        let points = vec![Point2::new(0.0, 0.0), Point2::new(100.0, 100.0), Point2::new(200.0, 0.0)];
+        let steps: Vec<Vec<Point>> = Steps::new(&algorithm, &points).take(4).collect();
+        assert_eq!(steps[0], points);
+        assert_eq!(steps[3], algorithm.get_step_points(&points, 3));
+
+        // Through a trait object too
+        let smoother: Box<dyn Smoother> = Box::new(CubicBSpline);
+        assert_eq!(Steps::new(smoother.as_ref(), &points).nth(2).unwrap(), smoother.get_step_points(&points, 2));
+    }
+
+    #[test]
+    fn test_nearest_point() {
+        let algorithm = ChaikinAlgorithm::new();
+        let points = vec![Point2::new(0.0, 0.0), Point2::new(100.0, 100.0), Point2::new(200.0, 0.0)];
+        // The corner is cut by the segment from (75, 75) to (125, 75), the third after a step
+        let nearest = algorithm.nearest_point(&points, 1, false, Point2::new(100.0, 100.0)).unwrap();
+        assert_eq!(nearest.point, Point2::new(100.0, 75.0));
+        assert_eq!((nearest.segment, nearest.t, nearest.distance), (2, 0.5, 25.0));
+        // At step 0, the points themselves
+        assert_eq!(algorithm.nearest_point(&points, 0, false, Point2::new(100.0, 100.0)).unwrap().distance, 0.0);
+        assert_eq!(algorithm.nearest_point(&[], 3, false, Point2::new(1.0, 1.0)), None);
+    }
+
+    #[test]
+    fn test_scheme_degrees() {
+        assert_eq!("lr5".parse::<Scheme>(), Ok(Scheme::LaneRiesenfeld(5)));
+        assert_eq!(Scheme::LaneRiesenfeld(5).to_string(), "lr5");
+        assert!("lr2".parse::<Scheme>().is_err());
+        let error = "spline".parse::<Scheme>().unwrap_err();
+        assert!(error.contains("chaikin,"));
+        assert_eq!(error.contains("chaikin-fixed"), cfg!(feature = "fixed-point"));
+        assert!(format!("lr{}", MAX_DEGREE + 1).parse::<Scheme>().is_err());
+
+        // The degrees go round from Chaikin's scheme
+        let mut scheme = Scheme::Chaikin;
+        let mut degrees = Vec::new();
+        for _ in 2..=MAX_DEGREE {
+            degrees.push(scheme.degree().unwrap());
+            scheme = scheme.with_next_degree();
+        }
+        assert_eq!(degrees, (2..=MAX_DEGREE).collect::<Vec<_>>());
+        assert_eq!(scheme, Scheme::Chaikin);
+        assert_eq!(Scheme::CatmullRom.degree(), None);
+        assert_eq!(Scheme::CatmullRom.with_next_degree(), Scheme::Chaikin);
+        assert!(Scheme::Chaikin.uses_ratios() && !Scheme::LaneRiesenfeld(3).uses_ratios());
+    }
+
+    #[test]
+    fn test_formulas() {
+        let formulas = ChaikinAlgorithm::with_ratios(0.2, 0.7).formulas();
+        assert_eq!(formulas[0], "Q_i = 0.8·P_i + 0.2·P_{i+1}");
+        assert_eq!(formulas[1], "R_i = 0.3·P_i + 0.7·P_{i+1}");
+        assert!(ChaikinAlgorithm::new().closed(true).formulas()[2].contains("closes"));
+        assert!(!FourPointScheme.formulas().is_empty());
+    }
+
+    #[test]
+    fn test_closed() {
+        let algorithm = ChaikinAlgorithm::new().closed(true);
+        let square = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(100.0, 0.0),
+            Point2::new(100.0, 100.0),
+            Point2::new(0.0, 100.0),
+        ];
+
+        // Every corner is cut, including the one where the shape closes
+        let step1 = algorithm.calculate_step(&square);
+        assert_eq!(step1.len(), 8);
+        assert_eq!(step1[0], Point2::new(25.0, 0.0));
+        assert_eq!(step1[6], Point2::new(0.0, 75.0));
+        assert_eq!(step1[7], Point2::new(0.0, 25.0));
+        assert!(!step1.contains(&square[0]));
+
+        assert_eq!(algorithm.get_step_points(&square, 3).len(), 32);
+        assert!(algorithm.with_closed(false).unwrap().calculate_step(&square).contains(&square[0]));
+        assert!(CubicBSpline.with_closed(true).is_none());
+    }
+
+    #[test]
+    fn test_adaptive() {
+        let points = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(100.0, 0.0),
+            Point2::new(200.0, 0.0),
+            Point2::new(250.0, 100.0),
+            Point2::new(300.0, 0.0),
+        ];
+        let algorithm = ChaikinAlgorithm::new();
+
+        // With no tolerance, a step cuts every corner as usual
+        for closed in [false, true] {
+            let algorithm = ChaikinAlgorithm::new().closed(closed);
+            let uniform = algorithm.get_step_points(&points, 3);
+            assert_eq!(ChaikinAlgorithm::new().closed(closed).adaptive(0.0).get_step_points(&points, 3), uniform);
+            let mut adaptive = Vec::new();
+            algorithm.adaptive_step_into(&points, 0.0, &mut adaptive);
+            assert_eq!(adaptive, algorithm.calculate_step(&points));
+        }
+
+        // The straight corner at (100, 0) is kept, and the curve ends up as close to the
+        // uniform one as the tolerance, with far fewer points
+        let adaptive = algorithm.calculate_adaptive(&points, 0.5);
+        assert!(adaptive.contains(&points[1]));
+        let uniform = algorithm.get_step_points(&points, MAX_ADAPTIVE_STEPS);
+        assert!(adaptive.len() * 10 < uniform.len());
+        assert!(geometry::hausdorff(&adaptive, &uniform) < 1.0);
+
+        // Steps of an adaptive smoother stop adding points once the curve is flat enough
+        let smoother = ChaikinAlgorithm::new().adaptive(0.5);
+        assert_eq!(smoother.get_step_points(&points, MAX_ADAPTIVE_STEPS), adaptive);
+        // Closed, the shape folds back on itself at (0, 0), which is as sharp as it gets
+        let closed = smoother.with_closed(true).unwrap().calculate_step(&points);
+        assert_eq!(closed.len(), 9);
+        assert!(closed.contains(&points[1]) && !closed.contains(&points[0]));
+        assert_eq!(algorithm.calculate_adaptive(&points[..2], 0.5), points[..2]);
+    }
+
+    /// A hash of the exact bits of the points
+    fn fingerprint(points: &[Point]) -> u64 {
+        // FNV-1a, which is simple enough not to change under us
+        points
+            .iter()
+            .flat_map(|point| [point.x.to_bits(), point.y.to_bits()])
+            .fold(0xcbf29ce484222325, |hash, bits| (hash ^ bits as u64).wrapping_mul(0x100000001b3))
+    }
+
+    #[test]
+    fn test_golden_bits() {
+        let points = vec![
+            Point2::new(12.3, 45.6),
+            Point2::new(78.9, 10.1),
+            Point2::new(111.1, 99.9),
+            Point2::new(150.7, 33.3),
+            Point2::new(201.5, 87.2),
+        ];
+        let schemes: [(Box<dyn Smoother>, u64); 4] = [
+            (Box::new(ChaikinAlgorithm::new()), 0xdcf9f3e7f9a31546),
+            (Box::new(ChaikinAlgorithm::new().closed(true)), 0x5afd049040074f6b),
+            (Box::new(CubicBSpline), 0x0ac3e2026982f772),
+            (Box::new(FourPointScheme), 0xa934c91f8e38c95d),
+        ];
+        for (smoother, expected) in schemes {
+            let curve = smoother.get_step_points(&points, 4);
+            assert_eq!(fingerprint(&curve), expected);
+            // Computed step by step into reused buffers, as the window does, it is the same
+            let mut pool = PointPool::default();
+            assert_eq!(fingerprint(&smoother.get_step_points_pooled(&points, 4, &mut pool)), expected);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::types::Point;
+
+/// The Bézier curve of a control polygon, evaluated exactly with De Casteljau's algorithm:
+/// the point at `t` is found by cutting each segment of the polygon at `t`, which gives a
+/// polygon with one point less, and so on down to a single point. Unlike the subdivision
+/// schemes, the curve has a closed form, so it isn't refined step by step
+pub struct Bezier<'a> {
+    control: &'a [Point],
+}
+
+impl<'a> Bezier<'a> {
+    /// The curve of the given control polygon, whose degree is one less than its number of
+    /// points
+    pub fn new(control: &'a [Point]) -> Self {
+        Self { control }
+    }
+
+    /// The polygons of De Casteljau's construction at `t`, from the control polygon down to
+    /// the single point of the curve at `t`. Empty if there are no control points
+    pub fn construction(&self, t: f32) -> Vec<Vec<Point>> {
+        let mut polygons = Vec::with_capacity(self.control.len());
+        if self.control.is_empty() {
+            return polygons;
+        }
+        polygons.push(self.control.to_vec());
+        while polygons[polygons.len() - 1].len() > 1 {
+            let cut = cut_segments(&polygons[polygons.len() - 1], t);
+            polygons.push(cut);
+        }
+        polygons
+    }
+
+    /// The point of the curve at `t`, from the first control point at 0 to the last one at
+    /// 1, or none if there are no control points
+    pub fn point_at(&self, t: f32) -> Option<Point> {
+        let mut polygon = self.control.to_vec();
+        // Cut in place: each point is replaced once its successor is no longer needed
+        for len in (1..polygon.len()).rev() {
+            for i in 0..len {
+                polygon[i] = lerp(polygon[i], polygon[i + 1], t);
+            }
+        }
+        polygon.first().copied()
+    }
+
+    /// The curve at `count` evenly spaced parameters from 0 to 1, e.g. to draw it as a
+    /// polyline. At least the two ends are given if there are control points
+    pub fn sample(&self, count: usize) -> Vec<Point> {
+        let last = count.max(2) - 1;
+        (0..=last).filter_map(|i| self.point_at(i as f32 / last as f32)).collect()
+    }
+}
+
+/// The points `t` of the way along each segment of the polygon
+fn cut_segments(polygon: &[Point], t: f32) -> Vec<Point> {
+    polygon.windows(2).map(|segment| lerp(segment[0], segment[1], t)).collect()
+}
+
+/// The point `t` of the way from `a` to `b`, exactly `a` at 0 and `b` at 1
+fn lerp(a: Point, b: Point, t: f32) -> Point {
+    Point::new(a.x * (1.0 - t) + b.x * t, a.y * (1.0 - t) + b.y * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_at() {
+        let control = [Point::new(0.0, 0.0), Point::new(50.0, 100.0), Point::new(100.0, 0.0)];
+        let curve = Bezier::new(&control);
+        assert_eq!(curve.point_at(0.0), Some(control[0]));
+        assert_eq!(curve.point_at(1.0), Some(control[2]));
+        // A quadratic reaches half way to its middle control point
+        assert_eq!(curve.point_at(0.5), Some(Point::new(50.0, 50.0)));
+        assert_eq!(Bezier::new(&[]).point_at(0.5), None);
+    }
+
+    #[test]
+    fn test_construction() {
+        let control = [Point::new(0.0, 0.0), Point::new(0.0, 100.0), Point::new(100.0, 100.0), Point::new(100.0, 0.0)];
+        let curve = Bezier::new(&control);
+        let polygons = curve.construction(0.25);
+        assert_eq!(polygons.iter().map(Vec::len).collect::<Vec<_>>(), [4, 3, 2, 1]);
+        assert_eq!(polygons[1][0], Point::new(0.0, 25.0));
+        assert_eq!(polygons[3][0], curve.point_at(0.25).unwrap());
+
+        let samples = curve.sample(11);
+        assert_eq!(samples.len(), 11);
+        assert_eq!((samples[0], samples[10]), (control[0], control[3]));
+    }
+}
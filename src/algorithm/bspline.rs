@@ -0,0 +1,119 @@
+use crate::types::Point;
+
+/// How many points each span of a limit curve is sampled at, enough for it to look smooth
+/// at the zoom the points were drawn at. Zoomed in, it takes more
+pub const LIMIT_SAMPLES: usize = 16;
+
+/// The uniform B-spline of the given degree over the control points, with `samples` points
+/// per span, each span being over `degree + 1` consecutive points. Open, it goes from
+/// near the first points to near the last ones without reaching them, and is ended by the
+/// point it ends at. Closed, the points wrap around, and the spline is a loop whose last
+/// point comes just before its first. None if there are too few points for a span
+pub fn uniform_bspline(points: &[Point], degree: usize, closed: bool, samples: usize) -> Option<Vec<Point>> {
+    let n = points.len();
+    if n <= degree || samples == 0 {
+        return None;
+    }
+    let spans = if closed { n } else { n - degree };
+
+    let mut curve = Vec::with_capacity(spans * samples + 1);
+    for span in 0..spans {
+        let control: Vec<Point> = (0..=degree).map(|i| points[(span + i) % n]).collect();
+        curve.extend((0..samples).map(|k| de_boor(&control, k as f32 / samples as f32)));
+    }
+    if !closed {
+        let control = &points[n - degree - 1..];
+        curve.push(de_boor(control, 1.0));
+    }
+    Some(curve)
+}
+
+/// The point of a span of a uniform B-spline, from 0 at its start to 1 at its end, with
+/// de Boor's algorithm: the control points of the span are blended into one, one degree
+/// at a time
+fn de_boor(control: &[Point], u: f32) -> Point {
+    let degree = control.len() - 1;
+    let mut blended = control.to_vec();
+    for r in 1..=degree {
+        for i in (r..=degree).rev() {
+            let alpha = (u + (degree - i) as f32) / (degree + 1 - r) as f32;
+            blended[i] = blended[i - 1] + (blended[i] - blended[i - 1]) * alpha;
+        }
+    }
+    blended[degree]
+}
+
+/// The curve Chaikin's scheme, cutting at a quarter and three quarters, converges to: the
+/// quadratic B-spline over the points, sampled with `samples` points per span, at least
+/// one. Open, the first and last points are kept, and the ends are the straight lines from
+/// them to the middle of their segments, where the spline starts and ends
+pub fn chaikin_limit(points: &[Point], closed: bool, samples: usize) -> Vec<Point> {
+    if points.len() <= 2 {
+        return points.to_vec();
+    }
+    let spline = uniform_bspline(points, 2, closed, samples.max(1)).expect("more points than the degree");
+    if closed {
+        return spline;
+    }
+    let mut curve = Vec::with_capacity(spline.len() + 2);
+    curve.push(points[0]);
+    curve.extend(spline);
+    curve.push(points[points.len() - 1]);
+    curve
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{ChaikinAlgorithm, Smoother};
+    use crate::geometry;
+
+    #[test]
+    fn test_uniform_bspline() {
+        let points = [Point::new(0.0, 0.0), Point::new(10.0, 20.0), Point::new(20.0, 0.0)];
+        // A quadratic span goes from the middle of a segment to the middle of the next one,
+        // halfway between the middle of those and the corner
+        let curve = uniform_bspline(&points, 2, false, 2).unwrap();
+        assert_eq!(curve, vec![Point::new(5.0, 10.0), Point::new(10.0, 15.0), Point::new(15.0, 10.0)]);
+
+        // A cubic span starts at (P0 + 4 P1 + P2) / 6
+        let square = [Point::new(0.0, 0.0), Point::new(6.0, 0.0), Point::new(6.0, 6.0), Point::new(0.0, 6.0)];
+        let closed = uniform_bspline(&square, 3, true, 4).unwrap();
+        assert_eq!(closed.len(), 16);
+        assert!((closed[0] - Point::new(5.0, 1.0)).norm() < 1e-5);
+
+        assert_eq!(uniform_bspline(&points, 3, false, 4), None);
+        assert_eq!(uniform_bspline(&points, 2, false, 0), None);
+    }
+
+    #[test]
+    fn test_chaikin_limit() {
+        let points = [Point::new(0.0, 0.0), Point::new(100.0, 200.0), Point::new(200.0, 0.0), Point::new(300.0, 150.0)];
+        for closed in [false, true] {
+            // Loops are compared with the segments that close them
+            let with_closing = |mut curve: Vec<Point>| {
+                if closed {
+                    curve.push(curve[0]);
+                }
+                curve
+            };
+            // The steps get closer and closer to the limit
+            let limit = with_closing(chaikin_limit(&points, closed, LIMIT_SAMPLES));
+            let chaikin = ChaikinAlgorithm::new().closed(closed);
+            let distances: Vec<f32> = [2, 4, 8]
+                .into_iter()
+                .map(|step| geometry::hausdorff(&with_closing(chaikin.get_step_points(&points, step)), &limit))
+                .collect();
+            assert!(distances[0] > distances[1] && distances[1] > distances[2]);
+            // Down to how far the segments between the samples of the limit cut across it
+            assert!(distances[2] < 0.3, "{:?}", distances);
+        }
+
+        let limit = chaikin_limit(&points, false, LIMIT_SAMPLES);
+        assert_eq!((limit[0], limit[limit.len() - 1]), (points[0], points[3]));
+        assert_eq!(chaikin_limit(&points[..2], false, LIMIT_SAMPLES), points[..2]);
+        // Sampled as finely as asked, each of the two spans
+        assert_eq!(chaikin_limit(&points, false, 64).len(), 2 * 64 + 3);
+        assert_eq!(chaikin_limit(&points, false, 0).len(), 2 + 3);
+    }
+}
@@ -0,0 +1,197 @@
+use nalgebra::Point2;
+use crate::types::Point;
+use super::Smoother;
+
+/// How the knots of a Catmull-Rom spline are spaced, from the distances between its points
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameterization {
+    /// Evenly, whatever the distances. The new points are then the same as the four-point
+    /// scheme's
+    Uniform,
+    /// By the square root of the distances, which keeps the curve from looping or forming
+    /// cusps within a segment
+    Centripetal,
+    /// By the distances themselves
+    Chordal,
+}
+
+/// Catmull-Rom splines, by subdivision. Like the four-point scheme, it is interpolating:
+/// each step keeps the points and adds one in the middle of each segment, on the spline
+/// through the segment and its two neighbours. Unlike Chaikin's corner cutting, the curve
+/// goes through every control point
+pub struct CatmullRom {
+    parameterization: Parameterization,
+    /// Whether the last point connects back to the first, making a closed shape
+    closed: bool,
+}
+
+impl CatmullRom {
+    /// Centripetal Catmull-Rom, which behaves best with unevenly spaced points
+    pub fn new() -> Self {
+        Self::with_parameterization(Parameterization::Centripetal)
+    }
+
+    pub fn with_parameterization(parameterization: Parameterization) -> Self {
+        Self { parameterization, closed: false }
+    }
+
+    /// The same scheme for closed shapes, where the last point connects back to the first
+    pub fn closed(self, closed: bool) -> Self {
+        Self { closed, ..self }
+    }
+
+    /// The knot interval between two points. Only square roots are taken, which are
+    /// exact, so the curves stay bit-identical on every platform
+    fn knot_interval(&self, a: Point, b: Point) -> f32 {
+        let interval = match self.parameterization {
+            Parameterization::Uniform => 1.0,
+            Parameterization::Centripetal => (b - a).norm().sqrt(),
+            Parameterization::Chordal => (b - a).norm(),
+        };
+        // Repeated points would divide by zero
+        if interval > f32::EPSILON { interval } else { 1.0 }
+    }
+
+    /// The point of the spline through p0, p1, p2 and p3 halfway between p1 and p2 in
+    /// parameter, with the pyramid of Barry and Goldman
+    fn midpoint(&self, p0: Point, p1: Point, p2: Point, p3: Point) -> Point {
+        let t1 = self.knot_interval(p0, p1);
+        let t2 = t1 + self.knot_interval(p1, p2);
+        let t3 = t2 + self.knot_interval(p2, p3);
+        let t = (t1 + t2) / 2.0;
+
+        let lerp = |a: Point, b: Point, from: f32, to: f32| -> Point {
+            let s = (t - from) / (to - from);
+            Point2::new(a.x + (b.x - a.x) * s, a.y + (b.y - a.y) * s)
+        };
+        let a1 = lerp(p0, p1, 0.0, t1);
+        let a2 = lerp(p1, p2, t1, t2);
+        let a3 = lerp(p2, p3, t2, t3);
+        let b1 = lerp(a1, a2, 0.0, t2);
+        let b2 = lerp(a2, a3, t1, t3);
+        lerp(b1, b2, t1, t2)
+    }
+}
+
+impl Default for CatmullRom {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Smoother for CatmullRom {
+    /// Does one round of smoothing
+    ///
+    /// Between each pair of points p1 and p2, a new point is placed on the Catmull-Rom
+    /// spline through p0, p1, p2 and p3, where p0 and p3 are their outer neighbours. At the
+    /// ends of an open shape, the missing neighbour is mirrored from the inner one
+    fn calculate_step(&self, points: &[Point]) -> Vec<Point> {
+        let mut new_points = Vec::with_capacity(points.len() * 2);
+        self.calculate_step_into(points, &mut new_points);
+        new_points
+    }
+
+    fn calculate_step_into(&self, points: &[Point], new_points: &mut Vec<Point>) {
+        new_points.clear();
+        if points.len() <= 2 {
+            return new_points.extend_from_slice(points);
+        }
+
+        let n = points.len();
+        let last = n - 1;
+        let neighbour = |i: isize| -> Point {
+            if self.closed {
+                points[i.rem_euclid(n as isize) as usize]
+            } else if i < 0 {
+                Point2::new(2.0 * points[0].x - points[1].x, 2.0 * points[0].y - points[1].y)
+            } else if i as usize > last {
+                Point2::new(
+                    2.0 * points[last].x - points[last - 1].x,
+                    2.0 * points[last].y - points[last - 1].y,
+                )
+            } else {
+                points[i as usize]
+            }
+        };
+
+        // A closed shape has a segment from the last point back to the first
+        let segments = if self.closed { n } else { last };
+        new_points.reserve(segments * 2 + 1);
+        for i in 0..segments {
+            let i = i as isize;
+            new_points.push(points[i as usize]);
+            new_points.push(self.midpoint(neighbour(i - 1), neighbour(i), neighbour(i + 1), neighbour(i + 2)));
+        }
+        if !self.closed {
+            new_points.push(points[last]);
+        }
+    }
+
+    fn with_closed(&self, closed: bool) -> Option<Box<dyn Smoother>> {
+        Some(Box::new(Self::with_parameterization(self.parameterization).closed(closed)))
+    }
+
+    fn formulas(&self) -> Vec<String> {
+        let knots = match self.parameterization {
+            Parameterization::Uniform => "t_{j+1} = t_j + 1: the knots are evenly spaced",
+            Parameterization::Centripetal => "t_{j+1} = t_j + √|P_{j+1} − P_j|: centripetal knots",
+            Parameterization::Chordal => "t_{j+1} = t_j + |P_{j+1} − P_j|: chordal knots",
+        };
+        vec![
+            "E_i = C(t) at t = (t_i + t_{i+1}) / 2, on the spline C through P_{i-1}..P_{i+2}".to_string(),
+            knots.to_string(),
+            "V_i = P_i: the points are kept, and the curve goes through them".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::FourPointScheme;
+
+    fn zigzag() -> Vec<Point> {
+        vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(100.0, 100.0),
+            Point2::new(130.0, 0.0),
+            Point2::new(300.0, 100.0),
+        ]
+    }
+
+    #[test]
+    fn test_keeps_control_points() {
+        let points = zigzag();
+        let step = CatmullRom::new().calculate_step(&points);
+        assert_eq!(step.len(), 7);
+        for (i, point) in points.iter().enumerate() {
+            assert_eq!(step[i * 2], *point);
+        }
+
+        // Closed shapes get a segment back to the first point
+        let closed = CatmullRom::new().closed(true).calculate_step(&points);
+        assert_eq!(closed.len(), 8);
+        assert_eq!(closed[6], points[3]);
+    }
+
+    #[test]
+    fn test_uniform_is_four_point() {
+        let points = zigzag();
+        let uniform = CatmullRom::with_parameterization(Parameterization::Uniform).calculate_step(&points);
+        let four_point = FourPointScheme.calculate_step(&points);
+        for (a, b) in uniform.iter().zip(&four_point) {
+            assert!((a - b).norm() < 1e-3, "{} != {}", a, b);
+        }
+
+        // Centripetal knots pull the new point toward the shorter segment's side
+        let centripetal = CatmullRom::new().calculate_step(&points);
+        assert_ne!(centripetal[3], uniform[3]);
+    }
+
+    #[test]
+    fn test_repeated_points() {
+        let points = vec![Point2::new(0.0, 0.0), Point2::new(0.0, 0.0), Point2::new(10.0, 0.0)];
+        let step = CatmullRom::new().calculate_step(&points);
+        assert!(step.iter().all(|p| p.x.is_finite() && p.y.is_finite()));
+    }
+}
@@ -0,0 +1,158 @@
+//! Chaikin's scheme in 16.16 fixed point, for plotters without an FPU and for curves that
+//! have to be bit-exact whatever the floating point hardware does.
+//!
+//! The points are converted to fixed point and back at each step. Both conversions only
+//! scale by a power of two and round between integers and floats, which IEEE 754 defines
+//! exactly, and the corners are cut in integer arithmetic. Coordinates keep all 16
+//! fractional bits up to 256, and fewer beyond, where an `f32` can't hold them.
+
+use nalgebra::Point2;
+
+use super::Smoother;
+use crate::types::Point;
+
+/// The number of fractional bits
+const FRACTION_BITS: u32 = 16;
+/// 1.0 in fixed point
+const ONE: i64 = 1 << FRACTION_BITS;
+
+/// A number in 16.16 fixed point: an integer part of 16 bits, sign included, and a
+/// fractional part of 16 bits
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fixed(pub i32);
+
+impl Fixed {
+    /// The nearest fixed point number, saturating outside of about ±32768
+    pub fn from_f32(value: f32) -> Self {
+        Self((value * ONE as f32).round() as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / ONE as f32
+    }
+
+    /// The point at `ratio` along the way from `self` to `other`, rounded to nearest
+    fn lerp(self, other: Self, ratio: Self) -> Self {
+        let sum = (ONE - ratio.0 as i64) * self.0 as i64 + ratio.0 as i64 * other.0 as i64;
+        Self(((sum + ONE / 2) >> FRACTION_BITS) as i32)
+    }
+}
+
+/// Chaikin's corner cutting computed in fixed point, see the module documentation
+pub struct FixedChaikin {
+    /// First point ratio (how far the new point is along the line)
+    q_ratio: Fixed,
+    /// Second point ratio (how far the other new point is along the line)
+    r_ratio: Fixed,
+    /// Whether the last point connects back to the first, making a closed shape
+    closed: bool,
+}
+
+impl FixedChaikin {
+    /// Places the new points at the given ratios along each line segment, rounded to fixed
+    /// point
+    pub fn with_ratios(q_ratio: f32, r_ratio: f32) -> Self {
+        Self { q_ratio: Fixed::from_f32(q_ratio), r_ratio: Fixed::from_f32(r_ratio), closed: false }
+    }
+
+    /// Makes the last point connect back to the first, so that the curve is a closed loop
+    pub fn closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+}
+
+impl Default for FixedChaikin {
+    fn default() -> Self {
+        Self::with_ratios(0.25, 0.75)
+    }
+}
+
+impl Smoother for FixedChaikin {
+    fn calculate_step(&self, points: &[Point]) -> Vec<Point> {
+        let mut new_points = Vec::with_capacity(points.len() * 2);
+        self.calculate_step_into(points, &mut new_points);
+        new_points
+    }
+
+    fn calculate_step_into(&self, points: &[Point], new_points: &mut Vec<Point>) {
+        new_points.clear();
+        if points.len() <= 2 {
+            return new_points.extend_from_slice(points);
+        }
+        new_points.reserve(points.len() * 2);
+
+        let fixed: Vec<(Fixed, Fixed)> = points
+            .iter()
+            .map(|point| (Fixed::from_f32(point.x), Fixed::from_f32(point.y)))
+            .collect();
+        let to_point = |(x, y): (Fixed, Fixed)| Point2::new(x.to_f32(), y.to_f32());
+        let segments = if self.closed { fixed.len() } else { fixed.len() - 1 };
+
+        if !self.closed {
+            new_points.push(to_point(fixed[0]));
+        }
+        for i in 0..segments {
+            let (p0, p1) = (fixed[i], fixed[(i + 1) % fixed.len()]);
+            for ratio in [self.q_ratio, self.r_ratio] {
+                new_points.push(to_point((p0.0.lerp(p1.0, ratio), p0.1.lerp(p1.1, ratio))));
+            }
+        }
+        if !self.closed {
+            new_points.push(to_point(fixed[fixed.len() - 1]));
+        }
+    }
+
+    fn with_closed(&self, closed: bool) -> Option<Box<dyn Smoother>> {
+        Some(Box::new(Self { closed, ..*self }))
+    }
+
+    fn formulas(&self) -> Vec<String> {
+        let mut formulas = super::chaikin_formulas(self.q_ratio.to_f32(), self.r_ratio.to_f32(), self.closed);
+        formulas.push("In 16.16 fixed point, rounded to nearest".to_string());
+        formulas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::PointPool;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Fixed::from_f32(1.5), Fixed(0x18000));
+        assert_eq!(Fixed::from_f32(-0.25).to_f32(), -0.25);
+        assert_eq!(Fixed::from_f32(1e9), Fixed(i32::MAX));
+        // The ratios of the standard scheme are exact
+        assert_eq!(Fixed(0).lerp(Fixed::from_f32(100.0), Fixed::from_f32(0.25)), Fixed::from_f32(25.0));
+    }
+
+    #[test]
+    fn test_matches_float_scheme() {
+        let points = vec![Point2::new(0.0, 0.0), Point2::new(100.0, 100.0), Point2::new(200.0, 0.0)];
+        let fixed = FixedChaikin::default().get_step_points(&points, 3);
+        let float = crate::algorithm::ChaikinAlgorithm::new().get_step_points(&points, 3);
+        assert_eq!(fixed, float);
+    }
+
+    #[test]
+    fn test_golden_bits() {
+        let points = vec![
+            Point2::new(12.3, 45.6),
+            Point2::new(78.9, 10.1),
+            Point2::new(111.1, 99.9),
+            Point2::new(150.7, 33.3),
+            Point2::new(201.5, 87.2),
+        ];
+        let smoother = FixedChaikin::with_ratios(0.2, 0.7);
+        let curve = smoother.get_step_points(&points, 4);
+        let bits: u64 = curve
+            .iter()
+            .flat_map(|point| [point.x.to_bits(), point.y.to_bits()])
+            .fold(0xcbf29ce484222325, |hash, bits| (hash ^ bits as u64).wrapping_mul(0x100000001b3));
+        assert_eq!(bits, 0xc3891f40ecbed7aa);
+        let mut pool = PointPool::default();
+        assert_eq!(smoother.get_step_points_pooled(&points, 4, &mut pool), curve);
+    }
+}
@@ -0,0 +1,98 @@
+use nalgebra::Point2;
+use crate::types::Point;
+use super::Smoother;
+
+/// The four-point scheme of Dyn, Levin and Gregory. Unlike Chaikin's, it is interpolating:
+/// the curve passes through every control point, and only new points get added in between
+pub struct FourPointScheme;
+
+impl Smoother for FourPointScheme {
+    /// Does one round of smoothing
+    ///
+    /// Between each pair of points p1 and p2, a new point is placed at
+    /// (-p0 + 9 * p1 + 9 * p2 - p3) / 16, where p0 and p3 are their outer neighbours.
+    /// At the ends, the missing neighbour is mirrored from the inner one
+    fn calculate_step(&self, points: &[Point]) -> Vec<Point> {
+        let mut new_points = Vec::with_capacity(points.len() * 2);
+        self.calculate_step_into(points, &mut new_points);
+        new_points
+    }
+
+    fn calculate_step_into(&self, points: &[Point], new_points: &mut Vec<Point>) {
+        new_points.clear();
+        if points.len() <= 2 {
+            return new_points.extend_from_slice(points);
+        }
+
+        let last = points.len() - 1;
+        let neighbour = |i: isize| -> Point {
+            if i < 0 {
+                Point2::new(2.0 * points[0].x - points[1].x, 2.0 * points[0].y - points[1].y)
+            } else if i as usize > last {
+                Point2::new(
+                    2.0 * points[last].x - points[last - 1].x,
+                    2.0 * points[last].y - points[last - 1].y,
+                )
+            } else {
+                points[i as usize]
+            }
+        };
+
+        new_points.reserve(points.len() * 2 - 1);
+        for i in 0..last {
+            let p0 = neighbour(i as isize - 1);
+            let p1 = points[i];
+            let p2 = points[i + 1];
+            let p3 = neighbour(i as isize + 2);
+
+            new_points.push(p1);
+            new_points.push(Point2::new(
+                (-p0.x + 9.0 * p1.x + 9.0 * p2.x - p3.x) / 16.0,
+                (-p0.y + 9.0 * p1.y + 9.0 * p2.y - p3.y) / 16.0,
+            ));
+        }
+        new_points.push(points[last]);
+    }
+
+    fn formulas(&self) -> Vec<String> {
+        vec![
+            "E_i = (−P_{i-1} + 9·P_i + 9·P_{i+1} − P_{i+2}) / 16".to_string(),
+            "V_i = P_i: the points are kept, and the curve goes through them".to_string(),
+            "P_{-1} = 2·P_0 − P_1 and P_{n+1} = 2·P_n − P_{n-1} at the ends".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keeps_control_points() {
+        let points = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(100.0, 100.0),
+            Point2::new(200.0, 0.0),
+            Point2::new(300.0, 100.0),
+        ];
+
+        let step = FourPointScheme.calculate_step(&points);
+        assert_eq!(step.len(), 7);
+        for (i, point) in points.iter().enumerate() {
+            assert_eq!(step[i * 2], *point);
+        }
+    }
+
+    #[test]
+    fn test_straight_line_stays_straight() {
+        let points = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(10.0, 0.0),
+            Point2::new(20.0, 0.0),
+        ];
+
+        let step = FourPointScheme.calculate_step(&points);
+        assert_eq!(step[1], Point2::new(5.0, 0.0));
+        assert_eq!(step[3], Point2::new(15.0, 0.0));
+    }
+}
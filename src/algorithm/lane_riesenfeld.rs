@@ -0,0 +1,203 @@
+use nalgebra::Point2;
+use crate::types::Point;
+use super::Smoother;
+
+/// The highest degree of Lane-Riesenfeld subdivision offered. Each degree adds a round of
+/// averaging to every step, and curves of higher degrees pull ever further from the points
+pub const MAX_DEGREE: usize = 7;
+
+/// Lane-Riesenfeld subdivision of any degree from 2, which converges to a B-spline of that
+/// degree. Every point is doubled, then every point is averaged with the next one, as many
+/// times as the degree. Degree 2 is Chaikin's corner cutting and degree 3 [`CubicBSpline`],
+/// which computes the same points with fixed weights
+pub struct LaneRiesenfeld {
+    degree: usize,
+}
+
+impl LaneRiesenfeld {
+    /// The scheme of the degree, between 2 and [`MAX_DEGREE`]
+    pub fn new(degree: usize) -> Self {
+        Self { degree: degree.clamp(2, MAX_DEGREE) }
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+}
+
+impl Smoother for LaneRiesenfeld {
+    /// Does one round of smoothing. Each round of averaging leaves one point fewer, and the
+    /// end points are kept as is, so that the curve still starts and ends where the user
+    /// clicked. Up to as many points as the degree are left as they are, as the rounds would
+    /// leave fewer points than there were
+    fn calculate_step(&self, points: &[Point]) -> Vec<Point> {
+        let mut new_points = Vec::with_capacity(points.len() * 2 + 1);
+        self.calculate_step_into(points, &mut new_points);
+        new_points
+    }
+
+    fn calculate_step_into(&self, points: &[Point], new_points: &mut Vec<Point>) {
+        new_points.clear();
+        if points.len() < self.min_points() {
+            return new_points.extend_from_slice(points);
+        }
+
+        new_points.reserve(points.len() * 2 + 1);
+        new_points.push(points[0]);
+        for &point in points {
+            new_points.extend_from_slice(&[point, point]);
+        }
+
+        // The first point stays where it is, before the points averaged
+        for _ in 0..self.degree {
+            for i in 1..new_points.len() - 1 {
+                let (p0, p1) = (new_points[i], new_points[i + 1]);
+                new_points[i] = Point2::new((p0.x + p1.x) / 2.0, (p0.y + p1.y) / 2.0);
+            }
+            new_points.pop();
+        }
+
+        new_points.push(*points.last().unwrap());
+    }
+
+    fn min_points(&self) -> usize {
+        self.degree + 1
+    }
+
+    fn formulas(&self) -> Vec<String> {
+        vec![
+            "Every point P_i is doubled".to_string(),
+            format!("Then {} times: P_i = 0.5·P_i + 0.5·P_{{i+1}}", self.degree),
+            "The first and last points are kept".to_string(),
+        ]
+    }
+}
+
+/// Lane-Riesenfeld subdivision of degree 3, which converges to a cubic B-spline.
+/// The curve is smoother than Chaikin's, but pulls further away from the control points
+pub struct CubicBSpline;
+
+impl Smoother for CubicBSpline {
+    /// Does one round of smoothing
+    ///
+    /// Every segment gets a new point at its middle, and every inner point is moved to
+    /// (previous + 6 * point + next) / 8. The end points are kept as is, so that the curve
+    /// still starts and ends where the user clicked
+    fn calculate_step(&self, points: &[Point]) -> Vec<Point> {
+        let mut new_points = Vec::with_capacity(points.len() * 2);
+        self.calculate_step_into(points, &mut new_points);
+        new_points
+    }
+
+    fn calculate_step_into(&self, points: &[Point], new_points: &mut Vec<Point>) {
+        new_points.clear();
+        if points.len() <= 2 {
+            return new_points.extend_from_slice(points);
+        }
+
+        new_points.reserve(points.len() * 2 - 1);
+        new_points.push(points[0]);
+
+        for i in 0..points.len() - 1 {
+            let p0 = points[i];
+            let p1 = points[i + 1];
+
+            // Keep the inner points, moved towards their neighbours
+            if i > 0 {
+                let previous = points[i - 1];
+                new_points.push(Point2::new(
+                    (previous.x + 6.0 * p0.x + p1.x) / 8.0,
+                    (previous.y + 6.0 * p0.y + p1.y) / 8.0,
+                ));
+            }
+
+            // Add the middle of the segment
+            new_points.push(Point2::new((p0.x + p1.x) / 2.0, (p0.y + p1.y) / 2.0));
+        }
+
+        new_points.push(*points.last().unwrap());
+    }
+
+    fn formulas(&self) -> Vec<String> {
+        vec![
+            "E_i = 0.5·P_i + 0.5·P_{i+1}".to_string(),
+            "V_i = 0.125·P_{i-1} + 0.75·P_i + 0.125·P_{i+1}".to_string(),
+            "The first and last points are kept".to_string(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cubic_step() {
+        let points = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(80.0, 80.0),
+            Point2::new(160.0, 0.0),
+        ];
+
+        let step = CubicBSpline.calculate_step(&points);
+        assert_eq!(step, vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(40.0, 40.0),
+            Point2::new(80.0, 60.0),
+            Point2::new(120.0, 40.0),
+            Point2::new(160.0, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn test_degrees() {
+        let points = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(80.0, 80.0),
+            Point2::new(160.0, 0.0),
+            Point2::new(240.0, 40.0),
+        ];
+
+        // Degree 2 cuts the corners as Chaikin's scheme does, and degree 3 is the cubic
+        // B-spline's
+        let chaikin = crate::algorithm::ChaikinAlgorithm::new();
+        assert_eq!(LaneRiesenfeld::new(2).calculate_step(&points), chaikin.calculate_step(&points));
+        assert_eq!(LaneRiesenfeld::new(3).calculate_step(&points), CubicBSpline.calculate_step(&points));
+
+        // Each degree has one point fewer, and keeps the ends
+        let mut points = points;
+        points.extend([Point2::new(320.0, 0.0), Point2::new(400.0, 80.0), Point2::new(480.0, 0.0), Point2::new(560.0, 40.0)]);
+        for degree in 2..=MAX_DEGREE {
+            let step = LaneRiesenfeld::new(degree).calculate_step(&points);
+            assert_eq!(step.len(), points.len() * 2 + 2 - degree);
+            assert_eq!((step[0], step[step.len() - 1]), (points[0], points[7]));
+        }
+        assert_eq!(LaneRiesenfeld::new(20).degree(), MAX_DEGREE);
+    }
+
+    #[test]
+    fn test_few_points() {
+        let points = [
+            Point2::new(0.0, 0.0),
+            Point2::new(80.0, 80.0),
+            Point2::new(160.0, 0.0),
+            Point2::new(240.0, 40.0),
+        ];
+
+        // Too few for the degree's rounds of averaging, the points are left as they are
+        // rather than collapsing a little more every step
+        for degree in [6, 7] {
+            let scheme = LaneRiesenfeld::new(degree);
+            for count in [3, 4] {
+                assert_eq!(scheme.get_step_points(&points[..count], 3), points[..count]);
+            }
+        }
+
+        // Enough, every step has more points than the one before
+        let points: Vec<Point> = (0..8).map(|i| Point2::new(i as f32 * 10.0, (i % 2) as f32 * 10.0)).collect();
+        let scheme = LaneRiesenfeld::new(7);
+        assert_eq!(scheme.min_points(), 8);
+        let steps: Vec<usize> = (0..4).map(|step| scheme.get_step_points(&points, step).len()).collect();
+        assert!(steps.windows(2).all(|pair| pair[1] > pair[0]), "{:?}", steps);
+    }
+}
@@ -0,0 +1,55 @@
+use crate::types::Point;
+
+/// How many free buffers a pool keeps at most; more are freed
+const MAX_POOLED: usize = 8;
+
+/// Buffers of points that are reused from one step or frame to the next, instead of being
+/// allocated and freed each time. Each step has about twice the points of the one before,
+/// so once the buffers have grown to the size of the last step, smoothing the same shape
+/// again doesn't allocate at all
+#[derive(Default)]
+pub struct PointPool {
+    free: Vec<Vec<Point>>,
+}
+
+impl PointPool {
+    /// An empty buffer, with the memory of one given back earlier if there is one. The
+    /// largest buffer is taken first, since it's the least likely to need growing
+    pub fn take(&mut self) -> Vec<Point> {
+        self.free.pop().unwrap_or_default()
+    }
+
+    /// Gives a buffer back for it to be reused
+    pub fn give(&mut self, mut buffer: Vec<Point>) {
+        if self.free.len() == MAX_POOLED || buffer.capacity() == 0 {
+            return;
+        }
+        buffer.clear();
+        // Kept sorted by capacity, so that the largest one is taken first
+        let index = self.free.partition_point(|free| free.capacity() < buffer.capacity());
+        self.free.insert(index, buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::{ChaikinAlgorithm, Smoother};
+
+    #[test]
+    fn test_reuses_buffers() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(50.0, 50.0), Point::new(100.0, 0.0)];
+        let (smoother, mut pool) = (ChaikinAlgorithm::new(), PointPool::default());
+
+        let curve = smoother.get_step_points_pooled(&points, 5, &mut pool);
+        assert_eq!(curve, smoother.get_step_points(&points, 5));
+        let memory = curve.as_ptr();
+        pool.give(curve);
+
+        // The next run ends up in one of the same two buffers
+        let again = smoother.get_step_points_pooled(&points, 4, &mut pool);
+        let spare = pool.take();
+        assert!(again.as_ptr() == memory || spare.as_ptr() == memory);
+        assert!(spare.is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::types::Point;
+
+/// The polyline with `count` points spread evenly along the length of the given one, from
+/// its first point to its last, e.g. to export a curve with a set number of points. Refined
+/// curves have their points closer together at sharp corners, where each step cuts the
+/// segments shorter; these are as far apart everywhere, the corners being cut by the
+/// chords between them. A polyline of a single point, or of no length, gives that point
+/// `count` times, and one of no points gives none
+pub fn resample_by_arclength(points: &[Point], count: usize) -> Vec<Point> {
+    let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+        return Vec::new();
+    };
+    let length: f32 = points.windows(2).map(|segment| (segment[1] - segment[0]).norm()).sum();
+    if count < 2 || length == 0.0 {
+        return vec![first; count];
+    }
+
+    let spacing = length / (count - 1) as f32;
+    let mut resampled = Vec::with_capacity(count);
+    resampled.push(first);
+    // The segment walked along, and how far along the polyline it starts
+    let (mut i, mut start) = (0, 0.0);
+    for k in 1..count - 1 {
+        let target = k as f32 * spacing;
+        let mut segment = (points[i + 1] - points[i]).norm();
+        while start + segment < target && i + 2 < points.len() {
+            start += segment;
+            i += 1;
+            segment = (points[i + 1] - points[i]).norm();
+        }
+        let t = if segment > 0.0 { ((target - start) / segment).clamp(0.0, 1.0) } else { 0.0 };
+        resampled.push(points[i] + (points[i + 1] - points[i]) * t);
+    }
+    resampled.push(last);
+    resampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample_by_arclength() {
+        // An L whose first side has many points and whose second has none between its ends
+        let mut points: Vec<Point> = (0..=10).map(|i| Point::new(i as f32, 0.0)).collect();
+        points.push(Point::new(10.0, 10.0));
+        let resampled = resample_by_arclength(&points, 5);
+        assert_eq!(resampled, vec![
+            Point::new(0.0, 0.0),
+            Point::new(5.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 5.0),
+            Point::new(10.0, 10.0),
+        ]);
+
+        // Evenly spaced along the length, however many points are asked for
+        let resampled = resample_by_arclength(&points, 41);
+        assert_eq!(resampled.len(), 41);
+        for pair in resampled.windows(2) {
+            assert!(((pair[1] - pair[0]).norm() - 0.5).abs() < 1e-4);
+        }
+
+        assert!(resample_by_arclength(&[], 4).is_empty());
+        assert_eq!(resample_by_arclength(&points[..1], 3), vec![points[0]; 3]);
+        assert_eq!(resample_by_arclength(&points, 1), vec![points[0]]);
+        assert_eq!(resample_by_arclength(&points, 2), vec![points[0], points[11]]);
+    }
+}
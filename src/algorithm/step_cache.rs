@@ -0,0 +1,106 @@
+use crate::types::Point;
+
+use super::Smoother;
+
+/// The steps of smoothing computed so far for a polyline, so that going to a step only
+/// computes the steps past the last one computed, rather than every step from the points
+/// again. Each step has about twice the points of the one before, so keeping them all takes
+/// about twice the memory of the last one
+#[derive(Default)]
+pub struct StepCache {
+    /// The points the steps are of
+    points: Vec<Point>,
+    /// The steps computed, starting with the points themselves at step 0
+    steps: Vec<Vec<Point>>,
+}
+
+impl StepCache {
+    /// The points at the step, computed with the smoother from the deepest step cached
+    /// before it. The steps cached are forgotten first if they are of other points; the
+    /// smoother has to be the same, or the cache cleared when it changes
+    pub fn get<S: Smoother + ?Sized>(&mut self, smoother: &S, points: &[Point], step: usize) -> &[Point] {
+        self.keep_if_of(points);
+        while self.steps.len() <= step {
+            let next = match self.steps.last() {
+                Some(last) => smoother.calculate_step(last),
+                None => points.to_vec(),
+            };
+            self.steps.push(next);
+        }
+        &self.steps[step]
+    }
+
+    /// The deepest step cached up to the given one for the points, and its number, to go on
+    /// from elsewhere, e.g. on a worker thread. None if no step of the points is cached
+    pub fn deepest(&self, points: &[Point], step: usize) -> Option<(usize, &[Point])> {
+        if self.points != points {
+            return None;
+        }
+        let deepest = self.steps.len().checked_sub(1)?.min(step);
+        Some((deepest, &self.steps[deepest]))
+    }
+
+    /// Forgets the steps, e.g. when the smoother changes
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.steps.clear();
+    }
+
+    /// Forgets the steps unless they are of the points
+    fn keep_if_of(&mut self, points: &[Point]) {
+        if self.points != points {
+            self.points.clear();
+            self.points.extend_from_slice(points);
+            self.steps.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::ChaikinAlgorithm;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Chaikin's scheme, counting the steps it computes
+    #[derive(Default)]
+    struct Counting {
+        steps: AtomicUsize,
+    }
+
+    impl Smoother for Counting {
+        fn calculate_step(&self, points: &[Point]) -> Vec<Point> {
+            self.steps.fetch_add(1, Ordering::Relaxed);
+            ChaikinAlgorithm::new().calculate_step(points)
+        }
+    }
+
+    #[test]
+    fn test_steps_are_computed_once() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(50.0, 50.0), Point::new(100.0, 0.0)];
+        let (smoother, mut cache) = (Counting::default(), StepCache::default());
+
+        assert_eq!(cache.get(&smoother, &points, 5), ChaikinAlgorithm::new().get_step_points(&points, 5));
+        assert_eq!(smoother.steps.load(Ordering::Relaxed), 5);
+        // Going back and forth between steps already computed computes nothing
+        assert_eq!(cache.get(&smoother, &points, 2).len(), 12);
+        cache.get(&smoother, &points, 5);
+        assert_eq!(smoother.steps.load(Ordering::Relaxed), 5);
+        // Going further only computes the steps past the last one
+        cache.get(&smoother, &points, 7);
+        assert_eq!(smoother.steps.load(Ordering::Relaxed), 7);
+        assert_eq!(cache.deepest(&points, 10).map(|(step, _)| step), Some(7));
+        assert_eq!(cache.deepest(&points, 3).map(|(step, curve)| (step, curve.len())), Some((3, 24)));
+
+        // Moving a point starts over
+        let mut moved = points.clone();
+        moved[1].y = 60.0;
+        assert!(cache.deepest(&moved, 3).is_none());
+        cache.get(&smoother, &moved, 1);
+        assert_eq!(smoother.steps.load(Ordering::Relaxed), 8);
+        assert!(cache.deepest(&points, 1).is_none());
+
+        cache.clear();
+        assert!(cache.deepest(&moved, 1).is_none());
+    }
+}
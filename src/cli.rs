@@ -0,0 +1,683 @@
+//! Command line options.
+
+use std::path::PathBuf;
+
+use clap::{ArgGroup, Args, Parser, Subcommand};
+
+use chaikin::algorithm::ChaikinAlgorithm;
+use chaikin::export::Format;
+use chaikin::units::Units;
+use chaikin::window::{
+    is_valid_refresh_rate, BlendMode, FontAxes, Glyph, Placement, Scheme, APP_NAME, MAX_STEPS, MAX_STEPS_LIMIT,
+    REFRESH_RATE_RANGE, STROKE_WIDTH_RANGE,
+};
+
+/// Smaller windows can't fit the toasts and the instructions
+const MIN_WIDTH: i64 = 320;
+/// Minimum window height, for the same reason as [`MIN_WIDTH`]
+const MIN_HEIGHT: i64 = 240;
+
+/// An interactive visualization of Chaikin's curve algorithm
+#[derive(Parser, Debug)]
+#[command(version, about, group = ArgGroup::new("random").args(["demo", "quiz"]))]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// A file of control points to open, one "x y" pair per line.
+    /// With --steps, "-" or no file reads the points from stdin
+    pub file: Option<PathBuf>,
+
+    /// Reload this file of control points whenever it changes on disk
+    #[arg(long, value_name = "FILE", conflicts_with = "file")]
+    pub watch: Option<PathBuf>,
+
+    /// Save a PNG screenshot of each animation step into this directory
+    #[arg(long, value_name = "DIR")]
+    pub capture_steps: Option<PathBuf>,
+
+    /// Run a looping demo of random shapes, for kiosk displays
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Start with an exercise: place the fewest points whose curve matches a target curve
+    #[arg(long)]
+    pub quiz: bool,
+
+    /// Seed for the demo shapes or the quiz's target; the same seed always gives the same
+    /// shapes
+    #[arg(long, default_value_t = 0, requires = "random")]
+    pub seed: u64,
+
+    /// Smooth the points with this many steps and print them to stdout, without a window
+    #[arg(long)]
+    pub steps: Option<u32>,
+
+    /// Render the curve of the file's points after --steps steps (4 by default) into the
+    /// PNG image given with --out, --width by --height pixels, without a window
+    #[arg(long, requires_all = ["file", "out"])]
+    pub headless: bool,
+
+    /// The image to render with --headless
+    #[arg(long, value_name = "IMAGE", requires = "headless")]
+    pub out: Option<PathBuf>,
+
+    /// Width of the window, in pixels
+    #[arg(long, default_value_t = 800, value_parser = clap::value_parser!(u32).range(MIN_WIDTH..))]
+    pub width: u32,
+
+    /// Height of the window, in pixels
+    #[arg(long, default_value_t = 600, value_parser = clap::value_parser!(u32).range(MIN_HEIGHT..))]
+    pub height: u32,
+
+    /// Open the window maximized
+    #[arg(long)]
+    pub maximized: bool,
+
+    /// Connect the last point back to the first, to smooth closed shapes (Chaikin's and
+    /// Catmull-Rom schemes only)
+    #[arg(long, global = true)]
+    pub closed: bool,
+
+    /// Show the fully smoothed curve faintly behind the points while drawing
+    #[arg(long)]
+    pub ghost: bool,
+
+    /// Show the curve the steps converge to, dashed behind them while animating, for the
+    /// schemes that know it
+    #[arg(long)]
+    pub limit: bool,
+
+    /// Jump from one step of the animation to the next, instead of morphing each step into
+    /// the next one
+    #[arg(long)]
+    pub discrete: bool,
+
+    /// Morph the steps of the animation with their points spread evenly along the curve,
+    /// rather than along the points of each step, which bunch up near sharp corners
+    #[arg(long, conflicts_with = "discrete")]
+    pub uniform_tween: bool,
+
+    /// Draw each step of the animation in its own color, from red to green
+    #[arg(long)]
+    pub gradient: bool,
+
+    /// Draw the steps before the current one under it while animating, fainter and fainter
+    #[arg(long)]
+    pub onion_skin: bool,
+
+    /// Walk through placing points, animating and exporting, as offered the first time
+    #[arg(long, conflicts_with = "demo")]
+    pub tutorial: bool,
+
+    /// Title of the window
+    #[arg(long, default_value_t = default_title())]
+    pub title: String,
+
+    /// Shape of the control points: circle, square, cross or diamond
+    #[arg(long, default_value_t = Glyph::Circle)]
+    pub point_glyph: Glyph,
+
+    /// Color of the control points, as hexadecimal RRGGBB
+    #[arg(long, value_name = "RRGGBB", value_parser = parse_color)]
+    pub point_color: Option<u32>,
+
+    /// Color of the control polygon and the curve, as hexadecimal RRGGBB
+    #[arg(long, value_name = "RRGGBB", value_parser = parse_color)]
+    pub line_color: Option<u32>,
+
+    /// Color of the control polygon drawn faintly under the curve while animating, as
+    /// hexadecimal RRGGBB
+    #[arg(long, value_name = "RRGGBB", value_parser = parse_color)]
+    pub polygon_color: Option<u32>,
+
+    /// Color of the canvas behind the drawing, as hexadecimal RRGGBB
+    #[arg(long, value_name = "RRGGBB", value_parser = parse_color)]
+    pub background_color: Option<u32>,
+
+    /// Radius of the control points, in pixels
+    #[arg(long, default_value_t = 5.0, value_parser = parse_point_radius)]
+    pub point_radius: f32,
+
+    /// How often the window is updated at most, in Hz. Defaults to the refresh rate of the
+    /// monitor the window is on, where it can be found, and to 60 Hz elsewhere
+    #[arg(long, value_name = "HZ", value_parser = parse_refresh_rate)]
+    pub refresh_rate: Option<f32>,
+
+    /// How long each step of the animation is shown, in milliseconds
+    #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u64).range(125..=8000))]
+    pub step_ms: u64,
+
+    /// Opacity of the lines, between 0 and 1
+    #[arg(long, default_value_t = 1.0, value_parser = parse_opacity)]
+    pub stroke_opacity: f32,
+
+    /// How wide the lines are, in pixels, from 1 to 5
+    #[arg(long, default_value_t = 1.0, value_parser = parse_stroke_width)]
+    pub stroke_width: f32,
+
+    /// How lines are blended with what is under them: normal or additive, which
+    /// brightens where they overlap
+    #[arg(long, default_value_t = BlendMode::Normal)]
+    pub blend: BlendMode,
+
+    /// Weight of the text, from 100 (thin) to 900 (black), and optionally its width, from
+    /// 75 (condensed) to 100 percent
+    #[arg(long, value_name = "WEIGHT[,WIDTH]", default_value_t = FontAxes::REGULAR)]
+    pub text_font: FontAxes,
+
+    /// Weight and optionally width of the titles of panels, like --text-font
+    #[arg(long, value_name = "WEIGHT[,WIDTH]", default_value_t = FontAxes::SEMIBOLD)]
+    pub title_font: FontAxes,
+
+    /// How points are placed while the mouse button is held: click (one per click),
+    /// repeat[:ms] (again at each interval) or freehand[:px] (along the mouse's path)
+    #[arg(long, default_value_t = Placement::Click)]
+    pub placement: Placement,
+
+    /// Print a JSON report (sizes, deviation, timing) instead of the usual output
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Subdivision scheme: chaikin, catmull-rom, lr3 (cubic Lane-Riesenfeld, or lr4 to lr7 for
+    /// higher degrees) or fourpoint, and chaikin-fixed (fixed point) when built with the
+    /// fixed-point feature
+    #[arg(long, global = true, default_value_t = Scheme::Chaikin)]
+    pub scheme: Scheme,
+
+    /// Where Chaikin's scheme places the two new points along each segment, as "q,r"
+    #[arg(long, global = true, default_value = "0.25,0.75", value_parser = parse_ratios)]
+    pub ratios: (f32, f32),
+
+    /// Resample the smoothed curves written by batch and stream to this many points, spread
+    /// evenly along their length, instead of writing the points of the step as they are. At
+    /// least 2
+    #[arg(long, global = true, value_name = "POINTS", value_parser = clap::value_parser!(u32).range(2..))]
+    pub resample: Option<u32>,
+
+    /// Report lengths in real-world units, as pixels (point coordinates) per unit: e.g.
+    /// "3.78/mm" or "100000/deg", where the unit is px, mm, m or deg. SVG exports are then
+    /// sized in millimeters
+    #[arg(long, global = true, default_value = "px")]
+    pub units: Units,
+
+    /// Decimals the lengths, angles and ratios shown are written with, instead of each
+    /// one's own. The decimal separator follows the locale (LC_NUMERIC or LANG)
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(0..=9), value_name = "DECIMALS")]
+    pub precision: Option<u32>,
+
+    /// Number of smoothing steps the animation goes through before starting over, which Up
+    /// and Down change while animating
+    #[arg(long, default_value_t = MAX_STEPS as u32, value_parser = clap::value_parser!(u32).range(1..=MAX_STEPS_LIMIT as i64))]
+    pub max_steps: u32,
+}
+
+/// Ways to run the application without a window
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Smooth every supported file in a directory
+    Batch(BatchArgs),
+    /// Render every step into a sprite sheet, with a JSON file describing its layout
+    Atlas(AtlasArgs),
+    /// Export the animation as a Lottie file, for web and mobile apps
+    Lottie(LottieArgs),
+    /// Export the steps as a printable PDF handout, a page per step with a caption
+    Handout(HandoutArgs),
+    /// Record the animation as an animated GIF, morphing from one step to the next
+    Gif(GifArgs),
+    /// Compare several schemes and ratios on the same points in an HTML report
+    Report(ReportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct BatchArgs {
+    /// Directory containing the files to smooth
+    #[arg(long = "in")]
+    pub input: PathBuf,
+
+    /// Directory to write the smoothed files to, created if needed
+    #[arg(long = "out")]
+    pub output: PathBuf,
+
+    /// Number of smoothing steps to apply
+    #[arg(long, default_value_t = 4)]
+    pub steps: u32,
+
+    /// Format of the smoothed files: svg or txt
+    #[arg(long, default_value_t = Format::Svg)]
+    pub format: Format,
+}
+
+#[derive(Args, Debug)]
+pub struct AtlasArgs {
+    /// A file of control points, one "x y" pair per line
+    pub input: PathBuf,
+
+    /// The PNG image to write; the metadata goes next to it, with a .json extension
+    #[arg(long = "out")]
+    pub output: PathBuf,
+
+    /// Number of steps to render, starting with the control polygon
+    #[arg(long, default_value_t = MAX_STEPS as u32, value_parser = clap::value_parser!(u32).range(1..))]
+    pub steps: u32,
+
+    /// Size of each cell, in pixels
+    #[arg(long, default_value_t = 256, value_parser = clap::value_parser!(u32).range(16..))]
+    pub cell: u32,
+
+    /// Number of cells in each row of the sheet
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+    pub columns: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct LottieArgs {
+    /// A file of control points, one "x y" pair per line
+    pub input: PathBuf,
+
+    /// The Lottie JSON file to write
+    #[arg(long = "out")]
+    pub output: PathBuf,
+
+    /// Number of steps to animate, starting with the control polygon
+    #[arg(long, default_value_t = MAX_STEPS as u32, value_parser = clap::value_parser!(u32).range(1..))]
+    pub steps: u32,
+
+    /// Frames per second of the animation
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..=120))]
+    pub fps: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct HandoutArgs {
+    /// A file of control points, one "x y" pair per line
+    pub input: PathBuf,
+
+    /// The PDF file to write
+    #[arg(long = "out")]
+    pub output: PathBuf,
+
+    /// Number of steps to print, starting with the control polygon
+    #[arg(long, default_value_t = MAX_STEPS as u32, value_parser = clap::value_parser!(u32).range(1..))]
+    pub steps: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct GifArgs {
+    /// A file of control points, one "x y" pair per line
+    pub input: PathBuf,
+
+    /// The GIF file to write
+    #[arg(long = "out")]
+    pub output: PathBuf,
+
+    /// Number of steps to animate, starting with the control polygon
+    #[arg(long, default_value_t = MAX_STEPS as u32, value_parser = clap::value_parser!(u32).range(1..))]
+    pub steps: u32,
+
+    /// Width and height of the animation, in pixels
+    #[arg(long, default_value_t = 400, value_parser = clap::value_parser!(u16).range(16..))]
+    pub size: u16,
+}
+
+#[derive(Args, Debug)]
+pub struct ReportArgs {
+    /// A file of control points, one "x y" pair per line
+    pub input: PathBuf,
+
+    /// The HTML file to write
+    #[arg(long = "out")]
+    pub output: PathBuf,
+
+    /// Number of smoothing steps to apply with each variant
+    #[arg(long, default_value_t = 4)]
+    pub steps: u32,
+
+    /// A scheme to compare, with its ratios for Chaikin's scheme, e.g. "lr3" or
+    /// "chaikin:0.2,0.8". Repeat it for each variant; by default every scheme is compared
+    #[arg(long = "variant", value_parser = parse_variant)]
+    pub variants: Vec<Variant>,
+}
+
+/// A scheme to compare in a report, with the ratios it is built with
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Variant {
+    pub scheme: Scheme,
+    /// The ratios, or none for the scheme's defaults
+    pub ratios: Option<(f32, f32)>,
+}
+
+impl Variant {
+    /// The variants compared when none are given: each scheme with its defaults
+    pub fn defaults() -> Vec<Variant> {
+        [Scheme::Chaikin, Scheme::CatmullRom, Scheme::LaneRiesenfeld(3), Scheme::FourPoint]
+            .into_iter()
+            .map(|scheme| Variant { scheme, ratios: None })
+            .collect()
+    }
+
+    /// What the variant is, e.g. "chaikin 0.2,0.8"
+    pub fn label(&self) -> String {
+        match self.ratios {
+            Some((q, r)) => format!("{} {},{}", self.scheme, q, r),
+            None => self.scheme.to_string(),
+        }
+    }
+}
+
+/// Parses a variant, the scheme optionally followed by a colon and its ratios, which only
+/// Chaikin's scheme takes
+fn parse_variant(s: &str) -> Result<Variant, String> {
+    let (scheme, ratios) = match s.split_once(':') {
+        Some((scheme, ratios)) => (scheme, Some(parse_ratios(ratios)?)),
+        None => (s, None),
+    };
+    let scheme: Scheme = scheme.trim().parse()?;
+    if ratios.is_some() && !scheme.uses_ratios() {
+        return Err(format!("{} has fixed weights and takes no ratios", scheme));
+    }
+    Ok(Variant { scheme, ratios })
+}
+
+/// Parses the "q,r" ratios, which have to be ordered and strictly between 0 and 1
+fn parse_ratios(s: &str) -> Result<(f32, f32), String> {
+    let (q, r) = s.split_once(',').ok_or("expected two ratios separated by a comma")?;
+    let parse = |s: &str| s.trim().parse::<f32>().map_err(|e| format!("{:?}: {}", s, e));
+    let (q, r) = (parse(q)?, parse(r)?);
+
+    if !ChaikinAlgorithm::valid_ratios(q, r) {
+        return Err("the ratios must satisfy 0 < q < r < 1".to_string());
+    }
+    Ok((q, r))
+}
+
+fn parse_opacity(s: &str) -> Result<f32, String> {
+    let opacity = s.trim().parse::<f32>().map_err(|e| format!("{:?}: {}", s, e))?;
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err("the opacity must be between 0 and 1".to_string());
+    }
+    Ok(opacity)
+}
+
+/// A color as hexadecimal RRGGBB, with or without a leading '#'
+fn parse_color(s: &str) -> Result<u32, String> {
+    let digits = s.trim().trim_start_matches('#');
+    if digits.len() != 6 {
+        return Err(format!("{:?}: expected six hexadecimal digits, e.g. 00FFAA", s));
+    }
+    u32::from_str_radix(digits, 16).map_err(|e| format!("{:?}: {}", s, e))
+}
+
+fn parse_stroke_width(s: &str) -> Result<f32, String> {
+    let width = s.trim().parse::<f32>().map_err(|e| format!("{:?}: {}", s, e))?;
+    let (min, max) = STROKE_WIDTH_RANGE;
+    if !(min..=max).contains(&width) {
+        return Err(format!("the width must be between {} and {} pixels", min, max));
+    }
+    Ok(width)
+}
+
+fn parse_point_radius(s: &str) -> Result<f32, String> {
+    let radius = s.trim().parse::<f32>().map_err(|e| format!("{:?}: {}", s, e))?;
+    if !(1.0..=50.0).contains(&radius) {
+        return Err("the radius must be between 1 and 50 pixels".to_string());
+    }
+    Ok(radius)
+}
+
+fn parse_refresh_rate(s: &str) -> Result<f32, String> {
+    let rate = s.trim().parse::<f32>().map_err(|e| format!("{:?}: {}", s, e))?;
+    if !is_valid_refresh_rate(rate) {
+        let (min, max) = REFRESH_RATE_RANGE;
+        return Err(format!("the refresh rate must be between {} and {} Hz", min, max));
+    }
+    Ok(rate)
+}
+
+fn default_title() -> String {
+    format!("{}'s Algorithm - [Ctrl + R]: Reset - [Escape]: Close", APP_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        let cli = Cli::try_parse_from(["chaikin"]).unwrap();
+        assert_eq!((cli.width, cli.height), (800, 600));
+        assert!(!cli.maximized);
+        assert!(cli.file.is_none());
+    }
+
+    #[test]
+    fn test_geometry_flags() {
+        let cli = Cli::try_parse_from([
+            "chaikin", "--width", "1280", "--height", "720", "--maximized", "--title", "Demo", "a.txt",
+        ]).unwrap();
+        assert_eq!((cli.width, cli.height), (1280, 720));
+        assert!(cli.maximized);
+        assert_eq!(cli.title, "Demo");
+        assert_eq!(cli.file, Some(PathBuf::from("a.txt")));
+    }
+
+    #[test]
+    fn test_smoothing_flags() {
+        let cli = Cli::try_parse_from([
+            "chaikin", "--scheme", "fourpoint", "--ratios", "0.1, 0.9", "--max-steps", "4",
+        ]).unwrap();
+        assert_eq!(cli.scheme, Scheme::FourPoint);
+        assert_eq!(cli.ratios, (0.1, 0.9));
+        assert_eq!(cli.max_steps, 4);
+
+        assert!(Cli::try_parse_from(["chaikin", "--scheme", "bezier"]).is_err());
+        assert!(Cli::try_parse_from(["chaikin", "--max-steps", "0"]).is_err());
+        assert!(Cli::try_parse_from(["chaikin", "--max-steps", "13"]).is_err());
+    }
+
+    #[test]
+    fn test_stroke_flags() {
+        let cli = Cli::try_parse_from(["chaikin", "--stroke-opacity", "0.4", "--blend", "additive"]).unwrap();
+        assert_eq!(cli.stroke_opacity, 0.4);
+        assert_eq!(cli.blend, BlendMode::Additive);
+        assert_eq!(cli.point_glyph, Glyph::Circle);
+        assert_eq!(cli.stroke_width, 1.0);
+
+        assert_eq!(Cli::try_parse_from(["chaikin", "--stroke-width", "2.5"]).unwrap().stroke_width, 2.5);
+        assert!(Cli::try_parse_from(["chaikin", "--stroke-width", "8"]).is_err());
+
+        let cli = Cli::try_parse_from(["chaikin", "--point-glyph", "diamond"]).unwrap();
+        assert_eq!(cli.point_glyph, Glyph::Diamond);
+
+        assert!(Cli::try_parse_from(["chaikin", "--stroke-opacity", "1.5"]).is_err());
+        assert!(Cli::try_parse_from(["chaikin", "--blend", "multiply"]).is_err());
+    }
+
+    #[test]
+    fn test_color_flags() {
+        let cli = Cli::try_parse_from([
+            "chaikin", "--line-color", "00FFAA", "--background-color", "#102030", "--polygon-color", "808080",
+        ]).unwrap();
+        assert_eq!(cli.line_color, Some(0x00FFAA));
+        assert_eq!(cli.polygon_color, Some(0x808080));
+        assert_eq!(cli.background_color, Some(0x102030));
+        assert_eq!(cli.point_color, None);
+
+        assert!(Cli::try_parse_from(["chaikin", "--line-color", "0FA"]).is_err());
+        assert!(Cli::try_parse_from(["chaikin", "--line-color", "00FFAG"]).is_err());
+        assert!(Cli::try_parse_from(["chaikin", "--point-radius", "0"]).is_err());
+        assert!(Cli::try_parse_from(["chaikin", "--step-ms", "50"]).is_err());
+    }
+
+    #[test]
+    fn test_font_flags() {
+        let cli = Cli::try_parse_from(["chaikin", "--title-font", "800,87.5"]).unwrap();
+        assert_eq!(cli.text_font, FontAxes::REGULAR);
+        assert_eq!(cli.title_font, FontAxes { weight: 800.0, width: 87.5 });
+
+        assert!(Cli::try_parse_from(["chaikin", "--text-font", "50"]).is_err());
+    }
+
+    #[test]
+    fn test_refresh_rate_flag() {
+        assert_eq!(Cli::try_parse_from(["chaikin"]).unwrap().refresh_rate, None);
+        let cli = Cli::try_parse_from(["chaikin", "--refresh-rate", "143.9"]).unwrap();
+        assert_eq!(cli.refresh_rate, Some(143.9));
+        assert!(Cli::try_parse_from(["chaikin", "--refresh-rate", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_placement_flag() {
+        assert_eq!(Cli::try_parse_from(["chaikin"]).unwrap().placement, Placement::Click);
+        assert!(Cli::try_parse_from(["chaikin", "--ghost"]).unwrap().ghost);
+        assert!(Cli::try_parse_from(["chaikin", "--closed"]).unwrap().closed);
+        let cli = Cli::try_parse_from(["chaikin", "--placement", "freehand:8"]).unwrap();
+        assert_eq!(cli.placement, Placement::Freehand(8.0));
+        assert!(Cli::try_parse_from(["chaikin", "--placement", "repeat:fast"]).is_err());
+    }
+
+    #[test]
+    fn test_batch_command() {
+        let cli = Cli::try_parse_from([
+            "chaikin", "batch", "--in", "shapes", "--out", "smooth", "--steps", "3", "--scheme", "lr3",
+        ]).unwrap();
+        let Some(Command::Batch(batch)) = cli.command else {
+            panic!("expected the batch command");
+        };
+        assert_eq!(batch.input, PathBuf::from("shapes"));
+        assert_eq!(batch.output, PathBuf::from("smooth"));
+        assert_eq!(batch.steps, 3);
+        assert_eq!(batch.format, Format::Svg);
+        assert_eq!(cli.scheme, Scheme::LaneRiesenfeld(3));
+        assert_eq!(cli.resample, None);
+
+        let cli = Cli::try_parse_from(["chaikin", "batch", "--in", "a", "--out", "b", "--resample", "200"]).unwrap();
+        assert_eq!(cli.resample, Some(200));
+        assert!(Cli::try_parse_from(["chaikin", "batch", "--in", "a", "--out", "b", "--resample", "1"]).is_err());
+
+        let cli = Cli::try_parse_from(["chaikin", "batch.txt"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.file, Some(PathBuf::from("batch.txt")));
+    }
+
+    #[test]
+    fn test_atlas_command() {
+        let cli = Cli::try_parse_from(["chaikin", "atlas", "shape.txt", "--out", "sheet.png"]).unwrap();
+        let Some(Command::Atlas(atlas)) = cli.command else {
+            panic!("expected the atlas command");
+        };
+        assert_eq!(atlas.input, PathBuf::from("shape.txt"));
+        assert_eq!(atlas.output, PathBuf::from("sheet.png"));
+        assert_eq!((atlas.steps, atlas.cell, atlas.columns), (MAX_STEPS as u32, 256, 4));
+
+        assert!(Cli::try_parse_from(["chaikin", "atlas", "shape.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_lottie_command() {
+        let cli = Cli::try_parse_from([
+            "chaikin", "lottie", "shape.txt", "--out", "shape.json", "--fps", "60",
+        ]).unwrap();
+        let Some(Command::Lottie(lottie)) = cli.command else {
+            panic!("expected the lottie command");
+        };
+        assert_eq!(lottie.output, PathBuf::from("shape.json"));
+        assert_eq!(lottie.fps, 60);
+        assert!(Cli::try_parse_from(["chaikin", "lottie", "a.txt", "--out", "a.json", "--fps", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_report_command() {
+        let cli = Cli::try_parse_from([
+            "chaikin", "report", "shape.txt", "--out", "report.html",
+            "--variant", "chaikin:0.2,0.8", "--variant", "lr3",
+        ]).unwrap();
+        let Some(Command::Report(report)) = cli.command else {
+            panic!("expected the report command");
+        };
+        assert_eq!(report.variants, vec![
+            Variant { scheme: Scheme::Chaikin, ratios: Some((0.2, 0.8)) },
+            Variant { scheme: Scheme::LaneRiesenfeld(3), ratios: None },
+        ]);
+        assert_eq!(report.variants[0].label(), "chaikin 0.2,0.8");
+        assert!(parse_variant("bezier").is_err());
+        assert!(parse_variant("chaikin:0.8,0.2").is_err());
+        assert!(parse_variant("lr3:0.2,0.8").unwrap_err().contains("no ratios"));
+    }
+
+    #[test]
+    fn test_gif_command() {
+        let cli = Cli::try_parse_from(["chaikin", "gif", "shape.txt", "--out", "shape.gif", "--size", "200"]).unwrap();
+        let Some(Command::Gif(gif)) = cli.command else {
+            panic!("expected the gif command");
+        };
+        assert_eq!(gif.output, PathBuf::from("shape.gif"));
+        assert_eq!(gif.size, 200);
+        assert!(Cli::try_parse_from(["chaikin", "gif", "a.txt", "--out", "a.gif", "--size", "2"]).is_err());
+    }
+
+    #[test]
+    fn test_handout_command() {
+        let cli = Cli::try_parse_from(["chaikin", "handout", "shape.txt", "--out", "shape.pdf"]).unwrap();
+        let Some(Command::Handout(handout)) = cli.command else {
+            panic!("expected the handout command");
+        };
+        assert_eq!(handout.output, PathBuf::from("shape.pdf"));
+        assert_eq!(handout.steps, MAX_STEPS as u32);
+        assert!(Cli::try_parse_from(["chaikin", "handout", "a.txt", "--out", "a.pdf", "--steps", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_streaming_steps() {
+        let cli = Cli::try_parse_from(["chaikin", "--steps", "5"]).unwrap();
+        assert_eq!(cli.steps, Some(5));
+        assert!(cli.file.is_none());
+
+        let cli = Cli::try_parse_from(["chaikin", "-", "--steps", "2"]).unwrap();
+        assert_eq!(cli.file, Some(PathBuf::from("-")));
+    }
+
+    #[test]
+    fn test_headless_flag() {
+        let cli = Cli::try_parse_from(["chaikin", "a.txt", "--headless", "--out", "a.png", "--steps", "3"]).unwrap();
+        assert!(cli.headless);
+        assert_eq!(cli.out, Some(PathBuf::from("a.png")));
+        assert!(Cli::try_parse_from(["chaikin", "a.txt", "--headless"]).is_err());
+        assert!(Cli::try_parse_from(["chaikin", "--headless", "--out", "a.png"]).is_err());
+        assert!(Cli::try_parse_from(["chaikin", "a.txt", "--out", "a.png"]).is_err());
+    }
+
+    #[test]
+    fn test_watch_flag() {
+        let cli = Cli::try_parse_from(["chaikin", "--watch", "shape.txt"]).unwrap();
+        assert_eq!(cli.watch, Some(PathBuf::from("shape.txt")));
+        assert!(Cli::try_parse_from(["chaikin", "--watch", "a.txt", "b.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_demo_flags() {
+        let cli = Cli::try_parse_from(["chaikin", "--demo", "--seed", "1234"]).unwrap();
+        assert!(cli.demo);
+        assert_eq!(cli.seed, 1234);
+        assert!(Cli::try_parse_from(["chaikin", "--seed", "1"]).is_err());
+
+        let cli = Cli::try_parse_from(["chaikin", "--quiz", "--seed", "7"]).unwrap();
+        assert!(cli.quiz);
+        assert_eq!(cli.seed, 7);
+        assert!(Cli::try_parse_from(["chaikin", "--quiz", "--demo"]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_ratios() {
+        assert!(parse_ratios("0.25").is_err());
+        assert!(parse_ratios("0.75,0.25").is_err());
+        assert!(parse_ratios("0,1").is_err());
+        assert!(parse_ratios("a,b").is_err());
+    }
+
+    #[test]
+    fn test_minimum_size() {
+        assert!(Cli::try_parse_from(["chaikin", "--width", "100"]).is_err());
+        assert!(Cli::try_parse_from(["chaikin", "--height", "100"]).is_err());
+    }
+}
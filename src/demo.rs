@@ -0,0 +1,85 @@
+//! A self-running demo, for kiosk displays.
+//!
+//! Shapes are generated from a seed with our own small random number generator, rather than
+//! a crate's, so that a given seed produces the same shapes on every platform and version.
+
+use crate::types::Point;
+
+/// The fewest points a demo shape has
+const MIN_POINTS: usize = 5;
+/// The most points a demo shape has
+const MAX_POINTS: usize = 10;
+/// Part of the window left empty around the shapes
+const MARGIN: f32 = 0.1;
+
+/// Generates a sequence of random shapes, determined by the seed
+pub struct Demo {
+    rng: SplitMix64,
+}
+
+impl Demo {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: SplitMix64(seed) }
+    }
+
+    /// The next shape, fitting a window of the given size. The points go around the center
+    /// of the window at random distances, which makes for star-like shapes that show off
+    /// the smoothing well
+    pub fn next_shape(&mut self, width: usize, height: usize) -> Vec<Point> {
+        let count = MIN_POINTS + (self.rng.next_f32() * (MAX_POINTS - MIN_POINTS + 1) as f32) as usize;
+        let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+        let (rx, ry) = (cx * (1.0 - 2.0 * MARGIN), cy * (1.0 - 2.0 * MARGIN));
+        let offset = self.rng.next_f32() * std::f32::consts::TAU;
+
+        (0..count)
+            .map(|i| {
+                let angle = offset + i as f32 / count as f32 * std::f32::consts::TAU;
+                let radius = 0.3 + 0.7 * self.rng.next_f32();
+                Point::new(cx + rx * radius * angle.cos(), cy + ry * radius * angle.sin())
+            })
+            .collect()
+    }
+}
+
+/// The SplitMix64 generator: tiny, fast, and good enough for picking shapes
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in [0, 1)
+    fn next_f32(&mut self) -> f32 {
+        // The top 24 bits are exactly representable as an f32
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_shapes() {
+        let (mut a, mut b) = (Demo::new(42), Demo::new(42));
+        for _ in 0..3 {
+            assert_eq!(a.next_shape(800, 600), b.next_shape(800, 600));
+        }
+        assert_ne!(Demo::new(1).next_shape(800, 600), Demo::new(2).next_shape(800, 600));
+    }
+
+    #[test]
+    fn test_shapes_fit_the_window() {
+        let mut demo = Demo::new(7);
+        for _ in 0..20 {
+            let shape = demo.next_shape(800, 600);
+            assert!((MIN_POINTS..=MAX_POINTS).contains(&shape.len()));
+            assert!(shape.iter().all(|p| (0.0..800.0).contains(&p.x) && (0.0..600.0).contains(&p.y)));
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Reading and writing control points as plain text files.
+//!
+//! The format is one point per line, with the x and y coordinates separated by whitespace
+//! and/or a comma. Blank lines and lines starting with `#` are ignored, except for those
+//! that describe the shape, which start with a key and a colon:
+//!
+//! ```text
+//! # name: Star
+//! # notes: Smoothed into a blob in week 3
+//! # tags: closed, week 3
+//! 10 20
+//! 30 40
+//! ```
+//!
+//! Notes spanning several lines take a `# notes:` line each. A file can hold several
+//! polylines, each smoothed on its own, with a `# polyline` line before each of them:
+//!
+//! ```text
+//! # polyline
+//! 10 20
+//! 30 40
+//! # polyline
+//! 50 60
+//! 70 80
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::types::Point;
+
+/// What the user wrote about a shape, stored in its file along with the points
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metadata {
+    pub name: String,
+    /// Free text, which may span several lines
+    pub notes: String,
+    pub tags: Vec<String>,
+}
+
+/// The comment that starts each polyline of a file with several
+const POLYLINE_MARKER: &str = "polyline";
+
+/// A shape as stored in a file: its control points and what was written about it
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Document {
+    /// The points of the last polyline, the only one in most files
+    pub points: Vec<Point>,
+    /// The polylines before the last one, if the file has several
+    pub polylines: Vec<Vec<Point>>,
+    pub metadata: Metadata,
+}
+
+impl Document {
+    /// Every polyline, in the order they are stored, the last one included. A file without
+    /// points has none
+    pub fn into_polylines(self) -> Vec<Vec<Point>> {
+        let mut polylines = self.polylines;
+        if !self.points.is_empty() {
+            polylines.push(self.points);
+        }
+        polylines
+    }
+}
+
+/// Reads the points stored in the file at the given path
+pub fn read_points(path: &Path) -> io::Result<Vec<Point>> {
+    parse_points(BufReader::new(File::open(path)?))
+}
+
+/// Reads every polyline stored in the file at the given path
+pub fn read_polylines(path: &Path) -> io::Result<Vec<Vec<Point>>> {
+    parse_polylines(BufReader::new(File::open(path)?))
+}
+
+/// Reads the points stored in the file at the given path, with what was written about them
+pub fn read_document(path: &Path) -> io::Result<Document> {
+    parse_document(BufReader::new(File::open(path)?))
+}
+
+/// Writes the points to the file at the given path, replacing its contents
+pub fn write_points(path: &Path, points: &[Point]) -> io::Result<()> {
+    write_document(path, &Document { points: points.to_vec(), ..Document::default() })
+}
+
+/// Writes the polylines to the file at the given path, after what was written about them,
+/// replacing its contents
+pub fn write_document(path: &Path, document: &Document) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    format_metadata(&mut writer, &document.metadata)?;
+    let polylines: Vec<&[Point]> = document.polylines.iter().chain([&document.points]).map(Vec::as_slice).collect();
+    format_polylines(&mut writer, &polylines)?;
+    writer.flush()
+}
+
+/// Writes the lines that describe the shape, leaving out what is empty
+pub fn format_metadata(mut writer: impl Write, metadata: &Metadata) -> io::Result<()> {
+    if !metadata.name.is_empty() {
+        writeln!(writer, "# name: {}", metadata.name.replace('\n', " "))?;
+    }
+    for line in metadata.notes.lines() {
+        writeln!(writer, "# notes: {}", line)?;
+    }
+    if !metadata.tags.is_empty() {
+        writeln!(writer, "# tags: {}", metadata.tags.join(", "))?;
+    }
+    Ok(())
+}
+
+/// Splits a comma-separated list of tags, e.g. "closed, week 3"
+pub fn parse_tags(text: &str) -> Vec<String> {
+    text.split(',').map(str::trim).filter(|tag| !tag.is_empty()).map(String::from).collect()
+}
+
+/// Writes the points in the format understood by [`parse_points`]
+pub fn format_points(mut writer: impl Write, points: &[Point]) -> io::Result<()> {
+    for point in points {
+        writeln!(writer, "{} {}", point.x, point.y)?;
+    }
+    Ok(())
+}
+
+/// Writes the polylines in the format understood by [`parse_polylines`], each after a
+/// `# polyline` line. A lone polyline is written as plain points, as before there could be
+/// several
+pub fn format_polylines(mut writer: impl Write, polylines: &[impl AsRef<[Point]>]) -> io::Result<()> {
+    if let [polyline] = polylines {
+        return format_points(writer, polyline.as_ref());
+    }
+    for polyline in polylines {
+        writeln!(writer, "# {}", POLYLINE_MARKER)?;
+        format_points(&mut writer, polyline.as_ref())?;
+    }
+    Ok(())
+}
+
+/// Parses the points of the last polyline from any reader, see the module documentation
+/// for the format
+pub fn parse_points(reader: impl BufRead) -> io::Result<Vec<Point>> {
+    parse_document(reader).map(|document| document.points)
+}
+
+/// Parses every polyline from any reader, see the module documentation for the format
+pub fn parse_polylines(reader: impl BufRead) -> io::Result<Vec<Vec<Point>>> {
+    parse_document(reader).map(Document::into_polylines)
+}
+
+/// Parses points and what was written about them from any reader, see the module
+/// documentation for the format
+pub fn parse_document(reader: impl BufRead) -> io::Result<Document> {
+    let mut points = Vec::new();
+    let mut polylines = Vec::new();
+    let mut metadata = Metadata::default();
+    let mut notes = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix('#') {
+            if comment.trim() == POLYLINE_MARKER {
+                if !points.is_empty() {
+                    polylines.push(std::mem::take(&mut points));
+                }
+                continue;
+            }
+            match comment.trim_start().split_once(':') {
+                Some(("name", name)) => metadata.name = name.trim().to_string(),
+                Some(("notes", line)) => notes.push(line.trim().to_string()),
+                Some(("tags", tags)) => metadata.tags.extend(parse_tags(tags)),
+                // Any other comment
+                _ => {}
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: expected an \"x y\" pair, got {:?}", index + 1, line),
+            )
+        };
+
+        let mut coordinates = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<f32>().map_err(|_| invalid()));
+
+        match (coordinates.next(), coordinates.next(), coordinates.next()) {
+            (Some(x), Some(y), None) => points.push(Point::new(x?, y?)),
+            _ => return Err(invalid()),
+        }
+    }
+
+    metadata.notes = notes.join("\n");
+    Ok(Document { points, polylines, metadata })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_points() {
+        let input = "# a triangle\n10 20\n30.5, 40\n\n  50,60  \n";
+        let points = parse_points(input.as_bytes()).unwrap();
+
+        assert_eq!(points, vec![
+            Point::new(10.0, 20.0),
+            Point::new(30.5, 40.0),
+            Point::new(50.0, 60.0),
+        ]);
+    }
+
+    #[test]
+    fn test_format_points_round_trip() {
+        let points = vec![Point::new(1.5, -2.0), Point::new(300.0, 0.125)];
+        let mut text = Vec::new();
+        format_points(&mut text, &points).unwrap();
+
+        assert_eq!(parse_points(text.as_slice()).unwrap(), points);
+    }
+
+    #[test]
+    fn test_polylines_round_trip() {
+        let document = Document {
+            points: vec![Point::new(5.0, 6.0)],
+            polylines: vec![vec![Point::new(1.0, 2.0), Point::new(3.0, 4.0)]],
+            metadata: Metadata::default(),
+        };
+        let path = std::env::temp_dir().join(format!("chaikin-polylines-{}.txt", std::process::id()));
+        write_document(&path, &document).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "# polyline\n1 2\n3 4\n# polyline\n5 6\n");
+        assert_eq!(read_document(&path).unwrap(), document);
+        assert_eq!(read_polylines(&path).unwrap(), [document.polylines[0].clone(), document.points]);
+        std::fs::remove_file(&path).unwrap();
+
+        // A lone polyline reads as itself, and none as no polylines
+        assert_eq!(parse_polylines("1 2\n".as_bytes()).unwrap(), [vec![Point::new(1.0, 2.0)]]);
+        assert!(parse_polylines("# polyline\n".as_bytes()).unwrap().is_empty());
+        let mut text = Vec::new();
+        format_polylines(&mut text, &[vec![Point::new(1.0, 2.0)]]).unwrap();
+        assert_eq!(text, b"1 2\n");
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let metadata = Metadata {
+            name: "Star".to_string(),
+            notes: "Smoothed into a blob\nin week 3".to_string(),
+            tags: vec!["closed".to_string(), "week 3".to_string()],
+        };
+        let points = vec![Point::new(10.0, 20.0)];
+        let mut text = Vec::new();
+        format_metadata(&mut text, &metadata).unwrap();
+        format_points(&mut text, &points).unwrap();
+
+        let document = parse_document(text.as_slice()).unwrap();
+        assert_eq!(document, Document { points, polylines: Vec::new(), metadata });
+
+        // Other comments are still ignored
+        let document = parse_document("# a triangle\n#name: Triangle\n# see: notes\n1 2\n".as_bytes()).unwrap();
+        assert_eq!(document.metadata, Metadata { name: "Triangle".to_string(), ..Metadata::default() });
+    }
+
+    #[test]
+    fn test_parse_points_invalid_line() {
+        assert!(parse_points("10 20\n30\n".as_bytes()).is_err());
+        assert!(parse_points("10 20 30\n".as_bytes()).is_err());
+        assert!(parse_points("ten twenty\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Writing smoothed curves to files other applications understand.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::types::Point;
+use crate::units::Units;
+
+#[cfg(feature = "gui")]
+pub mod atlas;
+#[cfg(feature = "gui")]
+pub mod gif;
+pub mod html;
+pub mod lottie;
+pub mod pdf;
+#[cfg(feature = "gui")]
+pub mod png;
+pub mod svg;
+
+/// The file formats curves can be exported to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// An SVG image with the curve as a path
+    Svg,
+    /// The plain text points format, which can be opened again
+    Txt,
+}
+
+impl Format {
+    /// The file extension used for this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Svg => "svg",
+            Format::Txt => "txt",
+        }
+    }
+
+    /// Writes the curves, one per polyline, in this format. The units only size SVG images,
+    /// the points are always written in their own coordinates. Closed, the SVG paths go
+    /// back to their first point, which text files leave to whatever reads them
+    pub fn write(self, writer: impl Write, curves: &[Vec<Point>], units: Units, closed: bool) -> io::Result<()> {
+        match self {
+            Format::Svg => {
+                let layers: Vec<_> = curves
+                    .iter()
+                    .map(|curve| svg::Layer::new(curve, svg::CURVE_STROKE).closed(closed))
+                    .collect();
+                svg::write(writer, &layers, units)
+            }
+            Format::Txt => crate::document::format_polylines(writer, curves),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "svg" => Ok(Format::Svg),
+            "txt" => Ok(Format::Txt),
+            _ => Err(format!("unknown format {:?}, expected svg or txt", s)),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+/// The corners of the box around the points, or a unit box if there are none
+fn bounds<'a>(points: impl IntoIterator<Item = &'a Point>) -> (Point, Point) {
+    let mut points = points.into_iter();
+    let Some(first) = points.next() else {
+        return (Point::origin(), Point::new(1.0, 1.0));
+    };
+
+    points.fold((*first, *first), |(min, max), p| {
+        (Point::new(min.x.min(p.x), min.y.min(p.y)), Point::new(max.x.max(p.x), max.y.max(p.y)))
+    })
+}
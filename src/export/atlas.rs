@@ -0,0 +1,208 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::types::Point;
+use crate::algorithm::Smoother;
+use crate::window::{Frame, WindowManager};
+
+/// Space left around the shape in each cell, as a fraction of the cell size
+const CELL_MARGIN: f32 = 0.08;
+
+/// A sprite sheet holding one cell per animation step, for use as a flipbook in game
+/// engines. Cells are laid out left to right, then top to bottom
+pub struct Atlas {
+    /// The pixels of the whole sheet, as 0x00RRGGBB
+    pub pixels: Vec<u32>,
+    /// What the sheet holds and where, to be saved next to the image
+    pub metadata: AtlasMetadata,
+}
+
+/// Describes the layout of an [`Atlas`]
+#[derive(Serialize, Debug)]
+pub struct AtlasMetadata {
+    /// The file name of the sheet image, filled in when saving
+    pub image: String,
+    pub width: usize,
+    pub height: usize,
+    pub cell_width: usize,
+    pub cell_height: usize,
+    pub columns: usize,
+    pub rows: usize,
+    pub frames: Vec<AtlasFrame>,
+}
+
+/// Where a single step is in the sheet
+#[derive(Serialize, Debug)]
+pub struct AtlasFrame {
+    /// The smoothing step shown in this cell
+    pub step: usize,
+    pub x: usize,
+    pub y: usize,
+    /// The number of points of the curve at this step
+    pub points: usize,
+}
+
+impl Atlas {
+    /// Renders steps `0..steps` of the smoothed polylines into cells of the given size.
+    /// The polylines are scaled to fit the cells, whatever their original coordinates.
+    /// Closed, the smoother should be too
+    pub fn render(
+        polylines: &[Vec<Point>],
+        smoother: Box<dyn Smoother>,
+        steps: usize,
+        cell_size: (usize, usize),
+        columns: usize,
+        closed: bool,
+    ) -> Self {
+        let (cell_width, cell_height) = cell_size;
+        let columns = columns.clamp(1, steps.max(1));
+        let rows = steps.div_ceil(columns);
+        let (width, height) = (cell_width * columns, cell_height * rows);
+
+        let fitted = fit_polylines(polylines, cell_width, cell_height);
+        // The points of every polyline's curve at each step
+        let counts: Vec<usize> = (0..steps)
+            .map(|step| fitted.iter().map(|polyline| smoother.get_step_points(polyline, step).len()).sum())
+            .collect();
+        let mut manager = WindowManager::offscreen(cell_width, cell_height);
+        // Before the smoother, which would otherwise be opened to match the window
+        manager.set_closed(closed);
+        manager.set_smoother(smoother);
+        manager.set_max_steps(steps);
+        manager.set_hud(false);
+        manager.set_polylines(fitted);
+
+        let mut pixels = vec![0; width * height];
+        let mut frames = Vec::with_capacity(steps);
+        for (step, points) in counts.into_iter().enumerate() {
+            let (x, y) = ((step % columns) * cell_width, (step / columns) * cell_height);
+            manager.show_step(step);
+            blit(&mut pixels, width, manager.frame(), x, y);
+
+            frames.push(AtlasFrame { step, x, y, points });
+        }
+
+        Self {
+            pixels,
+            metadata: AtlasMetadata {
+                image: String::new(),
+                width,
+                height,
+                cell_width,
+                cell_height,
+                columns,
+                rows,
+                frames,
+            },
+        }
+    }
+
+    /// The sheet, as a frame that can be saved with the PNG exporter
+    pub fn frame(&self) -> Frame<'_> {
+        Frame { pixels: &self.pixels, width: self.metadata.width, height: self.metadata.height }
+    }
+
+    /// Saves the sheet as a PNG image, with the metadata next to it in a `.json` file of
+    /// the same name
+    pub fn save(&mut self, path: &Path) -> io::Result<()> {
+        self.metadata.image = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        super::png::save(path, self.frame())?;
+
+        let mut writer = BufWriter::new(File::create(path.with_extension("json"))?);
+        self.write_metadata(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes the metadata as JSON
+    pub fn write_metadata(&self, writer: impl Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(writer, &self.metadata).map_err(io::Error::other)
+    }
+}
+
+/// Copies a frame into the sheet, with its top left corner at the given position
+fn blit(pixels: &mut [u32], width: usize, frame: Frame, x: usize, y: usize) {
+    for (row, line) in frame.pixels.chunks(frame.width).enumerate() {
+        let start = (y + row) * width + x;
+        pixels[start..start + frame.width].copy_from_slice(line);
+    }
+}
+
+/// Scales and moves the polylines together so that they fill a cell of the given size,
+/// keeping their aspect ratio and leaving a margin around them
+pub(super) fn fit_polylines(polylines: &[Vec<Point>], width: usize, height: usize) -> Vec<Vec<Point>> {
+    let (min, max) = super::bounds(polylines.iter().flatten());
+
+    let (width, height) = (width as f32, height as f32);
+    let available = (width * (1.0 - 2.0 * CELL_MARGIN), height * (1.0 - 2.0 * CELL_MARGIN));
+    let size = (max.x - min.x, max.y - min.y);
+    let scale = [available.0 / size.0, available.1 / size.1]
+        .into_iter()
+        .filter(|s| s.is_finite())
+        .fold(f32::INFINITY, f32::min);
+    // All the points are in the same place
+    let scale = if scale.is_finite() { scale } else { 1.0 };
+
+    let center = Point::new((min.x + max.x) / 2.0, (min.y + max.y) / 2.0);
+    let fit = |p: &Point| Point::new(width / 2.0 + (p.x - center.x) * scale, height / 2.0 + (p.y - center.y) * scale);
+    polylines.iter().map(|points| points.iter().map(fit).collect()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::ChaikinAlgorithm;
+
+    #[test]
+    fn test_atlas_layout() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(5000.0, 8000.0), Point::new(10000.0, 0.0)];
+        let atlas = Atlas::render(&[points], Box::new(ChaikinAlgorithm::new()), 5, (64, 48), 2, false);
+
+        let metadata = &atlas.metadata;
+        assert_eq!((metadata.columns, metadata.rows), (2, 3));
+        assert_eq!((metadata.width, metadata.height), (128, 144));
+        assert_eq!(atlas.pixels.len(), 128 * 144);
+        assert_eq!(metadata.frames.len(), 5);
+        assert_eq!((metadata.frames[3].x, metadata.frames[3].y), (64, 48));
+        assert_eq!(metadata.frames[0].points, 3);
+        assert_eq!(metadata.frames[1].points, 6);
+
+        // Every cell has something drawn in it, even though the points were far outside
+        for frame in &metadata.frames {
+            let drawn = (0..48).any(|y| {
+                let start = (frame.y + y) * metadata.width + frame.x;
+                atlas.pixels[start..start + 64].iter().any(|pixel| *pixel != 0)
+            });
+            assert!(drawn, "step {} is empty", frame.step);
+        }
+    }
+
+    #[test]
+    fn test_closed_atlas() {
+        let square = vec![Point::new(0.0, 0.0), Point::new(100.0, 0.0), Point::new(100.0, 100.0), Point::new(0.0, 100.0)];
+        let smoother = Box::new(ChaikinAlgorithm::new().closed(true));
+        let atlas = Atlas::render(&[square], smoother, 2, (64, 64), 2, true);
+
+        // The closing side, from the bottom left corner up to the top left one, is drawn
+        // in the first cell, and the corners it ends at are cut in the second
+        let width = atlas.metadata.width;
+        let drawn = |x: usize, y: usize| (x.saturating_sub(1)..=x + 1).any(|x| atlas.pixels[y * width + x] != 0);
+        let left = (64.0 * CELL_MARGIN).round() as usize;
+        assert!(drawn(left, 32), "the first step is missing its closing side");
+        assert!(drawn(64 + left, 32), "the second step is missing its closing side");
+    }
+
+    #[test]
+    fn test_fit_polylines() {
+        let polylines = vec![vec![Point::new(-10.0, -10.0)], vec![Point::new(10.0, 10.0)]];
+        let fitted = fit_polylines(&polylines, 100, 200);
+        assert!((fitted[0][0].x - 8.0).abs() < 1e-4);
+        assert!((fitted[1][0].x - 92.0).abs() < 1e-4);
+        assert!((fitted[0][0].y - 58.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,141 @@
+//! The smoothing animation as an animated GIF, e.g. for slides and chat messages.
+//!
+//! The frames are drawn offscreen, one after the other, rather than grabbed from the window
+//! as it animates: the recording doesn't depend on how fast the machine draws.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use gif::{Encoder, Repeat};
+
+use crate::algorithm::Smoother;
+use crate::types::Point;
+use crate::window::WindowManager;
+
+/// How the animation is played
+pub struct Timing {
+    /// How long each step is shown before morphing into the next, in hundredths of a second
+    pub hold: u16,
+    /// How many frames the morph between two steps takes
+    pub morph_frames: u16,
+    /// How long each frame of the morph is shown, in hundredths of a second
+    pub morph_delay: u16,
+}
+
+impl Default for Timing {
+    /// A step a second, as in the window
+    fn default() -> Self {
+        Self { hold: 50, morph_frames: 10, morph_delay: 5 }
+    }
+}
+
+/// The frames of the animation, each with how long it is shown
+pub struct Recording {
+    pub width: usize,
+    pub height: usize,
+    /// The pixels of each frame, as 0x00RRGGBB, and its delay in hundredths of a second
+    pub frames: Vec<(Vec<u32>, u16)>,
+}
+
+impl Recording {
+    /// Draws steps `0..steps` of the smoothed polylines, each held then morphing into the
+    /// next one, except for the last step, after which the animation starts over. The
+    /// polylines are scaled to fit the frames, whatever their original coordinates. Closed,
+    /// the smoother should be too
+    pub fn render(
+        polylines: &[Vec<Point>],
+        smoother: Box<dyn Smoother>,
+        steps: usize,
+        size: (usize, usize),
+        timing: &Timing,
+        closed: bool,
+    ) -> Self {
+        let (width, height) = size;
+        let mut manager = WindowManager::offscreen(width, height);
+        manager.set_smoother(smoother);
+        if closed {
+            manager.set_closed(true);
+        }
+        manager.set_max_steps(steps);
+        manager.set_hud(false);
+        manager.set_polylines(super::atlas::fit_polylines(polylines, width, height));
+
+        let mut frames = Vec::new();
+        for step in 0..steps {
+            manager.show_step(step);
+            frames.push((manager.frame().pixels.to_vec(), timing.hold));
+            if step + 1 == steps {
+                break;
+            }
+            for frame in 1..timing.morph_frames {
+                manager.show_step_tween(step, frame as f32 / timing.morph_frames as f32);
+                frames.push((manager.frame().pixels.to_vec(), timing.morph_delay));
+            }
+        }
+        Self { width, height, frames }
+    }
+
+    /// Saves the animation as a GIF file
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Encodes the animation as a GIF that loops forever. Each frame gets its own palette
+    pub fn write(&self, writer: impl Write) -> io::Result<()> {
+        let (width, height) = (self.width as u16, self.height as u16);
+        let mut encoder = Encoder::new(writer, width, height, &[]).map_err(io::Error::other)?;
+        encoder.set_repeat(Repeat::Infinite).map_err(io::Error::other)?;
+
+        for (pixels, delay) in &self.frames {
+            let rgb: Vec<u8> = pixels
+                .iter()
+                .flat_map(|pixel| {
+                    let [_, r, g, b] = pixel.to_be_bytes();
+                    [r, g, b]
+                })
+                .collect();
+            let mut frame = gif::Frame::from_rgb_speed(width, height, &rgb, 10);
+            frame.delay = *delay;
+            encoder.write_frame(&frame).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::ChaikinAlgorithm;
+
+    #[test]
+    fn test_record_morph() {
+        let points = [vec![Point::new(0.0, 100.0), Point::new(50.0, 0.0), Point::new(100.0, 100.0)]];
+        let timing = Timing { hold: 50, morph_frames: 4, morph_delay: 5 };
+        let recording = Recording::render(&points, Box::new(ChaikinAlgorithm::new()), 3, (64, 48), &timing, false);
+
+        // Each step, with 3 frames of morph between the steps
+        assert_eq!(recording.frames.len(), 3 + 2 * 3);
+        assert_eq!(recording.frames[0].1, 50);
+        assert_eq!(recording.frames[1].1, 5);
+        // The morph moves the curve from one step to the next
+        assert_ne!(recording.frames[0].0, recording.frames[2].0);
+        assert_ne!(recording.frames[2].0, recording.frames[4].0);
+
+        let mut encoded = Vec::new();
+        recording.write(&mut encoded).unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(encoded.as_slice()).unwrap();
+        let mut decoded = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            decoded += 1;
+        }
+        assert_eq!(decoded, recording.frames.len());
+
+        // Closed, the curve goes back to its first point
+        let closed = Box::new(ChaikinAlgorithm::new().closed(true));
+        let loop_recording = Recording::render(&points, closed, 3, (64, 48), &timing, true);
+        assert_ne!(loop_recording.frames[0].0, recording.frames[0].0);
+    }
+}
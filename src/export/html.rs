@@ -0,0 +1,131 @@
+//! A report comparing several ways of smoothing the same points, as a single HTML page that
+//! can be mailed or opened offline: the figures in a table, then a drawing of each curve.
+
+use std::io::{self, Write};
+
+use crate::types::Point;
+use crate::numbers::NumberFormat;
+use crate::units::Units;
+
+use super::svg::{self, CURVE_STROKE};
+
+/// Stroke color of the control polygon behind each curve
+const POLYGON_STROKE: u32 = 0xAAAAAA;
+
+/// One way of smoothing the points, with the figures of its curve
+pub struct Variant<'a> {
+    /// What the variant is, e.g. the scheme and its ratios
+    pub label: String,
+    /// The smoothed points, a curve per polyline
+    pub curves: &'a [Vec<Point>],
+    /// The length of the curve, in the points' coordinates
+    pub length: f32,
+    /// The largest distance from a point of the curve to the control polygon, in the points'
+    /// coordinates
+    pub max_deviation: f32,
+    /// How long smoothing took, in milliseconds
+    pub elapsed_ms: f64,
+}
+
+/// Writes the report on smoothing the polylines with each variant, under the title, with
+/// the lengths in the given units and the figures in the given format. Closed, the polygons
+/// and the curves are drawn back to their first point
+pub fn write(
+    mut writer: impl Write,
+    title: &str,
+    polylines: &[Vec<Point>],
+    variants: &[Variant],
+    units: Units,
+    numbers: &NumberFormat,
+    closed: bool,
+) -> io::Result<()> {
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, r#"<html lang="en"><head><meta charset="utf-8"><title>{}</title>"#, escape(title))?;
+    writeln!(
+        writer,
+        "<style>body {{ font-family: sans-serif; margin: 2em; }} \
+         table {{ border-collapse: collapse; }} \
+         th, td {{ border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: right; }} \
+         th:first-child, td:first-child {{ text-align: left; }} \
+         figure {{ display: inline-block; margin: 1em; }} \
+         svg {{ width: 320px; height: auto; }}</style>"
+    )?;
+    writeln!(writer, "</head><body>")?;
+    writeln!(writer, "<h1>{}</h1>", escape(title))?;
+    let points: usize = polylines.iter().map(Vec::len).sum();
+    writeln!(writer, "<p>{} control points, {} variants.</p>", points, variants.len())?;
+
+    writeln!(writer, "<table>")?;
+    writeln!(
+        writer,
+        "<tr><th>Variant</th><th>Points</th><th>Length ({unit})</th><th>Max deviation ({unit})</th><th>Time (ms)</th></tr>",
+        unit = units.unit,
+    )?;
+    for variant in variants {
+        writeln!(
+            writer,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(&variant.label),
+            variant.curves.iter().map(Vec::len).sum::<usize>(),
+            numbers.format(units.length(variant.length), 1),
+            numbers.format(units.length(variant.max_deviation), 2),
+            numbers.format(variant.elapsed_ms as f32, 3),
+        )?;
+    }
+    writeln!(writer, "</table>")?;
+
+    for variant in variants {
+        let polygons = polylines.iter().map(|points| svg::Layer::new(points, POLYGON_STROKE).closed(closed));
+        let curves = variant.curves.iter().map(|curve| svg::Layer::new(curve, CURVE_STROKE).closed(closed));
+        let layers: Vec<_> = polygons.chain(curves).collect();
+        let mut image = Vec::new();
+        svg::write(&mut image, &layers, units)?;
+        // The XML declaration has no place inside an HTML page
+        let image = String::from_utf8_lossy(&image);
+        let image = image.lines().filter(|line| !line.starts_with("<?xml")).collect::<Vec<_>>().join("\n");
+        writeln!(writer, "<figure>\n{}\n<figcaption>{}</figcaption>\n</figure>", image, escape(&variant.label))?;
+    }
+
+    writeln!(writer, "</body></html>")
+}
+
+/// Escapes the characters that would be read as markup
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_report() {
+        let points = vec![vec![Point::new(0.0, 0.0), Point::new(100.0, 100.0), Point::new(200.0, 0.0)]];
+        let curve = vec![vec![Point::new(50.0, 50.0), Point::new(150.0, 50.0)]];
+        let variants = [
+            Variant { label: "chaikin 0.25,0.75".to_string(), curves: &curve, length: 100.0, max_deviation: 0.0, elapsed_ms: 0.01 },
+            Variant { label: "<lr3>".to_string(), curves: &points, length: 282.8, max_deviation: 0.0, elapsed_ms: 0.02 },
+        ];
+        let mut output = Vec::new();
+        write(&mut output, "shape.txt", &points, &variants, Units::default(), &NumberFormat::default(), false).unwrap();
+
+        let html = String::from_utf8(output).unwrap();
+        assert!(html.contains("<th>Length (px)</th>"));
+        assert!(html.contains("<td>chaikin 0.25,0.75</td><td>2</td><td>100.0</td>"));
+
+        let mut output = Vec::new();
+        let german = NumberFormat::for_locale("de_DE");
+        write(&mut output, "shape.txt", &points, &variants, "4/mm".parse().unwrap(), &german, false).unwrap();
+        let html = String::from_utf8(output).unwrap();
+        assert!(html.contains("<td>chaikin 0.25,0.75</td><td>2</td><td>25,0</td>"));
+        assert!(html.contains("<figcaption>&lt;lr3&gt;</figcaption>"));
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert!(!html.contains("<?xml"));
+        assert!(!html.contains(" Z"));
+
+        // Closed, the polygon and each curve are drawn back to their first point
+        let mut output = Vec::new();
+        write(&mut output, "shape.txt", &points, &variants, Units::default(), &NumberFormat::default(), true).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap().matches(" Z").count(), 4);
+    }
+}
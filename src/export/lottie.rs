@@ -0,0 +1,237 @@
+//! The smoothing animation as a Lottie file, which web and mobile players can show without
+//! a video.
+//!
+//! The curves are a single shape layer with a path per polyline, keyframed with each step,
+//! so players morph from one step to the next. A path keeps the same vertices in all its
+//! keyframes, so every step is resampled to as many vertices as the last one has.
+
+use std::io::{self, Write};
+
+use serde_json::{json, Value};
+
+use crate::types::Point;
+
+use super::svg::CURVE_STROKE;
+
+/// Space left around the drawing, in the curve's units
+const MARGIN: f32 = 10.0;
+/// Width of the curve, in the curve's units
+const STROKE_WIDTH: f32 = 2.0;
+
+/// How the animation is played
+pub struct Timing {
+    /// Frames per second
+    pub frame_rate: u32,
+    /// How many frames each step is shown before morphing into the next
+    pub hold_frames: u32,
+    /// How many frames the morph between two steps takes
+    pub morph_frames: u32,
+}
+
+impl Default for Timing {
+    /// A step a second, as in the window
+    fn default() -> Self {
+        Self { frame_rate: 30, hold_frames: 15, morph_frames: 15 }
+    }
+}
+
+/// Writes the steps, each with a curve per polyline, as a Lottie animation that goes
+/// through them in order, then back to the first one, so it loops without a jump. The
+/// paths are closed from their last point back to their first if `closed`
+pub fn write(writer: impl Write, steps: &[Vec<Vec<Point>>], timing: &Timing, closed: bool) -> io::Result<()> {
+    serde_json::to_writer(writer, &animation(steps, timing, closed)).map_err(io::Error::other)
+}
+
+/// The animation as a Lottie document
+fn animation(steps: &[Vec<Vec<Point>>], timing: &Timing, closed: bool) -> Value {
+    let (min, max) = super::bounds(steps.iter().flatten().flatten());
+    let offset = Point::new(min.x - MARGIN, min.y - MARGIN);
+    let (width, height) = (max.x - min.x + 2.0 * MARGIN, max.y - min.y + 2.0 * MARGIN);
+
+    let step_frames = timing.hold_frames + timing.morph_frames;
+    let duration = step_frames * steps.len() as u32;
+
+    // A path per polyline, then the stroke of them all
+    let polylines = steps.first().map_or(0, Vec::len);
+    let mut shapes: Vec<Value> = (0..polylines)
+        .map(|polyline| {
+            let curves: Vec<&[Point]> = steps.iter().map(|curves| curves[polyline].as_slice()).collect();
+            json!({ "ty": "sh", "nm": "Path", "ks": { "a": 1, "k": keyframes(&curves, offset, timing, closed) } })
+        })
+        .collect();
+    shapes.push(json!({
+        "ty": "st",
+        "nm": "Stroke",
+        "c": { "a": 0, "k": color(CURVE_STROKE) },
+        "o": { "a": 0, "k": 100 },
+        "w": { "a": 0, "k": STROKE_WIDTH },
+        "lc": 2,
+        "lj": 2
+    }));
+
+    json!({
+        "v": "5.7.0",
+        "fr": timing.frame_rate,
+        "ip": 0,
+        "op": duration,
+        "w": width.ceil(),
+        "h": height.ceil(),
+        "nm": "Chaikin",
+        "ddd": 0,
+        "assets": [],
+        "layers": [{
+            "ddd": 0,
+            "ind": 1,
+            "ty": 4,
+            "nm": "Curve",
+            "sr": 1,
+            "ks": {
+                "o": { "a": 0, "k": 100 },
+                "r": { "a": 0, "k": 0 },
+                "p": { "a": 0, "k": [0, 0, 0] },
+                "a": { "a": 0, "k": [0, 0, 0] },
+                "s": { "a": 0, "k": [100, 100, 100] }
+            },
+            "ao": 0,
+            "shapes": shapes,
+            "ip": 0,
+            "op": duration,
+            "st": 0,
+            "bm": 0
+        }]
+    })
+}
+
+/// The keyframes of a polyline's path, from its curve at each step. Each step is held,
+/// then morphs into the next one. The last step morphs back into the first, which is
+/// repeated at the very end
+fn keyframes(curves: &[&[Point]], offset: Point, timing: &Timing, closed: bool) -> Vec<Value> {
+    let vertices = curves.iter().map(|curve| curve.len()).max().unwrap_or(0);
+    let step_frames = timing.hold_frames + timing.morph_frames;
+    let mut keyframes = Vec::new();
+    for (i, curve) in curves.iter().chain(curves.first()).enumerate() {
+        let path = path(&resample(curve, vertices), offset, closed);
+        let start = i as u32 * step_frames;
+        keyframes.push(keyframe(start, &path));
+        if i < curves.len() {
+            keyframes.push(keyframe(start + timing.hold_frames, &path));
+        }
+    }
+    keyframes
+}
+
+/// A keyframe of the path, eased in and out
+fn keyframe(frame: u32, path: &Value) -> Value {
+    json!({
+        "t": frame,
+        "s": [path],
+        "i": { "x": [0.5], "y": [1] },
+        "o": { "x": [0.5], "y": [0] }
+    })
+}
+
+/// A Lottie path through the points, closed or not. The segments are straight, so the
+/// tangents are zero
+fn path(points: &[Point], offset: Point, closed: bool) -> Value {
+    let vertices: Vec<[f32; 2]> = points.iter().map(|p| [p.x - offset.x, p.y - offset.y]).collect();
+    let tangents = vec![[0.0f32; 2]; vertices.len()];
+    json!({ "c": closed, "v": vertices, "i": tangents, "o": tangents })
+}
+
+/// Color components between 0 and 1, as Lottie wants them
+fn color(rgb: u32) -> [f32; 4] {
+    let channel = |shift: u32| ((rgb >> shift) & 0xFF) as f32 / 255.0;
+    [channel(16), channel(8), channel(0), 1.0]
+}
+
+/// Places `count` points along the polyline, evenly spaced by length, keeping its ends
+fn resample(points: &[Point], count: usize) -> Vec<Point> {
+    match points {
+        [] => return Vec::new(),
+        [only] => return vec![*only; count],
+        _ => {}
+    }
+    if count < 2 {
+        return points[..count].to_vec();
+    }
+
+    let lengths: Vec<f32> = points.windows(2).map(|s| (s[1] - s[0]).norm()).collect();
+    let total: f32 = lengths.iter().sum();
+    if total == 0.0 {
+        return vec![points[0]; count];
+    }
+
+    let mut resampled = Vec::with_capacity(count);
+    let (mut segment, mut walked) = (0, 0.0);
+    for i in 0..count {
+        let target = total * i as f32 / (count - 1) as f32;
+        while segment < lengths.len() - 1 && walked + lengths[segment] < target {
+            walked += lengths[segment];
+            segment += 1;
+        }
+        let t = if lengths[segment] == 0.0 {
+            0.0
+        } else {
+            ((target - walked) / lengths[segment]).clamp(0.0, 1.0)
+        };
+        resampled.push(points[segment] + (points[segment + 1] - points[segment]) * t);
+    }
+    resampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0)];
+        let resampled = resample(&points, 5);
+        assert_eq!(resampled.len(), 5);
+        assert_eq!(resampled[0], points[0]);
+        assert!((resampled[2] - points[1]).norm() < 1e-4);
+        assert!((resampled[3] - Point::new(10.0, 5.0)).norm() < 1e-4);
+        assert_eq!(resampled[4], points[2]);
+    }
+
+    #[test]
+    fn test_animation() {
+        let steps = vec![
+            vec![vec![Point::new(0.0, 0.0), Point::new(50.0, 50.0), Point::new(100.0, 0.0)]],
+            vec![vec![
+                Point::new(12.5, 12.5),
+                Point::new(37.5, 37.5),
+                Point::new(62.5, 37.5),
+                Point::new(87.5, 12.5),
+            ]],
+        ];
+        let timing = Timing::default();
+        let animation = animation(&steps, &timing, false);
+
+        assert_eq!(animation["op"], 60);
+        assert_eq!(animation["w"], 120.0);
+        let keyframes = animation["layers"][0]["shapes"][0]["ks"]["k"].as_array().unwrap();
+        // Held and morphing keyframes for both steps, then the first step again
+        assert_eq!(keyframes.len(), 5);
+        assert_eq!(keyframes[1]["t"], 15);
+        assert_eq!(keyframes[4]["t"], 60);
+        for keyframe in keyframes {
+            assert_eq!(keyframe["s"][0]["v"].as_array().unwrap().len(), 4);
+        }
+        // Shifted by the margin
+        assert_eq!(keyframes[0]["s"][0]["v"][0], json!([10.0, 10.0]));
+        assert_eq!(keyframes[0]["s"][0]["c"], false);
+
+        // Closed shapes have closed paths at every step
+        let closed = super::animation(&steps, &timing, true);
+        for keyframe in closed["layers"][0]["shapes"][0]["ks"]["k"].as_array().unwrap() {
+            assert_eq!(keyframe["s"][0]["c"], true);
+        }
+
+        // A path per polyline, stroked alike
+        let two: Vec<Vec<Vec<Point>>> = steps.iter().map(|curves| vec![curves[0].clone(), curves[0].clone()]).collect();
+        let shapes = super::animation(&two, &timing, false)["layers"][0]["shapes"].clone();
+        let types: Vec<&str> = shapes.as_array().unwrap().iter().map(|shape| shape["ty"].as_str().unwrap()).collect();
+        assert_eq!(types, ["sh", "sh", "st"]);
+    }
+}
@@ -0,0 +1,215 @@
+//! A printable handout of the smoothing, as a PDF document with one step per page, e.g. for
+//! teachers to hand out the construction they showed on screen.
+//!
+//! The document only uses what every PDF reader has built in: lines, and captions in
+//! Helvetica, so no font is embedded and the file stays small.
+
+use std::io::{self, Write};
+
+use crate::types::Point;
+
+use super::svg::CURVE_STROKE;
+
+/// A4 in points, portrait
+const PAGE_SIZE: (f32, f32) = (595.0, 842.0);
+/// Space left around the drawing and the caption, in points
+const MARGIN: f32 = 50.0;
+/// Size of the captions, in points
+const CAPTION_SIZE: f32 = 14.0;
+/// Gray level of the control polygon, behind the curve
+const POLYGON_GRAY: f32 = 0.6;
+
+/// Writes a page per step, each with the control polygons faintly behind the curves of the
+/// step, one per polyline, and a caption. All the pages share a scale, so that the steps can
+/// be compared
+pub fn write(writer: impl Write, polylines: &[Vec<Point>], steps: &[Vec<Vec<Point>>], closed: bool) -> io::Result<()> {
+    let pages: Vec<_> = steps
+        .iter()
+        .enumerate()
+        .map(|(i, curves)| (step_caption(i, steps.len(), point_count(curves)), curves.as_slice()))
+        .collect();
+    write_pages(writer, polylines, &pages, closed)
+}
+
+/// Writes a single page with the control polygons faintly behind the curves of the given
+/// step, one per polyline, e.g. to print what the window shows
+pub fn write_step(
+    writer: impl Write,
+    polylines: &[Vec<Point>],
+    curves: &[Vec<Point>],
+    step: usize,
+    closed: bool,
+) -> io::Result<()> {
+    let caption = match step {
+        0 => format!("The control polygon, {} points", point_count(curves)),
+        _ => format!("Step {}: {} points", step, point_count(curves)),
+    };
+    write_pages(writer, polylines, &[(caption, curves)], closed)
+}
+
+/// How many points the polylines have in all
+fn point_count(polylines: &[Vec<Point>]) -> usize {
+    polylines.iter().map(Vec::len).sum()
+}
+
+/// Writes a page per captioned step, with its curves over the control polygons, all at the
+/// same scale
+fn write_pages(
+    mut writer: impl Write,
+    polylines: &[Vec<Point>],
+    pages: &[(String, &[Vec<Point>])],
+    closed: bool,
+) -> io::Result<()> {
+    let (min, max) = super::bounds(polylines.iter().chain(pages.iter().flat_map(|(_, curves)| *curves)).flatten());
+    let (width, height) = (PAGE_SIZE.0 - 2.0 * MARGIN, PAGE_SIZE.1 - 3.0 * MARGIN);
+    let scale = (width / (max.x - min.x).max(1.0)).min(height / (max.y - min.y).max(1.0));
+    // Centered below the caption, upside down since the pages' y axis goes up
+    let left = MARGIN + (width - (max.x - min.x) * scale) / 2.0;
+    let top = PAGE_SIZE.1 - 2.0 * MARGIN - (height - (max.y - min.y) * scale) / 2.0;
+    let to_page = |p: &Point| ((p.x - min.x) * scale + left, top - (p.y - min.y) * scale);
+
+    let (r, g, b) = ((CURVE_STROKE >> 16) & 0xFF, (CURVE_STROKE >> 8) & 0xFF, CURVE_STROKE & 0xFF);
+    let pages: Vec<String> = pages
+        .iter()
+        .map(|(text, curves)| {
+            let mut content = String::new();
+            content += &caption(text);
+            content += &format!("{} G 0.5 w\n", POLYGON_GRAY);
+            for points in polylines {
+                content += &polyline(points.iter().map(to_page), closed);
+            }
+            content += &format!("{:.3} {:.3} {:.3} RG 1.5 w\n", r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+            for curve in *curves {
+                content += &polyline(curve.iter().map(to_page), closed);
+            }
+            content
+        })
+        .collect();
+
+    let mut document = Vec::new();
+    write_document(&mut document, &pages)?;
+    writer.write_all(&document)
+}
+
+/// The caption of a step, e.g. "Step 2 of 5: 16 points"
+fn step_caption(step: usize, steps: usize, points: usize) -> String {
+    let last = steps.saturating_sub(1);
+    match step {
+        0 => format!("Step 0 of {}: the control polygon, {} points", last, points),
+        _ => format!("Step {} of {}: {} points", step, last, points),
+    }
+}
+
+/// The operators that write the text at the top of the page
+fn caption(text: &str) -> String {
+    let escaped = text.replace('\\', "\\\\").replace('(', "\\(").replace(')', "\\)");
+    format!(
+        "BT /F1 {} Tf {} {} Td ({}) Tj ET\n",
+        CAPTION_SIZE,
+        MARGIN,
+        PAGE_SIZE.1 - MARGIN,
+        escaped
+    )
+}
+
+/// The operators that stroke the polyline, in page coordinates
+fn polyline(points: impl Iterator<Item = (f32, f32)>, closed: bool) -> String {
+    let mut path = String::new();
+    for (i, (x, y)) in points.enumerate() {
+        let operator = if i == 0 { "m" } else { "l" };
+        path += &format!("{:.2} {:.2} {}\n", x, y, operator);
+    }
+    if path.is_empty() {
+        return path;
+    }
+    path + if closed { "s\n" } else { "S\n" }
+}
+
+/// Writes the document: a catalog, the page tree, the font, then each page followed by its
+/// content, and the table of where each object starts
+fn write_document(document: &mut Vec<u8>, pages: &[String]) -> io::Result<()> {
+    let kids: Vec<String> = (0..pages.len()).map(|i| format!("{} 0 R", 4 + 2 * i)).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PAGE_SIZE.0,
+            PAGE_SIZE.1,
+            5 + 2 * i
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}endstream", content.len(), content));
+    }
+
+    writeln!(document, "%PDF-1.4")?;
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(document.len());
+        writeln!(document, "{} 0 obj\n{}\nendobj", i + 1, object)?;
+    }
+
+    let xref = document.len();
+    writeln!(document, "xref\n0 {}", objects.len() + 1)?;
+    writeln!(document, "0000000000 65535 f ")?;
+    for offset in offsets {
+        writeln!(document, "{:010} 00000 n ", offset)?;
+    }
+    writeln!(document, "trailer\n<< /Size {} /Root 1 0 R >>", objects.len() + 1)?;
+    write!(document, "startxref\n{}\n%%EOF\n", xref)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_pages() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(100.0, 100.0), Point::new(200.0, 0.0)];
+        let steps = vec![vec![points.clone()], vec![vec![Point::new(0.0, 0.0), Point::new(200.0, 0.0)]]];
+        let mut output = Vec::new();
+        write(&mut output, &[points], &steps, false).unwrap();
+
+        let pdf = String::from_utf8(output).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.contains("/Count 2"));
+        assert!(pdf.contains("(Step 0 of 1: the control polygon, 3 points) Tj"));
+        assert!(pdf.contains("(Step 1 of 1: 2 points) Tj"));
+
+        // Every object starts where the table says it does
+        let table = &pdf[pdf.find("xref\n").unwrap()..];
+        for (i, line) in table.lines().skip(3).take(7).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[test]
+    fn test_write_step() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(100.0, 100.0), Point::new(200.0, 0.0)];
+        let curve = vec![Point::new(0.0, 0.0), Point::new(200.0, 0.0)];
+        let (mut polylines, mut curves) = (vec![points], vec![curve]);
+        let mut output = Vec::new();
+        write_step(&mut output, &polylines, &curves, 3, false).unwrap();
+
+        let pdf = String::from_utf8(output).unwrap();
+        assert!(pdf.contains("/Count 1"));
+        assert!(pdf.contains("(Step 3: 2 points) Tj"));
+
+        // Each polyline and its curve are stroked on their own, and counted together
+        polylines.push(polylines[0].clone());
+        curves.push(curves[0].clone());
+        let mut output = Vec::new();
+        write_step(&mut output, &polylines, &curves, 3, false).unwrap();
+        let pdf = String::from_utf8(output).unwrap();
+        assert!(pdf.contains("(Step 3: 4 points) Tj"));
+        assert_eq!(pdf.matches("S\n").count(), 4);
+    }
+
+    #[test]
+    fn test_caption_escaped() {
+        assert_eq!(caption("a (b)"), "BT /F1 14 Tf 50 792 Td (a \\(b\\)) Tj ET\n");
+    }
+}
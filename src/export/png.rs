@@ -0,0 +1,42 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::window::Frame;
+
+/// Saves a frame drawn in the window to a PNG file
+pub fn save(path: &Path, frame: Frame) -> io::Result<()> {
+    write(BufWriter::new(File::create(path)?), frame)
+}
+
+/// Encodes a frame as an 8-bit RGBA PNG image
+pub fn write(writer: impl Write, frame: Frame) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(writer, frame.width as u32, frame.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data = frame.to_rgba();
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&data).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let pixels = [0x00FF5555, 0x0055CCAA, 0x00000000, 0x00FFFFFF];
+        let mut encoded = Vec::new();
+        write(&mut encoded, Frame { pixels: &pixels, width: 2, height: 2 }).unwrap();
+
+        let decoder = png::Decoder::new(encoded.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).unwrap();
+
+        assert_eq!((info.width, info.height), (2, 2));
+        assert_eq!(&data[..8], &[0xFF, 0x55, 0x55, 0xFF, 0x55, 0xCC, 0xAA, 0xFF]);
+    }
+}
@@ -0,0 +1,101 @@
+use std::io::{self, Write};
+
+use crate::types::Point;
+use crate::units::Units;
+
+/// Stroke color of the smoothed curve, the same blue-green as in the window
+pub const CURVE_STROKE: u32 = 0x55CCAA;
+/// Space left around the drawing, in the curve's units
+const MARGIN: f32 = 10.0;
+
+/// A polyline drawn in the SVG image
+pub struct Layer<'a> {
+    /// The points of the polyline
+    pub points: &'a [Point],
+    /// The stroke color, as 0xRRGGBB
+    pub stroke: u32,
+    /// Whether the last point connects back to the first
+    pub closed: bool,
+}
+
+impl<'a> Layer<'a> {
+    pub fn new(points: &'a [Point], stroke: u32) -> Self {
+        Self { points, stroke, closed: false }
+    }
+
+    /// Connects the last point back to the first, or not
+    pub fn closed(self, closed: bool) -> Self {
+        Self { closed, ..self }
+    }
+}
+
+/// Writes the layers as an SVG image, one path per layer, in the given order.
+/// The view box is fitted around all the points, with a small margin. With physical units,
+/// the image is sized in millimeters, so that it plots or prints at its real size
+pub fn write(mut writer: impl Write, layers: &[Layer], units: Units) -> io::Result<()> {
+    let (min, max) = super::bounds(layers.iter().flat_map(|layer| layer.points));
+    let (width, height) = (max.x - min.x + 2.0 * MARGIN, max.y - min.y + 2.0 * MARGIN);
+    let size = |length: f32| match units.millimeters(length) {
+        Some(millimeters) => format!("{}mm", millimeters),
+        None => length.to_string(),
+    };
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        min.x - MARGIN, min.y - MARGIN, width, height, size(width), size(height),
+    )?;
+
+    for layer in layers.iter().filter(|layer| !layer.points.is_empty()) {
+        write!(writer, r##"  <path fill="none" stroke="#{:06X}" d=""##, layer.stroke)?;
+        for (i, point) in layer.points.iter().enumerate() {
+            let command = if i == 0 { "M" } else { " L" };
+            write!(writer, "{}{} {}", command, point.x, point.y)?;
+        }
+        if layer.closed {
+            write!(writer, " Z")?;
+        }
+        writeln!(writer, r#""/>"#)?;
+    }
+
+    writeln!(writer, "</svg>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_path() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(100.0, 50.0)];
+        let mut output = Vec::new();
+        write(&mut output, &[Layer::new(&points, 0xFF0000)], Units::default()).unwrap();
+
+        let svg = String::from_utf8(output).unwrap();
+        assert!(svg.contains(r#"viewBox="-10 -10 120 70" width="120" height="70""#));
+        assert!(svg.contains(r##"<path fill="none" stroke="#FF0000" d="M0 0 L100 50"/>"##));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_closed_path() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(100.0, 50.0), Point::new(0.0, 50.0)];
+        let mut output = Vec::new();
+        write(&mut output, &[Layer::new(&points, 0xFF0000).closed(true)], Units::default()).unwrap();
+
+        let svg = String::from_utf8(output).unwrap();
+        assert!(svg.contains(r#"d="M0 0 L100 50 L0 50 Z""#));
+    }
+
+    #[test]
+    fn test_physical_size() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(100.0, 50.0)];
+        let mut output = Vec::new();
+        write(&mut output, &[Layer::new(&points, 0xFF0000)], "4/mm".parse().unwrap()).unwrap();
+
+        // The view box stays in the points' coordinates
+        let svg = String::from_utf8(output).unwrap();
+        assert!(svg.contains(r#"viewBox="-10 -10 120 70" width="30mm" height="17.5mm""#));
+    }
+}
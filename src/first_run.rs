@@ -0,0 +1,35 @@
+//! Remembers that the application ran before, so that the tutorial is only offered the
+//! first time.
+
+use std::path::PathBuf;
+
+use chaikin::window::APP_NAME;
+
+/// The file whose presence in the configuration directory means the app ran before
+const MARKER: &str = "tutorial-offered";
+
+/// Where the application keeps its settings, if the platform has such a place
+fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.map(|base| base.join(APP_NAME.to_lowercase()))
+}
+
+/// Whether this is the first time the application runs, which is then remembered. If it
+/// can't be remembered, it isn't the first time either, so that the user isn't asked on
+/// every run
+pub fn is_first_run() -> bool {
+    let Some(directory) = config_dir() else {
+        return false;
+    };
+    let marker = directory.join(MARKER);
+    if marker.exists() {
+        return false;
+    }
+    std::fs::create_dir_all(&directory).and_then(|_| std::fs::write(&marker, "")).is_ok()
+}
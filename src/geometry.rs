@@ -0,0 +1,354 @@
+//! Measurements of polylines, e.g. to annotate the control polygon.
+
+use crate::types::Point;
+
+mod bvh;
+
+pub use bvh::Bvh;
+
+/// The box bounding some points, from its smallest coordinates to its largest
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    /// The box bounding the points, or none if there are none
+    pub fn of(points: &[Point]) -> Option<Bounds> {
+        let (first, rest) = points.split_first()?;
+        Some(rest.iter().fold(Bounds { min: *first, max: *first }, |bounds, point| Bounds {
+            min: bounds.min.inf(point),
+            max: bounds.max.sup(point),
+        }))
+    }
+
+    /// The smallest box bounding both
+    pub fn union(&self, other: &Bounds) -> Bounds {
+        Bounds { min: self.min.inf(&other.min), max: self.max.sup(&other.max) }
+    }
+
+    pub fn intersects(&self, other: &Bounds) -> bool {
+        self.min.x <= other.max.x && other.min.x <= self.max.x && self.min.y <= other.max.y && other.min.y <= self.max.y
+    }
+
+    /// The distance from the point to the closest point of the box, 0 inside it
+    pub fn distance_to(&self, point: Point) -> f32 {
+        let dx = (self.min.x - point.x).max(point.x - self.max.x).max(0.0);
+        let dy = (self.min.y - point.y).max(point.y - self.max.y).max(0.0);
+        dx.hypot(dy)
+    }
+}
+
+/// The interior angle at `vertex` between the segments to `previous` and `next`, in
+/// degrees from 0 (folded back on itself) to 180 (straight). None if a segment has no
+/// length, since it has no direction then
+pub fn interior_angle(previous: Point, vertex: Point, next: Point) -> Option<f32> {
+    let (a, b) = (previous - vertex, next - vertex);
+    let lengths = a.norm() * b.norm();
+    if lengths == 0.0 {
+        return None;
+    }
+    let cos = (a.dot(&b) / lengths).clamp(-1.0, 1.0);
+    Some(cos.acos().to_degrees())
+}
+
+/// The interior angle at each point of the polyline, see [`interior_angle`]. The ends of an
+/// open polyline have none, while every point of a closed one has its neighbours on both
+/// sides
+pub fn vertex_angles(points: &[Point], closed: bool) -> Vec<Option<f32>> {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let ends = !closed && (i == 0 || i == n - 1);
+            if n < 3 || ends {
+                return None;
+            }
+            interior_angle(points[(i + n - 1) % n], points[i], points[(i + 1) % n])
+        })
+        .collect()
+}
+
+/// The length of the polyline, from its first point to its last
+pub fn polyline_length(points: &[Point]) -> f32 {
+    points.windows(2).map(|segment| (segment[1] - segment[0]).norm()).sum()
+}
+
+/// The area of the polygon, closed from its last point back to its first. Where its sides
+/// cross, the parts wound the other way take away from the area rather than add to it
+pub fn area(points: &[Point]) -> f32 {
+    // The shoelace formula, which sums twice the signed area under each side
+    let twice: f32 = (0..points.len())
+        .map(|i| {
+            let (start, end) = (points[i], points[(i + 1) % points.len()]);
+            start.x * end.y - end.x * start.y
+        })
+        .sum();
+    twice.abs() / 2.0
+}
+
+/// The dashes of the polyline, each `dash` long and `gap` after the one before, along it and
+/// back to its first point if it is closed. The first starts at its first point, and the
+/// last may be cut short by its end
+pub fn dashes(points: &[Point], closed: bool, dash: f32, gap: f32) -> Vec<Vec<Point>> {
+    let closing = (closed && points.len() > 2).then(|| (points[points.len() - 1], points[0]));
+    dashes_along(points.windows(2).map(|pair| (pair[0], pair[1])).chain(closing), dash, gap, 0.0)
+}
+
+/// The dashes of the open polyline, like [`dashes`], but starting `along` the first dash
+/// and the gap after it, e.g. to dash part of a longer polyline in step with the rest
+pub fn dashes_from(points: &[Point], dash: f32, gap: f32, along: f32) -> Vec<Vec<Point>> {
+    dashes_along(points.windows(2).map(|pair| (pair[0], pair[1])), dash, gap, along.rem_euclid(dash + gap))
+}
+
+fn dashes_along(segments: impl Iterator<Item = (Point, Point)>, dash: f32, gap: f32, along: f32) -> Vec<Vec<Point>> {
+    let (mut dashes, mut current) = (Vec::new(), Vec::new());
+    // How far along the current dash and the gap after it
+    let mut along = along;
+    for (start, end) in segments {
+        let length = (end - start).norm();
+        // How far along the segment
+        let mut done = 0.0;
+        while done < length {
+            let on = along < dash;
+            let left = if on { dash - along } else { dash + gap - along };
+            let from = start + (end - start) * (done / length);
+            let step = left.min(length - done);
+            done += step;
+            if on {
+                if current.is_empty() {
+                    current.push(from);
+                }
+                current.push(start + (end - start) * (done / length));
+            }
+            if step < left {
+                along += step;
+            } else if on {
+                along = dash;
+                dashes.push(std::mem::take(&mut current));
+            } else {
+                along = 0.0;
+            }
+        }
+    }
+    if current.len() >= 2 {
+        dashes.push(current);
+    }
+    dashes
+}
+
+/// The point of a polyline closest to another point, e.g. to snap to a curve or to insert a
+/// point into it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Projection {
+    /// The closest point of the polyline
+    pub point: Point,
+    /// The segment it lies on, from point `segment` to the next one, or back to the first
+    pub segment: usize,
+    /// How far along the segment it lies, from 0 at its start to 1 at its end
+    pub t: f32,
+    /// How far it is from the other point
+    pub distance: f32,
+}
+
+/// How far along the segment its point closest to the given one lies, from 0 at its start
+/// to 1 at its end
+pub fn segment_parameter(point: Point, start: Point, end: Point) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.norm_squared();
+    if length_squared == 0.0 {
+        return 0.0;
+    }
+    ((point - start).dot(&segment) / length_squared).clamp(0.0, 1.0)
+}
+
+/// The distance from the point to the closest point of the segment
+pub fn distance_to_segment(point: Point, start: Point, end: Point) -> f32 {
+    let t = segment_parameter(point, start, end);
+    (point - (start + (end - start) * t)).norm()
+}
+
+/// The point of the polyline closest to the given one, with the segment from its last point
+/// back to its first if it is closed. A single point is its own closest point, on segment 0
+/// at 0. None if the polyline has no points
+pub fn nearest_on_polyline(polyline: &[Point], point: Point, closed: bool) -> Option<Projection> {
+    match polyline {
+        [] => None,
+        [single] => Some(Projection { point: *single, segment: 0, t: 0.0, distance: (point - single).norm() }),
+        _ => Bvh::new(polyline, closed).project(polyline, point),
+    }
+}
+
+/// Where the segments cross, if they do, ends included. Parallel segments are taken not to
+/// cross, even if they overlap
+pub fn segment_intersection(a: (Point, Point), b: (Point, Point)) -> Option<Point> {
+    let (r, s) = (a.1 - a.0, b.1 - b.0);
+    let denominator = r.perp(&s);
+    if denominator == 0.0 {
+        return None;
+    }
+    let offset = b.0 - a.0;
+    let (t, u) = (offset.perp(&s) / denominator, offset.perp(&r) / denominator);
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then(|| a.0 + r * t)
+}
+
+/// The distance from the point to the closest point of the polyline, or infinity if the
+/// polyline has no points
+pub fn distance_to_polyline(point: Point, polyline: &[Point]) -> f32 {
+    match polyline {
+        [] => f32::INFINITY,
+        [single] => (point - single).norm(),
+        _ => polyline
+            .windows(2)
+            .map(|segment| distance_to_segment(point, segment[0], segment[1]))
+            .fold(f32::INFINITY, f32::min),
+    }
+}
+
+/// The Hausdorff distance between two polylines, measured from their points: how far the
+/// farthest point of either one is from the segments of the other, so that polylines with
+/// different numbers of points can be compared. The points along their segments aren't
+/// measured from, which can miss where two long segments part between their ends, but is
+/// close for finely sampled curves. Infinity if only one of them is empty
+pub fn hausdorff(a: &[Point], b: &[Point]) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 0.0;
+    }
+    // Each point is measured to the other polyline through a hierarchy of its segments,
+    // which skips those far from the point
+    let farthest = |from: &[Point], to: &[Point]| match to.len() {
+        0 | 1 => from.iter().map(|point| distance_to_polyline(*point, to)).fold(0.0, f32::max),
+        _ => {
+            let bvh = Bvh::new(to, false);
+            from.iter()
+                .filter_map(|point| bvh.nearest(to, *point).map(|(_, distance)| distance))
+                .fold(0.0, f32::max)
+        }
+    };
+    farthest(a, b).max(farthest(b, a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interior_angle() {
+        let vertex = Point::new(0.0, 0.0);
+        let angle = |x: f32, y: f32| interior_angle(Point::new(10.0, 0.0), vertex, Point::new(x, y)).unwrap();
+        assert!((angle(0.0, 5.0) - 90.0).abs() < 1e-4);
+        assert!((angle(-3.0, 0.0) - 180.0).abs() < 1e-4);
+        assert!((angle(10.0, 10.0) - 45.0).abs() < 1e-4);
+        assert_eq!(interior_angle(vertex, vertex, Point::new(1.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let points = [Point::new(3.0, -1.0), Point::new(-2.0, 4.0), Point::new(1.0, 1.0)];
+        let bounds = Bounds::of(&points).unwrap();
+        assert_eq!(bounds, Bounds { min: Point::new(-2.0, -1.0), max: Point::new(3.0, 4.0) });
+        assert_eq!(Bounds::of(&[]), None);
+        assert!(bounds.intersects(&Bounds { min: Point::new(3.0, 4.0), max: Point::new(9.0, 9.0) }));
+        assert!(!bounds.intersects(&Bounds { min: Point::new(3.5, 0.0), max: Point::new(9.0, 9.0) }));
+        assert_eq!(bounds.distance_to(Point::new(0.0, 0.0)), 0.0);
+        assert_eq!(bounds.distance_to(Point::new(6.0, 8.0)), 5.0);
+    }
+
+    #[test]
+    fn test_segment_intersection() {
+        let cross = segment_intersection(
+            (Point::new(0.0, 0.0), Point::new(10.0, 10.0)),
+            (Point::new(0.0, 10.0), Point::new(10.0, 0.0)),
+        );
+        assert_eq!(cross, Some(Point::new(5.0, 5.0)));
+        // Short of each other, and parallel
+        let short = (Point::new(0.0, 10.0), Point::new(4.0, 6.0));
+        assert_eq!(segment_intersection((Point::new(0.0, 0.0), Point::new(10.0, 10.0)), short), None);
+        let parallel = (Point::new(0.0, 1.0), Point::new(10.0, 11.0));
+        assert_eq!(segment_intersection((Point::new(0.0, 0.0), Point::new(10.0, 10.0)), parallel), None);
+    }
+
+    #[test]
+    fn test_nearest_on_polyline() {
+        let square = [Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0), Point::new(0.0, 10.0)];
+        let nearest = nearest_on_polyline(&square, Point::new(12.0, 7.5), false).unwrap();
+        assert_eq!(nearest, Projection { point: Point::new(10.0, 7.5), segment: 1, t: 0.75, distance: 2.0 });
+
+        // Past the end of an open polyline, or on the segment that closes it
+        let nearest = nearest_on_polyline(&square, Point::new(-1.0, 4.0), false).unwrap();
+        assert_eq!((nearest.point, nearest.segment, nearest.t), (Point::new(0.0, 0.0), 0, 0.0));
+        let nearest = nearest_on_polyline(&square, Point::new(-1.0, 4.0), true).unwrap();
+        assert_eq!(nearest, Projection { point: Point::new(0.0, 4.0), segment: 3, t: 0.6, distance: 1.0 });
+
+        let nearest = nearest_on_polyline(&square[..1], Point::new(3.0, 4.0), false).unwrap();
+        assert_eq!((nearest.point, nearest.distance), (square[0], 5.0));
+        assert_eq!(nearest_on_polyline(&[], Point::new(3.0, 4.0), false), None);
+    }
+
+    #[test]
+    fn test_area() {
+        let square = [Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0), Point::new(0.0, 10.0)];
+        assert_eq!(area(&square), 100.0);
+        // Either way around
+        let reversed: Vec<Point> = square.iter().rev().copied().collect();
+        assert_eq!(area(&reversed), 100.0);
+        // A bow tie's halves cancel out
+        assert_eq!(area(&[square[0], square[2], square[1], square[3]]), 0.0);
+        assert_eq!(area(&square[..2]), 0.0);
+        assert_eq!(area(&[]), 0.0);
+    }
+
+    #[test]
+    fn test_dashes() {
+        // Dashes of 4 with gaps of 2, across the corner of an L
+        let l = [Point::new(0.0, 0.0), Point::new(9.0, 0.0), Point::new(9.0, 5.0)];
+        assert_eq!(dashes(&l, false, 4.0, 2.0), vec![
+            vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0)],
+            vec![Point::new(6.0, 0.0), Point::new(9.0, 0.0), Point::new(9.0, 1.0)],
+            vec![Point::new(9.0, 3.0), Point::new(9.0, 5.0)],
+        ]);
+        // Closed, the segment back to the first point is dashed too
+        assert_eq!(dashes(&l, true, 4.0, 2.0).len(), 5);
+        assert!(dashes(&l[..1], false, 4.0, 2.0).is_empty());
+
+        // Its second side, dashed in step with the whole of it
+        assert_eq!(dashes_from(&l[1..], 4.0, 2.0, 9.0), vec![
+            vec![Point::new(9.0, 0.0), Point::new(9.0, 1.0)],
+            vec![Point::new(9.0, 3.0), Point::new(9.0, 5.0)],
+        ]);
+        assert_eq!(dashes_from(&l[1..], 4.0, 2.0, 3.0), dashes_from(&l[1..], 4.0, 2.0, 9.0));
+    }
+
+    #[test]
+    fn test_polyline_length() {
+        let points = [Point::new(0.0, 0.0), Point::new(3.0, 4.0), Point::new(3.0, 10.0)];
+        assert_eq!(polyline_length(&points), 11.0);
+        assert_eq!(polyline_length(&points[..1]), 0.0);
+    }
+
+    #[test]
+    fn test_hausdorff() {
+        let line = [Point::new(0.0, 0.0), Point::new(100.0, 0.0)];
+        // The same line, with more points, is at no distance
+        let finer: Vec<Point> = (0..=10).map(|i| Point::new(i as f32 * 10.0, 0.0)).collect();
+        assert!(hausdorff(&line, &finer) < 1e-4);
+
+        // A bump is as far as its top, and shorter lines are as far as what they miss
+        let bump = [Point::new(0.0, 0.0), Point::new(50.0, 5.0), Point::new(100.0, 0.0)];
+        assert!((hausdorff(&line, &bump) - 5.0).abs() < 1e-4);
+        assert_eq!(hausdorff(&line, &[Point::new(0.0, 0.0), Point::new(60.0, 0.0)]), 40.0);
+        assert_eq!(hausdorff(&line, &[]), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_vertex_angles() {
+        let square = [Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0), Point::new(0.0, 10.0)];
+        let open = vertex_angles(&square, false);
+        assert_eq!(open[0], None);
+        assert_eq!(open[3], None);
+        assert!((open[1].unwrap() - 90.0).abs() < 1e-4);
+
+        assert!(vertex_angles(&square, true).iter().all(|angle| (angle.unwrap() - 90.0).abs() < 1e-4));
+        assert_eq!(vertex_angles(&square[..2], true), vec![None, None]);
+    }
+}
@@ -0,0 +1,256 @@
+use std::ops::Range;
+
+use crate::types::Point;
+
+use super::{distance_to_segment, segment_intersection, segment_parameter, Bounds, Projection};
+
+/// How many segments each leaf of the hierarchy holds
+const LEAF_SEGMENTS: usize = 16;
+
+/// A box bounding a run of the segments, and the two boxes it is split into, if it isn't a
+/// leaf
+#[derive(Clone, Debug)]
+struct Node {
+    bounds: Bounds,
+    segments: Range<usize>,
+    children: Option<(usize, usize)>,
+}
+
+/// A bounding-volume hierarchy over the segments of a polyline, e.g. of a refined curve,
+/// which finds the segments in an area, nearest to a point or crossing a segment without
+/// going through all of them. Leaves bound runs of consecutive segments, which lie close
+/// together along a curve, and are paired up level by level into the root, so that it is
+/// built in linear time. The polyline itself isn't kept: queries are given it again, and
+/// it has to be the one the hierarchy was built over
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    /// The hierarchy over the segments of the polyline, with the segment from its last point
+    /// back to its first if it is closed
+    pub fn new(points: &[Point], closed: bool) -> Self {
+        let count = segment_count(points.len(), closed);
+        let mut nodes: Vec<Node> = (0..count)
+            .step_by(LEAF_SEGMENTS)
+            .map(|start| {
+                let segments = start..(start + LEAF_SEGMENTS).min(count);
+                let bounds = segments
+                    .clone()
+                    .map(|i| segment(points, i))
+                    .fold(Bounds { min: points[start], max: points[start] }, |bounds, (from, to)| {
+                        bounds.union(&Bounds { min: from.inf(&to), max: from.sup(&to) })
+                    });
+                Node { bounds, segments, children: None }
+            })
+            .collect();
+
+        let mut level: Vec<usize> = (0..nodes.len()).collect();
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| match *pair {
+                    [left, right] => {
+                        nodes.push(Node {
+                            bounds: nodes[left].bounds.union(&nodes[right].bounds),
+                            segments: nodes[left].segments.start..nodes[right].segments.end,
+                            children: Some((left, right)),
+                        });
+                        nodes.len() - 1
+                    }
+                    _ => pair[0],
+                })
+                .collect();
+        }
+        Self { nodes }
+    }
+
+    /// How many segments there are, the closing one included
+    pub fn len(&self) -> usize {
+        self.root().map_or(0, |root| root.segments.end)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The box bounding every segment, if there are any
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.root().map(|root| root.bounds)
+    }
+
+    fn root(&self) -> Option<&Node> {
+        self.nodes.last()
+    }
+
+    /// The runs of segments in leaves whose boxes meet the area, in order, as ranges of their
+    /// indices. Segment `i` goes from point `i` to the next one, or back to the first
+    pub fn segments_in(&self, area: &Bounds) -> Vec<Range<usize>> {
+        let mut runs: Vec<Range<usize>> = Vec::new();
+        let mut stack: Vec<usize> = self.root().map(|_| self.nodes.len() - 1).into_iter().collect();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.bounds.intersects(area) {
+                continue;
+            }
+            match node.children {
+                // The left child first, so that the runs come in order
+                Some((left, right)) => stack.extend([right, left]),
+                None => match runs.last_mut() {
+                    Some(run) if run.end == node.segments.start => run.end = node.segments.end,
+                    _ => runs.push(node.segments.clone()),
+                },
+            }
+        }
+        runs
+    }
+
+    /// The segment of the polyline closest to the point, and how far it is. Boxes further
+    /// than the closest segment found so far are skipped, nearer ones are looked into first
+    pub fn nearest(&self, points: &[Point], point: Point) -> Option<(usize, f32)> {
+        let mut best: Option<(usize, f32)> = None;
+        let mut stack: Vec<usize> = self.root().map(|_| self.nodes.len() - 1).into_iter().collect();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if best.is_some_and(|(_, distance)| node.bounds.distance_to(point) > distance) {
+                continue;
+            }
+            match node.children {
+                Some((left, right)) => {
+                    let left_nearer = self.nodes[left].bounds.distance_to(point) <= self.nodes[right].bounds.distance_to(point);
+                    let (near, far) = if left_nearer { (left, right) } else { (right, left) };
+                    stack.extend([far, near]);
+                }
+                None => {
+                    for i in node.segments.clone() {
+                        let (from, to) = segment(points, i);
+                        let distance = distance_to_segment(point, from, to);
+                        if best.is_none_or(|(_, closest)| distance < closest) {
+                            best = Some((i, distance));
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// The point of the polyline closest to the given one, on the segment [`Self::nearest`]
+    /// finds
+    pub fn project(&self, points: &[Point], point: Point) -> Option<Projection> {
+        let (i, distance) = self.nearest(points, point)?;
+        let (start, end) = segment(points, i);
+        let t = segment_parameter(point, start, end);
+        Some(Projection { point: start + (end - start) * t, segment: i, t, distance })
+    }
+
+    /// Where the segment from `start` to `end` crosses the polyline, as the segments it
+    /// crosses and the points it crosses them at, in the order of the segments
+    pub fn crossings(&self, points: &[Point], start: Point, end: Point) -> Vec<(usize, Point)> {
+        let area = Bounds { min: start.inf(&end), max: start.sup(&end) };
+        self.segments_in(&area)
+            .into_iter()
+            .flatten()
+            .filter_map(|i| segment_intersection((start, end), segment(points, i)).map(|point| (i, point)))
+            .collect()
+    }
+}
+
+/// How many segments a polyline of that many points has
+fn segment_count(points: usize, closed: bool) -> usize {
+    match points {
+        0 | 1 => 0,
+        2 => 1,
+        _ if closed => points,
+        _ => points - 1,
+    }
+}
+
+/// The segment from the point to the next one, or back to the first one
+fn segment(points: &[Point], i: usize) -> (Point, Point) {
+    (points[i], points[(i + 1) % points.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::distance_to_polyline;
+
+    /// A spiral of the given number of points, whose segments are close to many others'
+    fn spiral(count: usize) -> Vec<Point> {
+        (0..count)
+            .map(|i| {
+                let angle = i as f32 * 0.05;
+                Point::new(angle.cos(), angle.sin()) * (10.0 + i as f32 * 0.1)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_build() {
+        let points = spiral(1000);
+        let bvh = Bvh::new(&points, false);
+        assert_eq!(bvh.len(), 999);
+        assert_eq!(bvh.bounds(), Bounds::of(&points));
+        assert_eq!(Bvh::new(&points, true).len(), 1000);
+        assert!(Bvh::new(&points[..1], false).is_empty());
+        assert_eq!(Bvh::new(&points[..2], true).len(), 1);
+    }
+
+    #[test]
+    fn test_segments_in() {
+        // A line along x, with a box over a part of it
+        let points: Vec<Point> = (0..=200).map(|i| Point::new(i as f32, 0.0)).collect();
+        let bvh = Bvh::new(&points, false);
+        let area = Bounds { min: Point::new(40.0, -1.0), max: Point::new(70.0, 1.0) };
+        assert_eq!(bvh.segments_in(&area), vec![32..80]);
+        let away = Bounds { min: Point::new(40.0, 5.0), max: Point::new(70.0, 10.0) };
+        assert!(bvh.segments_in(&away).is_empty());
+        let everywhere = Bounds { min: Point::new(-1.0, -1.0), max: Point::new(201.0, 1.0) };
+        assert_eq!(bvh.segments_in(&everywhere), vec![0..200]);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let points = spiral(2000);
+        let bvh = Bvh::new(&points, false);
+        for probe in [Point::new(0.0, 0.0), Point::new(55.0, -3.0), Point::new(400.0, 400.0), Point::new(-120.0, 7.5)] {
+            // As near as the closest of all the segments
+            let (i, distance) = bvh.nearest(&points, probe).unwrap();
+            assert_eq!(distance, distance_to_polyline(probe, &points));
+            assert_eq!(distance, distance_to_segment(probe, points[i], points[i + 1]));
+        }
+        assert_eq!(Bvh::new(&[], false).nearest(&[], Point::new(0.0, 0.0)), None);
+
+        // The closing segment counts, if the polyline is closed
+        let square = [Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0), Point::new(0.0, 10.0)];
+        assert_eq!(Bvh::new(&square, true).nearest(&square, Point::new(-1.0, 5.0)), Some((3, 1.0)));
+        assert_eq!(Bvh::new(&square, false).nearest(&square, Point::new(-1.0, 5.0)).unwrap().0, 0);
+    }
+
+    #[test]
+    fn test_project() {
+        let points = spiral(2000);
+        let bvh = Bvh::new(&points, false);
+        for probe in [Point::new(0.0, 0.0), Point::new(55.0, -3.0), Point::new(-120.0, 7.5)] {
+            // On the nearest segment, as far from the probe as it is
+            let projection = bvh.project(&points, probe).unwrap();
+            let (start, end) = (points[projection.segment], points[projection.segment + 1]);
+            assert_eq!(projection.point, start + (end - start) * projection.t);
+            assert!(((projection.point - probe).norm() - projection.distance).abs() < 1e-3);
+        }
+        assert_eq!(Bvh::new(&[], false).project(&[], Point::new(0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_crossings() {
+        // A zigzag, crossed by a line along its middle once per segment
+        let zigzag: Vec<Point> = (0..100).map(|i| Point::new(i as f32 * 10.0, (i % 2) as f32 * 10.0)).collect();
+        let bvh = Bvh::new(&zigzag, false);
+        let crossings = bvh.crossings(&zigzag, Point::new(-5.0, 5.0), Point::new(2000.0, 5.0));
+        assert_eq!(crossings.len(), 99);
+        assert_eq!(crossings[3], (3, Point::new(35.0, 5.0)));
+        assert!(bvh.crossings(&zigzag, Point::new(0.0, 20.0), Point::new(900.0, 30.0)).is_empty());
+    }
+}
@@ -0,0 +1,755 @@
+//! Smoothing without a window, for scripts and data pipelines.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use chaikin::export::atlas::Atlas;
+use chaikin::export::gif::{Recording, Timing};
+use chaikin::export::{html, lottie, pdf, png};
+use chaikin::export::Format;
+use chaikin::geometry;
+use chaikin::types::Point;
+use chaikin::numbers::NumberFormat;
+use chaikin::units::Units;
+use chaikin::window::{BlendMode, Raster, Theme, View};
+use chaikin::algorithm::{resample_by_arclength, Smoother, Steps};
+
+/// File extensions that can be read as points
+const SUPPORTED_EXTENSIONS: [&str; 1] = ["txt"];
+/// Space left around the shape in a rendered image, in pixels
+const RENDER_PADDING: f32 = 40.0;
+/// Opacity of the control polygon behind the curve in a rendered image
+const RENDER_POLYGON_OPACITY: f32 = 0.3;
+/// Radius of the control points in a rendered image
+const RENDER_POINT_RADIUS: f32 = 4.0;
+
+/// How a batch should be run
+pub struct Batch<'a> {
+    /// Where the input files are read from
+    pub input: &'a Path,
+    /// Where the smoothed files are written to
+    pub output: &'a Path,
+    /// How many smoothing steps to apply to each file
+    pub steps: usize,
+    /// The format of the smoothed files
+    pub format: Format,
+    /// The subdivision scheme to smooth with
+    pub smoother: &'a dyn Smoother,
+    /// Whether to print a JSON report instead of a line per file
+    pub json: bool,
+    /// The units the report's lengths and the SVG images are sized in
+    pub units: Units,
+    /// How many points to resample each smoothed curve to, evenly along it, if any
+    pub resample: Option<usize>,
+    /// Whether the shapes are closed, from their last point back to their first
+    pub closed: bool,
+}
+
+/// What happened to a single input, as printed by `--json`
+#[derive(Serialize, Debug, Default)]
+pub struct Report {
+    /// The input file, or none for stdin
+    pub input: Option<PathBuf>,
+    /// The output file, or none for stdout
+    pub output: Option<PathBuf>,
+    /// Why smoothing failed, if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The number of polylines read, each smoothed on its own
+    pub polylines: usize,
+    /// The number of control points read
+    pub input_points: usize,
+    /// The number of smoothing steps applied
+    pub steps: usize,
+    /// The number of points in the smoothed curves
+    pub output_points: usize,
+    /// The length of the smoothed curves, in `units`
+    pub length: f32,
+    /// The size of the box bounding the smoothed curves, in `units`
+    pub width: f32,
+    pub height: f32,
+    /// The largest distance from a smoothed point to its control polygon, in `units`
+    pub max_deviation: f32,
+    /// The units of the lengths, e.g. "px" or "3.78/mm"
+    pub units: String,
+    /// How long smoothing took, in milliseconds
+    pub elapsed_ms: f64,
+}
+
+/// The summary of a batch, as printed by `--json`
+#[derive(Serialize, Debug)]
+struct BatchReport {
+    succeeded: usize,
+    failed: usize,
+    files: Vec<Report>,
+}
+
+impl Report {
+    /// Smooths each polyline on its own, recording the figures of the run with lengths in
+    /// the units. The smoother should be closed if the shapes are, for their lengths to
+    /// include the segments closing them
+    fn smooth(
+        polylines: &[Vec<Point>],
+        steps: usize,
+        smoother: &dyn Smoother,
+        units: Units,
+        closed: bool,
+    ) -> (Self, Vec<Vec<Point>>) {
+        let start = Instant::now();
+        let curves: Vec<Vec<Point>> = polylines.iter().map(|points| smoother.get_step_points(points, steps)).collect();
+        let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        let bounds = curves.iter().filter_map(|curve| geometry::Bounds::of(curve)).reduce(|a, b| a.union(&b));
+        let size = bounds.map_or(Default::default(), |bounds| bounds.max - bounds.min);
+        let length: f32 = curves.iter().map(|curve| geometry::polyline_length(&with_closing(curve, closed))).sum();
+        let deviation = polylines
+            .iter()
+            .zip(&curves)
+            .map(|(points, curve)| max_deviation(points, curve, closed))
+            .fold(0.0, f32::max);
+        let report = Report {
+            polylines: polylines.len(),
+            input_points: polylines.iter().map(Vec::len).sum(),
+            steps,
+            output_points: curves.iter().map(Vec::len).sum(),
+            length: units.length(length),
+            width: units.length(size.x),
+            height: units.length(size.y),
+            max_deviation: units.length(deviation),
+            units: units.to_string(),
+            elapsed_ms,
+            ..Report::default()
+        };
+        (report, curves)
+    }
+
+    /// Each curve resampled to the given number of points evenly along its length, if there
+    /// is one, which makes the number of points recorded. A closed curve is resampled
+    /// along the segment closing it too, its first point not being repeated at its end
+    fn resample(&mut self, curves: Vec<Vec<Point>>, count: Option<usize>, closed: bool) -> Vec<Vec<Point>> {
+        let Some(count) = count else {
+            return curves;
+        };
+        self.output_points = count * curves.len();
+        let resample = |curve: Vec<Point>| {
+            if !closed {
+                return resample_by_arclength(&curve, count);
+            }
+            let mut resampled = resample_by_arclength(&with_closing(&curve, true), count + 1);
+            resampled.truncate(count);
+            resampled
+        };
+        curves.into_iter().map(resample).collect()
+    }
+}
+
+/// Smooths every supported file in the input directory in parallel, writing the results
+/// to the output directory. Each file gets a line on stdout (or stderr if it failed), and
+/// the exit code is nonzero if any file failed
+pub fn batch(batch: &Batch) -> ExitCode {
+    let inputs = match supported_files(batch.input) {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("Could not read {}: {}", batch.input.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = fs::create_dir_all(batch.output) {
+        eprintln!("Could not create {}: {}", batch.output.display(), e);
+        return ExitCode::FAILURE;
+    }
+
+    // Workers take the next file from the queue until it is empty
+    let queue = Mutex::new(inputs.iter());
+    let reports = Mutex::new(Vec::with_capacity(inputs.len()));
+    // The JSON report only comes at the end, so the files processed are counted meanwhile
+    let show_progress = batch.json && io::stderr().is_terminal();
+    let workers = thread::available_parallelism().map_or(1, |n| n.get()).min(inputs.len());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let Some(input) = queue.lock().unwrap().next() else {
+                    return;
+                };
+
+                let output = output_path(input, batch.output, batch.format);
+                let report = process(input, &output, batch);
+                if !batch.json {
+                    print_line(&report);
+                }
+                let mut reports = reports.lock().unwrap();
+                reports.push(report);
+                if show_progress {
+                    eprint!("\rprocessed {}/{} files", reports.len(), inputs.len());
+                }
+            });
+        }
+    });
+    if show_progress {
+        eprintln!();
+    }
+
+    let mut files = reports.into_inner().unwrap();
+    let failed = files.iter().filter(|report| report.error.is_some()).count();
+    let succeeded = files.len() - failed;
+
+    if batch.json {
+        files.sort_by(|a, b| a.input.cmp(&b.input));
+        print_json(&BatchReport { succeeded, failed, files });
+    } else {
+        println!("{} succeeded, {} failed", succeeded, failed);
+    }
+
+    if failed > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Smooths the polylines read from the given file, or from stdin if there is none (or it
+/// is `-`), and writes the result to stdout, so the app can be used in shell pipelines.
+/// Several polylines are written as they are read, each after a `# polyline` line.
+/// With `json`, the report is written instead of the points, with lengths in the units.
+/// With `resample`, the curve is resampled to that many points evenly along it. The
+/// smoother should be closed if the shape is
+pub fn stream(
+    input: Option<&Path>,
+    steps: usize,
+    smoother: &dyn Smoother,
+    json: bool,
+    units: Units,
+    resample: Option<usize>,
+    closed: bool,
+) -> ExitCode {
+    let input = input.filter(|path| *path != Path::new("-"));
+    let polylines = match input {
+        Some(path) => {
+            File::open(path).and_then(|file| chaikin::document::parse_polylines(BufReader::new(file)))
+        }
+        None => chaikin::document::parse_polylines(io::stdin().lock()),
+    };
+    let polylines = match polylines {
+        Ok(polylines) => polylines,
+        Err(e) => {
+            eprintln!("Could not read the points: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (mut report, curves) = Report::smooth(&polylines, steps, smoother, units, closed);
+    let curves = report.resample(curves, resample, closed);
+    if json {
+        report.input = input.map(Path::to_path_buf);
+        print_json(&report);
+        return ExitCode::SUCCESS;
+    }
+
+    let mut writer = BufWriter::new(io::stdout().lock());
+    match chaikin::document::format_polylines(&mut writer, &curves).and_then(|_| writer.flush()) {
+        Ok(()) => ExitCode::SUCCESS,
+        // Whoever reads our output may stop early, as `head` does
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Could not write the points: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Renders every step of the polylines in the input file into a sprite sheet. The smoother
+/// should be closed if the shapes are
+pub fn atlas(
+    input: &Path,
+    output: &Path,
+    smoother: Box<dyn Smoother>,
+    steps: usize,
+    cell: usize,
+    columns: usize,
+    closed: bool,
+) -> ExitCode {
+    let polylines = match chaikin::document::read_polylines(input) {
+        Ok(polylines) => polylines,
+        Err(e) => {
+            eprintln!("Could not read {}: {}", input.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut atlas = Atlas::render(&polylines, smoother, steps, (cell, cell), columns, closed);
+    match atlas.save(output) {
+        Ok(()) => {
+            println!(
+                "ok    {} -> {} ({} cells of {}x{})",
+                input.display(),
+                output.display(),
+                steps,
+                cell,
+                cell
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Could not write {}: {}", output.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Writes the animation of the polylines in the input file as a Lottie file. The smoother
+/// should be closed if the shapes are
+pub fn lottie(
+    input: &Path,
+    output: &Path,
+    smoother: &dyn Smoother,
+    steps: usize,
+    frame_rate: u32,
+    closed: bool,
+) -> ExitCode {
+    let result = chaikin::document::read_polylines(input).and_then(|polylines| {
+        let steps: Vec<Vec<_>> = (0..steps)
+            .map(|step| polylines.iter().map(|points| smoother.get_step_points(points, step)).collect())
+            .collect();
+        // A step a second, as in the window, whatever the frame rate
+        let timing = lottie::Timing {
+            frame_rate,
+            hold_frames: frame_rate / 2,
+            morph_frames: frame_rate - frame_rate / 2,
+        };
+
+        let mut writer = BufWriter::new(File::create(output)?);
+        lottie::write(&mut writer, &steps, &timing, closed)?;
+        writer.flush()
+    });
+
+    match result {
+        Ok(()) => {
+            println!("ok    {} -> {} ({} steps)", input.display(), output.display(), steps);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error {}: {}", input.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Records the animation of smoothing the polylines as an animated GIF of the given size.
+/// The smoother should be closed if the shapes are
+pub fn gif(
+    input: &Path,
+    output: &Path,
+    smoother: Box<dyn Smoother>,
+    steps: usize,
+    size: usize,
+    closed: bool,
+) -> ExitCode {
+    let result = chaikin::document::read_polylines(input).and_then(|polylines| {
+        let recording = Recording::render(&polylines, smoother, steps, (size, size), &Timing::default(), closed);
+        recording.save(output)
+    });
+
+    match result {
+        Ok(()) => {
+            println!("ok    {} -> {} ({} steps)", input.display(), output.display(), steps);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error {}: {}", input.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Renders the curves of the polylines in the input file, after the given number of steps,
+/// into a PNG image of the given size, over their control polygons, as the window draws
+/// them. The shapes are fitted to the image, whatever their coordinates. The smoother
+/// should be closed if the shapes are
+pub fn render(
+    input: &Path,
+    output: &Path,
+    smoother: &dyn Smoother,
+    steps: usize,
+    size: (usize, usize),
+    closed: bool,
+) -> ExitCode {
+    let result = chaikin::document::read_polylines(input).and_then(|polylines| {
+        let curves: Vec<_> = polylines.iter().map(|points| smoother.get_step_points(points, steps)).collect();
+        let raster = render_curves(&polylines, &curves, size, closed);
+        png::save(output, raster.frame())
+    });
+
+    match result {
+        Ok(()) => {
+            println!("ok    {} -> {} ({} steps)", input.display(), output.display(), steps);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error {}: {}", input.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Draws the curves over their faint control polygons and the control points, in the
+/// default theme's colors, all closed from their last point back to their first if `closed`
+fn render_curves(
+    polylines: &[Vec<Point>],
+    curves: &[Vec<Point>],
+    (width, height): (usize, usize),
+    closed: bool,
+) -> Raster {
+    let theme = Theme::default();
+    let all: Vec<Point> = polylines.iter().flatten().copied().collect();
+    let view = View::fit(&all, (width, height), RENDER_PADDING).unwrap_or_default();
+    let mut raster = Raster::new(width, height);
+    raster.fill(theme.background);
+
+    let polygons = polylines.iter().map(|points| (points, RENDER_POLYGON_OPACITY));
+    for (path, opacity) in polygons.chain(curves.iter().map(|curve| (curve, 1.0))) {
+        let path: Vec<Point> = path.iter().map(|point| view.screen_point(*point)).collect();
+        raster.draw_polyline_aa(&path, closed, 1.0, theme.line, opacity, BlendMode::Normal);
+    }
+
+    for point in all.iter().map(|point| view.screen_point(*point)) {
+        raster.draw_glyph_aa(point.x, point.y, RENDER_POINT_RADIUS, theme.point, theme.point_glyph);
+    }
+    raster
+}
+
+/// Prints the steps of smoothing the polylines into a PDF handout, a page per step. The
+/// smoother should be closed if the shapes are
+pub fn handout(input: &Path, output: &Path, smoother: &dyn Smoother, steps: usize, closed: bool) -> ExitCode {
+    let result = chaikin::document::read_polylines(input).and_then(|polylines| {
+        let mut pages = vec![Vec::new(); steps];
+        for points in &polylines {
+            for (page, curve) in pages.iter_mut().zip(Steps::new(smoother, points)) {
+                page.push(curve);
+            }
+        }
+        let mut writer = BufWriter::new(File::create(output)?);
+        pdf::write(&mut writer, &polylines, &pages, closed)?;
+        writer.flush()
+    });
+
+    match result {
+        Ok(()) => {
+            println!("ok    {} -> {} ({} pages)", input.display(), output.display(), steps);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error {}: {}", input.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Smooths the polylines with each of the labelled schemes and compares the curves in an HTML
+/// report, with lengths in the units and the figures in the format. The smoothers should be
+/// closed if the shape is
+pub fn report(
+    input: &Path,
+    output: &Path,
+    variants: &[(String, Box<dyn Smoother>)],
+    steps: usize,
+    units: Units,
+    numbers: NumberFormat,
+    closed: bool,
+) -> ExitCode {
+    let result = chaikin::document::read_polylines(input).and_then(|polylines| {
+        let runs: Vec<_> = variants
+            .iter()
+            // The page converts the lengths itself
+            .map(|(_, smoother)| Report::smooth(&polylines, steps, smoother.as_ref(), Units::default(), closed))
+            .collect();
+        let variants: Vec<_> = variants
+            .iter()
+            .zip(&runs)
+            .map(|((label, _), (report, curves))| html::Variant {
+                label: label.clone(),
+                curves,
+                length: report.length,
+                max_deviation: report.max_deviation,
+                elapsed_ms: report.elapsed_ms,
+            })
+            .collect();
+
+        let title = format!("{}, {} steps", input.display(), steps);
+        let mut writer = BufWriter::new(File::create(output)?);
+        html::write(&mut writer, &title, &polylines, &variants, units, &numbers, closed)?;
+        writer.flush()
+    });
+
+    match result {
+        Ok(()) => {
+            println!("ok    {} -> {} ({} variants)", input.display(), output.display(), variants.len());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error {}: {}", input.display(), e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Smooths a single file into the output file
+fn process(input: &Path, output: &Path, batch: &Batch) -> Report {
+    let mut report = match try_process(input, output, batch) {
+        Ok(report) => report,
+        Err(e) => Report { error: Some(e.to_string()), ..Report::default() },
+    };
+    report.input = Some(input.to_path_buf());
+    report.output = Some(output.to_path_buf());
+    report
+}
+
+fn try_process(input: &Path, output: &Path, batch: &Batch) -> io::Result<Report> {
+    let polylines = chaikin::document::read_polylines(input)?;
+    let (mut report, curves) = Report::smooth(&polylines, batch.steps, batch.smoother, batch.units, batch.closed);
+    let curves = report.resample(curves, batch.resample, batch.closed);
+
+    let mut writer = BufWriter::new(File::create(output)?);
+    batch.format.write(&mut writer, &curves, batch.units, batch.closed)?;
+    writer.flush()?;
+
+    Ok(report)
+}
+
+fn print_line(report: &Report) {
+    let input = report.input.as_deref().unwrap_or(Path::new("-")).display();
+    match &report.error {
+        Some(error) => eprintln!("error {}: {}", input, error),
+        None => println!(
+            "ok    {} -> {} ({} -> {} points)",
+            input,
+            report.output.as_deref().unwrap_or(Path::new("-")).display(),
+            report.input_points,
+            report.output_points,
+        ),
+    }
+}
+
+fn print_json(value: &impl Serialize) {
+    match serde_json::to_string_pretty(value) {
+        // Like with the points, whoever reads the report may stop early
+        Ok(json) => {
+            let _ = writeln!(io::stdout(), "{}", json);
+        }
+        Err(e) => eprintln!("Could not write the report: {}", e),
+    }
+}
+
+/// The largest distance from a point of the curve to the control polygon, closed or not
+fn max_deviation(control: &[Point], curve: &[Point], closed: bool) -> f32 {
+    match control {
+        [] => 0.0,
+        [_] => curve.iter().map(|p| geometry::distance_to_polyline(*p, control)).fold(0.0, f32::max),
+        _ => {
+            // Measured through a hierarchy of the segments, as refined curves have many points
+            let bvh = geometry::Bvh::new(control, closed);
+            curve.iter().filter_map(|p| bvh.nearest(control, *p)).map(|(_, distance)| distance).fold(0.0, f32::max)
+        }
+    }
+}
+
+/// The points, with the first repeated at the end if they are closed, so that their
+/// segments include the one closing them
+fn with_closing(points: &[Point], closed: bool) -> Vec<Point> {
+    let mut points = points.to_vec();
+    if let (true, Some(&first)) = (closed && points.len() > 2, points.first()) {
+        points.push(first);
+    }
+    points
+}
+
+/// The files directly inside the directory with a supported extension, sorted by name
+fn supported_files(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let supported = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| SUPPORTED_EXTENSIONS.contains(&extension));
+        if supported && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The input's file name, in the output directory, with the format's extension
+fn output_path(input: &Path, output_directory: &Path, format: Format) -> PathBuf {
+    let name = input.file_stem().unwrap_or(input.as_os_str());
+    output_directory.join(name).with_extension(format.extension())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chaikin::algorithm::ChaikinAlgorithm;
+
+    #[test]
+    fn test_batch_directory() {
+        let root = std::env::temp_dir().join(format!("chaikin-batch-{}", std::process::id()));
+        let (input, output) = (root.join("in"), root.join("out"));
+        fs::create_dir_all(&input).unwrap();
+        fs::write(input.join("good.txt"), "0 0\n100 100\n200 0\n").unwrap();
+        fs::write(input.join("bad.txt"), "0 0\noops\n").unwrap();
+        fs::write(input.join("ignored.png"), "").unwrap();
+
+        let code = batch(&Batch {
+            input: &input,
+            output: &output,
+            steps: 2,
+            format: Format::Txt,
+            smoother: &ChaikinAlgorithm::new(),
+            json: false,
+            units: Units::default(),
+            resample: None,
+            closed: false,
+        });
+
+        assert_eq!(code, ExitCode::FAILURE);
+        let smoothed = chaikin::document::read_points(&output.join("good.txt")).unwrap();
+        assert_eq!(smoothed.len(), 12);
+        assert!(!output.join("bad.txt").exists());
+        assert!(!output.join("ignored.txt").exists());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_closed_batch() {
+        let root = std::env::temp_dir().join(format!("chaikin-closed-batch-{}", std::process::id()));
+        let (input, output) = (root.join("in"), root.join("out"));
+        fs::create_dir_all(&input).unwrap();
+        fs::write(input.join("square.txt"), "0 0\n100 0\n100 100\n0 100\n").unwrap();
+
+        let code = batch(&Batch {
+            input: &input,
+            output: &output,
+            steps: 2,
+            format: Format::Svg,
+            smoother: &ChaikinAlgorithm::new().closed(true),
+            json: false,
+            units: Units::default(),
+            resample: None,
+            closed: true,
+        });
+
+        assert_eq!(code, ExitCode::SUCCESS);
+        let svg = fs::read_to_string(output.join("square.svg")).unwrap();
+        assert!(svg.contains(" Z\""), "the path isn't closed: {}", svg);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_render_curves() {
+        let points = vec![Point::new(0.0, 0.0), Point::new(1.0, 2.0), Point::new(2.0, 0.0)];
+        let curve = ChaikinAlgorithm::new().get_step_points(&points, 3);
+        let raster = render_curves(std::slice::from_ref(&points), &[curve], (200, 100), false);
+        let background = Theme::default().background;
+
+        // The shape is fitted inside the padding, with its middle point in the middle
+        assert_ne!(raster.pixels()[60 * 200 + 100], background);
+        assert!(raster.pixels()[..30 * 200].iter().all(|pixel| *pixel == background));
+
+        // Closed, the polygon's last side goes from (110, 40) back to (90, 40)
+        assert_eq!(raster.pixels()[40 * 200 + 100], background);
+        let curve = ChaikinAlgorithm::new().closed(true).get_step_points(&points, 3);
+        let raster = render_curves(std::slice::from_ref(&points), std::slice::from_ref(&curve), (200, 100), true);
+        assert_ne!(raster.pixels()[40 * 200 + 100], background);
+
+        // Several polylines are fitted together, each drawn on its own, with their middle
+        // points at (80, 60) and (120, 60)
+        let beside: Vec<Point> = points.iter().map(|point| Point::new(point.x + 4.0, point.y)).collect();
+        let raster = render_curves(&[points, beside], &[curve.clone(), curve], (200, 100), false);
+        assert_ne!(raster.pixels()[60 * 200 + 80], background);
+        assert_ne!(raster.pixels()[60 * 200 + 120], background);
+        assert_eq!(raster.pixels()[60 * 200 + 100], background);
+    }
+
+    #[test]
+    fn test_output_path() {
+        let path = output_path(Path::new("in/shape.txt"), Path::new("out"), Format::Svg);
+        assert_eq!(path, PathBuf::from("out/shape.svg"));
+    }
+
+    #[test]
+    fn test_report() {
+        let points = vec![vec![Point::new(0.0, 0.0), Point::new(100.0, 100.0), Point::new(200.0, 0.0)]];
+        let (report, curves) = Report::smooth(&points, 1, &ChaikinAlgorithm::new(), Units::default(), false);
+
+        assert_eq!(report.input_points, 3);
+        assert_eq!(report.output_points, curves[0].len());
+        assert_eq!(report.steps, 1);
+        // Chaikin's points all lie on the control polygon
+        assert!(report.max_deviation < 1e-4);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["output_points"], 6);
+        assert_eq!((json["width"].as_f64(), json["height"].as_f64()), (Some(200.0), Some(75.0)));
+        assert_eq!(json["units"], "px");
+        assert!(json.get("error").is_none());
+
+        // Resampled evenly along the curve, ends included
+        let (mut report, curves) = Report::smooth(&points, 2, &ChaikinAlgorithm::new(), Units::default(), false);
+        let resampled = report.resample(curves.clone(), Some(50), false);
+        assert_eq!(report.output_points, 50);
+        let curve = &curves[0];
+        assert_eq!((resampled[0][0], resampled[0][49]), (curve[0], curve[curve.len() - 1]));
+        assert_eq!(report.resample(curves.clone(), None, false), curves);
+
+        let (report, _) = Report::smooth(&points, 0, &ChaikinAlgorithm::new(), "2/mm".parse().unwrap(), false);
+        assert!((report.length - 141.42136).abs() < 1e-3);
+        assert_eq!(report.units, "2/mm");
+
+        // Each polyline is smoothed on its own, and the figures are of them all
+        let shifted: Vec<Point> = points[0].iter().map(|point| Point::new(point.x, point.y + 100.0)).collect();
+        let polylines = vec![points[0].clone(), shifted];
+        let (mut report, curves) = Report::smooth(&polylines, 1, &ChaikinAlgorithm::new(), Units::default(), false);
+        assert_eq!((report.polylines, report.input_points, report.output_points), (2, 6, 12));
+        assert_eq!(curves[1][0], Point::new(0.0, 100.0));
+        assert_eq!((report.width, report.height), (200.0, 175.0));
+        report.resample(curves, Some(10), false);
+        assert_eq!(report.output_points, 20);
+    }
+
+    #[test]
+    fn test_report_closed() {
+        let square = vec![vec![Point::new(0.0, 0.0), Point::new(100.0, 0.0), Point::new(100.0, 100.0), Point::new(0.0, 100.0)]];
+        let chaikin = ChaikinAlgorithm::new().closed(true);
+
+        // The segment closing the shape counts towards its length
+        let (mut report, curves) = Report::smooth(&square, 0, &chaikin, Units::default(), true);
+        assert!((report.length - 400.0).abs() < 1e-3);
+        // Evenly along the closing segment too, without repeating the first point
+        let resampled = report.resample(curves, Some(8), true);
+        assert_eq!(resampled[0].len(), 8);
+        assert!((resampled[0][7] - Point::new(0.0, 50.0)).norm() < 1e-3);
+
+        // An octagon, each of whose sides is on the square's
+        let (report, curves) = Report::smooth(&square, 1, &chaikin, Units::default(), true);
+        assert_eq!(curves[0].len(), 8);
+        assert!((report.length - (200.0 + 100.0 * 2f32.sqrt())).abs() < 1e-3);
+        assert!(report.max_deviation < 1e-4);
+    }
+
+    #[test]
+    fn test_max_deviation() {
+        let control = vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0)];
+        let curve = vec![Point::new(5.0, 3.0), Point::new(12.0, 4.0)];
+        assert!((max_deviation(&control, &curve, false) - 4.472136).abs() < 1e-4);
+    }
+}
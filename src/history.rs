@@ -0,0 +1,273 @@
+//! Snapshots of the control points, for undoing edits.
+//!
+//! Keeping many states of a large path would take as many copies of it, although edits
+//! usually change only a few points. Snapshots are made of chunks of points instead, and a
+//! chunk that is the same as in the snapshot taken before is shared with it rather than
+//! copied. Appending or moving a point copies a single chunk; inserting or deleting one
+//! copies the chunks after it, which are shifted. The polylines finished before the points
+//! are kept whole, and shared as long as they don't change, which most edits don't.
+//!
+//! [`EditHistory`] keeps the snapshots to undo and redo the edits made to the points.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use crate::types::Point;
+
+/// How many points each chunk of a snapshot holds
+const CHUNK_POINTS: usize = 1024;
+
+/// The points as they were when the snapshot was taken, with the polylines finished before
+/// them
+#[derive(Clone, Default)]
+pub struct Snapshot {
+    chunks: Vec<Arc<[Point]>>,
+    polylines: Arc<[Vec<Point>]>,
+}
+
+impl Snapshot {
+    /// Takes a snapshot of the points and the polylines, sharing what didn't change since
+    /// the previous snapshot, if there is one
+    pub fn take(points: &[Point], polylines: &[Vec<Point>], previous: Option<&Snapshot>) -> Self {
+        let chunks = points
+            .chunks(CHUNK_POINTS)
+            .enumerate()
+            .map(|(i, chunk)| match previous.and_then(|previous| previous.chunks.get(i)) {
+                Some(shared) if **shared == *chunk => Arc::clone(shared),
+                _ => Arc::from(chunk),
+            })
+            .collect();
+        let polylines = match previous {
+            Some(previous) if *previous.polylines == *polylines => Arc::clone(&previous.polylines),
+            _ => Arc::from(polylines),
+        };
+        Self { chunks, polylines }
+    }
+
+    /// The points, as a new list that can be edited
+    pub fn restore(&self) -> Vec<Point> {
+        self.chunks.concat()
+    }
+
+    /// The polylines finished before the points, as a new list that can be edited
+    pub fn restore_polylines(&self) -> Vec<Vec<Point>> {
+        self.polylines.to_vec()
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+/// The last snapshots taken, up to a limit, after which the oldest ones are forgotten
+pub struct Snapshots {
+    snapshots: VecDeque<Snapshot>,
+    capacity: usize,
+}
+
+impl Snapshots {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { snapshots: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    /// Takes a snapshot of the points and the polylines, sharing what didn't change with the
+    /// last one
+    pub fn push(&mut self, points: &[Point], polylines: &[Vec<Point>]) {
+        let snapshot = Snapshot::take(points, polylines, self.snapshots.back());
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// Puts back a snapshot taken earlier, e.g. one being redone
+    pub fn push_snapshot(&mut self, snapshot: Snapshot) {
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// The last snapshot, which isn't kept anymore
+    pub fn pop(&mut self) -> Option<Snapshot> {
+        self.snapshots.pop_back()
+    }
+
+    /// The last snapshot, which is kept
+    pub fn last(&self) -> Option<&Snapshot> {
+        self.snapshots.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+/// How many edits can be undone
+pub const UNDO_LIMIT: usize = 100;
+
+/// The points and the polylines finished before them, as restored by [`EditHistory`]
+pub type Restored = (Vec<Point>, Vec<Vec<Point>>);
+
+/// The states of the points before the edits that can be undone, and after the ones that
+/// were undone and can be redone
+pub struct EditHistory {
+    undo: Snapshots,
+    redo: Snapshots,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::with_capacity(UNDO_LIMIT)
+    }
+}
+
+impl EditHistory {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { undo: Snapshots::with_capacity(capacity), redo: Snapshots::with_capacity(capacity) }
+    }
+
+    /// Records the points and the polylines as they are before an edit. The edits undone
+    /// until then can't be redone anymore
+    pub fn record(&mut self, points: &[Point], polylines: &[Vec<Point>]) {
+        self.undo.push(points, polylines);
+        self.redo.clear();
+    }
+
+    /// The points and the polylines as they were before the last edit, if there is one to
+    /// undo. The current ones are kept to redo it
+    pub fn undo(&mut self, points: &[Point], polylines: &[Vec<Point>]) -> Option<Restored> {
+        let snapshot = self.undo.pop()?;
+        self.redo.push(points, polylines);
+        Some((snapshot.restore(), snapshot.restore_polylines()))
+    }
+
+    /// The points and the polylines as they were after the last edit undone, if there is
+    /// one to redo
+    pub fn redo(&mut self, points: &[Point], polylines: &[Vec<Point>]) -> Option<Restored> {
+        let snapshot = self.redo.pop()?;
+        self.undo.push(points, polylines);
+        Some((snapshot.restore(), snapshot.restore_polylines()))
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forgets every edit, e.g. when another file is opened
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(len: usize) -> Vec<Point> {
+        (0..len).map(|i| Point::new(i as f32, (i % 10) as f32)).collect()
+    }
+
+    #[test]
+    fn test_restore() {
+        let points = path(2500);
+        let snapshot = Snapshot::take(&points, &[path(3)], None);
+        assert_eq!(snapshot.len(), 2500);
+        assert_eq!(snapshot.restore(), points);
+        assert_eq!(snapshot.restore_polylines(), [path(3)]);
+        assert!(Snapshot::take(&[], &[], None).is_empty());
+    }
+
+    #[test]
+    fn test_shares_unchanged_chunks() {
+        let mut points = path(20_000);
+        let polylines = [path(5000)];
+        let mut snapshots = Snapshots::with_capacity(100);
+        for i in 0..100 {
+            points[i * 10].y += 1.0;
+            snapshots.push(&points, &polylines);
+        }
+        assert_eq!(snapshots.len(), 100);
+        // The polylines didn't change, and are stored once
+        let first = &snapshots.snapshots[0].polylines;
+        assert!(snapshots.snapshots.iter().all(|snapshot| Arc::ptr_eq(&snapshot.polylines, first)));
+
+        // Every edit touched the first chunk only, and the other ones are stored once
+        let mut chunks: Vec<*const Point> = snapshots
+            .snapshots
+            .iter()
+            .flat_map(|snapshot| snapshot.chunks.iter().map(|chunk| chunk.as_ptr()))
+            .collect();
+        chunks.sort();
+        chunks.dedup();
+        assert_eq!(chunks.len(), 100 + 20_000 / CHUNK_POINTS);
+
+        assert_eq!(snapshots.pop().unwrap().restore(), points);
+        points[990].y -= 1.0;
+        assert_eq!(snapshots.last().unwrap().restore(), points);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut snapshots = Snapshots::with_capacity(3);
+        for len in 1..=5 {
+            snapshots.push(&path(len), &[]);
+        }
+        assert_eq!(snapshots.len(), 3);
+        let lengths: Vec<usize> = std::iter::from_fn(|| snapshots.pop()).map(|s| s.len()).collect();
+        assert_eq!(lengths, vec![5, 4, 3]);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut history = EditHistory::default();
+        let mut points = path(3);
+        assert_eq!(history.undo(&points, &[]), None);
+
+        history.record(&points, &[]);
+        points.push(Point::new(5.0, 5.0));
+        history.record(&points, &[]);
+        points[0].x = 10.0;
+
+        let moved = points.clone();
+        points = history.undo(&points, &[]).unwrap().0;
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[0].x, 0.0);
+        points = history.undo(&points, &[]).unwrap().0;
+        assert_eq!(points, path(3));
+        assert!(!history.can_undo());
+
+        points = history.redo(&points, &[]).unwrap().0;
+        points = history.redo(&points, &[]).unwrap().0;
+        assert_eq!(points, moved);
+        assert_eq!(history.redo(&points, &[]), None);
+
+        // A new edit drops what could be redone
+        history.undo(&points, &[]).unwrap();
+        history.record(&points, &[]);
+        assert!(!history.can_redo());
+
+        // Finishing a polyline is undone with the points it took
+        history.record(&points, &[]);
+        let polylines = vec![std::mem::take(&mut points)];
+        let (points, restored) = history.undo(&points, &polylines).unwrap();
+        assert_eq!((points, restored), (moved, Vec::new()));
+    }
+}
@@ -0,0 +1,107 @@
+//! Single-instance support.
+//!
+//! The first instance of the application listens on a local socket. When another instance is
+//! started, it connects to that socket, forwards the file it was asked to open (if any), and
+//! exits, so the user keeps working in a single window.
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener as Listener, UnixStream as Stream};
+#[cfg(not(unix))]
+use std::net::{TcpListener as Listener, TcpStream as Stream};
+
+/// On platforms without Unix sockets we fall back to a fixed port on the loopback interface
+#[cfg(not(unix))]
+const LOOPBACK_ADDRESS: &str = "127.0.0.1:47613";
+
+/// A request forwarded from a secondary instance
+pub enum Request {
+    /// Bring the window to the front
+    Focus,
+    /// Bring the window to the front and open the given file
+    Open(PathBuf),
+}
+
+/// The role this process got when it tried to become the running instance
+pub enum Instance {
+    /// We are the running instance; forwarded requests arrive on the receiver
+    Primary(Receiver<Request>),
+    /// Another instance is already running and has received our request
+    Secondary,
+}
+
+/// Becomes the running instance, or hands `file` over to the one that already exists.
+///
+/// If the socket can't be set up at all, we still run as the primary instance, only without
+/// receiving anything from other instances
+pub fn acquire(file: Option<&Path>) -> Instance {
+    if let Ok(mut stream) = connect() {
+        let line = file
+            .map(|path| absolute(path).display().to_string())
+            .unwrap_or_default();
+        if writeln!(stream, "{}", line).is_ok() {
+            return Instance::Secondary;
+        }
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    if let Ok(listener) = bind() {
+        thread::spawn(move || listen(listener, sender));
+    }
+    Instance::Primary(receiver)
+}
+
+/// Accepts connections from other instances, forwarding each line they send as a request
+fn listen(listener: Listener, sender: Sender<Request>) {
+    for stream in listener.incoming().flatten() {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            let request = if line.is_empty() {
+                Request::Focus
+            } else {
+                Request::Open(PathBuf::from(line))
+            };
+            // The window is gone, nobody is interested anymore
+            if sender.send(request).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// The receiving instance may run in another working directory, so relative paths won't do
+fn absolute(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(unix)]
+fn socket_path() -> PathBuf {
+    let user = std::env::var("USER").unwrap_or_default();
+    std::env::temp_dir().join(format!("chaikin-{}.sock", user))
+}
+
+#[cfg(unix)]
+fn connect() -> std::io::Result<Stream> {
+    Stream::connect(socket_path())
+}
+
+#[cfg(unix)]
+fn bind() -> std::io::Result<Listener> {
+    // Nobody answered on the socket, so whatever is left there is from a crashed instance
+    let path = socket_path();
+    let _ = std::fs::remove_file(&path);
+    Listener::bind(path)
+}
+
+#[cfg(not(unix))]
+fn connect() -> std::io::Result<Stream> {
+    Stream::connect(LOOPBACK_ADDRESS)
+}
+
+#[cfg(not(unix))]
+fn bind() -> std::io::Result<Listener> {
+    Listener::bind(LOOPBACK_ADDRESS)
+}
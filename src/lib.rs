@@ -0,0 +1,37 @@
+//! Chaikin's curve algorithm, with an interactive window to visualize it.
+//!
+//! The geometry is in [`algorithm`], which only depends on nalgebra: other crates can use it
+//! without the window by turning off the default `gui` feature:
+//!
+//! ```toml
+//! chaikin = { version = "0.1", default-features = false }
+//! ```
+//!
+//! ```
+//! use chaikin::{ChaikinAlgorithm, Point, Steps};
+//!
+//! let points = [Point::new(0.0, 0.0), Point::new(100.0, 100.0), Point::new(200.0, 0.0)];
+//! let curve = Steps::new(&ChaikinAlgorithm::new(), &points).nth(3).unwrap();
+//! assert_eq!(curve.len(), 24);
+//! ```
+//!
+//! With the `gui` feature, the window can also be embedded inside another application, see
+//! [`window::EmbeddedCanvas`].
+
+pub mod algorithm;
+pub mod demo;
+pub mod document;
+pub mod export;
+pub mod geometry;
+pub mod history;
+pub mod numbers;
+pub mod persistence;
+pub mod quiz;
+pub mod stats;
+pub mod types;
+pub mod units;
+#[cfg(feature = "gui")]
+pub mod window;
+
+pub use algorithm::{ChaikinAlgorithm, Smoother, Steps};
+pub use types::Point;
@@ -1,18 +1,233 @@
-mod types;
-mod window;
+mod cli;
+mod first_run;
+mod headless;
+mod ipc;
+mod recovery;
+mod watch;
 
-use window::WindowManager;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
+use std::sync::mpsc::Receiver;
 
-const WIDTH: usize = 800;
-const HEIGHT: usize = 600;
+use chaikin::algorithm::Smoother;
+use chaikin::numbers::NumberFormat;
+use chaikin::window::{Config, Gradient, Theme, WindowManager};
+use clap::Parser;
 
-fn main() {
-    let title = "Chaikin's Algorithm - [Ctrl + R]: Reset - [Escape]: Close";
-    let mut window_manager = WindowManager::new(WIDTH, HEIGHT, title);
+use cli::{Cli, Command, Variant};
+use ipc::{Instance, Request};
+use watch::FileWatcher;
 
+/// How many steps `--headless` renders, unless given with `--steps`
+const HEADLESS_STEPS: u32 = 4;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // Some schemes can't smooth closed shapes, without a window as within one
+    let Some(smoother) = closed_if(cli.scheme.smoother(cli.ratios), cli.closed) else {
+        eprintln!("The {} scheme only smooths open shapes", cli.scheme);
+        return ExitCode::FAILURE;
+    };
+
+    if let Some(Command::Batch(args)) = &cli.command {
+        return headless::batch(&headless::Batch {
+            input: &args.input,
+            output: &args.output,
+            steps: args.steps as usize,
+            format: args.format,
+            smoother: smoother.as_ref(),
+            json: cli.json,
+            units: cli.units,
+            resample: cli.resample.map(|points| points as usize),
+            closed: cli.closed,
+        });
+    }
+
+    if let Some(Command::Atlas(args)) = &cli.command {
+        return headless::atlas(
+            &args.input,
+            &args.output,
+            smoother,
+            args.steps as usize,
+            args.cell as usize,
+            args.columns as usize,
+            cli.closed,
+        );
+    }
+
+    if let Some(Command::Lottie(args)) = &cli.command {
+        return headless::lottie(
+            &args.input,
+            &args.output,
+            smoother.as_ref(),
+            args.steps as usize,
+            args.fps,
+            cli.closed,
+        );
+    }
+
+    if let Some(Command::Gif(args)) = &cli.command {
+        let size = args.size as usize;
+        return headless::gif(&args.input, &args.output, smoother, args.steps as usize, size, cli.closed);
+    }
+
+    if let Some(Command::Handout(args)) = &cli.command {
+        return headless::handout(&args.input, &args.output, smoother.as_ref(), args.steps as usize, cli.closed);
+    }
+
+    if let Some(Command::Report(args)) = &cli.command {
+        let defaults = args.variants.is_empty();
+        let mut variants = Vec::new();
+        for variant in if defaults { Variant::defaults() } else { args.variants.clone() } {
+            match closed_if(variant.scheme.smoother(variant.ratios.unwrap_or(cli.ratios)), cli.closed) {
+                Some(smoother) => variants.push((variant.label(), smoother)),
+                // Closed shapes are only compared across the schemes that can smooth them
+                None if defaults => {}
+                None => {
+                    eprintln!("The {} scheme only smooths open shapes", variant.scheme);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        let numbers = NumberFormat::from_env().with_precision(cli.precision.map(|decimals| decimals as usize));
+        let (steps, units) = (args.steps as usize, cli.units);
+        return headless::report(&args.input, &args.output, &variants, steps, units, numbers, cli.closed);
+    }
+
+    if let (true, Some(input), Some(output)) = (cli.headless, &cli.file, &cli.out) {
+        let steps = cli.steps.unwrap_or(HEADLESS_STEPS) as usize;
+        let size = (cli.width as usize, cli.height as usize);
+        return headless::render(input, output, smoother.as_ref(), steps, size, cli.closed);
+    }
+
+    if let Some(steps) = cli.steps {
+        return headless::stream(cli.file.as_deref(), steps as usize, smoother.as_ref(), cli.json, cli.units, cli.resample.map(|points| points as usize), cli.closed);
+    }
+
+    // If the app is already running, it takes over the file and we're done
+    let requests = match ipc::acquire(cli.file.as_deref()) {
+        Instance::Primary(requests) => requests,
+        Instance::Secondary => return ExitCode::SUCCESS,
+    };
+
+    recovery::install_hook();
+
+    let defaults = Theme::default();
+    let mut window_manager = WindowManager::new(&Config {
+        width: cli.width as usize,
+        height: cli.height as usize,
+        title: cli.title.clone(),
+        max_steps: cli.max_steps as usize,
+        step_interval: Duration::from_millis(cli.step_ms),
+        point_radius: cli.point_radius,
+        refresh_rate: cli.refresh_rate,
+        theme: Theme {
+            point: cli.point_color.unwrap_or(defaults.point),
+            point_glyph: cli.point_glyph,
+            line: cli.line_color.unwrap_or(defaults.line),
+            polygon: cli.polygon_color.unwrap_or(defaults.polygon),
+            background: cli.background_color.unwrap_or(defaults.background),
+            stroke_opacity: cli.stroke_opacity,
+            stroke_width: cli.stroke_width,
+            blend_mode: cli.blend,
+            text_font: cli.text_font,
+            title_font: cli.title_font,
+        },
+    });
+    if cli.maximized {
+        window_manager.maximize();
+    }
+    window_manager.set_scheme(cli.scheme, cli.ratios);
+    window_manager.set_units(cli.units);
+    window_manager.set_number_format(NumberFormat::from_env().with_precision(cli.precision.map(|decimals| decimals as usize)));
+    window_manager.set_placement(cli.placement);
+    window_manager.set_ghost(cli.ghost);
+    window_manager.set_limit(cli.limit);
+    window_manager.set_interpolate(!cli.discrete);
+    window_manager.set_tween_by_arclength(cli.uniform_tween);
+    window_manager.set_gradient(cli.gradient.then(Gradient::default));
+    window_manager.set_onion_skin(cli.onion_skin);
+    if cli.closed && !window_manager.set_closed(true) {
+        eprintln!("The {} scheme only smooths open shapes", cli.scheme);
+        return ExitCode::FAILURE;
+    }
+    let file = cli.watch.as_ref().or(cli.file.as_ref());
+    if let Some(file) = file {
+        window_manager.open_file(file);
+    }
+
+    if let Some(directory) = &cli.capture_steps {
+        window_manager.capture_steps_to(directory.clone());
+    }
+    if cli.demo {
+        window_manager.start_demo(cli.seed);
+    }
+    if cli.quiz {
+        window_manager.start_quiz(cli.seed);
+    }
+    if cli.tutorial {
+        window_manager.start_tutorial();
+    } else if !cli.demo && !cli.quiz && file.is_none() && first_run::is_first_run() {
+        window_manager.offer_tutorial();
+    }
+
+    let watcher = cli.watch.as_deref().and_then(|path| match FileWatcher::new(path) {
+        Ok(watcher) => Some((watcher, path)),
+        Err(e) => {
+            eprintln!("Could not watch {}: {}", path.display(), e);
+            None
+        }
+    });
+
+    // A crash shouldn't cost the user their points, so we hold on to them until the end
+    let result = panic::catch_unwind(AssertUnwindSafe(|| run(&mut window_manager, &requests, watcher.as_ref())));
+    if result.is_err() {
+        recovery::recover(window_manager.points());
+        return ExitCode::from(101);
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// The smoother, closed if the shape is, or none if its scheme only smooths open shapes
+fn closed_if(smoother: Box<dyn Smoother>, closed: bool) -> Option<Box<dyn Smoother>> {
+    if closed {
+        smoother.with_closed(true)
+    } else {
+        Some(smoother)
+    }
+}
+
+/// Runs the main loop until the window is closed
+fn run(
+    window_manager: &mut WindowManager,
+    requests: &Receiver<Request>,
+    watcher: Option<&(FileWatcher, &Path)>,
+) {
     while window_manager.handle_input() {
+        if let Some((watcher, path)) = watcher {
+            if watcher.changed() {
+                window_manager.reload_file(path);
+            }
+        }
+
+        for request in requests.try_iter() {
+            window_manager.bring_to_front();
+            if let Request::Open(path) = request {
+                window_manager.open_file(&path);
+            }
+        }
+
+        // Nothing on screen changes until the next input, so there's no frame to draw
+        if window_manager.is_idle() {
+            window_manager.wait_for_input();
+            continue;
+        }
+
         window_manager.redraw();
         window_manager.update();
         window_manager.update_buffer();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,104 @@
+//! Writing numbers for people to read, with the decimal separator of their locale.
+//!
+//! Only what is shown or printed goes through here: the files other programs read, such as
+//! the points, SVG images and JSON reports, always use a decimal point.
+
+use std::env;
+
+/// Languages that write a decimal comma, by their ISO 639-1 code
+const DECIMAL_COMMA_LANGUAGES: [&str; 28] = [
+    "bg", "ca", "cs", "da", "de", "el", "es", "et", "eu", "fi", "fr", "gl", "hr", "hu", "id", "is", "it", "lt",
+    "lv", "nb", "nl", "nn", "pl", "pt", "ro", "ru", "sk", "sv",
+];
+
+/// How numbers are written: with which decimal separator, and optionally with a fixed number
+/// of decimals instead of each readout's own
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal_separator: char,
+    /// Decimals every number is written with, or none for each readout's own
+    pub precision: Option<usize>,
+}
+
+impl Default for NumberFormat {
+    /// A decimal point, as in English
+    fn default() -> Self {
+        Self { decimal_separator: '.', precision: None }
+    }
+}
+
+impl NumberFormat {
+    /// The format of a POSIX locale name, e.g. "de_DE.UTF-8" writes a decimal comma. Unknown
+    /// locales, and "C" or "POSIX", write a decimal point
+    pub fn for_locale(locale: &str) -> Self {
+        let language = locale.split(['_', '-', '.', '@']).next().unwrap_or_default().to_ascii_lowercase();
+        let decimal_separator = if DECIMAL_COMMA_LANGUAGES.contains(&language.as_str()) { ',' } else { '.' };
+        Self { decimal_separator, ..Self::default() }
+    }
+
+    /// The format of the user's locale, from the environment as the C library reads it:
+    /// `LC_ALL`, then `LC_NUMERIC`, then `LANG`
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|locale| !locale.is_empty())
+            .map(|locale| Self::for_locale(&locale))
+            .unwrap_or_default()
+    }
+
+    /// The same format with a fixed number of decimals, or each readout's own
+    pub fn with_precision(self, precision: Option<usize>) -> Self {
+        Self { precision, ..self }
+    }
+
+    /// The number with the given decimals, unless the format fixes them, e.g. "12,5"
+    pub fn format(&self, value: f32, decimals: usize) -> String {
+        let text = format!("{:.*}", self.precision.unwrap_or(decimals), value);
+        self.localize(&text)
+    }
+
+    /// The text with the decimal points of its numbers written with the separator, e.g. for
+    /// formulas written by the schemes. Other dots, such as ending a sentence, are kept
+    pub fn localize(&self, text: &str) -> String {
+        if self.decimal_separator == '.' {
+            return text.to_string();
+        }
+        let chars: Vec<char> = text.chars().collect();
+        chars
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let between_digits = i > 0
+                    && chars[i - 1].is_ascii_digit()
+                    && chars.get(i + 1).is_some_and(|next| next.is_ascii_digit());
+                if *c == '.' && between_digits { self.decimal_separator } else { *c }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_locale() {
+        assert_eq!(NumberFormat::for_locale("de_DE.UTF-8").decimal_separator, ',');
+        assert_eq!(NumberFormat::for_locale("fr").decimal_separator, ',');
+        assert_eq!(NumberFormat::for_locale("pt-BR").decimal_separator, ',');
+        assert_eq!(NumberFormat::for_locale("en_US.UTF-8").decimal_separator, '.');
+        assert_eq!(NumberFormat::for_locale("C"), NumberFormat::default());
+    }
+
+    #[test]
+    fn test_format() {
+        let german = NumberFormat::for_locale("de_DE");
+        assert_eq!(german.format(12.345, 1), "12,3");
+        assert_eq!(german.format(90.0, 0), "90");
+        assert_eq!(german.with_precision(Some(3)).format(0.5, 1), "0,500");
+        assert_eq!(NumberFormat::default().format(12.345, 2), "12.35");
+
+        assert_eq!(german.localize("Q_i = 0.75·P_i + 0.25·P_{i+1}."), "Q_i = 0,75·P_i + 0,25·P_{i+1}.");
+    }
+}
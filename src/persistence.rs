@@ -0,0 +1,144 @@
+//! Sessions saved as JSON: the points along with the settings they were smoothed with, to
+//! pick up where one left off. Unlike the plain text files of points, sessions are meant to
+//! be opened by the application again rather than by other tools.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::document::Metadata;
+use crate::types::Point;
+
+/// The version of the session format written, increased when it changes incompatibly
+pub const SESSION_VERSION: u32 = 1;
+
+/// The points of a session and how they are smoothed. Points are written as `[x, y]`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Session {
+    pub version: u32,
+    /// The polyline being drawn
+    pub points: Vec<[f32; 2]>,
+    /// The polylines finished before it
+    #[serde(default)]
+    pub polylines: Vec<Vec<[f32; 2]>>,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub notes: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub settings: Settings,
+}
+
+/// How the points were smoothed and animated
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Settings {
+    /// The name of the built-in scheme, e.g. "chaikin", or none for another smoother
+    pub scheme: Option<String>,
+    /// Where Chaikin's scheme places the new points along each segment
+    pub ratios: (f32, f32),
+    /// Whether the last point connects back to the first
+    pub closed: bool,
+    /// The number of steps the animation goes through before starting over
+    pub max_steps: usize,
+    /// How long each step of the animation is shown, in milliseconds
+    pub step_interval_ms: u64,
+}
+
+impl Session {
+    /// The session of the polylines and what was written about them
+    pub fn new(points: &[Point], polylines: &[Vec<Point>], metadata: &Metadata, settings: Settings) -> Self {
+        Self {
+            version: SESSION_VERSION,
+            points: to_pairs(points),
+            polylines: polylines.iter().map(|polyline| to_pairs(polyline)).collect(),
+            name: metadata.name.clone(),
+            notes: metadata.notes.clone(),
+            tags: metadata.tags.clone(),
+            settings,
+        }
+    }
+
+    pub fn points(&self) -> Vec<Point> {
+        from_pairs(&self.points)
+    }
+
+    pub fn polylines(&self) -> Vec<Vec<Point>> {
+        self.polylines.iter().map(|polyline| from_pairs(polyline)).collect()
+    }
+
+    pub fn metadata(&self) -> Metadata {
+        Metadata { name: self.name.clone(), notes: self.notes.clone(), tags: self.tags.clone() }
+    }
+}
+
+/// Writes the session to the file, as indented JSON
+pub fn write_session(path: &Path, session: &Session) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(session).map_err(io::Error::other)?;
+    fs::write(path, json + "\n")
+}
+
+/// Reads a session from the file. Sessions written by a later version of the format are
+/// refused, rather than half understood
+pub fn read_session(path: &Path) -> io::Result<Session> {
+    let json = fs::read_to_string(path)?;
+    let session: Session = serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if session.version > SESSION_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the session is from a newer version (format {})", session.version),
+        ));
+    }
+    Ok(session)
+}
+
+fn to_pairs(points: &[Point]) -> Vec<[f32; 2]> {
+    points.iter().map(|point| [point.x, point.y]).collect()
+}
+
+fn from_pairs(pairs: &[[f32; 2]]) -> Vec<Point> {
+    pairs.iter().map(|[x, y]| Point::new(*x, *y)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> Settings {
+        Settings { scheme: Some("chaikin".to_string()), ratios: (0.2, 0.8), closed: true, max_steps: 5, step_interval_ms: 500 }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("chaikin-session-{}.json", std::process::id()));
+        let points = vec![Point::new(10.5, 20.0), Point::new(30.0, 40.25)];
+        let polylines = vec![vec![Point::new(0.0, 0.0), Point::new(5.0, 5.0)]];
+        let metadata = Metadata { name: "Star".to_string(), notes: "Five points".to_string(), tags: vec!["demo".to_string()] };
+        let session = Session::new(&points, &polylines, &metadata, settings());
+
+        write_session(&path, &session).unwrap();
+        let read = read_session(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read, session);
+        assert_eq!(read.points(), points);
+        assert_eq!(read.polylines(), polylines);
+        assert_eq!(read.metadata(), metadata);
+    }
+
+    #[test]
+    fn test_newer_version() {
+        let path = std::env::temp_dir().join(format!("chaikin-session-newer-{}.json", std::process::id()));
+        let session = Session { version: SESSION_VERSION + 1, ..Session::new(&[], &[], &Metadata::default(), settings()) };
+        write_session(&path, &session).unwrap();
+        let error = read_session(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        std::fs::write(&path, "{ \"points\": 3 }").unwrap();
+        assert!(read_session(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
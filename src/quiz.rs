@@ -0,0 +1,102 @@
+//! Exercises for the classroom: a target curve to match with as few control points as
+//! possible.
+
+use crate::algorithm::Smoother;
+use crate::demo::Demo;
+use crate::geometry::hausdorff;
+use crate::numbers::NumberFormat;
+use crate::types::Point;
+
+/// How far, in pixels, a curve can be from the target and still match it
+pub const TOLERANCE: f32 = 6.0;
+
+/// A target curve, smoothed from a random shape that the student has to find again
+pub struct Exercise {
+    /// The curve to match
+    pub target: Vec<Point>,
+    /// The number of points of the shape the target was smoothed from, which is the fewest
+    /// the student should need
+    pub par: usize,
+    /// The number of smoothing steps the target went through, which the student's points
+    /// go through too
+    pub steps: usize,
+}
+
+/// How close a student's attempt came to the target
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grade {
+    /// The Hausdorff distance between the attempt's curve and the target
+    pub distance: f32,
+    /// The number of control points the attempt used
+    pub points: usize,
+    /// The fewest points the target can be matched with
+    pub par: usize,
+}
+
+impl Exercise {
+    /// An exercise whose target is a random shape, determined by the seed and fitting a
+    /// canvas of the given size, smoothed with the scheme
+    pub fn generate(seed: u64, width: usize, height: usize, smoother: &dyn Smoother, steps: usize) -> Self {
+        let shape = Demo::new(seed).next_shape(width, height);
+        Self { target: smoother.get_step_points(&shape, steps), par: shape.len(), steps }
+    }
+
+    /// Grades the control points, smoothed with the same scheme as the target
+    pub fn grade(&self, points: &[Point], smoother: &dyn Smoother) -> Grade {
+        let curve = smoother.get_step_points(points, self.steps);
+        Grade { distance: hausdorff(&curve, &self.target), points: points.len(), par: self.par }
+    }
+}
+
+impl Grade {
+    /// Whether the curve is within the tolerance of the target
+    pub fn matched(&self) -> bool {
+        self.distance <= TOLERANCE
+    }
+
+    /// What to tell the student, with the distance written in the format
+    pub fn message(&self, numbers: &NumberFormat) -> String {
+        let distance = numbers.format(self.distance, 1);
+        if !self.matched() {
+            format!("{} px off the target, it has to be within {} px. Keep trying!", distance, TOLERANCE)
+        } else if self.points <= self.par {
+            format!("Matched within {} px with {} points, the fewest. Well done!", distance, self.points)
+        } else {
+            format!("Matched within {} px with {} points. Can you do it with {}?", distance, self.points, self.par)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::ChaikinAlgorithm;
+
+    #[test]
+    fn test_grade() {
+        let smoother = ChaikinAlgorithm::new();
+        let exercise = Exercise::generate(7, 800, 600, &smoother, 4);
+        let shape = Demo::new(7).next_shape(800, 600);
+        assert_eq!(exercise.par, shape.len());
+
+        // The shape the target came from matches it exactly
+        let grade = exercise.grade(&shape, &smoother);
+        assert_eq!(grade.distance, 0.0);
+        assert!(grade.matched());
+        assert!(grade.message(&NumberFormat::default()).contains("the fewest"));
+
+        // Moving a point far enough doesn't
+        let mut moved = shape.clone();
+        moved[2].x += 50.0;
+        let grade = exercise.grade(&moved, &smoother);
+        assert!(!grade.matched());
+        assert!(grade.message(&NumberFormat::default()).contains("Keep trying"));
+
+        // A point more than needed still matches, with a nudge to use fewer
+        let mut more = shape.clone();
+        more.insert(1, Point::new((shape[0].x + shape[1].x) / 2.0, (shape[0].y + shape[1].y) / 2.0));
+        let grade = exercise.grade(&more, &smoother);
+        assert_eq!(grade.points, shape.len() + 1);
+        assert_eq!(grade.par, shape.len());
+    }
+}
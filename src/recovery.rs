@@ -0,0 +1,97 @@
+//! Crash recovery.
+//!
+//! If the application panics, the points the user placed are written to a recovery file
+//! and the error is shown in a message box, so the work isn't silently lost.
+
+use std::panic;
+use std::path::Path;
+use std::sync::Mutex;
+
+use chaikin::types::Point;
+
+/// Where the points are saved when the application crashes
+pub const RECOVERY_PATH: &str = "chaikin-recovery.txt";
+
+/// The message of the last panic, kept around so it can be shown to the user
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+/// Installs a panic hook that remembers the panic message, on top of the default hook
+/// which still prints it to stderr
+pub fn install_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if let Ok(mut last_panic) = LAST_PANIC.lock() {
+            *last_panic = Some(info.to_string());
+        }
+        default_hook(info);
+    }));
+}
+
+/// Saves the given points to [`RECOVERY_PATH`] and tells the user what happened.
+/// Meant to be called after a panic was caught
+pub fn recover(points: &[Point]) {
+    let error = LAST_PANIC
+        .lock()
+        .ok()
+        .and_then(|mut last_panic| last_panic.take())
+        .unwrap_or_else(|| "Unknown error".to_string());
+
+    let mut message = format!("The application crashed:\n\n{}", error);
+    if !points.is_empty() {
+        match chaikin::document::write_points(Path::new(RECOVERY_PATH), points) {
+            Ok(()) => message.push_str(&format!(
+                "\n\nYour points were saved to {}",
+                RECOVERY_PATH
+            )),
+            Err(e) => message.push_str(&format!("\n\nYour points could not be saved: {}", e)),
+        }
+    }
+
+    eprintln!("{}", message);
+    show_message_box("Chaikin crashed", &message);
+}
+
+/// Shows a native error message box, if the platform gives us a way to
+#[cfg(target_os = "windows")]
+fn show_message_box(title: &str, message: &str) {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::winuser::{MessageBoxW, MB_ICONERROR, MB_OK};
+
+    let wide = |s: &str| -> Vec<u16> { OsStr::new(s).encode_wide().chain(Some(0)).collect() };
+    let (title, message) = (wide(title), wide(message));
+    unsafe {
+        MessageBoxW(std::ptr::null_mut(), message.as_ptr(), title.as_ptr(), MB_OK | MB_ICONERROR);
+    }
+}
+
+/// Shows a native error message box, if the platform gives us a way to
+#[cfg(target_os = "macos")]
+fn show_message_box(title: &str, message: &str) {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "display alert \"{}\" message \"{}\" as critical",
+        escape(title),
+        escape(message)
+    );
+    let _ = std::process::Command::new("osascript").args(["-e", &script]).status();
+}
+
+/// Shows a native error message box, if the platform gives us a way to.
+/// There's no standard API on other unixes, so we try the usual dialog tools in turn
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn show_message_box(title: &str, message: &str) {
+    use std::process::Command;
+
+    let tools: [(&str, Vec<&str>); 3] = [
+        ("zenity", vec!["--error", "--title", title, "--text", message]),
+        ("kdialog", vec!["--title", title, "--error", message]),
+        ("xmessage", vec!["-center", message]),
+    ];
+    for (program, args) in tools {
+        // The first tool that is installed shows the message
+        if Command::new(program).args(args).status().is_ok() {
+            return;
+        }
+    }
+}
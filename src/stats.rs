@@ -0,0 +1,94 @@
+//! Counters of what was done during a session, e.g. for an instructor to show at the end of
+//! a workshop. They are only kept in memory and never leave the machine.
+
+use std::time::{Duration, Instant};
+
+use crate::types::AnimationState;
+
+/// What was done since the application started
+#[derive(Default)]
+pub struct SessionStats {
+    /// Points added, by clicking, dragging or typing their coordinates
+    pub points_placed: usize,
+    /// Edits undone
+    pub undos: usize,
+    /// Edits redone
+    pub redos: usize,
+    /// Curves exported to other applications
+    pub exports: usize,
+    /// Time spent placing points
+    drawing: Duration,
+    /// Time spent watching the animation
+    animating: Duration,
+    /// When the time was last counted
+    last_tick: Option<Instant>,
+}
+
+impl SessionStats {
+    /// Counts the time since the last tick as spent in the given mode. Called every frame,
+    /// with the mode the frame was in
+    pub fn tick(&mut self, mode: AnimationState, now: Instant) {
+        if let Some(last_tick) = self.last_tick {
+            let elapsed = now.saturating_duration_since(last_tick);
+            match mode {
+                AnimationState::Drawing => self.drawing += elapsed,
+                AnimationState::Animating => self.animating += elapsed,
+            }
+        }
+        self.last_tick = Some(now);
+    }
+
+    /// The time spent in the given mode
+    pub fn time_in(&self, mode: AnimationState) -> Duration {
+        match mode {
+            AnimationState::Drawing => self.drawing,
+            AnimationState::Animating => self.animating,
+        }
+    }
+
+    /// The counters as lines of text, to show in a panel
+    pub fn lines(&self) -> Vec<String> {
+        vec![
+            format!("Points placed: {}", self.points_placed),
+            format!("Undos: {}, redos: {}", self.undos, self.redos),
+            format!("Exports: {}", self.exports),
+            format!("Drawing: {}", format_duration(self.drawing)),
+            format!("Animating: {}", format_duration(self.animating)),
+        ]
+    }
+}
+
+/// Formats a duration to the second, e.g. "1h 02m 03s" or "4m 05s"
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else {
+        format!("{}m {:02}s", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_in_modes() {
+        let mut stats = SessionStats::default();
+        let start = Instant::now();
+        stats.tick(AnimationState::Drawing, start);
+        stats.tick(AnimationState::Drawing, start + Duration::from_secs(90));
+        stats.tick(AnimationState::Animating, start + Duration::from_secs(100));
+
+        assert_eq!(stats.time_in(AnimationState::Drawing), Duration::from_secs(90));
+        assert_eq!(stats.time_in(AnimationState::Animating), Duration::from_secs(10));
+        assert_eq!(stats.lines()[3], "Drawing: 1m 30s");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(5900)), "0m 05s");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1h 02m 03s");
+    }
+}
@@ -1,7 +1,22 @@
 use nalgebra::Point2;
+use smallvec::SmallVec;
+use std::path::PathBuf;
+
+use crate::document::Metadata;
+use crate::geometry::Bvh;
+use crate::history::EditHistory;
+use crate::stats::SessionStats;
 
 pub type Point = Point2<f32>;
 
+/// Points kept inline up to this many, which covers most shapes drawn by hand
+pub const INLINE_POINTS: usize = 16;
+
+/// A polyline whose points are kept inline when there are few of them, e.g. for copies of
+/// the control points made every frame, so that they don't allocate. Longer ones spill onto
+/// the heap like a `Vec`
+pub type Polyline = SmallVec<[Point; INLINE_POINTS]>;
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum AnimationState {
     Drawing,      // User is placing points
@@ -9,9 +24,75 @@ pub enum AnimationState {
 }
 
 pub struct WindowState {
+    /// The points of the polyline being drawn
     pub points: Vec<Point>,
+    /// The polylines finished before it, each smoothed on its own
+    pub polylines: Vec<Vec<Point>>,
     pub animation_state: AnimationState,
     pub current_step: usize,
+    /// Whether the animation stays on the current step until resumed
+    pub paused: bool,
+    /// The number of smoothing steps the animation goes through before starting over
+    pub max_steps: usize,
     pub buffer_width: usize,
     pub buffer_height: usize,
-}
\ No newline at end of file
+    /// The point the arrow keys nudge, as its index in the points, picked by clicking it
+    pub selected: Option<usize>,
+    /// The file the points were opened from or last saved to
+    pub file_path: Option<PathBuf>,
+    /// Whether the points changed since they were last opened or saved
+    pub dirty: bool,
+    /// The name, notes and tags of the shape, saved with its points
+    pub metadata: Metadata,
+    /// The edits made to the points, to undo and redo them
+    pub history: EditHistory,
+    /// What was done since the application started
+    pub stats: SessionStats,
+}
+
+impl WindowState {
+    /// The segment of the points closest to the given point, if it is within the distance,
+    /// as the index of its end, where a point inserted into it goes, and its point closest
+    /// to the given one. The segment from the last point back to the first, if the shape is
+    /// closed, ends past the last point
+    pub fn segment_near(&self, point: Point, distance: f32, closed: bool) -> Option<(usize, Point)> {
+        Bvh::new(&self.points, closed)
+            .project(&self.points, point)
+            .filter(|projection| projection.distance <= distance)
+            .map(|projection| (projection.segment + 1, projection.point))
+    }
+
+    /// Records the points and the polylines as they are before an edit, to undo it
+    pub fn record_edit(&mut self) {
+        self.history.record(&self.points, &self.polylines);
+    }
+
+    /// Inserts the point at the index, between the ends of the segment it splits, as an edit
+    /// that can be undone. The selection stays on the point it was on
+    pub fn insert_point(&mut self, index: usize, point: Point) {
+        self.record_edit();
+        self.points.insert(index, point);
+        self.stats.points_placed += 1;
+        self.dirty = true;
+        self.selected = self.selected.map(|selected| if selected >= index { selected + 1 } else { selected });
+    }
+
+    /// Splits the points in two at the point on the segment from point `index` to the next
+    /// one, which ends both: the points up to it are kept, and the ones from it on are
+    /// finished as a polyline of their own, as an edit that can be undone. False, leaving the
+    /// points as they are, if the point is an end of the points, with nothing to split off
+    pub fn split_at(&mut self, index: usize, point: Point) -> bool {
+        let (before, after) = self.points.split_at(index + 1);
+        let first: Vec<Point> = before.iter().copied().chain((before.last() != Some(&point)).then_some(point)).collect();
+        let rest: Vec<Point> = (after.first() != Some(&point)).then_some(point).into_iter().chain(after.iter().copied()).collect();
+        if first.len() < 2 || rest.len() < 2 {
+            return false;
+        }
+        self.record_edit();
+        self.points = first;
+        self.polylines.push(rest);
+        self.selected = None;
+        self.dirty = true;
+        true
+    }
+}
@@ -0,0 +1,181 @@
+//! Real-world units for the points' coordinates, e.g. for plotters and maps.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::numbers::NumberFormat;
+
+/// Millimeters in a meter
+const MM_PER_METER: f32 = 1000.0;
+
+/// A unit that lengths can be reported in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    /// The points' own coordinates, which are the canvas' pixels
+    Pixel,
+    Millimeter,
+    Meter,
+    /// Degrees of latitude or longitude, e.g. for points from a GIS
+    Degree,
+}
+
+impl Unit {
+    /// The symbol written after a length, e.g. "mm"
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Pixel => "px",
+            Unit::Millimeter => "mm",
+            Unit::Meter => "m",
+            Unit::Degree => "deg",
+        }
+    }
+
+    /// How many decimals a length is written with, enough to tell apart lengths that differ
+    /// by about a pixel's worth
+    fn decimals(self) -> usize {
+        match self {
+            Unit::Pixel | Unit::Millimeter => 1,
+            Unit::Meter => 3,
+            Unit::Degree => 6,
+        }
+    }
+
+    /// Millimeters in one of the unit, or none if it isn't a physical length
+    fn millimeters(self) -> Option<f32> {
+        match self {
+            Unit::Millimeter => Some(1.0),
+            Unit::Meter => Some(MM_PER_METER),
+            Unit::Pixel | Unit::Degree => None,
+        }
+    }
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "px" => Ok(Unit::Pixel),
+            "mm" => Ok(Unit::Millimeter),
+            "m" => Ok(Unit::Meter),
+            "deg" => Ok(Unit::Degree),
+            _ => Err(format!("unknown unit {:?}, expected px, mm, m or deg", s)),
+        }
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// How many of the points' coordinates make up one real-world unit, to report lengths in
+/// that unit instead of pixels. Written "3.78/mm", or just "mm" for one coordinate per
+/// millimeter
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Units {
+    /// Coordinates, i.e. pixels at the default zoom, per unit
+    pub per_unit: f32,
+    pub unit: Unit,
+}
+
+impl Default for Units {
+    /// Lengths in pixels, as they are measured
+    fn default() -> Self {
+        Self { per_unit: 1.0, unit: Unit::Pixel }
+    }
+}
+
+impl Units {
+    /// Whether lengths are reported as they are measured
+    pub fn is_pixels(&self) -> bool {
+        self.unit == Unit::Pixel && self.per_unit == 1.0
+    }
+
+    /// The length, measured in the points' coordinates, in the unit
+    pub fn length(&self, length: f32) -> f32 {
+        length / self.per_unit
+    }
+
+    /// The length, measured in the points' coordinates, in the unit and followed by its
+    /// symbol, e.g. "12.5 mm"
+    pub fn format(&self, length: f32, numbers: &NumberFormat) -> String {
+        format!("{} {}", numbers.format(self.length(length), self.unit.decimals()), self.unit.symbol())
+    }
+
+    /// The area, measured in the points' coordinates, in the unit squared
+    pub fn area(&self, area: f32) -> f32 {
+        area / (self.per_unit * self.per_unit)
+    }
+
+    /// The area, measured in the points' coordinates, in the unit squared and followed by
+    /// its symbol, e.g. "12.5 mm²"
+    pub fn format_area(&self, area: f32, numbers: &NumberFormat) -> String {
+        format!("{} {}²", numbers.format(self.area(area), self.unit.decimals()), self.unit.symbol())
+    }
+
+    /// The length, measured in the points' coordinates, in millimeters, or none if the unit
+    /// isn't a physical length
+    pub fn millimeters(&self, length: f32) -> Option<f32> {
+        Some(self.length(length) * self.unit.millimeters()?)
+    }
+}
+
+impl FromStr for Units {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (per_unit, unit) = match s.split_once('/') {
+            Some((per_unit, unit)) => {
+                let per_unit = per_unit.trim().parse::<f32>().map_err(|e| format!("{:?}: {}", per_unit, e))?;
+                (per_unit, unit)
+            }
+            None => (1.0, s),
+        };
+        if !(per_unit.is_finite() && per_unit > 0.0) {
+            return Err("the scale must be a positive number of pixels per unit".to_string());
+        }
+        Ok(Self { per_unit, unit: unit.trim().parse()? })
+    }
+}
+
+impl fmt::Display for Units {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.per_unit == 1.0 {
+            write!(f, "{}", self.unit)
+        } else {
+            write!(f, "{}/{}", self.per_unit, self.unit)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("px".parse(), Ok(Units::default()));
+        assert_eq!("3.78/mm".parse(), Ok(Units { per_unit: 3.78, unit: Unit::Millimeter }));
+        assert_eq!("100000 / deg".parse(), Ok(Units { per_unit: 100000.0, unit: Unit::Degree }));
+        assert!("0/mm".parse::<Units>().is_err());
+        assert!("2/in".parse::<Units>().is_err());
+        assert_eq!("3.78/mm".parse::<Units>().unwrap().to_string(), "3.78/mm");
+        assert_eq!("m".parse::<Units>().unwrap().to_string(), "m");
+    }
+
+    #[test]
+    fn test_format() {
+        let units = Units { per_unit: 4.0, unit: Unit::Millimeter };
+        assert_eq!(units.format(50.0, &NumberFormat::default()), "12.5 mm");
+        assert_eq!(units.format(50.0, &NumberFormat::for_locale("fr_FR")), "12,5 mm");
+        assert_eq!(units.millimeters(50.0), Some(12.5));
+        assert_eq!(units.format_area(200.0, &NumberFormat::default()), "12.5 mm²");
+        assert_eq!(Units { per_unit: 2.0, unit: Unit::Meter }.millimeters(3.0), Some(1500.0));
+        let degrees = Units { per_unit: 1e5, unit: Unit::Degree };
+        assert_eq!(degrees.format(12.0, &NumberFormat::default()), "0.000120 deg");
+        assert_eq!(Units::default().millimeters(10.0), None);
+        assert!(Units::default().is_pixels());
+    }
+}
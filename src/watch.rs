@@ -0,0 +1,71 @@
+//! Reloading a file whenever it changes on disk.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Watches a single file, for as long as it is kept alive
+pub struct FileWatcher {
+    /// Kept so that the watch isn't dropped
+    _watcher: RecommendedWatcher,
+    /// Receives a message each time the file changes
+    changes: Receiver<()>,
+}
+
+impl FileWatcher {
+    /// Starts watching the file at the given path.
+    ///
+    /// The parent directory is watched rather than the file itself, because many editors
+    /// save by writing a new file and renaming it over the old one
+    pub fn new(path: &Path) -> notify::Result<Self> {
+        let path = std::fs::canonicalize(path)?;
+        let directory = path.parent().map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+
+        let (sender, changes) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let modified = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_));
+            if modified && event.paths.contains(&path) {
+                let _ = sender.send(());
+            }
+        })?;
+        watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+
+        Ok(Self { _watcher: watcher, changes })
+    }
+
+    /// Whether the file changed since the last call. Editors usually touch a file several
+    /// times when saving, all of those count as a single change
+    pub fn changed(&self) -> bool {
+        self.changes.try_iter().count() > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_detects_changes() {
+        let directory = std::env::temp_dir().join(format!("chaikin-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("points.txt");
+        std::fs::write(&path, "0 0\n").unwrap();
+
+        let watcher = FileWatcher::new(&path).unwrap();
+        assert!(!watcher.changed());
+
+        std::fs::write(&path, "0 0\n10 10\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !watcher.changed() {
+            assert!(Instant::now() < deadline, "the change was not detected");
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
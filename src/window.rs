@@ -1,20 +1,270 @@
-use minifb::{Window, WindowOptions, Key, MouseButton, MouseMode, KeyRepeat};
+use minifb::{Window, WindowOptions, Key, MouseButton};
 use nalgebra::Point2;
-use crate::types::{WindowState, AnimationState, Point};
-use std::time::{Duration, Instant};
-use crate::window::toast::Toast;
-use rusttype::{Font, Scale, point, PositionedGlyph};
+use crate::types::{WindowState, AnimationState, Point, Polyline};
+use crate::document::{self, Document, Metadata};
+use crate::history::EditHistory;
+use crate::stats::SessionStats;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::window::focus::{Focus, FocusTracker};
+use crate::window::input::{Input, TextCallback};
+use crate::window::modal::{Modal, ModalKind, ModalOutcome};
+use crate::window::grid::Grid;
+use crate::window::placement::Placer;
+use crate::window::rich_text::{Run, Style};
+use crate::window::throttle::Throttle;
+use crate::window::worker::Job;
+use crate::window::tutorial::{Lesson, Tutorial};
+use crate::window::color_picker::ColorPicker;
+use crate::window::search::{SearchOutcome, ShapeSearch};
+use crate::window::overlay::Overlays;
+use crate::demo::Demo;
+use crate::quiz::Exercise;
+use crate::export::{pdf, svg, Format};
+use crate::geometry::{self, Bvh};
+use crate::numbers::NumberFormat;
+use crate::persistence::{self, Session, Settings};
+use crate::units::Units;
+use crate::window::fonts::Fonts;
+use crate::window::display::{Monitor, DEFAULT_REFRESH_RATE};
+
+mod color_picker;
+mod config;
+mod cull;
+mod dirty;
+mod display;
+mod embed;
+mod focus;
+mod fonts;
+mod frame;
+mod glyph;
+mod grid;
+mod icon;
+mod input;
+mod lod;
+mod modal;
+mod mouse;
+mod shortcuts;
+mod overlay;
+mod palette;
+mod placement;
+mod print;
+mod raster;
+mod rich_text;
+mod search;
+mod theme;
+mod throttle;
+mod tutorial;
+mod tween;
+mod view;
+mod worker;
+
+pub use crate::algorithm::Scheme;
+pub use config::Config;
+pub use dirty::Rect;
+pub use display::{is_valid_refresh_rate, REFRESH_RATE_RANGE};
+pub use embed::EmbeddedCanvas;
+pub use frame::Frame;
+pub use glyph::Glyph;
+pub use input::InputEvent;
+pub use mouse::{Gesture, GestureConfig, MouseState};
+pub use shortcuts::{Chord, Command, Repeat, Shortcuts};
+pub use overlay::{Overlay, OverlayKind};
+pub use palette::Gradient;
+pub use placement::Placement;
+pub use raster::Raster;
+pub use icon::APP_NAME;
+pub use theme::{BlendMode, FontAxes, Theme, ThemeColor, STROKE_WIDTH_RANGE};
+pub use view::View;
+
+use crate::algorithm::{Bezier, ChaikinAlgorithm, PointPool, Smoother, StepCache, Steps, LIMIT_SAMPLES};
+
+/// The number of animation steps, unless configured otherwise
+pub const MAX_STEPS: usize = 7;
+/// The most animation steps that can be configured. Each step doubles the points, so this
+/// many already smooth a shape of a hundred points into hundreds of thousands
+pub const MAX_STEPS_LIMIT: usize = 12;
+/// When drawing points, which are circles, this specifies the radius, unless configured
+/// otherwise
+const POINT_RADIUS: f32 = 5.0;
+/// How far the ring around the selected point is from its edge
+const SELECTION_RING_GAP: f32 = 4.0;
+/// How far the angle labels are from their point
+const ANGLE_LABEL_DISTANCE: f32 = 18.0;
+/// Opacity of the preview of the curve with a point added under the cursor
+const HOVER_PREVIEW_OPACITY: f32 = 0.3;
+/// Opacity of the ghost of the fully smoothed curve behind the control polygon
+const GHOST_OPACITY: f32 = 0.2;
+/// Opacity of the limit curve behind the steps
+const LIMIT_OPACITY: f32 = 0.5;
+/// Length of the dashes of the limit curve and of the gaps between them, in pixels
+const LIMIT_DASHES: (f32, f32) = (6.0, 4.0);
+/// The most points the limit curve is sampled at, however far it is zoomed in
+const LIMIT_MAX_POINTS: usize = 1 << 20;
+/// Opacity of the grid's lines, faint enough to stay behind the shape
+const GRID_OPACITY: f32 = 0.15;
+/// Space between a key's text and its box, and between the box and the text around it
+const KEY_PADDING: f32 = 4.0;
+const KEY_MARGIN: f32 = 2.0;
+/// Opacity of the boxes around keys
+const KEY_OUTLINE_OPACITY: f32 = 0.6;
+/// Opacity of the curve to match in a quiz
+const QUIZ_TARGET_OPACITY: f32 = 0.35;
+/// Opacity of the polygons of De Casteljau's construction
+const BEZIER_CONSTRUCTION_OPACITY: f32 = 0.4;
+/// Opacity of the control polygon under the curve while animating
+const CONTROL_POLYGON_OPACITY: f32 = 0.35;
+/// Opacity of the step before the one shown, in the onion skin. Each step before it is as
+/// much fainter again
+const ONION_SKIN_OPACITY: f32 = 0.5;
+/// How far from flat, in pixels at the default zoom, a corner has to be for adaptive
+/// subdivision to cut it
+const ADAPTIVE_TOLERANCE: f32 = 0.5;
+/// Where Chaikin's scheme places the new points along each segment, unless told otherwise
+const DEFAULT_RATIOS: (f32, f32) = (0.25, 0.75);
+/// How many points the Bézier curve is drawn through
+const BEZIER_SAMPLES: usize = 256;
+/// How long each step of the animation is shown, unless configured otherwise or sped up or
+/// slowed down
+const STEP_INTERVAL: Duration = Duration::from_secs(1);
+/// The shortest and longest steps the animation can be sped up or slowed down to
+const STEP_INTERVAL_RANGE: (Duration, Duration) = (Duration::from_millis(125), Duration::from_secs(8));
+/// The previews are recomputed at most this often while the points or the cursor move
+const PREVIEW_INTERVAL: Duration = Duration::from_millis(50);
+/// How long the window keeps drawing frames after the last input or change, so that what
+/// follows from them settles on screen, e.g. the previews throttled to [`PREVIEW_INTERVAL`]
+const IDLE_DELAY: Duration = Duration::from_millis(500);
+/// How often the window looks for input while idle
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How often the unchanged frame is shown again while idle. Some window managers don't keep
+/// the window's pixels while it is covered by another
+const IDLE_REPAINT_INTERVAL: Duration = Duration::from_secs(1);
+/// How often the window looks for input while minimized, which nothing is drawn for
+const MINIMIZED_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How often the window is updated at most while another window has the focus, in Hz:
+/// enough to follow the animation out of the corner of an eye
+const BACKGROUND_REFRESH_RATE: f32 = 15.0;
+
+/// A curve computed from the points and something else, such as the cursor
+type Preview<K> = Throttle<(Polyline, K), Vec<Point>>;
+
+/// The curves of the finished polylines at a step, with their next step to morph into. Each
+/// polyline's are kept until it or the step changes, so that a frame only compares the
+/// polylines with the ones the curves were computed from
+#[derive(Default)]
+struct PolylineCurves {
+    step: usize,
+    /// Each polyline, with its curve at the step and the next one
+    curves: Vec<(Vec<Point>, Vec<Point>, Vec<Point>)>,
+}
 
-mod toast;
-mod algorithm;
+impl PolylineCurves {
+    /// The curves of the polylines at the step, computing only those of the polylines that
+    /// changed since
+    fn get(&mut self, smoother: &dyn Smoother, polylines: &[Vec<Point>], step: usize) -> &[(Vec<Point>, Vec<Point>, Vec<Point>)] {
+        if self.step != step {
+            self.curves.clear();
+            self.step = step;
+        }
+        self.curves.truncate(polylines.len());
+        for (i, polyline) in polylines.iter().enumerate() {
+            if self.curves.get(i).is_some_and(|(points, ..)| points == polyline) {
+                continue;
+            }
+            let curve = smoother.get_step_points(polyline, step);
+            let next = smoother.calculate_step(&curve);
+            let curves = (polyline.clone(), curve, next);
+            match self.curves.get_mut(i) {
+                Some(cached) => *cached = curves,
+                None => self.curves.push(curves),
+            }
+        }
+        &self.curves
+    }
 
-const MAX_STEPS: usize = 7;
-/// When drawing points, which are circles, this specifies the radius
-const POINT_RADIUS: f32 = 5.0;
-/// Draw the points with a shade of red
-const POINT_COLOR: u32 = 0x00FF5555;
-/// Draw the lines with a blue-green color mix
-const LINE_COLOR: u32 = 0x0055CCAA;
+    /// Forgets every curve, e.g. when the smoother changes them
+    fn clear(&mut self) {
+        self.curves.clear();
+    }
+}
+
+/// Steps with more points than this, roughly, are computed on a worker thread
+const HEAVY_STEP_POINTS: usize = 200_000;
+/// Radius of the spinner shown while a step is computed
+const SPINNER_RADIUS: f32 = 8.0;
+/// The number of dots of the spinner, one of which leads at a time
+const SPINNER_DOTS: usize = 8;
+/// How long the lead stays on each dot of the spinner
+const SPINNER_TICK: Duration = Duration::from_millis(100);
+/// Width and height of the bar showing how much of a step is computed, in pixels
+const PROGRESS_BAR_SIZE: (i32, i32) = (120, 6);
+
+/// The points and the step a curve was computed from
+type CurveKey = (Polyline, usize);
+/// The points and the number of samples per span a limit curve was computed with
+type LimitKey = (Polyline, usize);
+/// The points and the parameter, by its bits, a Bézier construction was computed at
+type BezierKey = (Polyline, u32);
+
+/// A limit curve, with how far along it each of its points is, and the first point again
+/// at the end if the shape is closed
+struct LimitCurve {
+    points: Vec<Point>,
+    lengths: Vec<f32>,
+}
+
+impl LimitCurve {
+    fn new(points: Vec<Point>, closed: bool) -> Self {
+        let closing = closed.then(|| points.last().zip(points.first())).flatten();
+        let segments = points.windows(2).map(|pair| (pair[0], pair[1])).chain(closing.map(|(&last, &first)| (last, first)));
+        let mut lengths = vec![0.0];
+        for (start, end) in segments {
+            lengths.push(lengths[lengths.len() - 1] + (end - start).norm());
+        }
+        Self { points, lengths }
+    }
+}
+
+/// Roughly how many points the step of the points has, each step having about twice the
+/// points of the one before. It saturates rather than overflows, e.g. on 32-bit targets
+fn step_size(points: usize, step: usize) -> usize {
+    let growth = u32::try_from(step).ok().and_then(|step| 1usize.checked_shl(step)).unwrap_or(usize::MAX);
+    points.saturating_mul(growth)
+}
+
+/// How many points each span of the limit curve is sampled at to look smooth at the zoom:
+/// [`LIMIT_SAMPLES`] as many more times as it is zoomed in, rounded up to a power of two so
+/// that zooming doesn't sample it again every frame, and [`LIMIT_MAX_POINTS`] at most in all
+fn limit_samples(scale: f32, points: usize) -> usize {
+    let zoom = scale.log2().ceil().exp2();
+    let samples = (LIMIT_SAMPLES as f32 * zoom) as usize;
+    samples.min(LIMIT_MAX_POINTS / points.max(1)).max(1)
+}
+
+/// Where a panel goes among the others: moved down below those it would cover, and cut
+/// short above the next one down or the bottom of the canvas. None if there is no room
+/// left for it
+fn fit_panel(mut panel: Rect, others: &[Rect], height: usize) -> Option<Rect> {
+    let panel_height = panel.height();
+    let covered = |panel: &Rect| {
+        others.iter().filter(|other| other.top <= panel.top && other.intersects(panel)).map(|other| other.bottom).max()
+    };
+    while let Some(bottom) = covered(&panel) {
+        panel.top = bottom + PANEL_GAP + 1;
+        panel.bottom = panel.top + panel_height - 1;
+    }
+    let floor = others
+        .iter()
+        .filter(|other| other.top > panel.top && other.left <= panel.right && panel.left <= other.right)
+        .map(|other| other.top.saturating_sub(PANEL_GAP + 1))
+        .fold(height.saturating_sub(1), usize::min);
+    panel.bottom = panel.bottom.min(floor);
+    (panel.bottom > panel.top).then_some(panel)
+}
+
+/// The step, view and canvas size a curve was refined on screen for
+type RefinedKey = (CurveKey, View, (usize, usize));
 /// We will be showing a toast message if the user hasn't yet included enough points for
 /// the chaikin algorithm points generation. This specifies for how long we'll show the
 /// toast before automatically hiding it
@@ -24,420 +274,3205 @@ const TOAST_DURATION: Duration = Duration::from_secs(8);
 const TOAST_BG_COLOR: u32 = 0x80333333;
 /// Accessible text color that is visible on the toast's background
 const TOAST_TEXT_COLOR: u32 = 0x00FFFFFF;
+/// The size of the toasts' text, and the height of each of its lines
+const TOAST_FONT_SIZE: f32 = 16.0;
+const TOAST_LINE_HEIGHT: usize = 20;
+/// Space between the toast's text and its edges, and between the toast and the window's
+const TOAST_PADDING: usize = 10;
+const TOAST_MARGIN: usize = 20;
+/// The size of the statistics panel's text, the height of each of its lines, and its title
+const STATS_FONT_SIZE: f32 = 14.0;
+const STATS_LINE_HEIGHT: usize = 20;
+const STATS_TITLE: &str = "# Session";
+/// The size of the HUD's text, and its height
+const HUD_FONT_SIZE: f32 = 14.0;
+const HUD_HEIGHT: usize = 26;
+/// Space kept between panels laid out around each other, in pixels
+const PANEL_GAP: usize = 10;
+/// Where the points are saved when they weren't opened from a file
+const DEFAULT_SAVE_PATH: &str = "chaikin-points.txt";
+/// Where the session is saved when the points weren't opened from or saved to a file
+const DEFAULT_SESSION_PATH: &str = "chaikin-session.json";
+/// Where the curve is exported when the points weren't opened from or saved to a file
+const DEFAULT_EXPORT_PATH: &str = "chaikin-curve.svg";
+/// Where the handout is exported, in the same case
+const DEFAULT_HANDOUT_PATH: &str = "chaikin-handout.pdf";
+/// Separates the lines of the notes while they are edited on a single line
+const NOTES_LINE_SEPARATOR: &str = " / ";
+/// Shown when the user tries to close the window with unsaved points
+const QUIT_PROMPT: &str = "Save changes before closing?";
+/// The canvas behind a modal is darkened to this fraction of its brightness
+const MODAL_DIM: f32 = 0.4;
+/// The background of the modal boxes
+const MODAL_BG_COLOR: u32 = 0x00333333;
+/// Space left around the shape when the view is fitted to it, in pixels
+const FIT_PADDING: f32 = 40.0;
+/// How much the view zooms in for each notch the wheel turns away from the user
+const ZOOM_PER_NOTCH: f32 = 1.1;
+/// The most notches a frame's scroll counts for, as some platforms report fast flicks in
+/// large bursts
+const MAX_SCROLL: f32 = 10.0;
+/// Background of the highlighted match in the search for a shape
+const SEARCH_SELECTION_COLOR: u32 = 0x00555555;
+
+/// What a file written on a worker thread is, so that we know what to do once it's written
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Written {
+    /// The points, which aren't unsaved anymore
+    Points,
+    /// A screenshot of an animation step, only reported if it fails
+    Capture,
+    /// The curve, exported for other applications
+    Export,
+    /// The page sent to the printer
+    Print,
+    /// The points with the settings, which aren't unsaved anymore either
+    Session,
+}
+
+/// A file being written on a worker thread
+struct PendingWrite {
+    path: PathBuf,
+    written: Written,
+    job: Job<std::io::Result<()>>,
+}
+
+/// What a modal asks the user, so that we know what to do with the answer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Dialog {
+    /// Whether to save the unsaved points before closing
+    Quit,
+    /// Whether to clear the unsaved points
+    Reset,
+    /// The coordinates of a point to add
+    EnterPoint,
+    /// The path of a file of points to open
+    OpenFile,
+    /// Whether to take the tutorial
+    Tutorial,
+    /// Whether to fit the view to points opened out of sight
+    FitView,
+    /// The name of the shape, followed by its notes and tags
+    EditName,
+    /// The notes on the shape
+    EditNotes,
+    /// The tags of the shape
+    EditTags,
+}
 
 pub struct WindowManager {
-    window: Window,
+    /// The OS window, or none when embedded in another application's window
+    window: Option<Window>,
+    /// The keyboard and mouse state for the current frame
+    input: Input,
+    /// The mouse, followed across frames for presses, drags and releases
+    mouse: MouseState,
+    /// The keyboard shortcuts of the canvas
+    shortcuts: Shortcuts,
+    /// Decides where points are placed while the mouse button is held
+    placer: Placer,
+    /// Radius of the control points, in pixels
+    point_radius: f32,
+    /// How close to a point a press has to be to pick it up
+    hit_radius: f32,
+    /// The point being dragged, if any
+    dragging: Option<usize>,
+    /// Whether coordinates are shown in full rather than rounded to the pixel, since opened
+    /// and entered points are often between pixels
+    precise_coordinates: bool,
+    /// Whether the drag was recorded in the history, which is done once it moves the point
+    drag_recorded: bool,
+    /// Whether nudging the selected point was recorded in the history, which is done once
+    /// per selection so that the nudges are undone at once
+    nudge_recorded: bool,
+    /// Whether the last step of the animation is shown faintly while drawing
+    ghost: bool,
+    /// Whether the curve the steps converge to is shown behind them while animating
+    limit: bool,
+    /// Whether each step of the animation morphs into the next one, rather than jumping
+    interpolate: bool,
+    /// Whether the steps morph with their points spread evenly along them, rather than along
+    /// the points of the steps
+    tween_by_arclength: bool,
+    /// The colors the steps of the animation are drawn in, rather than the line color
+    gradient: Option<Gradient>,
+    /// Whether the steps before the one shown are drawn under it, fainter and fainter
+    onion_skin: bool,
+    /// How far the paused animation is shown on its way to the next step, from 0 to 1, if
+    /// it isn't shown at a step
+    held_progress: Option<f32>,
+    /// Whether the mode and the step are shown in a corner
+    hud: bool,
+    /// Whether the session's statistics are shown
+    show_stats: bool,
+    /// Whether the control polygon is drawn faintly under the curve while animating
+    control_polygon: bool,
+    /// Whether the formulas of the scheme are shown
+    show_formulas: bool,
+    /// Whether the interior angle at each control point is shown
+    show_angles: bool,
+    /// The tutorial, while it is running
+    tutorial: Option<Tutorial>,
+    /// The overlays showing the tutorial's hint
+    tutorial_hints: Vec<OverlayKind>,
+    /// The exercise, while the user is doing one
+    quiz: Option<Exercise>,
+    /// The ghost, computed from the points and the step it shows
+    ghost_curve: Preview<usize>,
+    /// The limit curve of the points, if the scheme knows it, with the number of samples
+    /// per span it was computed with
+    limit_curve: Option<(LimitKey, Option<LimitCurve>)>,
+    /// The Bézier curve of the points, sampled, computed from them
+    bezier_curve: Option<(Polyline, Vec<Point>)>,
+    /// De Casteljau's construction of the Bézier curve, computed from the points and the
+    /// parameter the animation is at
+    bezier_construction: Option<(BezierKey, Vec<Vec<Point>>)>,
+    /// The hover preview, computed from the points and the cursor
+    hover_curve: Preview<(f32, f32)>,
+    /// The curves of the finished polylines, computed from them and the step
+    polyline_curves: PolylineCurves,
     state: WindowState,
-    buffer: Vec<u32>,
-    /// The current toast message, shown if active
-    toast: Toast,
-    /// The application's text font
-    font: Font<'static>,
+    /// What is drawn, shown in the window every frame
+    raster: Raster,
+    /// The toast message and the short-lived effects drawn over the canvas
+    overlays: Overlays,
+    /// The application's text font, and fallbacks for the characters it lacks
+    fonts: Fonts,
+    /// The colors everything is drawn with
+    theme: Theme,
+    /// The color picker, while the user is changing the theme
+    color_picker: Option<ColorPicker>,
+    /// The search for a shape by name, while the user is typing it
+    search: Option<ShapeSearch>,
+    /// The subdivision scheme used to smooth the points, shared with the worker threads
+    smoother: Arc<dyn Smoother>,
+    /// Which of the built-in schemes the smoother is, or none if it was given as is
+    scheme: Option<Scheme>,
+    /// The ratios Chaikin's scheme is built with when switching to it
+    ratios: (f32, f32),
+    /// Whether Chaikin's scheme only cuts the corners that aren't flat yet, see
+    /// [`ChaikinAlgorithm::adaptive`]
+    adaptive: bool,
+    /// The real-world units lengths are reported in
+    units: Units,
+    /// How the numbers shown are written, e.g. with a decimal comma
+    numbers: NumberFormat,
+    /// Whether the last point connects back to the first
+    closed: bool,
+    /// How the points are scaled and moved onto the canvas
+    view: View,
+    /// The grid behind the points, and whether they snap to it
+    grid: Grid,
+    /// Whether the animation traces the Bézier curve of the points by De Casteljau's
+    /// construction, instead of smoothing them
+    bezier: bool,
+    /// The last animation step computed, which is drawn until the next one is ready
+    curve: Option<(CurveKey, Vec<Point>)>,
+    /// The step after the one drawn, which it morphs into
+    next_curve: Option<(CurveKey, Vec<Point>)>,
+    /// The heavy step being computed on a worker thread, if any
+    curve_job: Option<(CurveKey, Job<Vec<Point>>)>,
+    /// The last step refined further where it is on screen while zoomed in, for the step,
+    /// view and canvas size it was refined for
+    refined: Option<(RefinedKey, Vec<Point>)>,
+    /// The hierarchies over the segments of the steps drawn, which only the parts of long
+    /// curves on the canvas are drawn through
+    curve_bvhs: cull::CurveBvhs,
+    /// The hierarchies over the other long paths drawn, e.g. the control polygons
+    path_bvhs: cull::PathBvhs,
+    /// The steps computed for the points, so that going from one step to another only
+    /// computes the steps not computed yet
+    steps: StepCache,
+    /// The buffers of the steps and previews, reused from one frame to the next
+    pool: PointPool,
     /// The instant when the last animation frame was made
     last_call: Instant,
+    /// How long each step of the animation is shown
+    step_interval: Duration,
+    /// The session the points were opened from or last saved to
+    session_path: Option<PathBuf>,
+    /// Where a screenshot of each animation step is saved, if anywhere
+    capture_dir: Option<PathBuf>,
+    /// Whether the next frame should be captured, because the animation step changed
+    capture_pending: bool,
+    /// The running demo, which replaces the shape each time the animation starts over
+    demo: Option<Demo>,
+    /// The dialog box the user is answering, if any
+    modal: Option<(Dialog, Modal)>,
+    /// Which part of the UI gets the input, the modal, the color picker or the canvas
+    focus: FocusTracker,
+    /// The characters typed in the window, collected by minifb between frames
+    typed_text: TextCallback,
+    /// The files being written on worker threads
+    writes: Vec<PendingWrite>,
+    /// Whether the window closes as soon as the files are written
+    quitting: bool,
+    /// Whether the window's close button was already handled. minifb keeps reporting the
+    /// window as closed from then on, so this lets the user cancel the quit prompt
+    close_handled: bool,
+    /// How often the window is updated at most, in Hz, whichever monitor it is on
+    refresh_rate_override: Option<f32>,
+    /// How often the window is being updated at most, in Hz
+    update_rate: Option<f32>,
+    /// The monitors of the desktop, found when the window opens
+    monitors: Vec<Monitor>,
+    /// Where the window was, and whether it had the focus, when its update rate was last
+    /// fitted to its monitor
+    fitted_to: Option<((isize, isize), bool)>,
+    /// The last time there was input, or something changed the canvas from elsewhere
+    last_activity: Instant,
+    /// When the frame was last shown in the window
+    last_shown: Instant,
+    /// How often the window is polled for input while it draws nothing, as nothing on
+    /// screen changes or it is minimized
+    idle: Option<Duration>,
 }
 
-impl WindowManager {
-    pub fn new(width: usize, height: usize, title: &str) -> Self {
-        let mut window = Window::new(
-            title,
-            width,
-            height,
-            WindowOptions {
-                resize: true,
-                decorations:false,
-                ..WindowOptions::default()
-            },
-        ).unwrap_or_else(|e| panic!("Failed to create window: {}", e));
+impl WindowManager {
+    /// Opens a window as configured
+    pub fn new(config: &Config) -> Self {
+        let mut window = Window::new(
+            &config.title,
+            config.width,
+            config.height,
+            WindowOptions {
+                resize: true,
+                borderless: true,
+                ..WindowOptions::default()
+            },
+        ).unwrap_or_else(|e| panic!("Failed to create window: {}", e));
+
+        icon::apply(&mut window);
+
+        let mut manager = Self::with_window(Some(window), config.width, config.height);
+        manager.monitors = display::monitors();
+        manager.refresh_rate_override = config.refresh_rate;
+        manager.fit_update_rate();
+        manager.set_max_steps(config.max_steps);
+        manager.set_step_interval(config.step_interval);
+        manager.set_point_radius(config.point_radius);
+        manager.set_theme(config.theme);
+        manager
+    }
+
+    /// Creates a manager without an OS window, drawing into a buffer of the given size.
+    /// Used for embedding (see [`EmbeddedCanvas`]) and for rendering images
+    pub fn offscreen(width: usize, height: usize) -> Self {
+        Self::with_window(None, width, height)
+    }
+
+    fn with_window(mut window: Option<Window>, width: usize, height: usize) -> Self {
+        let typed_text = TextCallback::default();
+        if let Some(window) = &mut window {
+            window.set_input_callback(Box::new(typed_text.clone()));
+        }
+
+        Self {
+            window,
+            input: Input::default(),
+            mouse: MouseState::default(),
+            shortcuts: Shortcuts::default(),
+            placer: Placer::new(Placement::Click),
+            point_radius: POINT_RADIUS,
+            hit_radius: POINT_RADIUS,
+            dragging: None,
+            precise_coordinates: false,
+            drag_recorded: false,
+            nudge_recorded: false,
+            show_stats: false,
+            control_polygon: true,
+            show_formulas: false,
+            show_angles: false,
+            tutorial: None,
+            tutorial_hints: Vec::new(),
+            quiz: None,
+            ghost: false,
+            limit: false,
+            interpolate: true,
+            tween_by_arclength: false,
+            gradient: None,
+            onion_skin: false,
+            held_progress: None,
+            hud: true,
+            ghost_curve: Throttle::new(PREVIEW_INTERVAL),
+            limit_curve: None,
+            bezier_curve: None,
+            bezier_construction: None,
+            hover_curve: Throttle::new(PREVIEW_INTERVAL),
+            polyline_curves: PolylineCurves::default(),
+            state: WindowState {
+                points: Vec::new(),
+                polylines: Vec::new(),
+                animation_state: AnimationState::Drawing,
+                current_step: 0,
+                paused: false,
+                max_steps: MAX_STEPS,
+                buffer_width: width,
+                buffer_height: height,
+                selected: None,
+                file_path: None,
+                dirty: false,
+                metadata: Metadata::default(),
+                history: EditHistory::default(),
+                stats: SessionStats::default(),
+            },
+            raster: Raster::new(width, height),
+            overlays: Overlays::default(),
+            fonts: Fonts::load(),
+            theme: Theme::default(),
+            color_picker: None,
+            search: None,
+            view: View::default(),
+            grid: Grid::default(),
+            bezier: false,
+            smoother: Arc::new(ChaikinAlgorithm::new()),
+            scheme: Some(Scheme::Chaikin),
+            ratios: DEFAULT_RATIOS,
+            adaptive: false,
+            units: Units::default(),
+            numbers: NumberFormat::default(),
+            closed: false,
+            curve: None,
+            next_curve: None,
+            curve_job: None,
+            refined: None,
+            curve_bvhs: cull::CurveBvhs::default(),
+            path_bvhs: cull::PathBvhs::default(),
+            steps: StepCache::default(),
+            pool: PointPool::default(),
+            last_call: Instant::now(),
+            step_interval: STEP_INTERVAL,
+            session_path: None,
+            capture_dir: None,
+            capture_pending: false,
+            demo: None,
+            modal: None,
+            focus: FocusTracker::default(),
+            typed_text,
+            writes: Vec::new(),
+            quitting: false,
+            close_handled: false,
+            refresh_rate_override: None,
+            update_rate: None,
+            monitors: Vec::new(),
+            fitted_to: None,
+            last_activity: Instant::now(),
+            last_shown: Instant::now(),
+            idle: None,
+        }
+    }
+
+    /// Adds a point to be drawn in the window at the given coordinate
+    fn add_point(&mut self, x: f32, y: f32) {
+        let point = Point::new(x, y);
+        self.state.record_edit();
+        self.state.points.push(point);
+        self.state.stats.points_placed += 1;
+        self.state.dirty = true;
+        // Lets the user know the click registered
+        self.overlays.show(Overlay::halo(self.view.screen_point(point)));
+        // The toast will be shown if the user didn't have enough points for chaikin,
+        // but a new point was just added; maybe we already have enough points
+        self.overlays.dismiss_toast();
+        self.redraw();
+    }
+
+    /// Replaces the current points with the ones stored in the given file, or the session
+    /// if it is a JSON file. If the file can't be read, a toast tells the user why and the
+    /// points are kept
+    pub fn open_file(&mut self, path: &Path) {
+        if path.extension().is_some_and(|extension| extension == "json") {
+            return self.open_session(path);
+        }
+        match document::read_document(path) {
+            Ok(document) => {
+                self.reset();
+                // The edits were made to another file
+                self.state.history.clear();
+                self.state.points = document.points;
+                self.state.polylines = document.polylines;
+                self.state.metadata = document.metadata;
+                self.state.file_path = Some(path.to_path_buf());
+                self.session_path = None;
+                self.offer_fit_view();
+            }
+            Err(e) => self.show_toast(&format!("Could not open {}: {}", path.display(), e)),
+        }
+        self.wake();
+        self.redraw();
+    }
+
+    /// Replaces the current points and settings with the session stored in the given file.
+    /// Schemes this build doesn't have are left as they are
+    pub fn open_session(&mut self, path: &Path) {
+        match persistence::read_session(path) {
+            Ok(session) => {
+                self.reset();
+                self.state.history.clear();
+                self.state.points = session.points();
+                self.state.polylines = session.polylines();
+                self.state.metadata = session.metadata();
+                // Saving the points as text must not overwrite the session
+                self.state.file_path = None;
+                self.session_path = Some(path.to_path_buf());
+
+                let settings = session.settings;
+                self.closed = settings.closed;
+                // Sessions can be edited by hand
+                let (q, r) = settings.ratios;
+                let ratios = if ChaikinAlgorithm::valid_ratios(q, r) {
+                    settings.ratios
+                } else {
+                    self.show_toast(&format!("Ignored the session's ratios {}, {}, which must satisfy 0 < q < r < 1", q, r));
+                    DEFAULT_RATIOS
+                };
+                match settings.scheme.and_then(|scheme| scheme.parse().ok()) {
+                    Some(scheme) => self.set_scheme(scheme, ratios),
+                    None => {
+                        self.set_closed(settings.closed);
+                    }
+                }
+                self.set_max_steps(settings.max_steps);
+                self.set_step_interval(Duration::from_millis(settings.step_interval_ms));
+                self.offer_fit_view();
+            }
+            Err(e) => self.show_toast(&format!("Could not open {}: {}", path.display(), e)),
+        }
+        self.wake();
+        self.redraw();
+    }
+
+    /// Reloads the points from the given file after it changed on disk. Unlike
+    /// [`Self::open_file`], a running animation keeps going with the new points
+    pub fn reload_file(&mut self, path: &Path) {
+        match document::read_document(path) {
+            Ok(document) => {
+                self.state.points = document.points;
+                self.state.polylines = document.polylines;
+                self.state.metadata = document.metadata;
+                self.state.file_path = Some(path.to_path_buf());
+                self.state.dirty = false;
+                // The point being dragged or selected may not be there anymore
+                self.dragging = None;
+                self.drag_recorded = false;
+                self.state.selected = None;
+                self.overlays.dismiss_toast();
+            }
+            Err(e) => self.show_toast(&format!("Could not reload {}: {}", path.display(), e)),
+        }
+        self.wake();
+        self.redraw();
+    }
+
+    /// Writes the points to the file they came from, or to [`DEFAULT_SAVE_PATH`]
+    pub fn save(&mut self) -> std::io::Result<()> {
+        let path = self.state.file_path.clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_SAVE_PATH));
+        document::write_document(&path, &self.document())?;
+        self.state.file_path = Some(path);
+        self.state.dirty = false;
+        Ok(())
+    }
+
+    /// Saves the points on a worker thread, so that the frames keep coming while a large
+    /// file is written. A toast tells when it's done
+    pub fn save_in_background(&mut self) {
+        let path = self.state.file_path.clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_SAVE_PATH));
+        self.state.file_path = Some(path.clone());
+        let document = self.document();
+        self.write_in_background(path, Written::Points, move |path| document::write_document(path, &document));
+    }
+
+    /// Saves the points with the current settings as a session on a worker thread, next to
+    /// the points' file or to the session it was opened from
+    pub fn save_session(&mut self) {
+        let path = match (&self.session_path, &self.state.file_path) {
+            (Some(path), _) => path.clone(),
+            (None, Some(file_path)) => file_path.with_extension("json"),
+            (None, None) => PathBuf::from(DEFAULT_SESSION_PATH),
+        };
+        self.session_path = Some(path.clone());
+        let session = self.session();
+        self.write_in_background(path, Written::Session, move |path| persistence::write_session(path, &session));
+    }
+
+    /// The points and how they are smoothed and animated, as saved in a session
+    fn session(&self) -> Session {
+        let settings = Settings {
+            scheme: self.scheme.map(|scheme| scheme.to_string()),
+            ratios: self.ratios,
+            closed: self.closed,
+            max_steps: self.state.max_steps,
+            step_interval_ms: self.step_interval.as_millis() as u64,
+        };
+        Session::new(&self.state.points, &self.state.polylines, &self.state.metadata, settings)
+    }
+
+    /// The polylines and what was written about them, as saved
+    fn document(&self) -> Document {
+        Document {
+            points: self.state.points.clone(),
+            polylines: self.state.polylines.clone(),
+            metadata: self.state.metadata.clone(),
+        }
+    }
+
+    /// Every polyline, the one being drawn last, as exported
+    fn all_polylines(&self) -> Vec<Vec<Point>> {
+        let mut polylines = self.state.polylines.clone();
+        if !self.state.points.is_empty() {
+            polylines.push(self.state.points.clone());
+        }
+        polylines
+    }
+
+    /// The curve of the current step if it is computed already, so that what is on screen is
+    /// reused rather than smoothed again. None while no point is being drawn
+    fn computed_curve(&self) -> Option<Vec<Point>> {
+        self.curve
+            .as_ref()
+            .filter(|_| !self.state.points.is_empty())
+            .filter(|((computed, step), _)| computed.as_slice() == self.state.points.as_slice() && *step == self.state.current_step)
+            .map(|(_, curve)| curve.clone())
+    }
+
+    /// The curves of the polylines at the step, the last one being the computed curve if
+    /// there is one. Meant to be called on a worker thread, as the others are smoothed again
+    fn step_curves(
+        smoother: &dyn Smoother,
+        polylines: &[Vec<Point>],
+        step: usize,
+        computed: Option<Vec<Point>>,
+    ) -> Vec<Vec<Point>> {
+        let smoothed = polylines.len() - usize::from(computed.is_some());
+        let mut curves: Vec<Vec<Point>> = polylines[..smoothed].iter().map(|points| smoother.get_step_points(points, step)).collect();
+        curves.extend(computed);
+        curves
+    }
+
+    /// Prints the control polygons and the curves at the current step on a page, with the
+    /// system's printer, in the background. The page is written to a PDF document in the
+    /// temporary directory, which the print spooler reads from
+    pub fn print(&mut self) {
+        let path = std::env::temp_dir().join(format!("chaikin-print-{}.pdf", std::process::id()));
+        let polylines = self.all_polylines();
+        let step = self.state.current_step;
+        let curve = self.computed_curve();
+        let smoother = Arc::clone(&self.smoother);
+        let closed = self.closed;
+        self.write_in_background(path, Written::Print, move |path| {
+            let curves = Self::step_curves(smoother.as_ref(), &polylines, step, curve);
+            let mut document = Vec::new();
+            pdf::write_step(&mut document, &polylines, &curves, step, closed)?;
+            std::fs::write(path, document)?;
+            print::print_pdf(path)
+        });
+    }
+
+    /// Exports the control polygons and the curves at the current step as an SVG image, next
+    /// to the file of points, or to [`DEFAULT_EXPORT_PATH`], in the background
+    pub fn export_svg(&mut self) {
+        let path = self.state.file_path
+            .as_ref()
+            .map(|path| path.with_extension(Format::Svg.extension()))
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_EXPORT_PATH));
+        let polylines = self.all_polylines();
+        let step = self.state.current_step;
+        let curve = self.computed_curve();
+        let smoother = Arc::clone(&self.smoother);
+        let (polygon_stroke, curve_stroke) = (self.theme.point & 0xFFFFFF, self.theme.line & 0xFFFFFF);
+        let (closed, units) = (self.closed, self.units);
+        self.write_in_background(path, Written::Export, move |path| {
+            let curves = Self::step_curves(smoother.as_ref(), &polylines, step, curve);
+            let polygons = polylines.iter().map(|points| svg::Layer::new(points, polygon_stroke).closed(closed));
+            let curves = curves.iter().map(|curve| svg::Layer::new(curve, curve_stroke).closed(closed));
+            let layers: Vec<_> = polygons.chain(curves).collect();
+            let mut image = Vec::new();
+            svg::write(&mut image, &layers, units)?;
+            std::fs::write(path, image)
+        });
+    }
+
+    /// Exports every step of the animation as a PDF handout, a page per step, next to the
+    /// file of points, or to [`DEFAULT_HANDOUT_PATH`], in the background
+    pub fn export_handout(&mut self) {
+        let path = self.state.file_path
+            .as_ref()
+            .map(|path| path.with_extension("pdf"))
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_HANDOUT_PATH));
+        let polylines = self.all_polylines();
+        let steps = self.state.max_steps;
+        let smoother = Arc::clone(&self.smoother);
+        let closed = self.closed;
+        self.write_in_background(path, Written::Export, move |path| {
+            let mut pages = vec![Vec::new(); steps];
+            for points in &polylines {
+                for (page, curve) in pages.iter_mut().zip(Steps::new(smoother.as_ref(), points)) {
+                    page.push(curve);
+                }
+            }
+            let mut document = Vec::new();
+            pdf::write(&mut document, &polylines, &pages, closed)?;
+            std::fs::write(path, document)
+        });
+    }
+
+    /// Saves what the window shows as a PNG image named after the time, e.g.
+    /// `chaikin-20240131-142501.png`, next to the file of points or in the working directory
+    pub fn save_screenshot(&mut self) {
+        let name = format!("chaikin-{}.png", timestamp(SystemTime::now()));
+        let path = match self.state.file_path.as_ref().and_then(|path| path.parent()) {
+            Some(directory) => directory.join(name),
+            None => PathBuf::from(name),
+        };
+        let (pixels, width, height) = (self.raster.pixels().to_vec(), self.state.buffer_width, self.state.buffer_height);
+        self.write_in_background(path, Written::Export, move |path| {
+            crate::export::png::save(path, Frame { pixels: &pixels, width, height })
+        });
+    }
+
+    /// Writes a file on a worker thread. Only what the file is written from should be
+    /// moved into the closure, e.g. a copy of the points or of the pixels
+    fn write_in_background<F>(&mut self, path: PathBuf, written: Written, write: F)
+    where
+        F: FnOnce(&Path) -> std::io::Result<()> + Send + 'static,
+    {
+        let target = path.clone();
+        let job = Job::spawn(move |_, _| Some(write(&target)));
+        self.writes.push(PendingWrite { path, written, job });
+    }
+
+    /// Reports the files that were written since the last frame
+    fn finish_writes(&mut self) {
+        let mut finished = Vec::new();
+        self.writes.retain(|write| match write.job.poll() {
+            Some(result) => {
+                finished.push((write.path.clone(), write.written, result));
+                false
+            }
+            None => true,
+        });
+
+        for (path, written, result) in finished {
+            match (written, result) {
+                (Written::Points | Written::Session, Ok(())) => {
+                    self.state.dirty = false;
+                    self.show_toast(&format!("Saved {}", path.display()));
+                }
+                (Written::Export, Ok(())) => {
+                    self.state.stats.exports += 1;
+                    self.show_toast(&format!("Exported {}", path.display()));
+                }
+                (Written::Print, Ok(())) => self.show_toast("Sent to the printer"),
+                (Written::Print, Err(e)) => self.show_toast(&format!("Could not print: {}", e)),
+                (Written::Capture, Ok(())) => {}
+                (Written::Capture, Err(e)) => {
+                    // A lost screenshot isn't worth staying open for, and the toast would
+                    // close with the window
+                    let message = format!("Could not save {}: {}", path.display(), e);
+                    if self.quitting {
+                        eprintln!("{}", message);
+                    }
+                    self.show_toast(&message);
+                }
+                (_, Err(e)) => {
+                    // Closing is called off so that the user can try again
+                    self.quitting = false;
+                    self.show_toast(&format!("Could not save {}: {}", path.display(), e));
+                }
+            }
+        }
+    }
+
+    /// The name, notes and tags of the shape
+    pub fn metadata(&self) -> &Metadata {
+        &self.state.metadata
+    }
+
+    /// Changes the name, notes and tags of the shape, which are saved with its points
+    pub fn set_metadata(&mut self, metadata: Metadata) {
+        if metadata != self.state.metadata {
+            self.state.metadata = metadata;
+            self.state.dirty = true;
+        }
+    }
+
+    /// The control points placed so far
+    pub fn points(&self) -> &[Point] {
+        &self.state.points
+    }
+
+    /// Replaces the control points, as if the user had placed them
+    pub fn set_points(&mut self, points: Vec<Point>) {
+        self.state.points = points;
+        self.state.selected = None;
+        self.state.dirty = true;
+        self.redraw();
+    }
+
+    /// The polylines finished before the one being drawn
+    pub fn polylines(&self) -> &[Vec<Point>] {
+        &self.state.polylines
+    }
+
+    /// Replaces every polyline, as if the user had drawn them one after the other: the last
+    /// one is the one being drawn, and the others are finished
+    pub fn set_polylines(&mut self, mut polylines: Vec<Vec<Point>>) {
+        self.set_points(polylines.pop().unwrap_or_default());
+        self.state.polylines = polylines;
+    }
+
+    /// Moves the points by whole pixels so that their bounding box is drawn in the middle of
+    /// the canvas, however the view is zoomed and panned, e.g. to bring a shape drawn on a
+    /// larger screen into view. The shape is the same, so it isn't marked as changed
+    pub fn center_points(&mut self) {
+        let size = (self.state.buffer_width, self.state.buffer_height);
+        let Some(offset) = self.view.centering(&self.state.points, size) else {
+            return;
+        };
+        for point in &mut self.state.points {
+            *point += offset;
+        }
+        self.redraw();
+    }
+
+    /// Scales and moves the view so that the points, and the curve being animated, fill the
+    /// canvas with some room around them, e.g. after opening a file drawn at another size
+    pub fn fit_view(&mut self) {
+        let mut content = self.state.points.clone();
+        content.extend(self.state.polylines.iter().flatten());
+        if self.state.animation_state == AnimationState::Animating {
+            if let Some((_, curve)) = &self.curve {
+                content.extend_from_slice(curve);
+            }
+        }
+        let size = (self.state.buffer_width, self.state.buffer_height);
+        if let Some(view) = View::fit(&content, size, FIT_PADDING) {
+            self.view = view;
+            self.redraw();
+        }
+    }
+
+    /// Zooms the view about the mouse as the wheel turns, and pans it while the middle button
+    /// drags
+    fn move_view(&mut self) {
+        let scroll = self.mouse.scroll().clamp(-MAX_SCROLL, MAX_SCROLL);
+        if let Some(position) = self.mouse.position().filter(|_| scroll != 0.0) {
+            self.view.zoom_at(ZOOM_PER_NOTCH.powf(scroll), position);
+        }
+        if self.mouse.is_down(MouseButton::Middle) {
+            self.view.pan(self.mouse.movement());
+        }
+    }
+
+    /// Finishes the polyline being drawn and starts a new one, each smoothed on its own
+    pub fn finish_polyline(&mut self) {
+        if self.state.points.len() < 2 {
+            self.show_toast("Place at least two points before starting another polyline");
+            return;
+        }
+        self.state.record_edit();
+        let finished = std::mem::take(&mut self.state.points);
+        self.state.polylines.push(finished);
+        self.state.selected = None;
+        self.state.dirty = true;
+        self.redraw();
+    }
+
+    /// Asks whether to fit the view to the points if they can't be seen well as they are,
+    /// e.g. when they were exported from a map or a drawing in other units
+    fn offer_fit_view(&mut self) {
+        let size = (self.state.buffer_width, self.state.buffer_height);
+        let points: Vec<Point> = self.state.points.iter().chain(self.state.polylines.iter().flatten()).copied().collect();
+        if !self.view.shows(&points, size) {
+            let question = "The points are out of sight or tiny. Fit the view to them?";
+            self.modal = Some((Dialog::FitView, Modal::confirm(question)));
+        }
+    }
+
+    /// The smoothed points at the given step
+    pub fn step_points(&self, step: usize) -> Vec<Point> {
+        self.smoother.get_step_points(&self.state.points, step)
+    }
+
+    /// Stops the animation at the given step and draws it
+    pub fn show_step(&mut self, step: usize) {
+        self.state.animation_state = AnimationState::Animating;
+        self.state.paused = true;
+        self.state.current_step = step;
+        self.held_progress = None;
+        self.redraw();
+    }
+
+    /// Stops the animation part of the way from the given step to the next one, from 0 at
+    /// the step to 1 at the next, and draws it, e.g. to record the morph frame by frame
+    pub fn show_step_tween(&mut self, step: usize, progress: f32) {
+        self.state.animation_state = AnimationState::Animating;
+        self.state.paused = true;
+        self.state.current_step = step;
+        self.held_progress = Some(progress.clamp(0.0, 1.0));
+        self.redraw();
+    }
+
+    /// Shows or hides the mode and the step in the bottom left corner, e.g. to hide them
+    /// from exported frames
+    pub fn set_hud(&mut self, hud: bool) {
+        self.hud = hud;
+    }
+
+    /// The mouse as of the last frame, with the state of drags in progress
+    pub fn mouse(&self) -> &MouseState {
+        &self.mouse
+    }
+
+    /// The keyboard shortcuts, e.g. to rebind them
+    pub fn shortcuts(&mut self) -> &mut Shortcuts {
+        &mut self.shortcuts
+    }
+
+    /// Changes how points are placed while the mouse button is held
+    pub fn set_placement(&mut self, placement: Placement) {
+        self.placer.placement = placement;
+    }
+
+    /// Changes how close to a point a press has to be to drag it, in pixels
+    pub fn set_hit_radius(&mut self, radius: f32) {
+        self.hit_radius = radius;
+    }
+
+    /// Shows or hides the ghost of the fully smoothed curve while drawing
+    pub fn set_ghost(&mut self, ghost: bool) {
+        self.ghost = ghost;
+    }
+
+    /// Shows or hides the curve the steps converge to, dashed behind them while animating,
+    /// for the schemes that know it
+    pub fn set_limit(&mut self, limit: bool) {
+        self.limit = limit;
+    }
+
+    /// Draws each step of the animation in its own color of the gradient, or all of them in
+    /// the line color
+    pub fn set_gradient(&mut self, gradient: Option<Gradient>) {
+        self.gradient = gradient;
+    }
+
+    /// Draws the steps before the one shown under it while animating, or only that one
+    pub fn set_onion_skin(&mut self, onion_skin: bool) {
+        self.onion_skin = onion_skin;
+    }
+
+    /// Morphs each step of the animation into the next one while it is shown, or jumps from
+    /// one to the next
+    pub fn set_interpolate(&mut self, interpolate: bool) {
+        self.interpolate = interpolate;
+    }
+
+    /// Morphs the steps with their points spread evenly along their length, so that they keep
+    /// the same spacing, or along the points of each step, which keeps them exactly
+    pub fn set_tween_by_arclength(&mut self, by_arclength: bool) {
+        self.tween_by_arclength = by_arclength;
+    }
+
+    /// Changes how long each step of the animation is shown, within [`STEP_INTERVAL_RANGE`]
+    pub fn set_step_interval(&mut self, interval: Duration) {
+        self.step_interval = interval.clamp(STEP_INTERVAL_RANGE.0, STEP_INTERVAL_RANGE.1);
+    }
+
+    /// Asks the user whether to take the tutorial, e.g. the first time the app runs
+    pub fn offer_tutorial(&mut self) {
+        self.modal = Some((Dialog::Tutorial, Modal::confirm("New here? Take the quick tutorial?")));
+    }
+
+    /// Walks the user through placing points, animating them, changing the speed and
+    /// exporting the curve, with a hint for each
+    pub fn start_tutorial(&mut self) {
+        self.tutorial = Some(Tutorial::default());
+    }
+
+    /// Changes how quick double-clicks and how long long presses are
+    pub fn set_gesture_config(&mut self, config: GestureConfig) {
+        self.mouse.gesture_config = config;
+    }
+
+    /// The pixels drawn so far, e.g. for embedders and tests to grab frames
+    pub fn frame(&self) -> Frame<'_> {
+        self.raster.frame()
+    }
+
+    /// Raises the window above the others.
+    /// minifb has no focus API, so we briefly make the window topmost instead
+    pub fn bring_to_front(&mut self) {
+        if let Some(window) = &self.window {
+            window.topmost(true);
+            window.topmost(false);
+        }
+        self.wake();
+    }
+
+    /// Draws frames again, for a while at least, e.g. after the points changed from outside
+    /// the window
+    pub fn wake(&mut self) {
+        self.last_activity = Instant::now();
+    }
+
+    /// Whether something on screen changes without any input: the animation, the overlays
+    /// which fade out, such as the toast, the time counted in the stats panel, or the
+    /// progress of a curve computed on a worker thread or of the files being written
+    fn is_busy(&self) -> bool {
+        (self.state.animation_state == AnimationState::Animating && !self.state.paused)
+            || self.overlays.is_fading()
+            || self.show_stats
+            || self.curve_job.is_some()
+            || !self.writes.is_empty()
+    }
+
+    /// Whether the frame on screen stays the same until the next input, which has been the
+    /// case for [`IDLE_DELAY`]
+    fn is_settled(&self) -> bool {
+        !self.is_busy() && self.last_activity.elapsed() >= IDLE_DELAY
+    }
+
+    /// Whether the window is minimized, which is only known where minimized windows report
+    /// no size, e.g. on Windows. Elsewhere they lose the focus, which slows them down too
+    fn is_minimized(&self) -> bool {
+        self.window.as_ref().is_some_and(|window| {
+            let (width, height) = window.get_size();
+            width == 0 || height == 0
+        })
+    }
+
+    /// Whether the window can wait for input with [`Self::wait_for_input`] rather than
+    /// drawing the same frame again, or drawing at all while it is minimized. Files being
+    /// written still need frames to be done with
+    pub fn is_idle(&self) -> bool {
+        self.window.is_some() && (self.is_settled() || (self.is_minimized() && self.writes.is_empty()))
+    }
+
+    /// Waits for input without drawing, only polling the window every
+    /// [`IDLE_POLL_INTERVAL`], and showing the frame again every [`IDLE_REPAINT_INTERVAL`].
+    /// Minimized, it is polled every [`MINIMIZED_POLL_INTERVAL`] and never shown. The window
+    /// goes back to its monitor's refresh rate as soon as a frame is drawn
+    pub fn wait_for_input(&mut self) {
+        let minimized = self.is_minimized();
+        let Some(window) = &mut self.window else {
+            return;
+        };
+        let interval = if minimized { MINIMIZED_POLL_INTERVAL } else { IDLE_POLL_INTERVAL };
+        if self.idle != Some(interval) {
+            self.idle = Some(interval);
+            window.limit_update_rate(Some(interval));
+        }
+        if !minimized && self.last_shown.elapsed() >= IDLE_REPAINT_INTERVAL {
+            self.last_shown = Instant::now();
+            window.update_with_buffer(
+                self.raster.pixels(),
+                self.state.buffer_width,
+                self.state.buffer_height,
+            ).unwrap();
+        } else {
+            window.update();
+        }
+    }
+
+    /// The colors everything is drawn with
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    /// Changes the radius the control points are drawn with. They can still be grabbed
+    /// anywhere on them
+    pub fn set_point_radius(&mut self, radius: f32) {
+        self.point_radius = radius;
+        self.hit_radius = self.hit_radius.max(radius);
+    }
+
+    /// Changes the colors everything is drawn with
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Shows something over the canvas, until it is over or dismissed
+    pub fn show_overlay(&mut self, overlay: Overlay) {
+        self.overlays.show(overlay);
+    }
+
+    /// Shows a message at the bottom of the canvas for a while
+    fn show_toast(&mut self, message: &str) {
+        self.overlays.show(Overlay::new(OverlayKind::Toast(message.to_string()), TOAST_DURATION));
+    }
+
+    /// Smooths the points with one of the built-in schemes, Chaikin's with the given ratios
+    pub fn set_scheme(&mut self, scheme: Scheme, ratios: (f32, f32)) {
+        match scheme {
+            Scheme::Chaikin if self.adaptive => {
+                self.set_smoother(Box::new(ChaikinAlgorithm::with_ratios(ratios.0, ratios.1).adaptive(ADAPTIVE_TOLERANCE)))
+            }
+            _ => self.set_smoother(scheme.smoother(ratios)),
+        }
+        self.scheme = Some(scheme);
+        self.ratios = ratios;
+    }
+
+    /// Changes the subdivision scheme used to smooth the points
+    pub fn set_smoother(&mut self, smoother: Box<dyn Smoother>) {
+        self.scheme = None;
+        // The shape stays closed if the scheme supports it
+        let smoother = match smoother.with_closed(self.closed) {
+            Some(closed) => closed,
+            None => {
+                self.closed = false;
+                smoother
+            }
+        };
+        self.smoother = Arc::from(smoother);
+        self.curve = None;
+        self.next_curve = None;
+        self.curve_job = None;
+        self.refined = None;
+        self.curve_bvhs.clear();
+        self.steps.clear();
+        self.ghost_curve.clear();
+        self.limit_curve = None;
+        self.hover_curve.clear();
+        self.polyline_curves.clear();
+    }
+
+    /// Connects the last point back to the first, or not, returning whether the scheme
+    /// supports it
+    pub fn set_closed(&mut self, closed: bool) -> bool {
+        let Some(smoother) = self.smoother.with_closed(closed) else {
+            return false;
+        };
+        self.closed = closed;
+        // Closing the shape doesn't change the scheme
+        let scheme = self.scheme;
+        self.set_smoother(smoother);
+        self.scheme = scheme;
+        true
+    }
+
+    /// Changes how often the previews are recomputed at most while the points or the cursor
+    /// move, to keep interaction smooth on large shapes
+    pub fn set_preview_interval(&mut self, interval: Duration) {
+        self.ghost_curve.set_interval(interval);
+        self.hover_curve.set_interval(interval);
+    }
+
+    /// Reports lengths in the units, in the HUD and in SVG exports, instead of pixels
+    pub fn set_units(&mut self, units: Units) {
+        self.units = units;
+    }
+
+    /// Writes the numbers shown, e.g. lengths, angles and the formulas' ratios, in the format
+    pub fn set_number_format(&mut self, numbers: NumberFormat) {
+        self.numbers = numbers;
+    }
+
+    /// Changes the number of steps the animation goes through before starting over, up to
+    /// [`MAX_STEPS_LIMIT`]
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.state.max_steps = max_steps.clamp(1, MAX_STEPS_LIMIT);
+        self.state.current_step %= self.state.max_steps;
+    }
+
+    /// Saves a PNG screenshot into the given directory each time the animation step
+    /// changes, named after the step, e.g. `step-3.png`
+    pub fn capture_steps_to(&mut self, directory: PathBuf) {
+        self.capture_dir = Some(directory);
+    }
+
+    /// Starts a demo that animates random shapes, generated from the given seed, one after
+    /// the other until the window is reset
+    pub fn start_demo(&mut self, seed: u64) {
+        self.reset();
+        self.quiz = None;
+        let mut demo = Demo::new(seed);
+        self.state.points = demo.next_shape(self.state.buffer_width, self.state.buffer_height);
+        self.state.animation_state = AnimationState::Animating;
+        self.capture_pending = true;
+        self.demo = Some(demo);
+    }
+
+    /// Starts an exercise: the curve of a random shape, generated from the given seed, is
+    /// shown faintly and the user has to place the fewest points whose curve matches it.
+    /// Each animation grades the points against it
+    pub fn start_quiz(&mut self, seed: u64) {
+        self.reset();
+        self.quiz = Some(Exercise::generate(
+            seed,
+            self.state.buffer_width,
+            self.state.buffer_height,
+            self.smoother.as_ref(),
+            self.state.max_steps - 1,
+        ));
+        self.show_toast("Place the fewest points whose curve matches the faint one, then press Enter");
+    }
+
+    /// Maximizes the window, where the platform allows it
+    pub fn maximize(&mut self) {
+        #[cfg(target_os = "windows")]
+        if let Some(window) = &self.window {
+            use winapi::um::winuser::{ShowWindow, SW_MAXIMIZE};
+            unsafe { ShowWindow(window.get_window_handle() as _, SW_MAXIMIZE) };
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        eprintln!("Maximizing the window is not supported on this platform");
+    }
+
+    /// Re-reads the state of the window and re-renders all the points,
+    /// lines, and the toast if active
+    pub fn redraw(&mut self) {
+        self.fit_buffer_to_window();
+        self.path_bvhs.start_frame();
+
+        if self.state.animation_state == AnimationState::Drawing {
+            self.clear_buffer();
+            self.draw_grid();
+            self.draw_quiz_target();
+            self.draw_ghost();
+            self.draw_hover_preview();
+            self.draw_polylines();
+            self.draw_lines();
+            self.draw_points();
+            self.draw_selection();
+            self.draw_angles();
+            self.draw_coordinates();
+            self.draw_overlays();
+            self.draw_hud();
+            self.draw_stats();
+            self.draw_formulas();
+            self.draw_toast();
+            self.draw_color_picker();
+            self.draw_search();
+            self.draw_modal();
+            return;
+        }
+
+        self.clear_buffer();
+        self.draw_grid();
+        self.draw_quiz_target();
+        self.draw_control_polygons();
+        self.draw_limit();
+        if self.bezier {
+            self.draw_bezier();
+        } else {
+            self.draw_onion_skin();
+            // We are animating. Until a heavy step is ready, the last one stays on screen
+            self.compute_curve();
+            let curve = self.curve.take();
+            match &curve {
+                Some((key, points)) => match self.tween_curve(key, points) {
+                    Some(tween) => self.draw_lines_between(&tween),
+                    None => match self.refine_on_screen(key, points) {
+                        Some(refined) => self.draw_lines_between(&refined),
+                        None => self.draw_curve(key, points),
+                    },
+                },
+                None => self.draw_lines(),
+            }
+            self.curve = curve;
+            self.draw_polylines();
+        }
+        self.draw_points();
+        self.draw_angles();
+
+        // Captures are taken before the overlays are drawn, so that they only
+        // show the curve, once it is ready
+        if self.capture_pending && self.curve_job.is_none() {
+            self.capture_pending = false;
+            self.capture_step();
+        }
+
+        self.draw_overlays();
+        self.draw_spinner();
+        self.draw_hud();
+        self.draw_stats();
+        self.draw_formulas();
+        self.draw_toast();
+        self.draw_color_picker();
+        self.draw_search();
+        self.draw_modal();
+    }
+
+    /// Makes sure the curve of the current step is computed, or being computed. Heavy steps
+    /// are computed on a worker thread, so that the window keeps responding in the meantime.
+    /// Zoomed out, the first step whose segments are shorter than a pixel stands in for the
+    /// steps after it, which would look just like it
+    fn compute_curve(&mut self) {
+        let done = self.curve_job.as_ref().and_then(|(_, job)| job.poll());
+        if let Some(points) = done {
+            let (key, _) = self.curve_job.take().expect("polled above");
+            self.replace_curve(key, points);
+            // The step is shown for as long as the others, however long it took
+            self.last_call = Instant::now();
+        }
+
+        let step = self.state.current_step.min(lod::subpixel_step(&self.state.points, &self.view));
+        let key = (Polyline::from_slice(&self.state.points), step);
+        let ready = matches!(&self.curve, Some((computed, _)) if *computed == key);
+        let computing = matches!(&self.curve_job, Some((computing, _)) if *computing == key);
+        if ready || computing {
+            return;
+        }
+
+        let (points, step) = (key.0.to_vec(), key.1);
+        if step_size(points.len(), step) < HEAVY_STEP_POINTS {
+            let mut curve = self.pool.take();
+            curve.extend_from_slice(self.steps.get(&*self.smoother, &points, step));
+            self.replace_curve(key, curve);
+        } else {
+            // The worker goes on from the last step computed on this thread
+            let (from, points) = match self.steps.deepest(&points, step) {
+                Some((from, curve)) => (from, curve.to_vec()),
+                None => (0, points),
+            };
+            let smoother = Arc::clone(&self.smoother);
+            let job = Job::spawn(move |cancel, progress| {
+                // Each step has about twice the points of the one before, and takes as long
+                // as all of them
+                progress.set_total((1 << step) - (1 << from));
+                let (mut points, mut next) = (points, Vec::new());
+                for i in from..step {
+                    if cancel.is_cancelled() {
+                        return None;
+                    }
+                    smoother.calculate_step_into(&points, &mut next);
+                    std::mem::swap(&mut points, &mut next);
+                    progress.advance(1 << i);
+                }
+                Some(points)
+            });
+            self.curve_job = Some((key, job));
+        }
+    }
+
+    /// The last step refined further where it is on screen, when zoomed in so far that its
+    /// segments show, so that the curve looks as smooth as it does at any other scale. Only
+    /// the part on the canvas is refined, by as many steps as it takes for its segments to
+    /// be a few pixels long, within a bound on the points it adds. None while the animation
+    /// is on another step, or when the step shows as smooth as it is
+    fn refine_on_screen(&mut self, key: &CurveKey, curve: &[Point]) -> Option<Vec<Point>> {
+        if key.1 + 1 < self.state.max_steps || key.1 != self.state.current_step {
+            return None;
+        }
+        let size = (self.state.buffer_width, self.state.buffer_height);
+        let refined_for = (key.clone(), self.view, size);
+        if let Some((computed, refined)) = &self.refined {
+            if *computed == refined_for {
+                return Some(refined.clone());
+            }
+        }
+
+        let range = lod::visible_range(curve, &self.view, size)?;
+        let mut extra = lod::extra_steps(lod::longest_segment(&curve[range.clone()], &self.view));
+        while extra > 0 && range.len() << extra >= HEAVY_STEP_POINTS / 4 {
+            extra -= 1;
+        }
+        if extra == 0 {
+            return None;
+        }
+        // A part of the curve is refined as an open polyline, which keeps its ends where they
+        // are, so that it joins the rest of the curve
+        let whole = range.len() == curve.len();
+        let open = if whole { None } else { self.smoother.with_closed(false) };
+        let smoother = open.as_deref().unwrap_or(&*self.smoother);
+        let part = smoother.get_step_points(&curve[range.clone()], extra);
+        let mut refined = Vec::with_capacity(curve.len() - range.len() + part.len());
+        refined.extend_from_slice(&curve[..range.start]);
+        refined.extend_from_slice(&part);
+        refined.extend_from_slice(&curve[range.end..]);
+        self.refined = Some((refined_for, refined.clone()));
+        Some(refined)
+    }
+
+    /// The curve of the step part of the way to the next step, as far as the step has been
+    /// shown. None when the step is shown as it is: while paused, on the last step, which
+    /// starts over rather than morph, or when the next step would be too heavy to compute
+    /// every step on this thread
+    fn tween_curve(&mut self, key: &CurveKey, curve: &[Point]) -> Option<Vec<Point>> {
+        let t = self.step_progress()?;
+        if curve.len() * 2 >= HEAVY_STEP_POINTS {
+            return None;
+        }
+
+        if !matches!(&self.next_curve, Some((computed, _)) if computed == key) {
+            // The next step is one more step from this one, and cached for when the
+            // animation gets there
+            let mut next = self.next_curve.take().map(|(_, next)| next).unwrap_or_default();
+            next.clear();
+            next.extend_from_slice(self.steps.get(&*self.smoother, key.0.as_slice(), key.1 + 1));
+            self.next_curve = Some((key.clone(), next));
+        }
+        let (_, next) = self.next_curve.as_ref().expect("computed above");
+        Some(self.tween()(curve, next, t, self.closed))
+    }
+
+    /// How a step is morphed into the next one
+    fn tween(&self) -> fn(&[Point], &[Point], f32, bool) -> Vec<Point> {
+        if self.tween_by_arclength {
+            tween::between_by_arclength
+        } else {
+            tween::between
+        }
+    }
+
+    /// How far the animation has gone from the current step toward the next one, from 0 to
+    /// 1, or none if it shows the step as it is: without interpolation, on the last step,
+    /// or until the step is computed and captured. A paused animation is only shown part
+    /// way when asked to
+    fn step_progress(&self) -> Option<f32> {
+        let last_step = self.state.current_step + 1 >= self.state.max_steps;
+        let held = self.state.paused && self.held_progress.is_none();
+        let still = held || self.curve_job.is_some() || self.capture_pending;
+        if !self.interpolate || still || last_step {
+            return None;
+        }
+        let t = match self.held_progress {
+            Some(progress) if self.state.paused => progress,
+            _ => self.last_call.elapsed().as_secs_f32() / self.step_interval.as_secs_f32(),
+        };
+        Some(t.min(1.0))
+    }
+
+    /// Makes the curve the one drawn, giving the memory of the last one back to the pool
+    fn replace_curve(&mut self, key: CurveKey, curve: Vec<Point>) {
+        if let Some((_, old)) = self.curve.replace((key, curve)) {
+            self.pool.give(old);
+        }
+    }
+
+    /// Cancels the step being computed on a worker thread and goes back to drawing, since
+    /// the animation can't go on without it
+    fn cancel_job(&mut self) {
+        if let Some((_, job)) = self.curve_job.take() {
+            job.cancel();
+            self.state.animation_state = AnimationState::Drawing;
+            self.show_toast("Cancelled");
+        }
+    }
+
+    /// Saves the buffer as a screenshot of the current step into the capture directory
+    fn capture_step(&mut self) {
+        let Some(directory) = &self.capture_dir else {
+            return;
+        };
+
+        let directory = directory.clone();
+        let path = directory.join(format!("step-{}.png", self.state.current_step));
+        let (pixels, width, height) = (self.raster.pixels().to_vec(), self.state.buffer_width, self.state.buffer_height);
+        self.write_in_background(path, Written::Capture, move |path| {
+            std::fs::create_dir_all(directory)?;
+            crate::export::png::save(path, Frame { pixels: &pixels, width, height })
+        });
+    }
+
+    pub fn handle_input(&mut self) -> bool {
+        // Closing waits for the files to be written, and is called off if one can't be,
+        // unless it is only a step's capture
+        if self.quitting {
+            return !self.writes.is_empty();
+        }
+
+        if let Some(window) = &self.window {
+            self.input = Input::from_window(window, self.typed_text.take());
+        }
+        let previous = self.mouse.position();
+        self.mouse.update(&self.input, Instant::now());
+        if !self.input.is_quiet() || self.mouse.position() != previous || self.input.close_requested {
+            self.wake();
+        }
+        if let Some(window) = &self.window {
+            if window.get_size() != (self.state.buffer_width, self.state.buffer_height) {
+                self.wake();
+            }
+        }
+
+        let window_closed = self.input.close_requested && !self.close_handled;
+        if window_closed {
+            self.close_handled = true;
+        }
+
+        let focus = if self.modal.is_some() {
+            Focus::Modal
+        } else if self.color_picker.is_some() {
+            Focus::ColorPicker
+        } else if self.search.is_some() {
+            Focus::Search
+        } else {
+            Focus::Canvas
+        };
+        self.focus.update(focus, &self.input);
+
+        if window_closed {
+            return self.quit();
+        }
+
+        // Open widgets take the mouse and keyboard until they are closed
+        match focus {
+            Focus::Modal => return self.handle_modal(),
+            Focus::ColorPicker => {
+                self.handle_color_picker();
+                return true;
+            }
+            Focus::Search => {
+                self.handle_search();
+                return true;
+            }
+            Focus::Canvas => {}
+        }
+
+        let mut finished = false;
+        for command in self.shortcuts.resolve(&self.input) {
+            // The animation starts after the mouse is handled, as a double-click does
+            if command == Command::Animate {
+                finished = true;
+            } else if !self.run(command) {
+                return false;
+            }
+            // The command opened a widget, which gets the input from the next frame on
+            if self.modal.is_some() || self.color_picker.is_some() || self.search.is_some() {
+                return true;
+            }
+        }
+
+        if self.focus.canvas_has_mouse() {
+            self.move_view();
+        }
+
+        let mut mouse_clicked = false;
+        if self.state.animation_state == AnimationState::Drawing && self.focus.canvas_has_mouse() {
+            // A press on a point of another polyline goes on with that one
+            let pressed = self.mouse.is_pressed(MouseButton::Left) || self.mouse.is_pressed(MouseButton::Right);
+            if let Some((x, y)) = self.mouse.position().filter(|_| pressed) {
+                self.select_polyline_at(x, y);
+            }
+
+            // A right click deletes the point under the cursor, if there is one
+            if self.mouse.is_pressed(MouseButton::Right) {
+                mouse_clicked = true;
+                if let Some(index) = self.mouse.position().and_then(|(x, y)| self.point_at(x, y)) {
+                    self.delete_point(index);
+                }
+            }
+
+            // A double-click finishes the shape, its second press doesn't add a point
+            if let Some(Gesture::DoubleClick(_)) = self.mouse.gesture() {
+                mouse_clicked = true;
+                finished = true;
+            } else if let Some(index) = self.dragged_point() {
+                // A point is picked up rather than a new one placed on top of it
+                mouse_clicked |= self.mouse.is_pressed(MouseButton::Left);
+                if let Some((x, y)) = self.mouse.position() {
+                    let point = self.grid.snap(self.view.point_at_pixel(x, y));
+                    if self.state.points[index] != point {
+                        // The whole drag is undone at once
+                        if !self.drag_recorded {
+                            self.state.record_edit();
+                            self.drag_recorded = true;
+                        }
+                        self.state.points[index] = point;
+                        self.state.dirty = true;
+                    }
+                }
+            } else if let Some((index, point)) = self.segment_clicked() {
+                // Alt+click splits the segment under the mouse rather than adding a point at
+                // the end
+                mouse_clicked = true;
+                self.insert_point(index, point);
+            } else if let Some((x, y)) = self.placer.next_point(&self.mouse, Instant::now()) {
+                let point = self.grid.snap(self.view.point_at_pixel(x, y));
+                mouse_clicked = true;
+                if !self.state.points.contains(&point) {
+                    self.add_point(point.x, point.y);
+                }
+            }
+        }
+
+        // Alt+click on the curve splits the shape where it is clicked. Its toast takes the
+        // place of the one shown, rather than being dismissed by the click
+        if self.state.animation_state == AnimationState::Animating && self.focus.canvas_has_mouse() {
+            if let Some(point) = self.curve_clicked() {
+                self.split_at_curve(point);
+            }
+        }
+
+        // Check if toast should be dismissed
+        self.check_toast_dismiss(mouse_clicked);
+
+        if finished {
+            self.start_animation();
+        }
+
+        true
+    }
+
+    /// The point being dragged, picked when the left button goes down near it, until the
+    /// button is released
+    fn dragged_point(&mut self) -> Option<usize> {
+        if !self.mouse.is_down(MouseButton::Left) {
+            self.dragging = None;
+        } else if self.mouse.is_pressed(MouseButton::Left) {
+            self.dragging = self.mouse.position().and_then(|(x, y)| self.point_at(x, y));
+            self.drag_recorded = false;
+            // The point pressed is selected, and pressing anywhere else lets go of it
+            self.state.selected = self.dragging;
+            self.nudge_recorded = false;
+        }
+        self.dragging
+    }
+
+    /// Where the point goes, and the point, on the segment under the mouse, when the left
+    /// button is pressed with Alt down within the hit radius of a segment of the points
+    fn segment_clicked(&self) -> Option<(usize, Point)> {
+        if !self.mouse.is_pressed(MouseButton::Left) || !self.input.is_alt_down() {
+            return None;
+        }
+        let (x, y) = self.mouse.position()?;
+        let point = self.view.point_at_pixel(x, y);
+        let (index, on_segment) = self.state.segment_near(point, self.hit_radius / self.view.scale, self.closed)?;
+        Some((index, self.grid.snap(on_segment)))
+    }
+
+    /// The point of the curve shown closest to the mouse, when the left button is pressed with
+    /// Alt down within the hit radius of the curve
+    fn curve_clicked(&mut self) -> Option<Point> {
+        if !self.mouse.is_pressed(MouseButton::Left) || !self.input.is_alt_down() {
+            return None;
+        }
+        let (x, y) = self.mouse.position()?;
+        let point = self.view.point_at_pixel(x, y);
+        let (key, curve) = self.curve.as_ref().filter(|((computed, _), _)| *computed == Polyline::from_slice(&self.state.points))?;
+        let projection = self.curve_bvhs.get(key, curve, self.closed).project(curve, point)?;
+        (projection.distance <= self.hit_radius / self.view.scale).then_some(projection.point)
+    }
+
+    /// Splits the points in two where the segment of the control polygon nearest to the
+    /// point of the curve is closest to it, which is where that point comes from for the
+    /// schemes that cut corners. Closed shapes have no ends to split them between
+    fn split_at_curve(&mut self, point: Point) {
+        if self.closed {
+            self.show_toast("Open the shape to split it");
+            return;
+        }
+        let Some(projection) = Bvh::new(&self.state.points, false).project(&self.state.points, point) else {
+            return;
+        };
+        if !self.state.split_at(projection.segment, projection.point) {
+            self.show_toast("Nothing to split off at the end of the shape");
+            return;
+        }
+        self.overlays.show(Overlay::halo(self.view.screen_point(projection.point)));
+        self.show_toast("Split the shape in two");
+        self.redraw();
+    }
+
+    /// Makes the finished polyline with a point drawn under the pixel the one being drawn,
+    /// unless the current one has a point there. The current one is finished in its place,
+    /// unless it is too short to be a polyline, as [`Self::finish_polyline`] would have it
+    fn select_polyline_at(&mut self, x: f32, y: f32) {
+        if self.point_at(x, y).is_some() {
+            return;
+        }
+        let position = Point2::new(x, y);
+        let Some(index) = self.state.polylines.iter().position(|polyline| {
+            polyline.iter().any(|point| (self.view.screen_point(*point) - position).norm() <= self.hit_radius)
+        }) else {
+            return;
+        };
+
+        self.state.record_edit();
+        let selected = self.state.polylines.remove(index);
+        let current = std::mem::replace(&mut self.state.points, selected);
+        if current.len() >= 2 {
+            self.state.polylines.insert(index, current);
+        } else if !current.is_empty() {
+            // The lone point is dropped, which can be undone
+            self.state.dirty = true;
+        }
+        self.state.selected = None;
+    }
+
+    /// The point drawn closest to the pixel, if it is within the hit radius
+    fn point_at(&self, x: f32, y: f32) -> Option<usize> {
+        let position = Point2::new(x, y);
+        self.state.points
+            .iter()
+            .map(|point| (self.view.screen_point(*point) - position).norm())
+            .enumerate()
+            .filter(|(_, distance)| *distance <= self.hit_radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index)
+    }
+
+    /// Closes the window, after asking what to do with the unsaved points if there are any.
+    /// Returns whether the app should keep running
+    fn quit(&mut self) -> bool {
+        // Nothing would be lost, so there's no need to ask
+        if !self.state.dirty {
+            return false;
+        }
+        let choices = vec![(Key::S, "Save"), (Key::D, "Discard"), (Key::C, "Cancel")];
+        self.modal = Some((Dialog::Quit, Modal::choice(QUIT_PROMPT, choices)));
+        true
+    }
+
+    /// Runs a command given from the keyboard, returning whether the app should keep running
+    fn run(&mut self, command: Command) -> bool {
+        match command {
+            // Escape stops a long computation first
+            Command::Quit if self.curve_job.is_some() => self.cancel_job(),
+            Command::Quit => return self.quit(),
+            Command::Reset if self.state.dirty => {
+                self.modal = Some((Dialog::Reset, Modal::confirm("Clear the unsaved points?")));
+            }
+            Command::Reset => self.reset(),
+            Command::EnterPoint if self.state.animation_state == AnimationState::Drawing => {
+                self.modal = Some((Dialog::EnterPoint, Modal::text_input("Add a point at \"x y\"", "")));
+            }
+            Command::OpenFile => {
+                let directory = std::env::current_dir()
+                    .map(|directory| format!("{}{}", directory.display(), std::path::MAIN_SEPARATOR))
+                    .unwrap_or_default();
+                self.modal = Some((Dialog::OpenFile, Modal::text_input("Open a file of points or a session", &directory)));
+            }
+            Command::SearchShapes => {
+                let directory = match self.state.file_path.as_ref().and_then(|path| path.parent()) {
+                    Some(directory) if !directory.as_os_str().is_empty() => directory.to_path_buf(),
+                    _ => PathBuf::from("."),
+                };
+                self.search = Some(ShapeSearch::scan(&directory));
+            }
+            Command::Describe => {
+                let name = self.state.metadata.name.clone();
+                self.modal = Some((Dialog::EditName, Modal::text_input("Name of the shape", &name)));
+            }
+            Command::PickColors => {
+                self.color_picker = Some(ColorPicker::new(ThemeColor::Point, self.theme.point));
+            }
+            Command::DismissToast => {
+                // Nothing else changes on screen, so an idle window has to be woken to show
+                // that the toast is gone
+                self.overlays.dismiss_toast();
+                self.wake();
+                self.redraw();
+            }
+            Command::ToggleGradient => {
+                self.gradient = match self.gradient {
+                    Some(_) => None,
+                    None => Some(Gradient::default()),
+                };
+                self.show_toast(if self.gradient.is_some() {
+                    "Each step in its own color"
+                } else {
+                    "Every step in the line color"
+                });
+            }
+            Command::ToggleOnionSkin => {
+                self.onion_skin = !self.onion_skin;
+                self.show_toast(if self.onion_skin {
+                    "Showing the earlier steps under the current one"
+                } else {
+                    "Showing the current step alone"
+                });
+            }
+            Command::ToggleGhost => {
+                self.ghost = !self.ghost;
+                self.show_toast(if self.ghost { "Showing the final curve" } else { "Hiding the final curve" });
+            }
+            Command::ToggleLimit => {
+                self.limit = !self.limit;
+                self.show_toast(match self.limit {
+                    false => "Hiding the limit curve",
+                    true if self.smoother.limit_curve(&[], LIMIT_SAMPLES).is_none() => "The limit curve of this scheme isn't known",
+                    true => "Showing the limit curve the steps converge to",
+                });
+            }
+            Command::ToggleClosed if self.bezier => self.show_toast("Bézier curves are always open"),
+            Command::ToggleClosed => {
+                let closed = !self.closed;
+                if !self.set_closed(closed) {
+                    self.show_toast("This scheme only smooths open shapes");
+                } else {
+                    self.show_toast(if closed { "Closed shape" } else { "Open shape" });
+                }
+            }
+            Command::ResetView => {
+                self.view = View::default();
+                self.show_toast("Zoom reset");
+            }
+            Command::ToggleAdaptive if self.scheme != Some(Scheme::Chaikin) => {
+                self.show_toast("Only Chaikin's scheme subdivides adaptively");
+            }
+            Command::ToggleAdaptive => {
+                self.adaptive = !self.adaptive;
+                self.set_scheme(Scheme::Chaikin, self.ratios);
+                self.show_toast(if self.adaptive {
+                    "Adaptive subdivision: corners already flat are left uncut"
+                } else {
+                    "Uniform subdivision: every corner is cut"
+                });
+            }
+            Command::Nudge { x, y } if self.state.animation_state == AnimationState::Drawing => {
+                self.nudge_selected(x, y);
+            }
+            Command::CycleStrokeWidth => {
+                self.theme = self.theme.with_next_stroke_width();
+                self.show_toast(&format!("Lines {} px wide", self.theme.stroke_width));
+            }
+            Command::ToggleControlPolygon => {
+                self.control_polygon = !self.control_polygon;
+                self.show_toast(if self.control_polygon {
+                    "Showing the control polygon while animating"
+                } else {
+                    "Hiding the control polygon while animating"
+                });
+            }
+            Command::ToggleGrid => {
+                self.grid.visible = !self.grid.visible;
+                self.show_toast(if self.grid.visible { "Showing the grid" } else { "Hiding the grid" });
+            }
+            Command::ToggleSnap => {
+                self.grid.snap = !self.grid.snap;
+                // Snapping to lines that can't be seen would be puzzling
+                self.grid.visible |= self.grid.snap;
+                self.show_toast(if self.grid.snap { "Snapping to the grid" } else { "Not snapping to the grid" });
+            }
+            Command::FinerGrid | Command::CoarserGrid => {
+                if command == Command::FinerGrid {
+                    self.grid.finer();
+                } else {
+                    self.grid.coarser();
+                }
+                self.grid.visible = true;
+                let spacing = self.units.format(self.grid.spacing, &self.numbers);
+                self.show_toast(&format!("Grid every {}", spacing));
+            }
+            Command::CyclePlacement => {
+                let placement = self.placer.placement.next();
+                self.placer.placement = placement;
+                self.show_toast(&format!("Placement: {}", placement));
+            }
+            Command::Undo => match self.state.history.undo(&self.state.points, &self.state.polylines) {
+                Some((points, polylines)) => {
+                    self.state.stats.undos += 1;
+                    self.restore_points(points, polylines);
+                }
+                None => self.show_toast("Nothing to undo"),
+            },
+            Command::Redo => match self.state.history.redo(&self.state.points, &self.state.polylines) {
+                Some((points, polylines)) => {
+                    self.state.stats.redos += 1;
+                    self.restore_points(points, polylines);
+                }
+                None => self.show_toast("Nothing to redo"),
+            },
+            Command::SaveSession => self.save_session(),
+            Command::ExportHandout => self.export_handout(),
+            Command::Screenshot => self.save_screenshot(),
+            Command::Print => {
+                if self.state.points.is_empty() {
+                    self.show_toast("Place points to print them");
+                } else {
+                    self.print();
+                }
+            }
+            Command::ExportSvg => {
+                self.export_svg();
+                self.complete_lesson(Lesson::Export);
+            }
+            Command::Faster | Command::Slower => {
+                let interval = if command == Command::Faster {
+                    self.step_interval / 2
+                } else {
+                    self.step_interval * 2
+                };
+                self.set_step_interval(interval);
+                self.show_toast(&format!("A step every {} ms", self.step_interval.as_millis()));
+                self.complete_lesson(Lesson::ChangeSpeed);
+            }
+            Command::ToggleStats => self.show_stats = !self.show_stats,
+            Command::ToggleFormulas => self.show_formulas = !self.show_formulas,
+            Command::ToggleAngles => self.show_angles = !self.show_angles,
+            Command::TogglePrecision => {
+                self.precise_coordinates = !self.precise_coordinates;
+                self.show_toast(if self.precise_coordinates {
+                    "Showing coordinates in full"
+                } else {
+                    "Showing coordinates to the pixel"
+                });
+            }
+            Command::FitView => self.fit_view(),
+            Command::NewPolyline if self.state.animation_state == AnimationState::Drawing => self.finish_polyline(),
+            Command::ToggleBezier => {
+                self.bezier = !self.bezier;
+                let mode = if self.bezier { "Bézier curve" } else { self.scheme.map_or("Subdivision", Scheme::title) };
+                self.show_toast(mode);
+            }
+            Command::UseScheme(scheme) => {
+                let was_closed = self.closed;
+                self.set_scheme(scheme, self.ratios);
+                if was_closed && !self.closed {
+                    self.show_toast(&format!("{}, which only smooths open shapes", scheme.title()));
+                } else {
+                    self.show_toast(scheme.title());
+                }
+            }
+            Command::CycleDegree => {
+                let scheme = self.scheme.map_or(Scheme::Chaikin, Scheme::with_next_degree);
+                let was_closed = self.closed;
+                self.set_scheme(scheme, self.ratios);
+                let degree = scheme.degree().expect("of the Lane-Riesenfeld family");
+                if was_closed && !self.closed {
+                    self.show_toast(&format!("{}, degree {}, which only smooths open shapes", scheme.title(), degree));
+                } else {
+                    self.show_toast(&format!("{}, degree {}", scheme.title(), degree));
+                }
+            }
+            Command::ToggleQuiz if self.quiz.is_some() => {
+                self.quiz = None;
+                self.show_toast("Quiz over");
+            }
+            Command::ToggleQuiz => {
+                let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64);
+                self.start_quiz(seed);
+            }
+            Command::Animate => self.start_animation(),
+            Command::TogglePause if self.state.animation_state == AnimationState::Animating => {
+                self.state.paused = !self.state.paused;
+                self.held_progress = None;
+                // Resuming shows the step for as long as any other
+                self.last_call = Instant::now();
+                // Counted from 1, as in the HUD
+                if self.state.paused {
+                    let (step, steps) = (self.state.current_step + 1, self.state.max_steps);
+                    self.show_toast(&format!("Paused at step {}/{}", step, steps));
+                } else {
+                    self.show_toast("Playing");
+                }
+            }
+            Command::StepForward | Command::StepBack
+                if self.state.animation_state == AnimationState::Animating =>
+            {
+                let max_steps = self.state.max_steps;
+                let step = if command == Command::StepForward {
+                    (self.state.current_step + 1) % max_steps
+                } else {
+                    (self.state.current_step + max_steps - 1) % max_steps
+                };
+                self.show_step(step);
+                self.capture_pending = true;
+                self.show_toast(&format!("Step {}/{}", step + 1, max_steps));
+            }
+            Command::MoreSteps | Command::FewerSteps
+                if self.state.animation_state == AnimationState::Animating =>
+            {
+                let max_steps = if command == Command::MoreSteps {
+                    self.state.max_steps + 1
+                } else {
+                    self.state.max_steps - 1
+                };
+                self.set_max_steps(max_steps);
+                self.show_toast(&format!("Up to step {} of {}", self.state.max_steps, MAX_STEPS_LIMIT));
+            }
+            // Points can't be added while animating, and there is nothing to pause or step
+            // through while drawing
+            Command::EnterPoint | Command::NewPolyline | Command::TogglePause | Command::StepForward | Command::StepBack
+            | Command::MoreSteps | Command::FewerSteps | Command::Nudge { .. } => {}
+        }
+        true
+    }
+
+    /// The selected point, as its index in the points, if it is still there
+    fn selected_point(&self) -> Option<usize> {
+        self.state.selected.filter(|&index| index < self.state.points.len())
+    }
+
+    /// Moves the selected point by the given number of pixels, right and down, whatever the
+    /// zoom. The grid doesn't snap it, so that it can be placed between the grid's lines
+    fn nudge_selected(&mut self, x: i32, y: i32) {
+        let Some(index) = self.selected_point() else {
+            return;
+        };
+        if !self.nudge_recorded {
+            self.state.record_edit();
+            self.nudge_recorded = true;
+        }
+        let pixel = self.view.screen_point(self.state.points[index]);
+        self.state.points[index] = self.view.point_at_pixel(pixel.x + x as f32, pixel.y + y as f32);
+        self.state.dirty = true;
+    }
+
+    /// Inserts a point into the segment ending at the index and selects it, so that the arrow
+    /// keys can move it on from there
+    fn insert_point(&mut self, index: usize, point: Point) {
+        self.state.insert_point(index, point);
+        self.state.selected = Some(index);
+        self.nudge_recorded = false;
+        self.overlays.show(Overlay::halo(self.view.screen_point(point)));
+        self.redraw();
+    }
+
+    /// Removes a point, leaving the others in the same order
+    fn delete_point(&mut self, index: usize) {
+        self.state.record_edit();
+        self.state.points.remove(index);
+        self.state.dirty = true;
+        // The indices after it moved
+        self.dragging = None;
+        self.state.selected = match self.state.selected {
+            Some(selected) if selected > index => Some(selected - 1),
+            Some(selected) if selected == index => None,
+            selected => selected,
+        };
+        self.redraw();
+    }
+
+    /// Moves the tutorial on once the user did what the lesson asks, and keeps its hint
+    /// next to what it is about
+    fn update_tutorial(&mut self) {
+        let Some(tutorial) = &mut self.tutorial else {
+            return;
+        };
+        if Lesson::has_enough_points(&self.state.points) {
+            tutorial.complete(Lesson::PlacePoints);
+        }
+        if self.state.animation_state == AnimationState::Animating {
+            tutorial.complete(Lesson::Animate);
+        }
+
+        let hints = tutorial
+            .lesson()
+            .map(|lesson| lesson.overlays(&self.state.points, self.state.buffer_width))
+            .unwrap_or_default();
+        if tutorial.is_over() {
+            self.tutorial = None;
+            self.show_toast("That's it! Ctrl+R starts over with a clean canvas");
+        }
+
+        // The hints are shown again each frame, since resetting clears the overlays
+        for hint in std::mem::take(&mut self.tutorial_hints) {
+            self.overlays.dismiss(&hint);
+        }
+        for hint in &hints {
+            self.overlays.show(Overlay::persistent(hint.clone()));
+        }
+        self.tutorial_hints = hints;
+    }
+
+    /// Ends the tutorial's lesson, if it is the one being taught
+    fn complete_lesson(&mut self, lesson: Lesson) {
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.complete(lesson);
+        }
+    }
+
+    /// Puts back points and polylines taken from the history
+    fn restore_points(&mut self, points: Vec<Point>, polylines: Vec<Vec<Point>>) {
+        self.state.points = points;
+        self.state.polylines = polylines;
+        self.state.dirty = true;
+        // The point being dragged may not be there anymore
+        self.dragging = None;
+        self.nudge_recorded = false;
+        self.overlays.dismiss_toast();
+        self.redraw();
+    }
+
+    /// Starts animating the points, if there are enough of them for the scheme to refine
+    fn start_animation(&mut self) {
+        if self.state.points.len() < self.smoother.min_points() && self.state.polylines.is_empty() {
+            self.show_toast("You did not select enough points");
+            self.draw_toast();
+        } else {
+            self.state.animation_state = AnimationState::Animating;
+            self.state.current_step = 0;
+            self.state.paused = false;
+            self.capture_pending = true;
+            if let Some(quiz) = &self.quiz {
+                let grade = quiz.grade(&self.state.points, self.smoother.as_ref());
+                self.show_toast(&grade.message(&self.numbers));
+            }
+        }
+    }
+
+    /// Lets the user pick the theme's colors, which are applied as they change.
+    /// [Tab] goes to the next color, [Enter], [Escape] or [Ctrl + K] close the picker
+    fn handle_color_picker(&mut self) {
+        let Some(picker) = &mut self.color_picker else {
+            return;
+        };
+
+        let toggled = self.input.is_ctrl_down() && self.input.is_key_pressed(Key::K);
+        if toggled || self.input.is_key_pressed(Key::Escape) || self.input.is_key_pressed(Key::Enter) {
+            self.color_picker = None;
+        } else if self.input.is_key_pressed(Key::Tab) {
+            let target = picker.target.next();
+            picker.edit(target, self.theme.get(target));
+        } else if picker.handle_mouse(&self.mouse, self.state.buffer_width) {
+            self.theme.set(picker.target, picker.color());
+        }
+    }
+
+    /// Filters the shapes as the user types, and opens the chosen one in the middle of the
+    /// canvas
+    fn handle_search(&mut self) {
+        let Some(search) = &mut self.search else {
+            return;
+        };
+
+        match search.handle(&self.input) {
+            SearchOutcome::Open => {}
+            SearchOutcome::Cancelled => self.search = None,
+            SearchOutcome::Chose(path) => {
+                self.search = None;
+                self.open_file(&path);
+                if self.state.file_path.as_deref() == Some(path.as_path()) {
+                    self.center_points();
+                }
+            }
+        }
+        self.redraw();
+    }
+
+    /// Passes the input to the open modal, and acts on the answer once there is one.
+    /// Returns whether the app should keep running
+    fn handle_modal(&mut self) -> bool {
+        let Some((dialog, modal)) = &mut self.modal else {
+            return true;
+        };
+        let dialog = *dialog;
+
+        let outcome = modal.handle(&self.input);
+        if outcome == ModalOutcome::Open {
+            return true;
+        }
+        self.modal = None;
+
+        match (dialog, outcome) {
+            (Dialog::Quit, ModalOutcome::Chose(Key::S)) => {
+                self.save_in_background();
+                self.quitting = true;
+            }
+            (Dialog::Quit, ModalOutcome::Chose(Key::D)) => return false,
+            (Dialog::Reset, ModalOutcome::Chose(Key::Y)) => self.reset(),
+            (Dialog::EnterPoint, ModalOutcome::Entered(text)) => {
+                match crate::document::parse_points(text.as_bytes()).as_deref() {
+                    Ok([point]) => self.add_point(point.x, point.y),
+                    _ => self.show_toast(&format!("Expected \"x y\", got {:?}", text)),
+                }
+            }
+            (Dialog::OpenFile, ModalOutcome::Entered(path)) => self.open_file(Path::new(path.trim())),
+            (Dialog::Tutorial, ModalOutcome::Chose(Key::Y)) => self.start_tutorial(),
+            (Dialog::FitView, ModalOutcome::Chose(Key::Y)) => self.fit_view(),
+            // The shape is described one field after the other, until cancelled
+            (Dialog::EditName, ModalOutcome::Entered(name)) => {
+                self.set_metadata(Metadata { name: name.trim().to_string(), ..self.state.metadata.clone() });
+                let notes = self.state.metadata.notes.replace('\n', NOTES_LINE_SEPARATOR);
+                self.modal = Some((Dialog::EditNotes, Modal::text_input("Notes, with lines separated by \" / \"", &notes)));
+            }
+            (Dialog::EditNotes, ModalOutcome::Entered(notes)) => {
+                let notes = notes.split(NOTES_LINE_SEPARATOR).map(str::trim).collect::<Vec<_>>().join("\n");
+                self.set_metadata(Metadata { notes: notes.trim().to_string(), ..self.state.metadata.clone() });
+                let tags = self.state.metadata.tags.join(", ");
+                self.modal = Some((Dialog::EditTags, Modal::text_input("Tags, separated by commas", &tags)));
+            }
+            (Dialog::EditTags, ModalOutcome::Entered(tags)) => {
+                self.set_metadata(Metadata { tags: document::parse_tags(&tags), ..self.state.metadata.clone() });
+            }
+            // Cancelled, or answered no
+            _ => {}
+        }
+        true
+    }
+
+    pub fn update(&mut self) {
+        self.state.stats.tick(self.state.animation_state, Instant::now());
+        self.overlays.update();
+        self.finish_writes();
+        self.update_tutorial();
+
+        // The animation waits for a step computed on a worker thread
+        if self.state.animation_state == AnimationState::Animating
+            && !self.state.paused
+            && self.curve_job.is_none()
+            && self.last_call.elapsed() > self.step_interval {
+            self.state.current_step = (self.state.current_step + 1) % self.state.max_steps;
+            self.last_call = Instant::now();
+            self.capture_pending = true;
+
+            // The animation starts over, with a new shape if this is a demo
+            if self.state.current_step == 0 {
+                if let Some(demo) = &mut self.demo {
+                    self.state.points = demo.next_shape(self.state.buffer_width, self.state.buffer_height);
+                }
+            }
+        }
+    }
+
+    /// Paints the canvas with the background, only where something was drawn since, see
+    /// [`Raster::clear`]
+    pub fn clear_buffer(&mut self) {
+        self.raster.clear(self.theme.background);
+    }
+
+    /// Resizes the buffer to match the window, if the user resized it.
+    /// This keeps one buffer pixel per screen pixel, instead of stretching the drawing
+    fn fit_buffer_to_window(&mut self) {
+        // Embedded canvases are resized by the host application instead
+        let Some(window) = &self.window else {
+            return;
+        };
+
+        let (width, height) = window.get_size();
+        self.resize_buffer(width, height);
+    }
+
+    /// Resizes the buffer, unless the size didn't change
+    fn resize_buffer(&mut self, width: usize, height: usize) {
+        // A minimized window reports no size, keep what we have until it comes back
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        if (width, height) != (self.state.buffer_width, self.state.buffer_height) {
+            self.state.buffer_width = width;
+            self.state.buffer_height = height;
+            self.raster.resize(width, height);
+        }
+    }
+
+    /// Limits how often the window is updated to the refresh rate of the monitor it is on,
+    /// so animations are as smooth as the monitor shows them without drawing frames it
+    /// never shows, and to [`BACKGROUND_REFRESH_RATE`] while another window has the focus.
+    /// Checked again whenever the window moves, as it may be on another monitor, and
+    /// whenever it gains or loses the focus
+    fn fit_update_rate(&mut self) {
+        let Some(window) = &mut self.window else {
+            return;
+        };
+        let (position, focused) = (window.get_position(), window.is_active());
+        if self.fitted_to == Some((position, focused)) {
+            return;
+        }
+        self.fitted_to = Some((position, focused));
+
+        #[cfg(target_os = "windows")]
+        let found = display::window_refresh_rate(window);
+        #[cfg(not(target_os = "windows"))]
+        let found = None;
+        let rate = self.refresh_rate_override
+            .or(found)
+            .or_else(|| {
+                let (width, height) = window.get_size();
+                let center = (position.0 + width as isize / 2, position.1 + height as isize / 2);
+                display::refresh_rate_at(&self.monitors, center)
+            })
+            .unwrap_or(DEFAULT_REFRESH_RATE);
+        let rate = if focused { rate } else { rate.min(BACKGROUND_REFRESH_RATE) };
+        if self.update_rate != Some(rate) {
+            self.update_rate = Some(rate);
+            window.limit_update_rate(Some(Duration::from_secs_f32(1.0 / rate)));
+        }
+    }
+
+    pub fn update_buffer(&mut self) {
+        if self.idle.take().is_some() {
+            // The update rate is fitted to the monitor again
+            self.update_rate = None;
+            self.fitted_to = None;
+        }
+        self.fit_update_rate();
+        self.last_shown = Instant::now();
+        if let Some(window) = &mut self.window {
+            window.update_with_buffer(
+                self.raster.pixels(),
+                self.state.buffer_width,
+                self.state.buffer_height,
+            ).unwrap();
+        }
+    }
+
+    /// Reset the window to it's initial startup state
+    pub fn reset(&mut self) {
+        self.last_call = Instant::now();
+        if !self.state.points.is_empty() || !self.state.polylines.is_empty() {
+            self.state.record_edit();
+        }
+        self.state.points.clear();
+        self.state.polylines.clear();
+        self.state.selected = None;
+        self.state.animation_state = AnimationState::Drawing;
+        self.state.current_step = 0;
+        self.state.paused = false;
+        self.state.file_path = None;
+        self.session_path = None;
+        self.state.metadata = Metadata::default();
+        self.state.dirty = false;
+        self.demo = None;
+        self.view = View::default();
+        self.overlays.clear();
+        self.clear_buffer();
+    }
+
+    //==================== Drawing Utilities =====================
+
+    /// Draws a line between the two points with the theme's stroke opacity and blend mode,
+    /// faded by the line's own opacity, see [`Raster::draw_line_aa`]
+    fn draw_line_aa(&mut self, x0: f32, y0: f32, x1: f32, y1: f32, color: u32, opacity: f32) {
+        let (opacity, mode) = (opacity * self.theme.stroke_opacity, self.theme.blend_mode);
+        self.raster.draw_line_aa(x0, y0, x1, y1, color, opacity, mode);
+    }
+
+    //=============== Text Drawing ========================
+
+    /// Draws a line of text with its top left corner at the given pixel
+    fn draw_text(&mut self, x: i32, y: i32, text: &str, color: u32, size: f32) {
+        self.draw_text_in(self.theme.text_font, x, y, text, color, size);
+    }
+
+    /// Draws a line of text in the instance of the font, see [`Self::draw_text`]
+    fn draw_text_in(&mut self, axes: FontAxes, x: i32, y: i32, text: &str, color: u32, size: f32) {
+        let width = self.state.buffer_width;
+        let height = self.state.buffer_height;
+
+        self.fonts.draw(text, size, axes, (x, y), |x, y, coverage| {
+            // Glyphs can start left of or above the canvas, e.g. centered text wider than
+            // it, and are cut off there
+            if (0..width as i32).contains(&x) && (0..height as i32).contains(&y) {
+                self.raster.draw_pixel_aa(x, y, color, coverage);
+            }
+        });
+    }
+
+    /// Draws styled text, with subscripts, bold runs, keys and headings, see
+    /// [`rich_text::parse`]
+    fn draw_rich_text(&mut self, x: i32, y: i32, markup: &str, color: u32, size: f32) {
+        let mut x = x as f32;
+        for run in rich_text::parse(markup) {
+            let (run_size, drop) = run.size_and_drop(size);
+            let advance = self.run_advance(&run, size);
+            let text_x = match run.style {
+                Style::Key => {
+                    self.draw_keycap(x + KEY_MARGIN, y, advance - 2.0 * KEY_MARGIN, run_size, color);
+                    x + KEY_MARGIN + KEY_PADDING
+                }
+                _ => x,
+            };
+            let (text_x, text_y) = (text_x.round() as i32, y + drop as i32);
+            self.draw_text_in(self.run_axes(run.style), text_x, text_y, &run.text, color, run_size);
+            x += advance;
+        }
+    }
+
+    /// Outlines a key's box, starting at x and as wide as given, around a line of text of
+    /// the given size drawn at y
+    fn draw_keycap(&mut self, x: f32, y: i32, width: f32, size: f32, color: u32) {
+        let (ascent, descent) = self.fonts.line_metrics(size);
+        let (left, right) = (x, x + width);
+        let (top, bottom) = (y as f32 - 1.0, y as f32 + ascent - descent + 1.0);
+        for (x0, y0, x1, y1) in [(left, top, right, top), (right, top, right, bottom), (right, bottom, left, bottom), (left, bottom, left, top)] {
+            self.raster.draw_line_aa(x0, y0, x1, y1, color, KEY_OUTLINE_OPACITY, BlendMode::Normal);
+        }
+    }
+
+    /// The instance of the font runs of the style are written in: titles in the theme's
+    /// title font, bold runs and keys in a heavier text font
+    fn run_axes(&self, style: Style) -> FontAxes {
+        match style {
+            Style::Regular => self.theme.text_font,
+            Style::Bold | Style::Key => self.theme.text_font.bolder(),
+            Style::Heading => self.theme.title_font,
+        }
+    }
+
+    /// How far the pen moves for the run, in styled text of the given size
+    fn run_advance(&self, run: &Run, size: f32) -> f32 {
+        let (run_size, _) = run.size_and_drop(size);
+        let advance = self.fonts.advance(&run.text, run_size, self.run_axes(run.style));
+        match run.style {
+            Style::Key => advance + 2.0 * (KEY_PADDING + KEY_MARGIN),
+            _ => advance,
+        }
+    }
+
+    /// How wide styled text is, see [`rich_text::parse`]
+    fn rich_text_width(&self, markup: &str, size: f32) -> f32 {
+        rich_text::parse(markup)
+            .iter()
+            .map(|run| self.run_advance(run, size))
+            .sum()
+    }
+
+    /// How far the pen moves for the text, trailing spaces included, which is where the
+    /// text after it starts
+    fn text_advance(&self, text: &str, size: f32) -> f32 {
+        self.fonts.advance(text, size, self.theme.text_font)
+    }
+
+    /// Where the text spans horizontally when drawn at x = 0: from the left edge of its
+    /// leftmost glyph, past the first glyph's side bearing, to the right edge of its rightmost
+    /// glyph or where the pen ends up, whichever is further, so that trailing spaces count
+    fn text_extents(&self, text: &str, size: f32) -> (f32, f32) {
+        let advance = self.text_advance(text, size);
+        self.fonts
+            .ink_extents(text, size, self.theme.text_font)
+            .map_or((0.0, advance), |(left, right)| (left, right.max(advance)))
+    }
+
+    /// How wide the text is, from the leftmost to the rightmost pixel it covers, trailing
+    /// spaces included
+    fn text_width(&self, text: &str, size: f32) -> f32 {
+        let (left, right) = self.text_extents(text, size);
+        right - left
+    }
+
+    /// Draws the text with its extents centered on the given x
+    fn draw_text_centered(&mut self, center_x: f32, y: i32, text: &str, color: u32, size: f32) {
+        let (left, right) = self.text_extents(text, size);
+        let x = center_x - (left + right) / 2.0;
+        self.draw_text(x.round() as i32, y, text, color, size);
+    }
+
+    /// Breaks the text into lines at most as wide as given, between words. A word wider
+    /// than that on its own gets a line to itself
+    fn wrap_text(&self, text: &str, size: f32, max_width: f32) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        for word in text.split(' ') {
+            match lines.last_mut() {
+                Some(line) if self.text_width(&format!("{} {}", line, word), size) <= max_width => {
+                    line.push(' ');
+                    line.push_str(word);
+                }
+                _ => lines.push(word.to_string()),
+            }
+        }
+        lines
+    }
+
+    /// Draws the toast centered at the bottom of the canvas, its message wrapped to fit the
+    /// window. On windows too small for it, the toast is cut off rather than laid out
+    /// outside of the canvas
+    fn draw_toast(&mut self) {
+        let Some(msg) = self.overlays.toast().map(str::to_string) else {
+            return;
+        };
+
+        let width = self.state.buffer_width;
+        let height = self.state.buffer_height;
 
-        window.limit_update_rate(Some(Duration::from_micros(16600)));
+        let max_text_width = width.saturating_sub(2 * (TOAST_MARGIN + TOAST_PADDING));
+        let lines = self.wrap_text(&msg, TOAST_FONT_SIZE, max_text_width as f32);
+        let text_width = lines.iter().map(|line| self.text_width(line, TOAST_FONT_SIZE)).fold(0.0, f32::max);
+        let toast_width = (text_width as usize + 2 * TOAST_PADDING).min(width);
+        let toast_height = (lines.len() * TOAST_LINE_HEIGHT + 2 * TOAST_PADDING).min(height);
+        let x_start = (width - toast_width) / 2;
+        let y_start = height.saturating_sub(toast_height + TOAST_MARGIN);
 
-        // Load font
-        let font_data = include_bytes!("../assets/Roboto-VariableFont_wdth_wght.ttf");
-        let font = Font::try_from_bytes(font_data as &[u8])
-            .expect("Error loading font");
+        // Draw toast background
+        for y in y_start..(y_start + toast_height) {
+            for x in x_start..(x_start + toast_width) {
+                self.raster.draw_pixel(x as i32, y as i32, TOAST_BG_COLOR);
+            }
+        }
 
-        Self {
-            window,
-            state: WindowState {
-                points: Vec::new(),
-                animation_state: AnimationState::Drawing,
-                current_step: 0,
-                buffer_width: width,
-                buffer_height: height,
-            },
-            buffer: vec![0; width * height],
-            toast: Toast::new(),
-            font,
-            last_call: Instant::now(),
+        // Draw toast text, a line at a time
+        let center_x = (x_start + toast_width / 2) as f32;
+        let text_y = y_start + TOAST_PADDING + (TOAST_LINE_HEIGHT - TOAST_FONT_SIZE as usize) / 2;
+        for (i, line) in lines.iter().enumerate() {
+            let y = (text_y + i * TOAST_LINE_HEIGHT) as i32;
+            self.draw_text_centered(center_x, y, line, TOAST_TEXT_COLOR, TOAST_FONT_SIZE);
         }
     }
 
-    /// Adds a point to be drawn in the window at the given coordinate
-    fn add_point(&mut self, x: f32, y: f32) {
-        let point = Point::new(x, y);
-        self.state.points.push(point);
-        // The toast will be shown if the user didn't have enough points for chaikin,
-        // but a new point was just added; maybe we already have enough points
-        self.toast.dismiss();
-        self.redraw();
+    /// The pixels the statistics panel covers in the top left corner, if it is shown
+    fn stats_panel(&self) -> Option<Rect> {
+        if !self.show_stats {
+            return None;
+        }
+        let lines = self.state.stats.lines();
+        let text_width = lines
+            .iter()
+            .map(|line| self.text_width(line, STATS_FONT_SIZE))
+            .fold(self.rich_text_width(STATS_TITLE, STATS_FONT_SIZE), f32::max);
+        let (width, height) = (self.state.buffer_width, self.state.buffer_height);
+        let panel_width = ((text_width + 20.0) as usize).min(width);
+        let panel_height = (STATS_LINE_HEIGHT * (lines.len() + 1) + 10).min(height);
+        Some(Rect { left: 10, top: 10, right: 10 + panel_width - 1, bottom: 10 + panel_height - 1 })
     }
 
-    /// Re-reads the state of the window and re-renders all the points,
-    /// lines, and the toast if active
-    pub fn redraw(&mut self) {
-        if self.state.animation_state == AnimationState::Drawing {
-            self.clear_buffer();
-            self.draw_lines();
-            self.draw_points();
-            self.draw_toast();
+    /// Draws the session's statistics in a panel in the top left corner, if they are shown
+    fn draw_stats(&mut self) {
+        let Some(panel) = self.stats_panel() else {
             return;
+        };
+
+        for y in panel.top..=panel.bottom {
+            for x in panel.left..=panel.right {
+                self.raster.draw_pixel(x as i32, y as i32, TOAST_BG_COLOR);
+            }
+        }
+        self.draw_rich_text(20, 13, STATS_TITLE, TOAST_TEXT_COLOR, STATS_FONT_SIZE);
+        for (i, line) in self.state.stats.lines().iter().enumerate() {
+            let y = 15 + ((i + 1) * STATS_LINE_HEIGHT) as i32;
+            self.draw_text(20, y, line, TOAST_TEXT_COLOR, STATS_FONT_SIZE);
         }
+    }
 
-        // We are animating
-        let paths = algorithm::ChaikinAlgorithm::new()
-            .get_step_points(&self.state.points, self.state.current_step);
+    /// What is drawn: the curve of the step shown once it is computed, or the control
+    /// polygon. Zoomed out, that is the step drawn in place of the current one
+    fn drawn_points(&self) -> &[Point] {
+        let points = Polyline::from_slice(&self.state.points);
+        match &self.curve {
+            Some(((computed, _), curve)) if self.state.animation_state == AnimationState::Animating && *computed == points => {
+                curve
+            }
+            _ => &self.state.points,
+        }
+    }
 
-        self.clear_buffer();
-        self.draw_lines_between(&paths);
-        self.draw_points();
+    /// The length of what is drawn, the segment that closes it included
+    fn drawn_length(&self) -> f32 {
+        let points = self.drawn_points();
+        geometry::polyline_length(points) + self.closing_length(points)
     }
 
-    pub fn handle_input(&mut self) -> bool {
-        if !self.window.is_open() || self.window.is_key_down(Key::Escape) {
-            return false;
+    /// The length of the segment from the last point back to the first, if the shape is
+    /// closed
+    fn closing_length(&self, points: &[Point]) -> f32 {
+        match (self.closed, points.first(), points.last()) {
+            (true, Some(first), Some(last)) => (first - last).norm(),
+            _ => 0.0,
         }
+    }
 
-        if (self.window.is_key_down(Key::LeftCtrl) || self.window.is_key_down(Key::RightCtrl)) &&
-            self.window.is_key_pressed(Key::R, KeyRepeat::No) {
-            self.reset();
+    /// What the canvas is doing, e.g. "Drawing: 4 points" or "Animating: step 3/7", with the
+    /// length drawn, and the area a closed shape encloses, the scheme it smooths with, and
+    /// after the name of the shape if it has one. While drawing, the length is only shown
+    /// in real-world units, if they are set; while animating, it shows how the length of
+    /// the steps converges
+    fn hud_text(&self) -> String {
+        let mode = match self.state.animation_state {
+            AnimationState::Drawing => match self.state.points.len() {
+                1 => "Drawing: 1 point".to_string(),
+                count => format!("Drawing: {} points", count),
+            },
+            AnimationState::Animating => {
+                let paused = if self.state.paused { " (paused)" } else { "" };
+                format!("Animating: step {}/{}{}", self.state.current_step + 1, self.state.max_steps, paused)
+            }
+        };
+        let animating = self.state.animation_state == AnimationState::Animating;
+        let mode = match self.units {
+            units if units.is_pixels() && !animating => mode,
+            units => format!("{} · {}", mode, units.format(self.drawn_length(), &self.numbers)),
+        };
+        let mode = match self.drawn_points() {
+            points if self.closed && points.len() >= 3 => {
+                format!("{} · {}", mode, self.units.format_area(geometry::area(points), &self.numbers))
+            }
+            _ => mode,
+        };
+        let mode = match self.scheme {
+            _ if self.bezier => format!("{} · Bézier", mode),
+            // Chaikin's scheme, of degree 2, is left at its name
+            Some(scheme @ Scheme::LaneRiesenfeld(degree)) => format!("{} · {} · degree {}", mode, scheme.title(), degree),
+            Some(scheme) => format!("{} · {}", mode, scheme.title()),
+            None => mode,
+        };
+        match self.state.metadata.name.as_str() {
+            "" => mode,
+            name => format!("{} · {}", name, mode),
         }
+    }
 
-        let delete_pressed = self.window.is_key_pressed(Key::Delete, KeyRepeat::No);
-        let mut mouse_clicked = false;
-        if self.state.animation_state == AnimationState::Drawing {
-            if let Some((x, y)) = self.window.get_mouse_pos(MouseMode::Discard) {
-                if self.window.get_mouse_down(MouseButton::Left) {
-                    let point = Point2::new(x, y);
-                    mouse_clicked = true;
-                    if !self.state.points.iter().any(|p| *p == point) {
-                        self.add_point(x, y);
-                    }
-                }
-            }
+    /// The pixels the HUD covers in the bottom left corner, if it is shown and there is room
+    /// for it
+    fn hud_panel(&self, text: &str) -> Option<Rect> {
+        if !self.hud {
+            return None;
         }
+        let (width, height) = (self.state.buffer_width, self.state.buffer_height);
+        let panel_width = ((self.text_width(text, HUD_FONT_SIZE) + 20.0) as usize).min(width);
+        // Tiny canvases have no room for it
+        if height < HUD_HEIGHT + 10 {
+            return None;
+        }
+        let top = height - HUD_HEIGHT - 10;
+        Some(Rect { left: 10, top, right: 10 + panel_width - 1, bottom: top + HUD_HEIGHT - 1 })
+    }
 
-        // Check if toast should be dismissed
-        self.check_toast_dismiss(mouse_clicked, delete_pressed);
+    /// Draws what the canvas is doing in the bottom left corner, every frame
+    fn draw_hud(&mut self) {
+        let text = self.hud_text();
+        let Some(panel) = self.hud_panel(&text) else {
+            return;
+        };
 
-        if self.window.is_key_pressed(Key::Enter, KeyRepeat::No) {
-            if self.state.points.len() < 2 {
-                self.toast.show("You did not select enough points");
-                self.draw_toast();
-            } else {
-                self.state.animation_state = AnimationState::Animating;
-                self.state.current_step = 0;
+        for y in panel.top..=panel.bottom {
+            for x in panel.left..=panel.right {
+                self.raster.draw_pixel(x as i32, y as i32, TOAST_BG_COLOR);
             }
         }
-
-        true
+        self.draw_text(20, panel.top as i32 + 5, &text, TOAST_TEXT_COLOR, HUD_FONT_SIZE);
     }
 
-    pub fn update(&mut self) {
-        if self.state.animation_state == AnimationState::Animating {
-            if self.last_call.elapsed() > Duration::from_secs(1) {
-                println!("animation step: {}", self.state.current_step + 1);
-                self.state.current_step = (self.state.current_step + 1) % MAX_STEPS;
-                self.last_call = Instant::now();
+    /// Draws how the scheme computes the new points in a panel in the top right corner, if
+    /// they are shown, with the scheme's current ratios. It goes below the statistics panel
+    /// on canvases too narrow for both side by side, and stops above the HUD
+    fn draw_formulas(&mut self) {
+        if !self.show_formulas {
+            return;
+        }
+        let formulas: Vec<String> = self.smoother.formulas().iter().map(|formula| self.numbers.localize(formula)).collect();
+        if formulas.is_empty() {
+            return;
+        }
+
+        let font_size = 16.0;
+        let line_height = 26;
+        let text_width = formulas
+            .iter()
+            .map(|formula| self.rich_text_width(formula, font_size))
+            .fold(0.0, f32::max);
+        let (width, height) = (self.state.buffer_width, self.state.buffer_height);
+        if width <= 10 {
+            return;
+        }
+        let panel_width = ((text_width + 20.0) as usize).min(width - 10);
+        let panel = Rect {
+            left: width - 10 - panel_width,
+            top: 40,
+            right: width - 11,
+            bottom: 40 + line_height * formulas.len() + 10 - 1,
+        };
+        let panels: Vec<Rect> = self.stats_panel().into_iter().chain(self.hud_panel(&self.hud_text())).collect();
+        let Some(mut panel) = fit_panel(panel, &panels, height) else {
+            return;
+        };
+        // The formulas that don't fit above the panels below are left out
+        let shown = (panel.height().saturating_sub(10) / line_height).min(formulas.len());
+        if shown == 0 {
+            return;
+        }
+        panel.bottom = panel.top + line_height * shown + 10 - 1;
+
+        for y in panel.top..=panel.bottom {
+            for x in panel.left..=panel.right {
+                self.raster.draw_pixel(x as i32, y as i32, TOAST_BG_COLOR);
             }
         }
+        for (i, formula) in formulas[..shown].iter().enumerate() {
+            let y = panel.top + 5 + i * line_height;
+            self.draw_rich_text(panel.left as i32 + 10, y as i32, formula, TOAST_TEXT_COLOR, font_size);
+        }
     }
 
-    pub fn clear_buffer(&mut self) {
-        self.buffer.fill(0);
-    }
+    fn draw_color_picker(&mut self) {
+        let Some(picker) = &self.color_picker else {
+            return;
+        };
 
-    pub fn update_buffer(&mut self) {
-        self.window.update_with_buffer(
-            &self.buffer,
-            self.state.buffer_width,
-            self.state.buffer_height,
-        ).unwrap();
-    }
+        let (width, height) = (self.state.buffer_width, self.state.buffer_height);
+        picker.draw(self.raster.pixels_mut(), width, height);
 
-    /// Reset the window to it's initial startup state
-    pub fn reset(&mut self) {
-        self.last_call = Instant::now();
-        self.toast = Toast::new();
-        self.state.points.clear();
-        self.state.animation_state = AnimationState::Drawing;
-        self.state.current_step = 0;
-        self.toast.dismiss();
-        self.clear_buffer();
+        let title = format!("{} - [Tab]: Next", picker.target.label());
+        let (left, top) = ColorPicker::origin(width);
+        self.draw_rich_text(left as i32 + 10, top as i32 + 10, &title, TOAST_TEXT_COLOR, 14.0);
     }
 
-    //==================== Drawing Utilities =====================
-
-    /// Draws the given color at the given pixel in the window buffer using linear alpha blending.
-    /// This is a common technique, that forms the basis for antialiasing techniques such as
-    /// Xiaolin Wu's line algorithm
-    /// It blends a new color (color) with an existing one in the buffer (bg) at pixel (x, y)
-    /// based on an alpha value (opacity).
-    fn draw_pixel_aa(&mut self, x: i32, y: i32, color: u32, alpha: f32) {
-        let width = self.state.buffer_width;
-        let height = self.state.buffer_height;
-        if x < 0 || x >= width as i32 || y < 0 || y >= height as i32 {
+    /// Draws the search box at the top of the canvas, with the matches listed under it
+    fn draw_search(&mut self) {
+        let Some(search) = &self.search else {
             return;
+        };
+        let query = format!("Find a shape: {}|", search.query);
+        let names: Vec<String> = search.matches().iter().map(|shape| shape.name.clone()).collect();
+        let selected = search.selected;
+        let hint = if names.is_empty() {
+            "No shape matches - [Escape]: Cancel"
+        } else {
+            "[Up] / [Down]: Select - [Enter]: Open - [Escape]: Cancel"
+        };
+
+        let (width, height) = (self.state.buffer_width, self.state.buffer_height);
+        let (font_size, line_height) = (16.0, 24);
+        let text_width = names
+            .iter()
+            .chain([&query])
+            .map(|text| self.text_width(text, font_size))
+            .fold(self.rich_text_width(hint, font_size), f32::max);
+        let box_width = ((text_width + 40.0) as usize).max(300).min(width);
+        let box_height = line_height * (names.len() + 2) + 20;
+        let x_start = (width - box_width) / 2;
+        let y_start = 20.min(height);
+
+        for y in y_start..(y_start + box_height).min(height) {
+            let row = (y - y_start).checked_sub(10 + line_height).map(|offset| offset / line_height);
+            let color = if row == Some(selected) && selected < names.len() { SEARCH_SELECTION_COLOR } else { MODAL_BG_COLOR };
+            for x in x_start..x_start + box_width {
+                self.raster.draw_pixel(x as i32, y as i32, color);
+            }
         }
 
-        let index = y as usize * width + x as usize;
-        let bg = self.buffer[index];
-
-        // Extract color components
-        let r1 = ((color >> 16) & 0xFF) as f32;
-        let g1 = ((color >> 8) & 0xFF) as f32;
-        let b1 = (color & 0xFF) as f32;
+        let (x, mut y) = (x_start as i32 + 20, y_start as i32 + 14);
+        self.draw_text(x, y, &query, TOAST_TEXT_COLOR, font_size);
+        for name in &names {
+            y += line_height as i32;
+            self.draw_text(x, y, name, TOAST_TEXT_COLOR, font_size);
+        }
+        y += line_height as i32;
+        self.draw_rich_text(x, y, hint, TOAST_TEXT_COLOR, font_size);
+    }
 
-        let r2 = ((bg >> 16) & 0xFF) as f32;
-        let g2 = ((bg >> 8) & 0xFF) as f32;
-        let b2 = (bg & 0xFF) as f32;
+    /// Dims the canvas and draws the modal's box in the middle of it
+    fn draw_modal(&mut self) {
+        let Some((_, modal)) = &self.modal else {
+            return;
+        };
+        let title = modal.title.clone();
+        let hint = modal.hint();
+        let field = match &modal.kind {
+            ModalKind::TextInput(text) => Some(format!("{}|", text)),
+            ModalKind::Choice(_) => None,
+        };
+
+        for pixel in self.raster.pixels_mut() {
+            *pixel = BlendMode::Normal.blend(0, *pixel, 1.0 - MODAL_DIM);
+        }
 
-        // Blend colors
-        let r = (r1 * alpha + r2 * (1.0 - alpha)) as u32;
-        let g = (g1 * alpha + g2 * (1.0 - alpha)) as u32;
-        let b = (b1 * alpha + b2 * (1.0 - alpha)) as u32;
+        let (width, height) = (self.state.buffer_width, self.state.buffer_height);
+        let font_size = 16.0;
+        let lines = if field.is_some() { 3 } else { 2 };
+        let text_width = [&title]
+            .into_iter()
+            .chain(field.as_ref())
+            .map(|text| self.text_width(text, font_size))
+            .fold(self.rich_text_width(&hint, font_size), f32::max);
+        let box_width = ((text_width + 40.0) as usize).max(300).min(width);
+        let box_height = 30 * lines + 20;
+        let x_start = (width - box_width) / 2;
+        let y_start = height.saturating_sub(box_height) / 2;
+
+        for y in y_start..(y_start + box_height).min(height) {
+            for x in x_start..x_start + box_width {
+                self.raster.draw_pixel(x as i32, y as i32, MODAL_BG_COLOR);
+            }
+        }
 
-        self.buffer[index] = (r << 16) | (g << 8) | b;
+        let (x, mut y) = (x_start as i32 + 20, y_start as i32 + 15);
+        self.draw_text(x, y, &title, TOAST_TEXT_COLOR, font_size);
+        if let Some(field) = field {
+            y += 30;
+            self.draw_text(x, y, &field, TOAST_TEXT_COLOR, font_size);
+        }
+        y += 30;
+        self.draw_rich_text(x, y, &hint, TOAST_TEXT_COLOR, font_size);
     }
 
-    /// Draw a given pixel with the target color, without antialiasing
-    fn draw_pixel(&mut self, x: i32, y: i32, color: u32) {
-        let width = self.state.buffer_width;
-        let height = self.state.buffer_height;
-
-        if x >= 0 && x < width as i32 && y >= 0 && y < height as i32 {
-            self.buffer[y as usize * width + x as usize] = color;
+    fn check_toast_dismiss(&mut self, mouse_clicked: bool) {
+        if self.overlays.toast().is_some() && mouse_clicked {
+            self.overlays.dismiss_toast();
+            self.redraw();
         }
     }
 
-    /// Draw a circle centered at the given coordinates, and radius, with the given color
-    /// with antialiasing enabled
-    fn draw_circle_aa(&mut self, center_x: f32, center_y: f32, radius: f32, color: u32) {
-        let width = self.state.buffer_width;
-        let height = self.state.buffer_height;
+    //=============== Window State Drawing ========================
+
+    /// Draws the overlays, except for the toast which is drawn over everything else
+    fn draw_overlays(&mut self) {
+        let overlays: Vec<(OverlayKind, f32)> = self.overlays
+            .iter()
+            .map(|overlay| (overlay.kind.clone(), overlay.progress()))
+            .collect();
 
-        let x0 = (center_x - radius - 1.0).max(0.0) as i32;
-        let y0 = (center_y - radius - 1.0).max(0.0) as i32;
-        let x1 = (center_x + radius + 1.0).min(width as f32 - 1.0) as i32;
-        let y1 = (center_y + radius + 1.0).min(height as f32 - 1.0) as i32;
-
-        for y in y0..=y1 {
-            for x in x0..=x1 {
-                let dx = x as f32 - center_x;
-                let dy = y as f32 - center_y;
-                let distance = (dx * dx + dy * dy).sqrt();
-
-                if distance <= radius + 1.0 {
-                    let alpha = if distance <= radius - 1.0 {
-                        1.0
-                    } else {
-                        let t = distance - (radius - 1.0);
-                        1.0 - t.min(1.0)
-                    };
-
-                    self.draw_pixel_aa(x, y, color, alpha);
+        for (kind, progress) in overlays {
+            let fade = 1.0 - progress;
+            match kind {
+                OverlayKind::Toast(_) => {}
+                OverlayKind::Halo(center) => {
+                    // Shrinks from four times the point's size onto it, fading out
+                    let radius = self.point_radius * (4.0 - 3.0 * progress);
+                    self.raster.draw_ring_aa(center.x, center.y, radius, self.theme.point, fade);
+                }
+                OverlayKind::Flash(center) => {
+                    self.raster.draw_ring_aa(center.x, center.y, self.point_radius * 2.0, TOAST_TEXT_COLOR, fade);
+                    self.raster.draw_ring_aa(center.x, center.y, self.point_radius * 3.0, TOAST_TEXT_COLOR, fade * 0.5);
+                }
+                OverlayKind::Arrow { from, to } => self.raster.draw_arrow(from, to, TOAST_TEXT_COLOR, fade),
+                OverlayKind::Label { position, text } => {
+                    self.draw_rich_text(position.x as i32, position.y as i32, &text, TOAST_TEXT_COLOR, 14.0);
                 }
             }
         }
     }
 
-    /// Draws a line between the two points, with the target color using
-    /// Xiaolin Wu's line algorithm, with antialiasing enabled
-    fn draw_line_aa(&mut self, mut x0: f32, mut y0: f32, mut x1: f32, mut y1: f32, color: u32) {
-        // Determine if the line is steep
-        let steep = (y1 - y0).abs() > (x1 - x0).abs();
-
-        if steep {
-            std::mem::swap(&mut x0, &mut y0);
-            std::mem::swap(&mut x1, &mut y1);
+    /// Draws a spinner in the top right corner while a step is computed on a worker thread,
+    /// with a bar of its progress to the left of it
+    fn draw_spinner(&mut self) {
+        let Some((_, job)) = &self.curve_job else {
+            return;
+        };
+        let ticks = job.started.elapsed().as_millis() / SPINNER_TICK.as_millis();
+        let lead = ticks as usize % SPINNER_DOTS;
+        let progress = job.progress();
+        let (center_x, center_y) = (self.state.buffer_width as f32 - 3.0 * SPINNER_RADIUS, 3.0 * SPINNER_RADIUS);
+
+        for dot in 0..SPINNER_DOTS {
+            let angle = dot as f32 / SPINNER_DOTS as f32 * std::f32::consts::TAU;
+            // The dots fade out behind the lead one
+            let behind = (lead + SPINNER_DOTS - dot) % SPINNER_DOTS;
+            let opacity = 1.0 - behind as f32 / SPINNER_DOTS as f32;
+            let (x, y) = (center_x + SPINNER_RADIUS * angle.sin(), center_y - SPINNER_RADIUS * angle.cos());
+            self.raster.draw_ring_aa(x, y, 1.0, TOAST_TEXT_COLOR, opacity);
         }
 
-        // Make sure x0 <= x1
-        if x0 > x1 {
-            std::mem::swap(&mut x0, &mut x1);
-            std::mem::swap(&mut y0, &mut y1);
+        if let Some(progress) = progress {
+            let right = (center_x - 2.0 * SPINNER_RADIUS) as i32;
+            let (left, top) = (right - PROGRESS_BAR_SIZE.0, center_y as i32 - PROGRESS_BAR_SIZE.1 / 2);
+            let filled = left + (PROGRESS_BAR_SIZE.0 as f32 * progress) as i32;
+            for y in top..top + PROGRESS_BAR_SIZE.1 {
+                for x in left..right {
+                    let opacity = if x < filled { 1.0 } else { 0.3 };
+                    self.raster.draw_pixel_aa(x, y, TOAST_TEXT_COLOR, opacity);
+                }
+            }
         }
+    }
 
-        let dx = x1 - x0;
-        let dy = y1 - y0;
-        let gradient = if dx.abs() < 1e-6 { 1.0 } else { dy / dx };
+    /// Draws all points defined in the window
+    pub fn draw_points(&mut self) {
+        self.draw_point_glyphs(&Polyline::from_slice(&self.state.points));
+    }
 
-        // Handle first endpoint
-        let xend = x0.round();
-        let yend = y0 + gradient * (xend - x0);
-        let xgap = 1.0 - (x0 + 0.5 - xend).abs();
-        let xpxl1 = xend as i32;
-        let ypxl1 = yend.floor() as i32;
+    /// Draws a ring around the selected point, which the arrow keys nudge
+    fn draw_selection(&mut self) {
+        let Some(index) = self.selected_point() else {
+            return;
+        };
+        let center = self.view.screen_point(self.state.points[index]);
+        self.raster.draw_ring_aa(center.x, center.y, self.point_radius + SELECTION_RING_GAP, self.theme.point, 1.0);
+    }
 
-        if steep {
-            self.draw_pixel_aa(ypxl1, xpxl1, color, (1.0 - (yend - yend.floor())) * xgap);
-            self.draw_pixel_aa(ypxl1 + 1, xpxl1, color, (yend - yend.floor()) * xgap);
+    /// Draws a glyph at each of the given points. Of many points, only those near the canvas
+    /// are looked at
+    fn draw_point_glyphs(&mut self, points: &[Point]) {
+        let (color, glyph) = (self.theme.point, self.theme.point_glyph);
+        let parts: Vec<&[Point]> = if points.len() >= cull::CULL_MIN_POINTS {
+            let size = (self.state.buffer_width, self.state.buffer_height);
+            let area = cull::canvas_bounds(&self.view, size, self.point_radius + 1.0);
+            self.path_bvhs.get(points, false).segments_in(&area).into_iter().map(|run| &points[run.start..=run.end]).collect()
         } else {
-            self.draw_pixel_aa(xpxl1, ypxl1, color, (1.0 - (yend - yend.floor())) * xgap);
-            self.draw_pixel_aa(xpxl1, ypxl1 + 1, color, (yend - yend.floor()) * xgap);
+            vec![points]
+        };
+        for point in parts.into_iter().flatten() {
+            let point = self.view.screen_point(*point);
+            self.raster.draw_glyph_aa(point.x, point.y, self.point_radius, color, glyph);
         }
+    }
 
-        let mut intery = yend + gradient;
-
-        // Handle second endpoint
-        let xend = x1.round();
-        let yend = y1 + gradient * (xend - x1);
-        let xgap = (x1 + 0.5 - xend).abs();
-        let xpxl2 = xend as i32;
-        let ypxl2 = yend.floor() as i32;
+    /// Labels each control point with the interior angle of the control polygon there, if
+    /// the angles are shown. The label is on the outer side of the corner, off the lines
+    fn draw_angles(&mut self) {
+        if !self.show_angles {
+            return;
+        }
 
-        if steep {
-            self.draw_pixel_aa(ypxl2, xpxl2, color, (1.0 - (yend - yend.floor())) * xgap);
-            self.draw_pixel_aa(ypxl2 + 1, xpxl2, color, (yend - yend.floor()) * xgap);
-        } else {
-            self.draw_pixel_aa(xpxl2, ypxl2, color, (1.0 - (yend - yend.floor())) * xgap);
-            self.draw_pixel_aa(xpxl2, ypxl2 + 1, color, (yend - yend.floor()) * xgap);
+        let font_size = 13.0;
+        let points = Polyline::from_slice(&self.state.points);
+        let angles = crate::geometry::vertex_angles(&points, self.closed);
+        let n = points.len();
+        for (i, angle) in angles.into_iter().enumerate() {
+            let Some(angle) = angle else {
+                continue;
+            };
+            let [previous, vertex, next] = [points[(i + n - 1) % n], points[i], points[(i + 1) % n]]
+                .map(|point| self.view.screen_point(point));
+            // Away from both segments, or across a straight angle
+            let inward = (previous - vertex).normalize() + (next - vertex).normalize();
+            let outward = if inward.norm() > 1e-3 {
+                -inward.normalize()
+            } else {
+                let along = (next - previous).normalize();
+                nalgebra::Vector2::new(along.y, -along.x)
+            };
+            let label = format!("{}°", self.numbers.format(angle, 0));
+            let center = vertex + outward * ANGLE_LABEL_DISTANCE;
+            let y = center.y - font_size / 2.0;
+            self.draw_text_centered(center.x, y as i32, &label, TOAST_TEXT_COLOR, font_size);
         }
+    }
 
-        // Main loop
-        if steep {
-            for x in (xpxl1 + 1)..xpxl2 {
-                self.draw_pixel_aa(intery.floor() as i32, x, color, 1.0 - (intery - intery.floor()));
-                self.draw_pixel_aa(intery.floor() as i32 + 1, x, color, intery - intery.floor());
-                intery += gradient;
-            }
-        } else {
-            for x in (xpxl1 + 1)..xpxl2 {
-                self.draw_pixel_aa(x, intery.floor() as i32, color, 1.0 - (intery - intery.floor()));
-                self.draw_pixel_aa(x, intery.floor() as i32 + 1, color, intery - intery.floor());
-                intery += gradient;
+    /// The coordinates of the point, e.g. "(120, 48)", or "(120.25, 47.8)" in full
+    fn coordinates_text(&self, point: Point) -> String {
+        let coordinate = |value: f32| {
+            if self.precise_coordinates {
+                self.numbers.localize(&value.to_string())
+            } else {
+                self.numbers.format(value, 0)
             }
-        }
+        };
+        // A decimal comma would be mistaken for the separator of the coordinates
+        let separator = if self.numbers.decimal_separator == ',' { "; " } else { ", " };
+        format!("({}{}{})", coordinate(point.x), separator, coordinate(point.y))
     }
 
-    //=============== Text Drawing ========================
+    /// Draws the coordinates of the point being dragged, or else of the point under the
+    /// mouse, next to it
+    fn draw_coordinates(&mut self) {
+        if !self.focus.canvas_has_mouse() {
+            return;
+        }
+        let hovered = self.mouse.position().and_then(|(x, y)| self.point_at(x, y));
+        let Some(point) = self.dragging.or(hovered).and_then(|index| self.state.points.get(index).copied()) else {
+            return;
+        };
 
-    // Draw text using rusttype
-    fn draw_text(&mut self, x: i32, y: i32, text: &str, color: u32, size: f32) {
-        let scale = Scale::uniform(size);
-        let v_metrics = self.font.v_metrics(scale);
-        let offset = point(x as f32, y as f32 + v_metrics.ascent);
+        let font_size = 13.0;
+        let label = self.coordinates_text(point);
+        let at = self.view.screen_point(point);
+        let offset = self.point_radius + 4.0;
+        self.draw_text((at.x + offset) as i32, (at.y - offset - font_size) as i32, &label, TOAST_TEXT_COLOR, font_size);
+    }
 
-        // Layout the glyphs in a line with 1 pixel padding
-        let glyphs: Vec<PositionedGlyph> = self.font
-            .layout(text, scale, offset)
-            .collect();
+    /// Draws lines between all points defined in the window
+    fn draw_lines(&mut self) {
+        self.draw_lines_between(&Polyline::from_slice(&self.state.points));
+    }
 
-        let width = self.state.buffer_width;
-        let height = self.state.buffer_height;
+    /// Utility function to draw lines between given points in the window. While animating,
+    /// they are in the color of the step shown, see [`Self::step_color`]
+    fn draw_lines_between(&mut self, points: &[Point]) {
+        let color = self.lines_color();
+        self.draw_path(points, self.closed, color, 1.0);
+    }
 
-        // Draw the glyphs
-        for glyph in glyphs {
-            if let Some(bounding_box) = glyph.pixel_bounding_box() {
-                glyph.draw(|rx, ry, v| {
-                    let x = rx + bounding_box.min.x as u32;
-                    let y = ry + bounding_box.min.y as u32;
+    /// Draws the curve of the step like [`Self::draw_lines_between`]. Long curves are only
+    /// drawn where they are on the canvas, found through the hierarchy of their segments
+    /// kept for the step
+    fn draw_curve(&mut self, key: &CurveKey, curve: &[Point]) {
+        if curve.len() < cull::CULL_MIN_POINTS {
+            return self.draw_lines_between(curve);
+        }
+        let area = self.canvas_area();
+        let runs = self.curve_bvhs.get(key, curve, self.closed).segments_in(&area);
+        let color = self.lines_color();
+        self.draw_segments(curve, &runs, color, 1.0);
+    }
 
-                    if x < width as u32 && y < height as u32 {
-                        // Convert alpha value to 0-1 range
-                        let alpha = v;
+    /// The color of the lines: while animating, that of the step shown, see
+    /// [`Self::step_color`]
+    fn lines_color(&self) -> u32 {
+        match self.state.animation_state {
+            AnimationState::Animating => {
+                self.step_color(self.state.current_step as f32 + self.step_progress().unwrap_or(0.0))
+            }
+            AnimationState::Drawing => self.theme.line,
+        }
+    }
 
-                        let pixel_x = x as i32;
-                        let pixel_y = y as i32;
+    /// The color of the step of the animation, which can be part of the way to the next one:
+    /// along the gradient if there is one, the line color otherwise
+    fn step_color(&self, step: f32) -> u32 {
+        match self.gradient {
+            Some(gradient) => gradient.step(step, self.state.max_steps),
+            None => self.theme.line,
+        }
+    }
 
-                        self.draw_pixel_aa(pixel_x, pixel_y, color, alpha);
-                    }
-                });
+    /// Draws the steps before the one shown under it, each one fainter than the one after it,
+    /// so that the curve can be seen converging in a single frame. Steps too heavy to compute
+    /// on this thread are left out
+    fn draw_onion_skin(&mut self) {
+        if !self.onion_skin {
+            return;
+        }
+        // Put back once drawn, since drawing borrows the whole window
+        let (mut steps, points) = (std::mem::take(&mut self.steps), std::mem::take(&mut self.state.points));
+        let smoother = Arc::clone(&self.smoother);
+        let current = self.state.current_step;
+        for step in 0..current {
+            if step_size(points.len(), step) >= HEAVY_STEP_POINTS {
+                break;
             }
+            let opacity = ONION_SKIN_OPACITY.powi((current - step) as i32);
+            let color = self.step_color(step as f32);
+            self.draw_path(steps.get(&*smoother, &points, step), self.closed, color, opacity);
         }
+        self.steps = steps;
+        self.state.points = points;
     }
 
-    // Text width calculation for centering
-    fn text_width(&self, text: &str, size: f32) -> f32 {
-        let scale = Scale::uniform(size);
-        let v_metrics = self.font.v_metrics(scale);
-        let offset = point(0.0, v_metrics.ascent);
+    /// Draws lines between the given points, through the view, in the line color, with the
+    /// given opacity, back to the first one if the shape is closed
+    fn draw_polyline(&mut self, points: &[Point], opacity: f32) {
+        self.draw_path(points, self.closed, self.theme.line, opacity);
+    }
 
-        let glyphs: Vec<PositionedGlyph> = self.font
-            .layout(text, scale, offset)
-            .collect();
+    /// Like [`Self::draw_polyline`], in any color, closed or not whatever the shape is. The
+    /// lines are as wide as the theme's strokes
+    fn draw_path(&mut self, points: &[Point], closed: bool, color: u32, opacity: f32) {
+        if points.len() >= cull::CULL_MIN_POINTS {
+            // Long paths, e.g. imported traces, are only drawn where they are on the canvas
+            let area = self.canvas_area();
+            let runs = self.path_bvhs.get(points, closed).segments_in(&area);
+            return self.draw_segments(points, &runs, color, opacity);
+        }
+        let view = self.view;
+        let screen: Vec<Point> = points.iter().map(|&point| view.screen_point(point)).collect();
+        let (opacity, mode) = (opacity * self.theme.stroke_opacity, self.theme.blend_mode);
+        self.raster.draw_polyline_aa(&screen, closed, self.theme.stroke_width, color, opacity, mode);
+    }
 
-        if let Some(last_glyph) = glyphs.last() {
-            if let Some(bounding_box) = last_glyph.pixel_bounding_box() {
-                return bounding_box.max.x as f32;
-            }
+    /// Draws the runs of the path's segments, each one on its own, see [`Bvh::segments_in`].
+    /// The segment past the last point goes back to the first
+    fn draw_segments(&mut self, points: &[Point], runs: &[std::ops::Range<usize>], color: u32, opacity: f32) {
+        let view = self.view;
+        let (opacity, mode) = (opacity * self.theme.stroke_opacity, self.theme.blend_mode);
+        for run in runs {
+            let screen: Vec<Point> = (run.start..=run.end)
+                .map(|i| view.screen_point(points[i % points.len()]))
+                .collect();
+            self.raster.draw_polyline_aa(&screen, false, self.theme.stroke_width, color, opacity, mode);
         }
+    }
 
-        0.0
+    /// The points of the canvas, with a margin for the width of the lines drawn on it
+    fn canvas_area(&self) -> geometry::Bounds {
+        let size = (self.state.buffer_width, self.state.buffer_height);
+        cull::canvas_bounds(&self.view, size, self.theme.stroke_width / 2.0 + 1.0)
     }
 
-    fn draw_toast(&mut self) {
-        if !self.toast.is_showing() {
+    /// Draws the curve the steps of the points converge to dashed, behind them while
+    /// animating, if it is shown and the scheme knows it
+    fn draw_limit(&mut self) {
+        if !self.limit || self.bezier {
+            return;
+        }
+        let samples = limit_samples(self.view.scale, self.state.points.len());
+        let key = (Polyline::from_slice(&self.state.points), samples);
+        if !matches!(&self.limit_curve, Some((computed, _)) if *computed == key) {
+            let curve = self.smoother.limit_curve(&key.0, samples).map(|curve| LimitCurve::new(curve, self.closed));
+            self.limit_curve = Some((key, curve));
+        }
+        let Some((_, Some(LimitCurve { points, lengths }))) = &self.limit_curve else {
             return;
+        };
+        if points.len() < 2 {
+            return;
+        }
+        // Long curves are only dashed where they are on the canvas
+        let runs = if points.len() >= cull::CULL_MIN_POINTS {
+            let area = self.canvas_area();
+            self.path_bvhs.get(points, self.closed).segments_in(&area)
+        } else {
+            std::iter::once(0..lengths.len() - 1).collect()
+        };
+        let view = self.view;
+        let (opacity, mode) = (LIMIT_OPACITY * self.theme.stroke_opacity, self.theme.blend_mode);
+        let (dash, gap) = LIMIT_DASHES;
+        for run in runs {
+            let screen: Vec<Point> = (run.start..=run.end).map(|i| view.screen_point(points[i % points.len()])).collect();
+            // In step with the dashes of the rest of the curve
+            for dash in geometry::dashes_from(&screen, dash, gap, lengths[run.start] * view.scale) {
+                self.raster.draw_polyline_aa(&dash, false, self.theme.stroke_width, self.theme.line, opacity, mode);
+            }
         }
+    }
 
-        let width = self.state.buffer_width;
-        let height = self.state.buffer_height;
+    /// Faintly draws the control polygons under the curves while animating, if enabled, to
+    /// show how each curve relates to its cage. Bézier curves draw theirs with the rest of
+    /// De Casteljau's construction instead
+    fn draw_control_polygons(&mut self) {
+        if !self.control_polygon || self.bezier {
+            return;
+        }
+        let polygons: Vec<Polyline> = self.state.polylines
+            .iter()
+            .map(|polyline| Polyline::from_slice(polyline))
+            .chain([Polyline::from_slice(&self.state.points)])
+            .collect();
+        for polygon in &polygons {
+            self.draw_path(polygon, self.closed, self.theme.polygon, CONTROL_POLYGON_OPACITY);
+        }
+    }
 
-        let msg = &self.toast.message.clone();
-        let font_size = 16.0;
-        let text_width = self.text_width(msg, font_size);
-        let toast_width = (text_width + 20.0) as usize;
-        let toast_height = 40;
-        let x_start = (width - toast_width) / 2;
-        let y_start = height - toast_height - 20;
+    /// How far the Bézier curve is traced, from 0 at the first step to 1 at the last. The
+    /// steps are evenly spaced, and the curve moves on between them like the subdivision
+    /// curves morph, see [`Self::step_progress`]
+    fn bezier_parameter(&self) -> f32 {
+        let (step, steps) = (self.state.current_step, self.state.max_steps);
+        if steps < 2 {
+            return 1.0;
+        }
+        let progress = self.step_progress().unwrap_or(0.0);
+        ((step as f32 + progress) / (steps - 1) as f32).min(1.0)
+    }
 
-        // Draw toast background
-        for y in y_start..(y_start + toast_height) {
-            for x in x_start..(x_start + toast_width) {
-                if x < width && y < height {
-                    self.draw_pixel(x as i32, y as i32, TOAST_BG_COLOR);
-                }
-            }
+    /// Draws De Casteljau's construction of the Bézier curve of the points as far as the
+    /// animation is: the polygons cut from the control polygon faintly, the curve traced
+    /// so far, and a ring around the point it is traced to
+    fn draw_bezier(&mut self) {
+        let t = self.bezier_parameter();
+        let points = Polyline::from_slice(&self.state.points);
+        if !matches!(&self.bezier_curve, Some((computed, _)) if *computed == points) {
+            let samples = Bezier::new(&points).sample(BEZIER_SAMPLES);
+            self.bezier_curve = Some((points.clone(), samples));
+        }
+        let key = (points, t.to_bits());
+        if !matches!(&self.bezier_construction, Some((computed, _)) if *computed == key) {
+            let construction = Bezier::new(&key.0).construction(t);
+            self.bezier_construction = Some((key, construction));
         }
+        // Put back once drawn, since drawing borrows the whole window
+        let cached = (self.bezier_curve.take(), self.bezier_construction.take());
+        let (Some((points, samples)), Some((key, construction))) = cached else {
+            return;
+        };
 
-        // Draw toast text
-        let text_x = x_start as i32 + 10;
-        let text_y = y_start as i32 + ((toast_height - font_size as usize) / 2) as i32;
-        self.draw_text(text_x, text_y, msg, TOAST_TEXT_COLOR, font_size);
+        if let Some(&[tip]) = construction.last().map(Vec::as_slice) {
+            for polygon in &construction[..construction.len() - 1] {
+                self.draw_path(polygon, false, self.theme.line, BEZIER_CONSTRUCTION_OPACITY);
+            }
+            let traced = ((samples.len() - 1) as f32 * t) as usize + 1;
+            let mut curve = samples[..traced].to_vec();
+            if curve.last() != Some(&tip) {
+                curve.push(tip);
+            }
+            self.draw_path(&curve, false, self.theme.line, 1.0);
+            let tip = self.view.screen_point(tip);
+            self.raster.draw_ring_aa(tip.x, tip.y, self.point_radius, TOAST_TEXT_COLOR, 1.0);
+        }
+        self.bezier_curve = Some((points, samples));
+        self.bezier_construction = Some((key, construction));
     }
 
-    fn check_toast_dismiss(&mut self, mouse_clicked: bool, delete_pressed: bool) {
-        if self.toast.is_showing() && (mouse_clicked || delete_pressed) {
-            self.toast.dismiss();
-            self.redraw();
+    /// Draws the polylines finished before the one being drawn: their control polygons while
+    /// drawing, and their curves at the current step while animating
+    fn draw_polylines(&mut self) {
+        if self.state.polylines.is_empty() {
+            return;
+        }
+        // Put back once drawn, since drawing borrows the whole window
+        let polylines = std::mem::take(&mut self.state.polylines);
+
+        if self.state.animation_state == AnimationState::Drawing {
+            for polyline in &polylines {
+                self.draw_polyline(polyline, 1.0);
+            }
+        } else {
+            let mut cache = std::mem::take(&mut self.polyline_curves);
+            let progress = self.step_progress();
+            let tween = self.tween();
+            for (_, curve, next) in cache.get(self.smoother.as_ref(), &polylines, self.state.current_step) {
+                match progress {
+                    Some(t) => self.draw_lines_between(&tween(curve, next, t, self.closed)),
+                    None => self.draw_lines_between(curve),
+                }
+            }
+            self.polyline_curves = cache;
+        }
+        for polyline in &polylines {
+            self.draw_point_glyphs(polyline);
         }
+        self.state.polylines = polylines;
     }
 
-    //=============== Window State Drawing ========================
+    /// Faintly draws the curve to match, during a quiz
+    fn draw_quiz_target(&mut self) {
+        let Some(quiz) = self.quiz.take() else {
+            return;
+        };
+        self.draw_polyline(&quiz.target, QUIZ_TARGET_OPACITY);
+        self.quiz = Some(quiz);
+    }
 
-    /// Draws all points defined in the window
-    pub fn draw_points(&mut self) {
-        for point in &self.state.points.clone() {
-            self.draw_circle_aa(point.x, point.y, POINT_RADIUS, POINT_COLOR);
+    /// Faintly draws the grid's lines across the canvas, if it is shown
+    fn draw_grid(&mut self) {
+        let (width, height) = (self.state.buffer_width as f32, self.state.buffer_height as f32);
+        let (xs, ys) = self.grid.lines(&self.view, (self.state.buffer_width, self.state.buffer_height));
+        for x in xs {
+            self.draw_line_aa(x, 0.0, x, height, self.theme.line, GRID_OPACITY);
+        }
+        for y in ys {
+            self.draw_line_aa(0.0, y, width, y, self.theme.line, GRID_OPACITY);
         }
     }
 
-    /// Draws lines between all points defined in the window
-    fn draw_lines(&mut self) {
-        self.draw_lines_between(&self.state.points.clone());
+    /// Faintly draws the curve as it will be at the end of the animation, if enabled, so that
+    /// the user can design toward the final shape
+    fn draw_ghost(&mut self) {
+        if !self.ghost {
+            return;
+        }
+        let key = (Polyline::from_slice(&self.state.points), self.state.max_steps - 1);
+        let (smoother, pool) = (&self.smoother, &mut self.pool);
+        let curve = self.ghost_curve
+            .get(key, Instant::now(), |(points, step)| smoother.get_step_points_pooled(points, *step, pool));
+
+        // Copied into a pooled buffer, since drawing borrows the whole window
+        let mut ghost = self.pool.take();
+        ghost.extend_from_slice(curve);
+        self.draw_polyline(&ghost, GHOST_OPACITY);
+        self.pool.give(ghost);
     }
 
-    /// Utility function to draw lines between given points in the window
-    fn draw_lines_between(&mut self, points: &[Point]) {
-        for i in 1..points.len() {
-            let p1 = points[i - 1];
-            let p2 = points[i];
-            self.draw_line_aa(p1.x, p1.y, p2.x, p2.y, LINE_COLOR);
+    /// Faintly draws the curve one step ahead, as it would be with a point placed under the
+    /// cursor, so that the effect of a click can be seen before making it
+    fn draw_hover_preview(&mut self) {
+        if !self.focus.canvas_has_mouse() || self.mouse.is_down(MouseButton::Left) {
+            return;
         }
+        let Some((x, y)) = self.mouse.position() else {
+            return;
+        };
+        if self.state.points.is_empty() {
+            return;
+        }
+        let cursor = self.view.point_at_pixel(x, y);
+        let (x, y) = (cursor.x, cursor.y);
+
+        let (smoother, pool) = (&self.smoother, &mut self.pool);
+        let curve = self.hover_curve
+            .get((Polyline::from_slice(&self.state.points), (x, y)), Instant::now(), |(points, (x, y))| {
+                let mut with_cursor = points.clone();
+                with_cursor.push(Point2::new(*x, *y));
+                let mut preview = pool.take();
+                smoother.calculate_step_into(&with_cursor, &mut preview);
+                preview
+            });
+
+        let mut preview = self.pool.take();
+        preview.extend_from_slice(curve);
+        self.draw_polyline(&preview, HOVER_PREVIEW_OPACITY);
+        self.pool.give(preview);
     }
 }
 
+/// The time as "YYYYMMDD-HHMMSS", in UTC, which sorts in chronological order
+fn timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+    let (days, seconds) = (seconds / 86_400, seconds % 86_400);
+
+    // The civil date of a day since the epoch, from Howard Hinnant's date algorithms
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year, month, day, seconds / 3600, seconds / 60 % 60, seconds % 60
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -445,7 +3480,7 @@ mod tests {
 
     #[test]
     fn test_window_creation() {
-        let window_manager = WindowManager::new(800, 600, "Test Window");
+        let window_manager = WindowManager::new(&Config { title: "Test Window".to_string(), ..Config::default() });
         assert_eq!(window_manager.state.buffer_width, 800);
         assert_eq!(window_manager.state.buffer_height, 600);
         assert_eq!(window_manager.state.points.len(), 0);
@@ -454,7 +3489,7 @@ mod tests {
 
     #[test]
     fn test_animation_state_transition() {
-        let mut window_manager = WindowManager::new(800, 600, "Test Window");
+        let mut window_manager = WindowManager::new(&Config { title: "Test Window".to_string(), ..Config::default() });
         
         // Add a test point
         window_manager.state.points.push(Point2::new(100.0, 100.0));
@@ -474,22 +3509,32 @@ mod tests {
         assert_eq!(window_manager.state.current_step, 1);
     }
 
+    #[test]
+    fn test_frame_accessor() {
+        let mut window_manager = WindowManager::offscreen(800, 600);
+        window_manager.raster.pixels_mut()[801] = 0x00FF5555;
+
+        let frame = window_manager.frame();
+        assert_eq!((frame.width, frame.height), (800, 600));
+        assert_eq!(frame.pixels[801], 0x00FF5555);
+    }
+
     #[test]
     fn test_buffer_operations() {
-        let mut window_manager = WindowManager::new(800, 600, "Test Window");
+        let mut window_manager = WindowManager::new(&Config { title: "Test Window".to_string(), ..Config::default() });
         
         // Test buffer size
-        assert_eq!(window_manager.buffer.len(), 800 * 600);
+        assert_eq!(window_manager.raster.pixels().len(), 800 * 600);
         
         // Test clear buffer
-        window_manager.buffer[0] = 0xFFFFFFFF;
+        window_manager.raster.pixels_mut()[0] = 0xFFFFFFFF;
         window_manager.clear_buffer();
-        assert_eq!(window_manager.buffer[0], 0);
+        assert_eq!(window_manager.raster.pixels()[0], 0);
     }
 
     #[test]
     fn test_empty_points_no_animation() {
-        let mut window_manager = WindowManager::new(800, 600, "Test Window");
+        let mut window_manager = WindowManager::new(&Config { title: "Test Window".to_string(), ..Config::default() });
         assert!(matches!(window_manager.state.animation_state, AnimationState::Drawing));
         
         // Simulate Enter press by changing state directly
@@ -503,14 +3548,14 @@ mod tests {
 
     #[test]
     fn test_duplicate_point_prevention() {
-        let mut window_manager = WindowManager::new(800, 600, "Test Window");
+        let mut window_manager = WindowManager::new(&Config { title: "Test Window".to_string(), ..Config::default() });
         let test_point = Point2::new(100.0, 100.0);
         
         // Simulate adding a point through the points vector
         window_manager.state.points.push(test_point);
         
         // Try to add the same point through our prevention logic
-        if !window_manager.state.points.iter().any(|p| *p == test_point) {
+        if !window_manager.state.points.contains(&test_point) {
             window_manager.state.points.push(test_point);
         }
         
@@ -523,4 +3568,75 @@ mod tests {
     fn test_max_steps_constant() {
         assert_eq!(MAX_STEPS, 7, "MAX_STEPS should be 7 as per requirements");
     }
+
+    #[test]
+    fn test_timestamp() {
+        let at = |seconds: u64| timestamp(UNIX_EPOCH + Duration::from_secs(seconds));
+        assert_eq!(at(0), "19700101-000000");
+        assert_eq!(at(951_782_400), "20000229-000000");
+        assert_eq!(at(1_706_711_101), "20240131-142501");
+    }
+
+    #[test]
+    fn test_step_size() {
+        assert_eq!(step_size(3, 0), 3);
+        assert_eq!(step_size(3, 4), 48);
+        assert_eq!(step_size(3, usize::BITS as usize - 1), usize::MAX);
+        assert_eq!(step_size(1, usize::BITS as usize), usize::MAX);
+        assert_eq!(step_size(0, 100), 0);
+    }
+
+    #[test]
+    fn test_limit_samples() {
+        assert_eq!(limit_samples(1.0, 10), LIMIT_SAMPLES);
+        // Zoomed in three times, the curve is sampled as finely as four times as much
+        assert_eq!(limit_samples(3.0, 10), 4 * LIMIT_SAMPLES);
+        assert_eq!(limit_samples(0.25, 10), LIMIT_SAMPLES / 4);
+        assert_eq!(limit_samples(0.001, 10), 1);
+        assert_eq!(limit_samples(1000.0, LIMIT_MAX_POINTS / 2), 2);
+
+        let square = vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0), Point::new(0.0, 10.0)];
+        assert_eq!(LimitCurve::new(square.clone(), false).lengths, vec![0.0, 10.0, 20.0, 30.0]);
+        assert_eq!(LimitCurve::new(square, true).lengths, vec![0.0, 10.0, 20.0, 30.0, 40.0]);
+    }
+
+    #[test]
+    fn test_fit_panel() {
+        let formulas = Rect { left: 300, top: 40, right: 389, bottom: 129 };
+        assert_eq!(fit_panel(formulas, &[], 600), Some(formulas));
+
+        // Below a wide stats panel, and cut short above the HUD
+        let stats = Rect { left: 10, top: 10, right: 349, bottom: 209 };
+        let hud = Rect { left: 10, top: 264, right: 399, bottom: 289 };
+        assert_eq!(fit_panel(formulas, &[stats], 600), Some(Rect { left: 300, top: 220, right: 389, bottom: 309 }));
+        assert_eq!(fit_panel(formulas, &[stats, hud], 300), Some(Rect { left: 300, top: 220, right: 389, bottom: 253 }));
+        // A HUD off to the side doesn't cut it short
+        let narrow_hud = Rect { right: 199, ..hud };
+        assert_eq!(fit_panel(formulas, &[narrow_hud], 300), Some(formulas));
+        // No room left at all
+        assert_eq!(fit_panel(formulas, &[stats], 215), None);
+    }
+
+    #[test]
+    fn test_polyline_curves() {
+        let smoother = ChaikinAlgorithm::new();
+        let mut polylines = vec![
+            vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0)],
+            vec![Point::new(20.0, 0.0), Point::new(30.0, 10.0)],
+        ];
+        let mut cache = PolylineCurves::default();
+        let curves = cache.get(&smoother, &polylines, 1).to_vec();
+        assert_eq!(curves.len(), 2);
+        assert_eq!(curves[0].1, smoother.get_step_points(&polylines[0], 1));
+        assert_eq!(curves[0].2, smoother.get_step_points(&polylines[0], 2));
+
+        // Only the polylines that changed are smoothed again
+        polylines[1].push(Point::new(40.0, 0.0));
+        let moved = cache.get(&smoother, &polylines, 1).to_vec();
+        assert_eq!(moved[0], curves[0]);
+        assert_eq!(moved[1].1, smoother.get_step_points(&polylines[1], 1));
+
+        assert_eq!(cache.get(&smoother, &polylines[..1], 2)[0].1, curves[0].2);
+        assert!(cache.get(&smoother, &[], 2).is_empty());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,275 @@
+use minifb::MouseButton;
+
+use super::mouse::MouseState;
+use super::theme::ThemeColor;
+
+/// Size of the saturation/value square, in pixels
+const SQUARE_SIZE: usize = 150;
+/// Width of the hue bar, in pixels
+const HUE_BAR_WIDTH: usize = 20;
+/// Space around and between the parts of the picker, in pixels
+const PADDING: usize = 10;
+/// Height of the title above the square, in pixels
+pub const TITLE_HEIGHT: usize = 24;
+/// Background of the picker panel, the same grey as the toasts
+const PANEL_COLOR: u32 = 0x00333333;
+/// Marks the selected color on the square and the bar
+const MARKER_COLOR: u32 = 0x00FFFFFF;
+
+/// A part of the picker the user can drag the mouse on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Part {
+    Square,
+    HueBar,
+}
+
+/// A color picker drawn into the canvas: a saturation/value square with a hue bar next to
+/// it. It edits one of the theme's colors, which the caller applies as the selection changes
+pub struct ColorPicker {
+    /// The theme color being edited
+    pub target: ThemeColor,
+    /// In degrees, between 0 and 360
+    hue: f32,
+    /// Between 0 and 1
+    saturation: f32,
+    /// Between 0 and 1
+    value: f32,
+    /// The part the mouse was pressed on, while it is held
+    dragging: Option<Part>,
+}
+
+impl ColorPicker {
+    /// Opens the picker on the given color
+    pub fn new(target: ThemeColor, color: u32) -> Self {
+        let mut picker = Self { target, hue: 0.0, saturation: 0.0, value: 0.0, dragging: None };
+        picker.set_color(color);
+        picker
+    }
+
+    /// Switches to editing another color
+    pub fn edit(&mut self, target: ThemeColor, color: u32) {
+        self.target = target;
+        self.set_color(color);
+    }
+
+    /// The selected color, as 0x00RRGGBB
+    pub fn color(&self) -> u32 {
+        hsv_to_rgb(self.hue, self.saturation, self.value)
+    }
+
+    fn set_color(&mut self, color: u32) {
+        let (hue, saturation, value) = rgb_to_hsv(color);
+        // Greys have no hue, keep the one we had so the bar doesn't jump
+        if saturation > 0.0 && value > 0.0 {
+            self.hue = hue;
+        }
+        self.saturation = saturation;
+        self.value = value;
+    }
+
+    /// The top left corner of the panel, in the top right corner of the canvas
+    pub fn origin(width: usize) -> (usize, usize) {
+        (width.saturating_sub(Self::panel_size().0 + PADDING), PADDING)
+    }
+
+    /// The width and height of the panel
+    pub fn panel_size() -> (usize, usize) {
+        (
+            SQUARE_SIZE + HUE_BAR_WIDTH + 3 * PADDING,
+            TITLE_HEIGHT + SQUARE_SIZE + 2 * PADDING,
+        )
+    }
+
+    /// Updates the selection from the mouse, returning whether the color changed
+    pub fn handle_mouse(&mut self, mouse: &MouseState, width: usize) -> bool {
+        if !mouse.is_down(MouseButton::Left) {
+            self.dragging = None;
+            return false;
+        }
+        let Some((x, y)) = mouse.position() else {
+            return false;
+        };
+
+        let (left, top) = Self::origin(width);
+        let square = ((left + PADDING) as f32, (top + TITLE_HEIGHT + PADDING) as f32);
+        let bar_left = square.0 + (SQUARE_SIZE + PADDING) as f32;
+        let size = SQUARE_SIZE as f32;
+        let in_rows = y >= square.1 && y < square.1 + size;
+
+        // The part is picked when the button goes down, then dragging outside of it
+        // keeps changing it, clamped to its edges
+        if mouse.is_pressed(MouseButton::Left) {
+            self.dragging = if in_rows && x >= square.0 && x < square.0 + size {
+                Some(Part::Square)
+            } else if in_rows && x >= bar_left && x < bar_left + HUE_BAR_WIDTH as f32 {
+                Some(Part::HueBar)
+            } else {
+                None
+            };
+        }
+
+        let previous = self.color();
+        let v = ((y - square.1) / (size - 1.0)).clamp(0.0, 1.0);
+        match self.dragging {
+            Some(Part::Square) => {
+                self.saturation = ((x - square.0) / (size - 1.0)).clamp(0.0, 1.0);
+                self.value = 1.0 - v;
+            }
+            Some(Part::HueBar) => self.hue = v * 360.0,
+            None => return false,
+        }
+        self.color() != previous
+    }
+
+    /// Draws the panel, except for its title which needs the font, into the buffer
+    pub fn draw(&self, buffer: &mut [u32], width: usize, height: usize) {
+        let (left, top) = Self::origin(width);
+        let (panel_width, panel_height) = Self::panel_size();
+        let mut set = |x: usize, y: usize, color: u32| {
+            if x < width && y < height {
+                buffer[y * width + x] = color;
+            }
+        };
+
+        for y in top..top + panel_height {
+            for x in left..left + panel_width {
+                set(x, y, PANEL_COLOR);
+            }
+        }
+
+        // The title has a swatch of the selected color on its right
+        let swatch = left + panel_width - PADDING - TITLE_HEIGHT;
+        for y in top + PADDING..top + TITLE_HEIGHT {
+            for x in swatch..swatch + TITLE_HEIGHT {
+                set(x, y, self.color());
+            }
+        }
+
+        let (square_left, square_top) = (left + PADDING, top + TITLE_HEIGHT + PADDING);
+        let last = (SQUARE_SIZE - 1) as f32;
+        for row in 0..SQUARE_SIZE {
+            let value = 1.0 - row as f32 / last;
+            for column in 0..SQUARE_SIZE {
+                let saturation = column as f32 / last;
+                set(square_left + column, square_top + row, hsv_to_rgb(self.hue, saturation, value));
+            }
+
+            let hue_color = hsv_to_rgb(row as f32 / last * 360.0, 1.0, 1.0);
+            let bar_left = square_left + SQUARE_SIZE + PADDING;
+            for column in 0..HUE_BAR_WIDTH {
+                set(bar_left + column, square_top + row, hue_color);
+            }
+        }
+
+        // A cross on the square, a line across the bar
+        let marker_x = square_left + (self.saturation * last).round() as usize;
+        let marker_y = square_top + ((1.0 - self.value) * last).round() as usize;
+        for d in 0..7 {
+            set(marker_x + d - 3, marker_y, MARKER_COLOR);
+            set(marker_x, marker_y + d - 3, MARKER_COLOR);
+        }
+        let hue_y = square_top + (self.hue / 360.0 * last).round() as usize;
+        let bar_left = square_left + SQUARE_SIZE + PADDING;
+        for x in bar_left - 2..bar_left + HUE_BAR_WIDTH + 2 {
+            set(x, hue_y, MARKER_COLOR);
+        }
+    }
+}
+
+/// Converts a color from hue (in degrees), saturation and value to 0x00RRGGBB
+pub fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> u32 {
+    let chroma = value * saturation;
+    let sector = (hue.rem_euclid(360.0)) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    let channel = |c: f32| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u32;
+    (channel(r) << 16) | (channel(g) << 8) | channel(b)
+}
+
+/// Converts a 0x00RRGGBB color to hue (in degrees), saturation and value
+pub fn rgb_to_hsv(color: u32) -> (f32, f32, f32) {
+    let channel = |shift: u32| ((color >> shift) & 0xFF) as f32 / 255.0;
+    let (r, g, b) = (channel(16), channel(8), channel(0));
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+
+    let hue = if chroma == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+    (hue, saturation, max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::input::Input;
+    use crate::window::InputEvent;
+
+    /// Moves the mouse with the left button held or not, as a new frame
+    fn drag(mouse: &mut MouseState, position: Option<(f32, f32)>, down: bool) -> &MouseState {
+        let mut input = Input::default();
+        if let Some((x, y)) = position {
+            input.apply(InputEvent::MouseMove(x, y));
+        }
+        if down {
+            input.apply(InputEvent::MouseDown(MouseButton::Left));
+        }
+        mouse.update(&input, std::time::Instant::now());
+        mouse
+    }
+
+    #[test]
+    fn test_hsv_round_trip() {
+        for color in [0x00FF5555, 0x0055CCAA, 0x00000000, 0x00FFFFFF, 0x00123456, 0x00808080] {
+            let (h, s, v) = rgb_to_hsv(color);
+            assert_eq!(hsv_to_rgb(h, s, v), color, "{:06X}", color);
+        }
+        assert_eq!(hsv_to_rgb(120.0, 1.0, 1.0), 0x0000FF00);
+    }
+
+    #[test]
+    fn test_drag_square() {
+        let mut picker = ColorPicker::new(ThemeColor::Line, 0x00FF0000);
+        let (left, top) = ColorPicker::origin(800);
+        let square = ((left + PADDING) as f32, (top + TITLE_HEIGHT + PADDING) as f32);
+
+        let mut mouse = MouseState::default();
+
+        // Top right of the square is the pure hue, the bottom is black
+        assert!(!picker.handle_mouse(drag(&mut mouse, Some((square.0 + 149.0, square.1)), true), 800));
+        assert!(picker.handle_mouse(drag(&mut mouse, Some((square.0 + 149.0, square.1 + 500.0)), true), 800));
+        assert_eq!(picker.color(), 0);
+
+        // Releasing the button stops dragging, and a drag from outside the parts does nothing
+        picker.handle_mouse(drag(&mut mouse, None, false), 800);
+        assert!(!picker.handle_mouse(drag(&mut mouse, Some((0.0, 0.0)), true), 800));
+        assert!(!picker.handle_mouse(drag(&mut mouse, Some(square), true), 800));
+        assert_eq!(picker.color(), 0);
+    }
+
+    #[test]
+    fn test_drag_hue_bar() {
+        let mut picker = ColorPicker::new(ThemeColor::Point, 0x00FF0000);
+        let (left, top) = ColorPicker::origin(800);
+        let bar = ((left + 2 * PADDING + SQUARE_SIZE + 5) as f32, (top + TITLE_HEIGHT + PADDING) as f32);
+
+        let mut mouse = MouseState::default();
+        assert!(picker.handle_mouse(drag(&mut mouse, Some((bar.0, bar.1 + 149.0 / 3.0)), true), 800));
+        assert_eq!(picker.color(), 0x0000FF00);
+    }
+}
@@ -0,0 +1,41 @@
+use std::time::Duration;
+
+use super::theme::Theme;
+use super::{MAX_STEPS, POINT_RADIUS, STEP_INTERVAL};
+
+/// What the window starts with, e.g. from the command line. Everything can still be changed
+/// while it runs
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// Size of the canvas, in pixels
+    pub width: usize,
+    pub height: usize,
+    /// Title of the window
+    pub title: String,
+    /// The number of smoothing steps the animation goes through before starting over
+    pub max_steps: usize,
+    /// How long each step of the animation is shown
+    pub step_interval: Duration,
+    /// Radius of the control points, in pixels
+    pub point_radius: f32,
+    /// How often the window is updated at most, in Hz, rather than the refresh rate of the
+    /// monitor it is on
+    pub refresh_rate: Option<f32>,
+    /// The colors everything is drawn with
+    pub theme: Theme,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            title: super::APP_NAME.to_string(),
+            max_steps: MAX_STEPS,
+            step_interval: STEP_INTERVAL,
+            point_radius: POINT_RADIUS,
+            refresh_rate: None,
+            theme: Theme::default(),
+        }
+    }
+}
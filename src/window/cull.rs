@@ -0,0 +1,145 @@
+use crate::geometry::{Bounds, Bvh};
+use crate::types::{Point, Polyline};
+
+use super::view::View;
+use super::CurveKey;
+
+/// Paths with fewer points than this are drawn whole, as finding what is on screen would
+/// take about as long as drawing them
+pub const CULL_MIN_POINTS: usize = 4096;
+
+/// The points of the canvas, of the given size and widened on every side by the margin, in
+/// pixels, e.g. for the width of the lines
+pub fn canvas_bounds(view: &View, (width, height): (usize, usize), margin: f32) -> Bounds {
+    Bounds {
+        min: view.point_at_pixel(-margin, -margin),
+        max: view.point_at_pixel(width as f32 + margin, height as f32 + margin),
+    }
+}
+
+/// The hierarchies over the steps of the points' curve built so far, so that going back to
+/// a step, or panning around it, doesn't build its hierarchy again. Only the steps shown
+/// are built, when they are first drawn; the others are forgotten when the points change
+#[derive(Default)]
+pub struct CurveBvhs {
+    /// The points the steps are of
+    points: Polyline,
+    closed: bool,
+    /// The hierarchy of each step, if it was built
+    bvhs: Vec<Option<Bvh>>,
+}
+
+impl CurveBvhs {
+    /// The hierarchy over the curve of the step of the points. The curve has to be the one
+    /// of that step, computed with the smoother the others were, or the cache cleared
+    pub fn get(&mut self, (points, step): &CurveKey, curve: &[Point], closed: bool) -> &Bvh {
+        if self.points != *points || self.closed != closed {
+            self.clear();
+            self.points = points.clone();
+            self.closed = closed;
+        }
+        if self.bvhs.len() <= *step {
+            self.bvhs.resize(step + 1, None);
+        }
+        self.bvhs[*step].get_or_insert_with(|| Bvh::new(curve, closed))
+    }
+
+    /// Forgets every hierarchy, e.g. when the smoother changes the steps
+    pub fn clear(&mut self) {
+        self.bvhs.clear();
+    }
+}
+
+/// A long path drawn, with the hierarchy over its segments
+struct DrawnPath {
+    points: Polyline,
+    closed: bool,
+    bvh: Bvh,
+    /// Whether it was drawn since the frame started
+    drawn: bool,
+}
+
+/// The hierarchies over the long paths drawn in the last frame, such as the control
+/// polygons of imported traces, so that they are only built again once the paths change.
+/// The paths that weren't drawn in the last frame are forgotten
+#[derive(Default)]
+pub struct PathBvhs {
+    paths: Vec<DrawnPath>,
+}
+
+impl PathBvhs {
+    /// The hierarchy over the path, built if it wasn't drawn in the last frame
+    pub fn get(&mut self, points: &[Point], closed: bool) -> &Bvh {
+        let found = self.paths.iter().position(|path| path.closed == closed && path.points.as_slice() == points);
+        let i = found.unwrap_or_else(|| {
+            let bvh = Bvh::new(points, closed);
+            self.paths.push(DrawnPath { points: Polyline::from_slice(points), closed, bvh, drawn: false });
+            self.paths.len() - 1
+        });
+        self.paths[i].drawn = true;
+        &self.paths[i].bvh
+    }
+
+    /// Forgets the paths that weren't drawn since the last frame started, to be called as
+    /// each frame starts
+    pub fn start_frame(&mut self) {
+        self.paths.retain(|path| path.drawn);
+        for path in &mut self.paths {
+            path.drawn = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_canvas_bounds() {
+        // Zoomed in twice and panned, with a margin of 2 pixels
+        let view = View { scale: 2.0, offset: Vector2::new(-100.0, 0.0) };
+        let canvas = canvas_bounds(&view, (200, 100), 2.0);
+        assert_eq!(canvas, Bounds { min: Point::new(49.0, -1.0), max: Point::new(151.0, 51.0) });
+    }
+
+    #[test]
+    fn test_curve_bvhs() {
+        let points = Polyline::from_slice(&[Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0)]);
+        let curves = [points.to_vec(), vec![Point::new(0.0, 0.0), Point::new(20.0, 20.0)]];
+        let mut bvhs = CurveBvhs::default();
+        let built = |bvhs: &CurveBvhs| bvhs.bvhs.iter().flatten().count();
+        assert_eq!(bvhs.get(&(points.clone(), 1), &curves[1], false).len(), 1);
+        assert_eq!(bvhs.get(&(points.clone(), 0), &curves[0], false).len(), 2);
+        // Built once per step
+        assert_eq!(bvhs.get(&(points.clone(), 1), &curves[0], false).len(), 1);
+        assert_eq!(built(&bvhs), 2);
+
+        // And again for other points, or once the shape is closed
+        assert_eq!(bvhs.get(&(points.clone(), 0), &curves[0], true).len(), 3);
+        assert_eq!(built(&bvhs), 1);
+        let moved = Polyline::from_slice(&[Point::new(1.0, 0.0)]);
+        bvhs.get(&(moved, 2), &curves[0], true);
+        assert_eq!(built(&bvhs), 1);
+    }
+
+    #[test]
+    fn test_path_bvhs() {
+        let polygon = [Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 10.0)];
+        let curve = [Point::new(0.0, 0.0), Point::new(20.0, 20.0)];
+        let mut bvhs = PathBvhs::default();
+        assert_eq!(bvhs.get(&polygon, false).len(), 2);
+        assert_eq!(bvhs.get(&polygon, true).len(), 3);
+        assert_eq!(bvhs.get(&curve, false).len(), 1);
+        // Built once while the paths are drawn every frame
+        bvhs.start_frame();
+        bvhs.get(&polygon, false);
+        bvhs.get(&polygon, true);
+        assert_eq!(bvhs.paths.len(), 3);
+        // Then forgotten once they aren't
+        bvhs.start_frame();
+        assert_eq!(bvhs.paths.len(), 2);
+        bvhs.start_frame();
+        assert!(bvhs.paths.is_empty());
+    }
+}
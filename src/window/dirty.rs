@@ -0,0 +1,89 @@
+/// A rectangle of pixels, from its top left pixel to its bottom right one, both included
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub left: usize,
+    pub top: usize,
+    pub right: usize,
+    pub bottom: usize,
+}
+
+impl Rect {
+    /// The smallest rectangle covering both
+    pub fn union(self, other: Rect) -> Rect {
+        Rect {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    /// Whether the rectangles share a pixel
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.left <= other.right && other.left <= self.right && self.top <= other.bottom && other.top <= self.bottom
+    }
+
+    pub fn width(&self) -> usize {
+        self.right - self.left + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.bottom - self.top + 1
+    }
+}
+
+/// The pixels drawn over since the buffer was last cleared, as the rectangle bounding them.
+/// Clearing only that rectangle rather than the whole buffer spares most of the work when
+/// the drawing covers a small part of a large window, e.g. a few points in a corner
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DirtyRegion {
+    bounds: Option<Rect>,
+}
+
+impl DirtyRegion {
+    /// Marks a pixel as drawn over
+    pub fn add(&mut self, x: usize, y: usize) {
+        self.add_rect(Rect { left: x, top: y, right: x, bottom: y });
+    }
+
+    /// Marks every pixel of the rectangle as drawn over
+    pub fn add_rect(&mut self, rect: Rect) {
+        self.bounds = Some(self.bounds.map_or(rect, |bounds| bounds.union(rect)));
+    }
+
+    /// The rectangle bounding the pixels drawn over, if any, which are forgotten
+    pub fn take(&mut self) -> Option<Rect> {
+        self.bounds.take()
+    }
+
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dirty_region() {
+        let mut dirty = DirtyRegion::default();
+        assert_eq!(dirty.bounds(), None);
+        dirty.add(5, 7);
+        dirty.add(2, 9);
+        dirty.add_rect(Rect { left: 3, top: 1, right: 4, bottom: 2 });
+        let bounds = dirty.take().unwrap();
+        assert_eq!(bounds, Rect { left: 2, top: 1, right: 5, bottom: 9 });
+        assert_eq!((bounds.width(), bounds.height()), (4, 9));
+        assert_eq!(dirty.take(), None);
+    }
+
+    #[test]
+    fn test_intersects() {
+        let rect = Rect { left: 2, top: 2, right: 5, bottom: 5 };
+        assert!(rect.intersects(&Rect { left: 5, top: 0, right: 8, bottom: 2 }));
+        assert!(rect.intersects(&Rect { left: 0, top: 0, right: 9, bottom: 9 }));
+        assert!(!rect.intersects(&Rect { left: 6, top: 2, right: 8, bottom: 5 }));
+        assert!(!rect.intersects(&Rect { left: 2, top: 6, right: 5, bottom: 8 }));
+    }
+}
@@ -0,0 +1,155 @@
+/// The refresh rate assumed when the display's can't be found
+pub const DEFAULT_REFRESH_RATE: f32 = 60.0;
+/// The refresh rates the window may be updated at. Rates reported outside of it are taken
+/// as wrong, e.g. 0 Hz for "whatever the hardware does"
+pub const REFRESH_RATE_RANGE: (f32, f32) = (24.0, 500.0);
+
+/// A monitor of the desktop, where it is on it and how often it refreshes
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monitor {
+    pub x: isize,
+    pub y: isize,
+    pub width: isize,
+    pub height: isize,
+    /// In Hz
+    pub refresh_rate: f32,
+    pub primary: bool,
+}
+
+impl Monitor {
+    pub fn contains(&self, (x, y): (isize, isize)) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+/// Whether the rate looks like a refresh rate the window can be updated at
+pub fn is_valid_refresh_rate(rate: f32) -> bool {
+    (REFRESH_RATE_RANGE.0..=REFRESH_RATE_RANGE.1).contains(&rate)
+}
+
+/// The monitors in the output of `xrandr --current` which are on, with the refresh rate of
+/// the mode they are in, the one marked with a `*`
+pub fn parse_xrandr(output: &str) -> Vec<Monitor> {
+    let mut monitors = Vec::new();
+    // Whether the lines of modes are those of a monitor which is on
+    let mut on = false;
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let geometry = fields.get(1)
+                .filter(|&&state| state == "connected")
+                .and_then(|_| fields.iter().find_map(|field| parse_geometry(field)));
+            on = geometry.is_some();
+            if let Some((width, height, x, y)) = geometry {
+                let primary = fields.contains(&"primary");
+                monitors.push(Monitor { x, y, width, height, refresh_rate: DEFAULT_REFRESH_RATE, primary });
+            }
+        } else if on {
+            let current = line.split_whitespace()
+                .skip(1)
+                .find(|rate| rate.contains('*'))
+                .and_then(|rate| rate.trim_end_matches(['*', '+']).parse::<f32>().ok())
+                .filter(|&rate| is_valid_refresh_rate(rate));
+            if let (Some(rate), Some(monitor)) = (current, monitors.last_mut()) {
+                monitor.refresh_rate = rate;
+            }
+        }
+    }
+    monitors
+}
+
+/// The size and position of a monitor, as "1920x1080+1920+0"
+fn parse_geometry(field: &str) -> Option<(isize, isize, isize, isize)> {
+    let (size, position) = field.split_once('+')?;
+    let (width, height) = size.split_once('x')?;
+    let (x, y) = position.split_once('+')?;
+    Some((width.parse().ok()?, height.parse().ok()?, x.parse().ok()?, y.parse().ok()?))
+}
+
+/// The refresh rate of the monitor the point is on, the primary one's if it is on none, e.g.
+/// while the window is dragged across, or none if there are no monitors
+pub fn refresh_rate_at(monitors: &[Monitor], point: (isize, isize)) -> Option<f32> {
+    monitors.iter()
+        .find(|monitor| monitor.contains(point))
+        .or_else(|| monitors.iter().find(|monitor| monitor.primary))
+        .or(monitors.first())
+        .map(|monitor| monitor.refresh_rate)
+}
+
+/// The monitors of the desktop, with their refresh rates, asked to xrandr. They are only
+/// found under X11 (or XWayland); elsewhere there are none
+pub fn monitors() -> Vec<Monitor> {
+    if cfg!(target_os = "windows") {
+        return Vec::new();
+    }
+    if let Ok(output) = std::process::Command::new("xrandr").arg("--current").output() {
+        if output.status.success() {
+            return parse_xrandr(&String::from_utf8_lossy(&output.stdout));
+        }
+    }
+    Vec::new()
+}
+
+/// The refresh rate of the monitor most of the window is on, asked to Windows
+#[cfg(target_os = "windows")]
+pub fn window_refresh_rate(window: &minifb::Window) -> Option<f32> {
+    use winapi::um::wingdi::DEVMODEW;
+    use winapi::um::winuser::{
+        EnumDisplaySettingsW, GetMonitorInfoW, MonitorFromWindow, ENUM_CURRENT_SETTINGS, MONITORINFOEXW,
+        MONITOR_DEFAULTTONEAREST,
+    };
+
+    unsafe {
+        let monitor = MonitorFromWindow(window.get_window_handle() as _, MONITOR_DEFAULTTONEAREST);
+        let mut info: MONITORINFOEXW = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+        if GetMonitorInfoW(monitor, &mut info as *mut MONITORINFOEXW as *mut _) == 0 {
+            return None;
+        }
+        let mut mode: DEVMODEW = std::mem::zeroed();
+        mode.dmSize = std::mem::size_of::<DEVMODEW>() as u16;
+        if EnumDisplaySettingsW(info.szDevice.as_ptr(), ENUM_CURRENT_SETTINGS, &mut mode) == 0 {
+            return None;
+        }
+        // 0 and 1 stand for the hardware's default rate
+        Some(mode.dmDisplayFrequency as f32).filter(|&rate| is_valid_refresh_rate(rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XRANDR: &str = "\
+Screen 0: minimum 8 x 8, current 4480 x 1440, maximum 32767 x 32767
+DP-1 connected primary 2560x1440+0+0 (normal left inverted right x axis y axis) 597mm x 336mm
+   2560x1440    143.91*+  59.95
+   1920x1080     60.00
+HDMI-1 connected 1920x1080+2560+0 (normal left inverted right x axis y axis) 527mm x 296mm
+   1920x1080     60.00 +  50.00    74.97*
+   1280x720      60.00
+eDP-1 disconnected (normal left inverted right x axis y axis)
+DP-2 connected (normal left inverted right x axis y axis)
+   1920x1080     60.00 +
+";
+
+    #[test]
+    fn test_parse_xrandr() {
+        let monitors = parse_xrandr(XRANDR);
+        assert_eq!(monitors, vec![
+            Monitor { x: 0, y: 0, width: 2560, height: 1440, refresh_rate: 143.91, primary: true },
+            Monitor { x: 2560, y: 0, width: 1920, height: 1080, refresh_rate: 74.97, primary: false },
+        ]);
+        assert!(parse_xrandr("").is_empty());
+    }
+
+    #[test]
+    fn test_refresh_rate_at() {
+        let monitors = parse_xrandr(XRANDR);
+        assert_eq!(refresh_rate_at(&monitors, (100, 100)), Some(143.91));
+        assert_eq!(refresh_rate_at(&monitors, (3000, 500)), Some(74.97));
+        // Off every monitor, the primary one's
+        assert_eq!(refresh_rate_at(&monitors, (3000, 1200)), Some(143.91));
+        assert_eq!(refresh_rate_at(&[], (0, 0)), None);
+    }
+}
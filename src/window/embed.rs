@@ -0,0 +1,1621 @@
+use crate::window::input::InputEvent;
+use crate::window::WindowManager;
+
+/// The whole Chaikin canvas, for embedding inside another application's window.
+///
+/// The host application owns the window: it feeds the canvas the input events it receives,
+/// and hands it a buffer to draw into once per frame
+pub struct EmbeddedCanvas {
+    manager: WindowManager,
+}
+
+impl EmbeddedCanvas {
+    pub fn new() -> Self {
+        Self { manager: WindowManager::offscreen(1, 1) }
+    }
+
+    /// Feeds an input event to the canvas. Positions are in pixels of the host's buffer
+    pub fn handle_event(&mut self, event: InputEvent) {
+        self.manager.input.apply(event);
+    }
+
+    /// Runs one frame: handles the events fed since the last frame, advances the animation,
+    /// and draws into the given buffer of 0x00RRGGBB pixels.
+    ///
+    /// Returns false once the user asked to close the canvas (and dealt with any unsaved
+    /// changes), at which point the host should stop calling it
+    pub fn render(&mut self, buffer: &mut [u32], width: usize, height: usize) -> bool {
+        assert_eq!(buffer.len(), width * height, "the buffer doesn't match its size");
+
+        self.manager.resize_buffer(width, height);
+        let running = self.manager.handle_input();
+        self.manager.input.end_frame();
+
+        self.manager.redraw();
+        self.manager.update();
+        let pixels = self.manager.raster.pixels();
+        if pixels.len() == buffer.len() {
+            buffer.copy_from_slice(pixels);
+        }
+
+        running
+    }
+
+    /// The underlying manager, to load files or change settings
+    pub fn manager(&mut self) -> &mut WindowManager {
+        &mut self.manager
+    }
+}
+
+impl Default for EmbeddedCanvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AnimationState, Point};
+    use crate::algorithm::{CubicBSpline, Scheme, Smoother};
+    use crate::demo::Demo;
+    use crate::document::Metadata;
+    use crate::window::color_picker::ColorPicker;
+    use crate::window::tutorial::Lesson;
+    use crate::window::{Gradient, OverlayKind, View, IDLE_DELAY, MAX_STEPS_LIMIT, TOAST_FONT_SIZE};
+    use minifb::{Key, MouseButton};
+    use nalgebra::Vector2;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_embedded_frames() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+
+        for (x, y) in [(40.0, 200.0), (160.0, 40.0), (280.0, 200.0)] {
+            canvas.handle_event(InputEvent::MouseMove(x, y));
+            canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+            assert!(canvas.render(&mut buffer, 320, 240));
+            canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+            canvas.render(&mut buffer, 320, 240);
+        }
+        assert_eq!(canvas.manager().points().len(), 3);
+        assert_eq!(canvas.manager().overlays.iter().count(), 3);
+        // The point placed last is drawn in the host's buffer
+        assert_ne!(buffer[200 * 320 + 280], 0);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::Enter));
+        assert!(canvas.render(&mut buffer, 320, 240));
+        assert!(canvas.manager().state.animation_state == AnimationState::Animating);
+    }
+
+    #[test]
+    fn test_hover_preview() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_preview_interval(Duration::ZERO);
+        for (x, y) in [(40.0, 200.0), (280.0, 200.0)] {
+            canvas.handle_event(InputEvent::MouseMove(x, y));
+            canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+            canvas.render(&mut buffer, 320, 240);
+            canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+            canvas.render(&mut buffer, 320, 240);
+        }
+
+        // With a third point at the cursor, the first step cuts the corner at (250, 160)
+        canvas.handle_event(InputEvent::MouseMove(160.0, 40.0));
+        canvas.render(&mut buffer, 320, 240);
+        let preview = buffer[160 * 320 + 250];
+        assert_ne!(preview, 0);
+        // Fainter than the lines between the points
+        assert!(preview & 0xFF < buffer[200 * 320 + 160] & 0xFF);
+
+        canvas.handle_event(InputEvent::MouseLeave);
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(buffer[160 * 320 + 250], 0);
+        assert_eq!(canvas.manager().points().len(), 2);
+    }
+
+    #[test]
+    fn test_zoom_and_pan() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_points(vec![Point::new(100.0, 100.0)]);
+
+        // Zoomed in about the point, which stays under the mouse
+        canvas.handle_event(InputEvent::MouseMove(100.0, 100.0));
+        canvas.handle_event(InputEvent::Scroll(0.0, 3.0));
+        canvas.render(&mut buffer, 320, 240);
+        let view = canvas.manager().view;
+        assert!((view.scale - 1.1f32.powi(3)).abs() < 1e-5);
+        assert!((view.screen_point(Point::new(100.0, 100.0)) - Point::new(100.0, 100.0)).norm() < 1e-3);
+        // The scroll is only counted once
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().view, view);
+
+        // Dragging with the middle button pans, and places no point
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Middle));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::MouseMove(140.0, 90.0));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::MouseUp(MouseButton::Middle));
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().view.screen_point(Point::new(100.0, 100.0)), Point::new(140.0, 90.0));
+        assert_eq!(canvas.manager().points().len(), 1);
+
+        // Points placed go where the mouse is in the zoomed view
+        canvas.handle_event(InputEvent::MouseMove(200.0, 90.0));
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        let placed = canvas.manager().points()[1];
+        assert!((placed - Point::new(100.0 + 60.0 / view.scale, 100.0)).norm() < 1e-3);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::Key0));
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().view, View::default());
+    }
+
+    #[test]
+    fn test_control_polygon() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.manager().show_step(3);
+        canvas.render(&mut buffer, 320, 240);
+        // Near the apex, which the curve cuts well below
+        let edge = 56 * 320 + 148;
+        assert_ne!(buffer[edge], 0);
+        // Faint and grey, unlike the curve
+        assert!(buffer[edge] & 0xFF < 0x80);
+        assert_eq!(buffer[edge] >> 16 & 0xFF, buffer[edge] & 0xFF);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::K));
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(buffer[edge], 0);
+    }
+
+    #[test]
+    fn test_gradient() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.manager().set_gradient(Some(Gradient { from: 0x00FF0000, to: 0x0000FF00 }));
+        canvas.manager().control_polygon = false;
+        // The pixels more red than green, and those more green than red, leaving out those
+        // with blue in them, such as the points and the text
+        let count = |buffer: &[u32]| {
+            let curve = buffer.iter().filter(|&&pixel| pixel & 0xFF == 0);
+            let channels = curve.map(|pixel| (pixel >> 16 & 0xFF, pixel >> 8 & 0xFF));
+            let reds = channels.clone().filter(|(red, green)| red > green).count();
+            let greens = channels.filter(|(red, green)| green > red).count();
+            (reds, greens)
+        };
+
+        // The first step is red and the last one green
+        canvas.manager().show_step(0);
+        canvas.render(&mut buffer, 320, 240);
+        let (reds, greens) = count(&buffer);
+        assert!(reds > 0 && greens == 0);
+        canvas.manager().show_step(6);
+        canvas.render(&mut buffer, 320, 240);
+        let (reds, greens) = count(&buffer);
+        assert!(reds == 0 && greens > 0);
+
+        // The onion skin shows the earlier steps under it, in their own colors
+        canvas.handle_event(InputEvent::KeyDown(Key::O));
+        canvas.render(&mut buffer, 320, 240);
+        let (reds, _) = count(&buffer);
+        assert!(reds > 0);
+    }
+
+    #[test]
+    fn test_adaptive() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let press = |canvas: &mut EmbeddedCanvas, buffer: &mut [u32], key: Key| {
+            canvas.handle_event(InputEvent::KeyDown(key));
+            canvas.render(buffer, 320, 240);
+            canvas.handle_event(InputEvent::KeyUp(key));
+            canvas.render(buffer, 320, 240);
+        };
+        let points = vec![Point::new(20.0, 200.0), Point::new(120.0, 200.0), Point::new(220.0, 200.0), Point::new(300.0, 40.0)];
+        canvas.manager().set_points(points.clone());
+        let uniform = canvas.manager().step_points(6);
+
+        press(&mut canvas, &mut buffer, Key::D);
+        assert!(canvas.manager().adaptive);
+        let adaptive = canvas.manager().step_points(6);
+        assert!(adaptive.len() * 2 < uniform.len());
+        assert!(adaptive.contains(&points[1]));
+
+        // Only Chaikin's scheme subdivides adaptively
+        canvas.manager().set_scheme(Scheme::LaneRiesenfeld(3), (0.25, 0.75));
+        press(&mut canvas, &mut buffer, Key::D);
+        assert!(canvas.manager().adaptive);
+        assert_eq!(canvas.manager().step_points(1).len(), CubicBSpline.calculate_step(&points).len());
+    }
+
+    #[test]
+    fn test_grid() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let press = |canvas: &mut EmbeddedCanvas, buffer: &mut [u32], key: Key| {
+            canvas.handle_event(InputEvent::KeyDown(key));
+            canvas.render(buffer, 320, 240);
+            canvas.handle_event(InputEvent::KeyUp(key));
+            canvas.render(buffer, 320, 240);
+        };
+        press(&mut canvas, &mut buffer, Key::G);
+        canvas.manager().overlays.dismiss_toast();
+        canvas.render(&mut buffer, 320, 240);
+        // Lines every 20 pixels, faint
+        assert_ne!(buffer[100 * 320 + 20], 0);
+        assert!(buffer[100 * 320 + 20] & 0xFF < 0x80);
+        assert_eq!(buffer[110 * 320 + 30], 0);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftShift));
+        press(&mut canvas, &mut buffer, Key::G);
+        canvas.handle_event(InputEvent::KeyUp(Key::LeftShift));
+        assert!(canvas.manager().grid.snap);
+
+        for (x, y) in [(43.0, 198.0), (158.0, 51.0)] {
+            canvas.handle_event(InputEvent::MouseMove(x, y));
+            canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+            canvas.render(&mut buffer, 320, 240);
+            canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+            canvas.render(&mut buffer, 320, 240);
+        }
+        assert_eq!(canvas.manager().points(), &[Point::new(40.0, 200.0), Point::new(160.0, 60.0)]);
+
+        press(&mut canvas, &mut buffer, Key::RightBracket);
+        assert_eq!(canvas.manager().grid.spacing, 40.0);
+        assert_eq!(canvas.manager().overlays.toast(), Some("Grid every 40.0 px"));
+    }
+
+    #[test]
+    fn test_ghost() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.handle_event(InputEvent::MouseLeave);
+        canvas.render(&mut buffer, 320, 240);
+        // The smoothed curve passes below the corner
+        let apex = 80 * 320 + 160;
+        assert_eq!(buffer[apex], 0);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::G));
+        canvas.render(&mut buffer, 320, 240);
+        assert_ne!(buffer[apex], 0);
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.handle_event(InputEvent::MouseLeave);
+        canvas.manager().show_step(0);
+        canvas.render(&mut buffer, 320, 240);
+        // The limit passes through (160, 80), well below the corner of the control polygon
+        let near_apex = |buffer: &[u32]| (75..90).any(|y| (150..170).any(|x| buffer[y * 320 + x] != 0));
+        assert!(!near_apex(&buffer));
+
+        canvas.handle_event(InputEvent::KeyDown(Key::I));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::I));
+        assert_eq!(canvas.manager().overlays.toast(), Some("Showing the limit curve the steps converge to"));
+        canvas.render(&mut buffer, 320, 240);
+        assert!(near_apex(&buffer));
+
+        // Catmull-Rom's limit isn't known, so there is none to show
+        canvas.handle_event(InputEvent::KeyDown(Key::Key2));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::Key2));
+        canvas.manager().show_step(0);
+        canvas.render(&mut buffer, 320, 240);
+        assert!(!near_apex(&buffer));
+    }
+
+    /// Takes its time over each step, so that it can be cancelled
+    struct Slow;
+
+    impl Smoother for Slow {
+        fn calculate_step(&self, points: &[Point]) -> Vec<Point> {
+            std::thread::sleep(Duration::from_millis(100));
+            points.repeat(2)
+        }
+    }
+
+    #[test]
+    fn test_heavy_step() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let zigzag = (0..4000).map(|i| Point::new((i % 300) as f32, (i % 7 * 30) as f32)).collect();
+        canvas.manager().set_points(zigzag);
+
+        // 4000 points, doubled six times, are computed on a worker thread
+        canvas.manager().show_step(6);
+        assert!(canvas.manager().curve_job.is_some());
+        let start = std::time::Instant::now();
+        while canvas.manager().curve_job.is_some() {
+            assert!(canvas.render(&mut buffer, 320, 240));
+            assert!(start.elapsed() < Duration::from_secs(30));
+        }
+        let (key, curve) = canvas.manager().curve.as_ref().unwrap();
+        assert_eq!(key.1, 6);
+        assert!(curve.len() > 200_000);
+
+        // Escape cancels the next heavy step instead of closing the canvas
+        canvas.manager().set_smoother(Box::new(Slow));
+        canvas.manager().show_step(6);
+        assert!(canvas.manager().curve_job.is_some());
+        // A bar shows the progress left of the spinner, once the first step is done
+        while canvas.manager().curve_job.as_ref().unwrap().1.progress() == Some(0.0) {
+            std::thread::yield_now();
+        }
+        canvas.render(&mut buffer, 320, 240);
+        assert_ne!(buffer[24 * 320 + 161], 0);
+        canvas.handle_event(InputEvent::KeyDown(Key::Escape));
+        assert!(canvas.render(&mut buffer, 320, 240));
+        assert!(canvas.manager().curve_job.is_none());
+        assert!(canvas.manager().state.animation_state == AnimationState::Drawing);
+        assert_eq!(canvas.manager().overlays.toast(), Some("Cancelled"));
+    }
+
+    #[test]
+    fn test_closed() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.handle_event(InputEvent::MouseLeave);
+        canvas.render(&mut buffer, 320, 240);
+        let closing = 200 * 320 + 160;
+        assert_eq!(buffer[closing], 0);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::C));
+        canvas.render(&mut buffer, 320, 240);
+        assert_ne!(buffer[closing], 0);
+        // The curve has no ends left
+        let curve = canvas.manager().step_points(2);
+        assert!(!curve.contains(&Point::new(40.0, 200.0)));
+
+        // Other schemes only smooth open shapes
+        canvas.manager().set_smoother(Box::new(crate::algorithm::CubicBSpline));
+        assert!(!canvas.manager().closed);
+        assert!(!canvas.manager().set_closed(true));
+    }
+
+    #[test]
+    fn test_fit_view() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_points(vec![Point::new(1000.0, 2000.0), Point::new(1100.0, 2050.0)]);
+        canvas.handle_event(InputEvent::MouseLeave);
+        canvas.render(&mut buffer, 320, 240);
+        let background = buffer[120 * 320 + 160];
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftShift));
+        canvas.handle_event(InputEvent::KeyDown(Key::F));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::F));
+        canvas.handle_event(InputEvent::KeyUp(Key::LeftShift));
+        // The line now crosses the middle of the canvas, from (40, 60) to (280, 180)
+        assert_ne!(buffer[120 * 320 + 160], background);
+
+        // The points keep their coordinates, and the mouse goes through the view
+        canvas.handle_event(InputEvent::MouseMove(41.0, 61.0));
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::MouseMove(64.0, 60.0));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+        let moved = canvas.manager().points()[0];
+        assert!((moved - Point::new(1010.0, 2000.0)).norm() < 1e-2);
+        assert_eq!(canvas.manager().points()[1], Point::new(1100.0, 2050.0));
+    }
+
+    #[test]
+    fn test_fit_opened_points() {
+        let path = std::env::temp_dir().join(format!("chaikin-map-{}.txt", std::process::id()));
+        std::fs::write(&path, "36.8 -1.3\n36.9 -1.25\n37.0 -1.3\n").unwrap();
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.render(&mut buffer, 320, 240);
+        canvas.manager().open_file(&path);
+        let points = canvas.manager().points().to_vec();
+
+        // Fitting is offered, and only the view changes
+        canvas.handle_event(InputEvent::KeyDown(Key::Y));
+        canvas.render(&mut buffer, 320, 240);
+        let size = (320, 240);
+        assert!(canvas.manager().view.shows(&points, size));
+        assert_eq!(canvas.manager().points(), points);
+        canvas.manager().save().unwrap();
+        assert_eq!(crate::document::read_points(&path).unwrap(), points);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_polylines() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let first = vec![Point::new(20.0, 200.0), Point::new(80.0, 40.0), Point::new(140.0, 200.0)];
+        canvas.manager().set_points(first.clone());
+        canvas.handle_event(InputEvent::KeyDown(Key::N));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::N));
+        assert!(canvas.manager().points().is_empty());
+        let second = vec![Point::new(180.0, 200.0), Point::new(240.0, 40.0), Point::new(300.0, 200.0)];
+        canvas.manager().set_points(second.clone());
+
+        // Each is smoothed on its own, with no line between them
+        canvas.manager().show_step(2);
+        canvas.render(&mut buffer, 320, 240);
+        let drawn = |buffer: &[u32], x: usize| (70..90).any(|y| buffer[y * 320 + x] != 0);
+        assert!(drawn(&buffer, 80));
+        assert!(drawn(&buffer, 240));
+        assert_eq!(buffer[200 * 320 + 160], 0);
+
+        // A press on a point of the first goes on with it
+        canvas.manager().state.animation_state = crate::types::AnimationState::Drawing;
+        canvas.handle_event(InputEvent::MouseMove(80.0, 40.0));
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Right));
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().points(), &[first[0], first[2]]);
+        let second = vec![second];
+        assert_eq!(canvas.manager().state.polylines, second);
+
+        // Clearing the canvas is undone with every polyline
+        let press = |canvas: &mut EmbeddedCanvas, keys: &[Key]| {
+            keys.iter().for_each(|key| canvas.handle_event(InputEvent::KeyDown(*key)));
+            canvas.render(&mut vec![0; 320 * 240], 320, 240);
+            keys.iter().for_each(|key| canvas.handle_event(InputEvent::KeyUp(*key)));
+        };
+        canvas.manager().reset();
+        assert!(canvas.manager().points().is_empty() && canvas.manager().polylines().is_empty());
+        press(&mut canvas, &[Key::LeftCtrl, Key::Z]);
+        assert_eq!(canvas.manager().points(), &[first[0], first[2]]);
+        assert_eq!(canvas.manager().polylines(), second);
+
+        // And so is finishing a polyline
+        press(&mut canvas, &[Key::N]);
+        assert_eq!(canvas.manager().polylines().len(), 2);
+        press(&mut canvas, &[Key::LeftCtrl, Key::Z]);
+        assert_eq!(canvas.manager().polylines(), second);
+
+        // A single point isn't finished as a polyline of its own when another is selected
+        canvas.manager().set_points(vec![Point::new(160.0, 120.0)]);
+        canvas.handle_event(InputEvent::MouseMove(240.0, 40.0));
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+        assert_eq!(canvas.manager().points(), second[0].as_slice());
+        assert!(canvas.manager().polylines().is_empty());
+    }
+
+    #[test]
+    fn test_drag_point() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(280.0, 200.0)]);
+
+        // Pressed a few pixels off the point, which moves with the mouse
+        canvas.handle_event(InputEvent::MouseMove(43.0, 198.0));
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        for (x, y) in [(60.0, 150.0), (80.0, 100.0)] {
+            canvas.handle_event(InputEvent::MouseMove(x, y));
+            canvas.render(&mut buffer, 320, 240);
+        }
+        assert_eq!(canvas.manager().points()[0], Point::new(80.0, 100.0));
+        canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+
+        // A line is drawn to where the point was dropped, and no point was added
+        assert_ne!(buffer[150 * 320 + 180], 0);
+        assert_eq!(canvas.manager().points(), &[Point::new(80.0, 100.0), Point::new(280.0, 200.0)]);
+
+        // Away from the points, a press places a new one
+        canvas.handle_event(InputEvent::MouseMove(160.0, 40.0));
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().points().len(), 3);
+    }
+
+    #[test]
+    fn test_reload_while_dragging() {
+        let path = std::env::temp_dir().join(format!("chaikin-reload-{}.txt", std::process::id()));
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let points = vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)];
+        canvas.manager().set_points(points);
+
+        // The last point is dragged when the file is changed to have a single one
+        canvas.handle_event(InputEvent::MouseMove(280.0, 200.0));
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        std::fs::write(&path, "10 10\n").unwrap();
+        canvas.manager().reload_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        // The drag is let go of rather than reaching past the points
+        canvas.handle_event(InputEvent::MouseMove(200.0, 100.0));
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().points(), &[Point::new(10.0, 10.0)]);
+        assert_eq!(canvas.manager().state.selected, None);
+    }
+
+    #[test]
+    fn test_delete_point() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let points = vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)];
+        canvas.manager().set_points(points.clone());
+
+        // Away from the points, nothing is deleted, nor added
+        for (x, y) in [(100.0, 150.0), (162.0, 43.0)] {
+            canvas.handle_event(InputEvent::MouseMove(x, y));
+            canvas.handle_event(InputEvent::MouseDown(MouseButton::Right));
+            canvas.render(&mut buffer, 320, 240);
+            canvas.handle_event(InputEvent::MouseUp(MouseButton::Right));
+            canvas.render(&mut buffer, 320, 240);
+        }
+        assert_eq!(canvas.manager().points(), &[points[0], points[2]]);
+        // The line now goes straight across, under where the deleted point was
+        assert_ne!(buffer[200 * 320 + 160], buffer[10 * 320 + 10]);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::Z));
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().points(), points.as_slice());
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        fn press(canvas: &mut EmbeddedCanvas, keys: &[Key]) {
+            for key in keys {
+                canvas.handle_event(InputEvent::KeyDown(*key));
+            }
+            canvas.render(&mut vec![0; 320 * 240], 320, 240);
+            for key in keys {
+                canvas.handle_event(InputEvent::KeyUp(*key));
+            }
+        }
+        fn drag(canvas: &mut EmbeddedCanvas, path: &[(f32, f32)]) {
+            let mut buffer = vec![0; 320 * 240];
+            canvas.handle_event(InputEvent::MouseMove(path[0].0, path[0].1));
+            canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+            canvas.render(&mut buffer, 320, 240);
+            for (x, y) in &path[1..] {
+                canvas.handle_event(InputEvent::MouseMove(*x, *y));
+                canvas.render(&mut buffer, 320, 240);
+            }
+            canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+            canvas.render(&mut buffer, 320, 240);
+        }
+        let mut canvas = EmbeddedCanvas::new();
+
+        drag(&mut canvas, &[(40.0, 200.0)]);
+        drag(&mut canvas, &[(280.0, 200.0)]);
+        // The whole drag is a single edit
+        drag(&mut canvas, &[(40.0, 200.0), (60.0, 150.0), (80.0, 100.0)]);
+        assert_eq!(canvas.manager().points(), &[Point::new(80.0, 100.0), Point::new(280.0, 200.0)]);
+
+        press(&mut canvas, &[Key::LeftCtrl, Key::Z]);
+        assert_eq!(canvas.manager().points(), &[Point::new(40.0, 200.0), Point::new(280.0, 200.0)]);
+        press(&mut canvas, &[Key::LeftCtrl, Key::Z]);
+        assert_eq!(canvas.manager().points(), &[Point::new(40.0, 200.0)]);
+
+        press(&mut canvas, &[Key::LeftCtrl, Key::Y]);
+        press(&mut canvas, &[Key::LeftCtrl, Key::LeftShift, Key::Z]);
+        assert_eq!(canvas.manager().points(), &[Point::new(80.0, 100.0), Point::new(280.0, 200.0)]);
+
+        // Clearing the points can be undone too
+        canvas.manager().reset();
+        press(&mut canvas, &[Key::LeftCtrl, Key::Z]);
+        assert_eq!(canvas.manager().points().len(), 2);
+    }
+
+    #[test]
+    fn test_session_stats() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        for (x, y) in [(40.0, 200.0), (160.0, 40.0)] {
+            canvas.handle_event(InputEvent::MouseMove(x, y));
+            canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+            canvas.render(&mut buffer, 320, 240);
+            canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+            canvas.render(&mut buffer, 320, 240);
+        }
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::Z));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::Z));
+
+        let stats = &canvas.manager().state.stats;
+        assert_eq!((stats.points_placed, stats.undos), (2, 1));
+        assert!(stats.time_in(AnimationState::Drawing) > Duration::ZERO);
+
+        // The panel is only drawn once asked for
+        let background = buffer[30 * 320 + 30];
+        canvas.handle_event(InputEvent::KeyDown(Key::I));
+        canvas.render(&mut buffer, 320, 240);
+        assert_ne!(buffer[30 * 320 + 30], background);
+    }
+
+    #[test]
+    fn test_pause_and_step() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let mut press = |canvas: &mut EmbeddedCanvas, key: Key| {
+            canvas.handle_event(InputEvent::KeyDown(key));
+            canvas.render(&mut buffer, 320, 240);
+            canvas.handle_event(InputEvent::KeyUp(key));
+            canvas.render(&mut buffer, 320, 240);
+        };
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.manager().set_step_interval(Duration::from_millis(125));
+
+        // Nothing to pause or step through while drawing
+        press(&mut canvas, Key::Right);
+        assert_eq!(canvas.manager().state.current_step, 0);
+
+        press(&mut canvas, Key::Enter);
+        press(&mut canvas, Key::Space);
+        assert!(canvas.manager().state.paused);
+        let paused_at = canvas.manager().state.current_step;
+        let max_steps = canvas.manager().state.max_steps;
+        // Counted from 1 in the toast, as in the HUD
+        let paused_toast = format!("Paused at step {}/{}", paused_at + 1, max_steps);
+        assert_eq!(canvas.manager().overlays.toast(), Some(paused_toast.as_str()));
+        assert!(canvas.manager().hud_text().contains(&format!("step {}/{}", paused_at + 1, max_steps)));
+        std::thread::sleep(Duration::from_millis(200));
+        canvas.manager().update();
+        assert_eq!(canvas.manager().state.current_step, paused_at);
+
+        // Stepping back from the first step goes around to the last one
+        press(&mut canvas, Key::Left);
+        let step = (paused_at + max_steps - 1) % max_steps;
+        assert_eq!(canvas.manager().state.current_step, step);
+        assert_eq!(canvas.manager().overlays.toast(), Some(format!("Step {}/{}", step + 1, max_steps).as_str()));
+        press(&mut canvas, Key::Right);
+        press(&mut canvas, Key::Right);
+        assert_eq!(canvas.manager().state.current_step, (paused_at + 1) % max_steps);
+
+        press(&mut canvas, Key::Space);
+        assert!(!canvas.manager().state.paused);
+    }
+
+    #[test]
+    fn test_max_steps() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let mut press = |canvas: &mut EmbeddedCanvas, key: Key| {
+            canvas.handle_event(InputEvent::KeyDown(key));
+            canvas.render(&mut buffer, 320, 240);
+            canvas.handle_event(InputEvent::KeyUp(key));
+            canvas.render(&mut buffer, 320, 240);
+        };
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.manager().set_step_interval(Duration::from_millis(125));
+        canvas.manager().set_max_steps(3);
+
+        // Nothing to change while drawing
+        press(&mut canvas, Key::Up);
+        assert_eq!(canvas.manager().state.max_steps, 3);
+
+        press(&mut canvas, Key::Enter);
+        press(&mut canvas, Key::Up);
+        assert_eq!(canvas.manager().state.max_steps, 4);
+        assert!(canvas.manager().hud_text().contains("/4"));
+
+        // The animation starts over after the last step
+        canvas.manager().state.current_step = 3;
+        std::thread::sleep(Duration::from_millis(150));
+        canvas.manager().update();
+        assert_eq!(canvas.manager().state.current_step, 0);
+
+        for _ in 0..5 {
+            press(&mut canvas, Key::Down);
+        }
+        assert_eq!(canvas.manager().state.max_steps, 1);
+        assert_eq!(canvas.manager().state.current_step, 0);
+
+        canvas.manager().set_max_steps(MAX_STEPS_LIMIT);
+        press(&mut canvas, Key::Up);
+        assert_eq!(canvas.manager().state.max_steps, MAX_STEPS_LIMIT);
+    }
+
+    #[test]
+    fn test_formulas_panel() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.render(&mut buffer, 320, 240);
+        let background = buffer[50 * 320 + 300];
+
+        canvas.handle_event(InputEvent::KeyDown(Key::F));
+        canvas.render(&mut buffer, 320, 240);
+        assert_ne!(buffer[50 * 320 + 300], background);
+
+        // The panel fits the formulas of the scheme shown, with its ratios
+        let manager = canvas.manager();
+        let chaikin = manager.rich_text_width("Q_i = 0.75·P_i + 0.25·P_{i+1}", 16.0);
+        assert!(chaikin > manager.text_advance("Q = 0.75·P + 0.25·P", 16.0));
+        assert!(chaikin < manager.text_advance("Q_i = 0.75·P_i + 0.25·P_{i+1}", 16.0));
+    }
+
+    #[test]
+    fn test_styled_text() {
+        let mut canvas = EmbeddedCanvas::new();
+        let manager = canvas.manager();
+        let plain = manager.text_advance("Enter", 16.0);
+        assert!((manager.rich_text_width("**Enter**", 16.0) - plain).abs() < plain * 0.1);
+        assert!(manager.rich_text_width("[Enter]", 16.0) > plain + 10.0);
+        assert!(manager.rich_text_width("# Enter", 16.0) > plain * 1.2);
+
+        // Bold text is set in a heavier instance of the font
+        let mut buffer = vec![0; 320 * 240];
+        canvas.render(&mut buffer, 320, 240);
+        let manager = canvas.manager();
+        let mut ink = |markup: &str| {
+            manager.clear_buffer();
+            manager.draw_rich_text(10, 10, markup, 0x00FFFFFF, 16.0);
+            manager.raster.pixels().iter().map(|pixel| pixel & 0xFF).sum::<u32>()
+        };
+        assert!(ink("**Enter**") as f32 > ink("Enter") as f32 * 1.2);
+
+        // Keys are boxed: the box's left side is drawn before the text starts
+        manager.clear_buffer();
+        manager.draw_rich_text(10, 10, "[A]", 0x00FFFFFF, 16.0);
+        let background = manager.theme.background;
+        let pixels = manager.raster.pixels();
+        assert_ne!(pixels[20 * 320 + 12], background);
+        assert_eq!(pixels[20 * 320 + 14], background);
+    }
+
+    #[test]
+    fn test_angle_labels() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 80.0), Point::new(280.0, 200.0)]);
+        canvas.render(&mut buffer, 320, 240);
+        let without = buffer.clone();
+
+        // The label of the right angle is drawn above the middle point, outside of the corner
+        canvas.handle_event(InputEvent::KeyDown(Key::A));
+        canvas.render(&mut buffer, 320, 240);
+        let changed: Vec<(usize, usize)> = (0..buffer.len())
+            .filter(|&i| buffer[i] != without[i])
+            .map(|i| (i % 320, i / 320))
+            .collect();
+        assert!(!changed.is_empty());
+        assert!(changed.iter().all(|&(x, y)| (140..180).contains(&x) && (50..80).contains(&y)));
+    }
+
+    #[test]
+    fn test_interpolated_animation() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let points = vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)];
+        canvas.manager().set_points(points);
+        canvas.manager().show_step(0);
+        canvas.render(&mut buffer, 320, 240);
+        // Above the HUD, which tells whether the animation is paused
+        let curve = |buffer: &[u32]| buffer[..190 * 320].to_vec();
+        let first = curve(&buffer);
+        canvas.manager().show_step(1);
+        canvas.render(&mut buffer, 320, 240);
+        let second = curve(&buffer);
+
+        // Half way through the first step, the corner is half cut
+        let manager = canvas.manager();
+        manager.state.paused = false;
+        manager.state.current_step = 0;
+        manager.capture_pending = false;
+        manager.step_interval = Duration::from_secs(60);
+        manager.last_call = Instant::now() - Duration::from_secs(30);
+        canvas.render(&mut buffer, 320, 240);
+        assert_ne!(curve(&buffer), first);
+        assert_ne!(curve(&buffer), second);
+
+        // Without interpolation, the step is shown as it is
+        canvas.manager().set_interpolate(false);
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(curve(&buffer), first);
+    }
+
+    #[test]
+    fn test_hud() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().hud_text(), "Drawing: 0 points · Chaikin");
+        // It sits in the bottom left corner
+        assert_ne!(buffer[220 * 320 + 12], buffer[220 * 320 + 300]);
+
+        canvas.manager().set_points(vec![Point::new(40.0, 40.0)]);
+        assert_eq!(canvas.manager().hud_text(), "Drawing: 1 point · Chaikin");
+        canvas.manager().set_points(vec![Point::new(40.0, 40.0), Point::new(80.0, 40.0)]);
+        canvas.manager().show_step(2);
+        assert_eq!(canvas.manager().hud_text(), "Animating: step 3/7 (paused) · 40.0 px · Chaikin");
+
+        // L goes up a degree from Chaikin's scheme, which is shown
+        canvas.handle_event(InputEvent::KeyDown(Key::L));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::L));
+        assert_eq!(canvas.manager().hud_text(), "Animating: step 3/7 (paused) · 40.0 px · Cubic B-spline · degree 3");
+        assert_eq!(canvas.manager().overlays.toast(), Some("Cubic B-spline, degree 3"));
+
+        // A scheme given as is has no name to show
+        canvas.manager().set_smoother(Box::new(crate::algorithm::CubicBSpline));
+        assert_eq!(canvas.manager().hud_text(), "Animating: step 3/7 (paused) · 40.0 px");
+    }
+
+    #[test]
+    fn test_units() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().set_units("4/mm".parse().unwrap());
+        canvas.manager().set_points(vec![Point::new(40.0, 40.0), Point::new(80.0, 40.0), Point::new(80.0, 70.0)]);
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().hud_text(), "Drawing: 3 points · 17.5 mm · Chaikin");
+
+        // The curve cuts the corner, so it is shorter than its control polygon
+        canvas.manager().show_step(1);
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().hud_text(), "Animating: step 2/7 (paused) · 16.2 mm · Chaikin");
+
+        canvas.manager().set_number_format(crate::numbers::NumberFormat::for_locale("de_DE"));
+        assert_eq!(canvas.manager().hud_text(), "Animating: step 2/7 (paused) · 16,2 mm · Chaikin");
+
+        // Closed, with the area it encloses: half of the 10 by 7.5 mm box
+        canvas.manager().set_number_format(crate::numbers::NumberFormat::default());
+        canvas.manager().show_step(0);
+        canvas.manager().set_closed(true);
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().hud_text(), "Animating: step 1/7 (paused) · 30.0 mm · 37.5 mm² · Chaikin");
+    }
+
+    #[test]
+    fn test_bezier() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 640 * 480];
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.handle_event(InputEvent::MouseLeave);
+        canvas.handle_event(InputEvent::KeyDown(Key::B));
+        canvas.render(&mut buffer, 640, 480);
+        canvas.handle_event(InputEvent::KeyUp(Key::B));
+        assert!(canvas.manager().hud_text().ends_with(" · Bézier"));
+
+        // The curve is traced from its first point, through the middle of the construction
+        let middle = 120 * 640 + 160;
+        canvas.manager().show_step(0);
+        canvas.render(&mut buffer, 640, 480);
+        let background = buffer[middle];
+        canvas.manager().show_step(6);
+        canvas.render(&mut buffer, 640, 480);
+        assert_ne!(buffer[middle], background);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::B));
+        canvas.render(&mut buffer, 640, 480);
+        assert!(canvas.manager().hud_text().ends_with(" · Chaikin"));
+    }
+
+    #[test]
+    fn test_switch_scheme() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 640 * 480];
+        let points = vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)];
+        canvas.manager().set_points(points.clone());
+        canvas.handle_event(InputEvent::KeyDown(Key::C));
+        canvas.render(&mut buffer, 640, 480);
+        canvas.handle_event(InputEvent::KeyUp(Key::C));
+
+        canvas.handle_event(InputEvent::KeyDown(Key::Key2));
+        canvas.render(&mut buffer, 640, 480);
+        canvas.handle_event(InputEvent::KeyUp(Key::Key2));
+        assert!(canvas.manager().hud_text().ends_with(" · Catmull-Rom"));
+        // Catmull-Rom passes through the points and keeps the shape closed
+        assert!(canvas.manager().closed);
+        let curve = canvas.manager().step_points(1);
+        assert_eq!(curve.len(), 6);
+        assert!(points.iter().all(|point| curve.contains(point)));
+
+        // The four-point scheme opens it
+        canvas.handle_event(InputEvent::KeyDown(Key::Key4));
+        canvas.render(&mut buffer, 640, 480);
+        assert!(canvas.manager().hud_text().ends_with(" · Four-point"));
+        assert!(!canvas.manager().closed);
+    }
+
+    #[test]
+    fn test_quiz() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 640 * 480];
+        canvas.render(&mut buffer, 640, 480);
+        let blank = buffer.clone();
+
+        // The target is shown before any point is placed
+        canvas.manager().start_quiz(3);
+        canvas.render(&mut buffer, 640, 480);
+        assert_ne!(buffer, blank);
+
+        // The shape the target came from matches it with the fewest points
+        let shape = Demo::new(3).next_shape(640, 480);
+        canvas.manager().set_points(shape);
+        canvas.handle_event(InputEvent::KeyDown(Key::Enter));
+        canvas.render(&mut buffer, 640, 480);
+        assert!(canvas.manager().overlays.toast().unwrap().contains("the fewest"));
+
+        canvas.handle_event(InputEvent::KeyUp(Key::Enter));
+        canvas.handle_event(InputEvent::KeyDown(Key::Q));
+        canvas.render(&mut buffer, 640, 480);
+        assert!(canvas.manager().quiz.is_none());
+    }
+
+    #[test]
+    fn test_tutorial() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let mut press = |canvas: &mut EmbeddedCanvas, keys: &[Key]| {
+            for key in keys {
+                canvas.handle_event(InputEvent::KeyDown(*key));
+            }
+            canvas.render(&mut buffer, 320, 240);
+            for key in keys {
+                canvas.handle_event(InputEvent::KeyUp(*key));
+            }
+            canvas.render(&mut buffer, 320, 240);
+        };
+        canvas.manager().offer_tutorial();
+        press(&mut canvas, &[Key::Y]);
+        let lesson = |canvas: &mut EmbeddedCanvas| canvas.manager().tutorial.as_ref().and_then(|t| t.lesson());
+        assert_eq!(lesson(&mut canvas), Some(Lesson::PlacePoints));
+
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        press(&mut canvas, &[]);
+        assert_eq!(lesson(&mut canvas), Some(Lesson::Animate));
+        // The hint points at the last point
+        assert!(canvas.manager().tutorial_hints.iter().any(|hint| matches!(hint, OverlayKind::Arrow { .. })));
+
+        press(&mut canvas, &[Key::Enter]);
+        assert_eq!(lesson(&mut canvas), Some(Lesson::ChangeSpeed));
+        press(&mut canvas, &[Key::Minus]);
+        assert_eq!(canvas.manager().step_interval, Duration::from_secs(2));
+        assert_eq!(lesson(&mut canvas), Some(Lesson::Export));
+
+        // Exporting ends it, and its hint goes away
+        canvas.manager().state.file_path = Some(std::env::temp_dir().join(format!("chaikin-tutorial-{}.txt", std::process::id())));
+        press(&mut canvas, &[Key::LeftCtrl, Key::S]);
+        assert!(canvas.manager().tutorial.is_none());
+        assert!(canvas.manager().overlays.iter().all(|overlay| !matches!(overlay.kind, OverlayKind::Label { .. })));
+        while !canvas.manager().writes.is_empty() {
+            press(&mut canvas, &[]);
+        }
+        std::fs::remove_file(std::env::temp_dir().join(format!("chaikin-tutorial-{}.svg", std::process::id()))).unwrap();
+    }
+
+    #[test]
+    fn test_color_picker() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::K));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyUp(Key::K));
+
+        // The bottom of the square is black; the click picks a color instead of adding a point
+        let (left, top) = ColorPicker::origin(320);
+        canvas.handle_event(InputEvent::MouseMove(left as f32 + 20.0, top as f32 + 183.0));
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().theme().point, 0);
+        assert!(canvas.manager().points().is_empty());
+
+        // Closing the picker while the button is still down doesn't place a point either
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+        canvas.handle_event(InputEvent::KeyDown(Key::Enter));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::Enter));
+        canvas.render(&mut buffer, 320, 240);
+        assert!(canvas.manager().color_picker.is_none());
+        assert!(canvas.manager().points().is_empty());
+        canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::K));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyUp(Key::K));
+
+        // Escape closes the picker without closing the canvas
+        canvas.handle_event(InputEvent::KeyDown(Key::Escape));
+        assert!(canvas.render(&mut buffer, 320, 240));
+        assert!(canvas.manager().color_picker.is_none());
+    }
+
+    #[test]
+    fn test_toast_in_small_windows() {
+        let message = "Could not save /home/someone/shapes/a rather long name.txt: permission denied";
+        for (width, height) in [(1, 1), (8, 8), (60, 20), (120, 45), (200, 400)] {
+            let mut canvas = EmbeddedCanvas::new();
+            let mut buffer = vec![0; width * height];
+            canvas.manager().show_toast(message);
+            canvas.render(&mut buffer, width, height);
+        }
+
+        // Long messages wrap onto several lines, each fitting the window
+        let mut canvas = EmbeddedCanvas::new();
+        let lines = canvas.manager().wrap_text(message, TOAST_FONT_SIZE, 260.0);
+        assert!(lines.len() > 1);
+        assert_eq!(lines.join(" "), message);
+        assert!(lines.iter().all(|line| canvas.manager().text_width(line, TOAST_FONT_SIZE) <= 260.0));
+        assert_eq!(canvas.manager().wrap_text("OK", TOAST_FONT_SIZE, 0.0), ["OK"]);
+
+        // The toast grows upward, past where a single line would end
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().show_toast(message);
+        canvas.render(&mut buffer, 320, 240);
+        let background = buffer[5 * 320 + 5];
+        assert_ne!(buffer[170 * 320 + 160], background);
+    }
+
+    #[test]
+    fn test_text_width() {
+        let mut canvas = EmbeddedCanvas::new();
+        let manager = canvas.manager();
+        // Trailing spaces count, and leading ones push the text right
+        assert!(manager.text_width("OK  ", 16.0) > manager.text_width("OK", 16.0));
+        assert!(manager.text_width("   ", 16.0) > 0.0);
+        assert_eq!(manager.text_width("", 16.0), 0.0);
+        let (left, _) = manager.text_extents("  OK", 16.0);
+        assert!(left > manager.text_extents("OK", 16.0).0);
+
+        // A toast's text is centered in the canvas, to the pixel
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().show_toast("Jy");
+        canvas.render(&mut buffer, 320, 240);
+        let row = 240 - 40;
+        let lit: Vec<usize> = (0..320)
+            .filter(|x| (row - 10..row + 10).any(|y| buffer[y * 320 + x] & 0xFF > 0xA0))
+            .collect();
+        let (first, last) = (lit[0], lit[lit.len() - 1]);
+        assert!((first + last).abs_diff(320) <= 2, "text from {} to {}", first, last);
+    }
+
+    #[test]
+    fn test_modals() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let mut frame = |canvas: &mut EmbeddedCanvas, events: &[InputEvent]| {
+            for event in events {
+                canvas.handle_event(*event);
+            }
+            let running = canvas.render(&mut buffer, 320, 240);
+            for event in events {
+                if let InputEvent::KeyDown(key) = event {
+                    canvas.handle_event(InputEvent::KeyUp(*key));
+                }
+            }
+            running
+        };
+
+        frame(&mut canvas, &[InputEvent::KeyDown(Key::LeftCtrl), InputEvent::KeyDown(Key::E)]);
+        frame(&mut canvas, &"12, 34".chars().map(InputEvent::Char).collect::<Vec<_>>());
+        frame(&mut canvas, &[InputEvent::KeyDown(Key::Enter)]);
+        assert_eq!(canvas.manager().points(), &[Point::new(12.0, 34.0)]);
+
+        // The point is unsaved, so closing asks first, and cancelling keeps the canvas open
+        assert!(frame(&mut canvas, &[InputEvent::KeyDown(Key::Escape)]));
+        assert!(canvas.manager().modal.is_some());
+        assert!(frame(&mut canvas, &[InputEvent::KeyDown(Key::C)]));
+        assert!(canvas.manager().modal.is_none());
+
+        // So does resetting
+        frame(&mut canvas, &[InputEvent::KeyDown(Key::LeftCtrl), InputEvent::KeyDown(Key::R)]);
+        assert_eq!(canvas.manager().points().len(), 1);
+        frame(&mut canvas, &[InputEvent::KeyDown(Key::Y)]);
+        assert!(canvas.manager().points().is_empty());
+
+        assert!(!frame(&mut canvas, &[InputEvent::KeyDown(Key::Escape)]));
+    }
+
+    #[test]
+    fn test_describe_shape() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let mut frame = |canvas: &mut EmbeddedCanvas, events: &[InputEvent]| {
+            for event in events {
+                canvas.handle_event(*event);
+            }
+            canvas.render(&mut buffer, 320, 240);
+            for event in events {
+                if let InputEvent::KeyDown(key) = event {
+                    canvas.handle_event(InputEvent::KeyUp(*key));
+                }
+            }
+        };
+
+        // The name, the notes and the tags are asked one after the other
+        frame(&mut canvas, &[InputEvent::KeyDown(Key::LeftCtrl), InputEvent::KeyDown(Key::D)]);
+        for line in ["Star", "Cut twice / then closed", "closed, week 3"] {
+            frame(&mut canvas, &line.chars().map(InputEvent::Char).collect::<Vec<_>>());
+            frame(&mut canvas, &[InputEvent::KeyDown(Key::Enter)]);
+        }
+        assert!(canvas.manager().modal.is_none());
+        assert_eq!(canvas.manager().metadata(), &Metadata {
+            name: "Star".to_string(),
+            notes: "Cut twice\nthen closed".to_string(),
+            tags: vec!["closed".to_string(), "week 3".to_string()],
+        });
+        assert!(canvas.manager().hud_text().starts_with("Star · "));
+
+        // They are saved with the points, and read back
+        let path = std::env::temp_dir().join(format!("chaikin-describe-{}.txt", std::process::id()));
+        canvas.manager().state.file_path = Some(path.clone());
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0)]);
+        canvas.manager().save().unwrap();
+        let metadata = canvas.manager().metadata().clone();
+        canvas.manager().reset();
+        canvas.manager().open_file(&path);
+        assert_eq!(canvas.manager().metadata(), &metadata);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_search_shapes() {
+        let directory = std::env::temp_dir().join(format!("chaikin-search-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("a.txt"), "# name: Big star\n0 0\n100 0\n").unwrap();
+        std::fs::write(directory.join("b.txt"), "# name: Spiral\n1000 1000\n1100 1200\n").unwrap();
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 640 * 480];
+        canvas.render(&mut buffer, 640, 480);
+        canvas.manager().open_file(&directory.join("a.txt"));
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::F));
+        canvas.render(&mut buffer, 640, 480);
+        canvas.handle_event(InputEvent::KeyUp(Key::F));
+        canvas.handle_event(InputEvent::KeyUp(Key::LeftCtrl));
+        for c in "spi".chars() {
+            canvas.handle_event(InputEvent::Char(c));
+        }
+        canvas.render(&mut buffer, 640, 480);
+        canvas.handle_event(InputEvent::KeyDown(Key::Enter));
+        canvas.render(&mut buffer, 640, 480);
+
+        // The spiral is opened, off screen no more
+        assert_eq!(canvas.manager().metadata().name, "Spiral");
+        assert_eq!(canvas.manager().points(), [Point::new(270.0, 140.0), Point::new(370.0, 340.0)]);
+        assert!(!canvas.manager().state.dirty);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_background_writes() {
+        let directory = std::env::temp_dir().join(format!("chaikin-writes-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().capture_steps_to(directory.clone());
+        canvas.manager().state.file_path = Some(directory.join("points.txt"));
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::Enter));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::Enter));
+
+        // Saving before closing keeps the frames coming until the points are written
+        canvas.handle_event(InputEvent::KeyDown(Key::Escape));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyDown(Key::S));
+        let start = std::time::Instant::now();
+        while canvas.render(&mut buffer, 320, 240) {
+            assert!(start.elapsed() < Duration::from_secs(10));
+        }
+
+        assert!(!canvas.manager().state.dirty);
+        assert_eq!(crate::document::read_points(&directory.join("points.txt")).unwrap().len(), 3);
+        assert!(directory.join("step-0.png").exists());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_export_svg() {
+        let directory = std::env::temp_dir().join(format!("chaikin-export-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().state.file_path = Some(directory.join("shape.txt"));
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.manager().show_step(2);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::S));
+        canvas.render(&mut buffer, 320, 240);
+        let start = std::time::Instant::now();
+        while !canvas.manager().writes.is_empty() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            canvas.render(&mut buffer, 320, 240);
+        }
+
+        // The control polygon, then the curve at step 2
+        let svg = std::fs::read_to_string(directory.join("shape.svg")).unwrap();
+        let paths: Vec<&str> = svg.lines().filter(|line| line.contains("<path")).collect();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].matches(" L").count(), 2);
+        assert_eq!(paths[1].matches(" L").count(), canvas.manager().step_points(2).len() - 1);
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_session() {
+        let directory = std::env::temp_dir().join(format!("chaikin-session-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let points = vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)];
+        canvas.manager().state.file_path = Some(directory.join("shape.txt"));
+        canvas.manager().set_points(points.clone());
+        canvas.manager().set_scheme(Scheme::Chaikin, (0.1, 0.9));
+        canvas.manager().set_max_steps(4);
+        canvas.handle_event(InputEvent::KeyDown(Key::C));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::KeyUp(Key::C));
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftShift));
+        canvas.handle_event(InputEvent::KeyDown(Key::S));
+        canvas.render(&mut buffer, 320, 240);
+        let start = std::time::Instant::now();
+        while !canvas.manager().writes.is_empty() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            canvas.render(&mut buffer, 320, 240);
+        }
+        let path = directory.join("shape.json");
+        assert!(path.exists());
+
+        // Opening it in a fresh window brings back the points and the settings
+        let mut canvas = EmbeddedCanvas::new();
+        canvas.manager().open_file(&path);
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().state.points, points);
+        assert_eq!(canvas.manager().scheme, Some(Scheme::Chaikin));
+        assert_eq!(canvas.manager().ratios, (0.1, 0.9));
+        assert!(canvas.manager().closed);
+        assert_eq!(canvas.manager().state.max_steps, 4);
+        assert_eq!(canvas.manager().state.file_path, None);
+
+        // Ratios edited out of order are left for the standard ones
+        let mut session: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        session["settings"]["ratios"] = serde_json::json!([0.9, 0.1]);
+        let edited = directory.join("edited.json");
+        std::fs::write(&edited, session.to_string()).unwrap();
+        let mut canvas = EmbeddedCanvas::new();
+        canvas.manager().open_file(&edited);
+        assert_eq!(canvas.manager().state.points, points);
+        assert_eq!(canvas.manager().ratios, (0.25, 0.75));
+        assert!(canvas.manager().overlays.toast().is_some_and(|toast| toast.contains("ratios")));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_screenshot() {
+        let directory = std::env::temp_dir().join(format!("chaikin-screenshot-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.manager().state.file_path = Some(directory.join("shape.txt"));
+        canvas.render(&mut buffer, 320, 240);
+
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::P));
+        canvas.render(&mut buffer, 320, 240);
+        let start = std::time::Instant::now();
+        while !canvas.manager().writes.is_empty() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            canvas.render(&mut buffer, 320, 240);
+        }
+
+        let names: Vec<String> = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names.len(), 1);
+        assert!(names[0].starts_with("chaikin-") && names[0].ends_with(".png"));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_precise_coordinates() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 640 * 480];
+        let point = Point::new(120.25, 47.8);
+        canvas.manager().set_points(vec![point]);
+        assert_eq!(canvas.manager().coordinates_text(point), "(120, 48)");
+
+        // The readout shows above and right of the point under the mouse
+        let label = |buffer: &[u32]| (20..40).any(|y| (130..200).any(|x| buffer[y * 640 + x] != 0));
+        canvas.handle_event(InputEvent::MouseMove(300.0, 300.0));
+        canvas.render(&mut buffer, 640, 480);
+        assert!(!label(&buffer));
+        canvas.handle_event(InputEvent::MouseMove(120.0, 48.0));
+        canvas.render(&mut buffer, 640, 480);
+        assert!(label(&buffer));
+
+        canvas.handle_event(InputEvent::KeyDown(Key::P));
+        canvas.render(&mut buffer, 640, 480);
+        assert_eq!(canvas.manager().coordinates_text(point), "(120.25, 47.8)");
+        canvas.manager().set_number_format(crate::numbers::NumberFormat::for_locale("fr_FR"));
+        assert_eq!(canvas.manager().coordinates_text(point), "(120,25; 47,8)");
+    }
+
+    #[test]
+    fn test_print_nothing() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 640 * 480];
+
+        for key in [Key::LeftCtrl, Key::LeftShift, Key::P] {
+            canvas.handle_event(InputEvent::KeyDown(key));
+        }
+        canvas.render(&mut buffer, 640, 480);
+        // Nothing is sent to the printer, and no screenshot is taken either
+        assert!(canvas.manager().writes.is_empty());
+        assert_eq!(canvas.manager().overlays.toast(), Some("Place points to print them"));
+    }
+
+    #[test]
+    fn test_level_of_detail() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.render(&mut buffer, 320, 240);
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.manager().set_max_steps(8);
+
+        // Zoomed out, a step whose segments are under a pixel stands in for the last one
+        canvas.manager().view = View { scale: 0.02, ..View::default() };
+        canvas.manager().show_step(7);
+        let ((_, step), _) = canvas.manager().curve.as_ref().unwrap();
+        assert!(*step < 7);
+        assert!(canvas.manager().refined.is_none());
+
+        // Zoomed in, the last step is refined further where it is on screen
+        // on the top of the curve, halfway down from the top of the triangle
+        let offset = Vector2::new(160.0, 120.0) - Vector2::new(160.0, 80.0) * 60.0;
+        canvas.manager().view = View { scale: 60.0, offset };
+        canvas.manager().show_step(7);
+        let ((_, step), curve) = canvas.manager().curve.clone().unwrap();
+        assert_eq!(step, 7);
+        let (_, refined) = canvas.manager().refined.clone().unwrap();
+        assert!(refined.len() > curve.len());
+        assert_eq!((refined.first(), refined.last()), (curve.first(), curve.last()));
+
+        // Not on the steps before it, which the animation goes through
+        canvas.manager().refined = None;
+        canvas.manager().show_step(6);
+        assert!(canvas.manager().refined.is_none());
+    }
+
+    #[test]
+    fn test_long_trace() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        canvas.render(&mut buffer, 320, 240);
+        let background = buffer[50 * 320 + 160];
+
+        // A trace far wider than the canvas, of which only a few chunks are drawn
+        let trace: Vec<Point> = (0..200_000).map(|i| Point::new(i as f32 - 100_000.0, 100.0)).collect();
+        canvas.manager().set_points(trace);
+        canvas.manager().view = View::default();
+        canvas.render(&mut buffer, 320, 240);
+        assert_ne!(buffer[100 * 320 + 160], background);
+        assert_ne!(buffer[100 * 320 + 2], background);
+        assert_eq!(buffer[50 * 320 + 160], background);
+
+        // Panned away from it, nothing is
+        canvas.manager().view.pan((0.0, 200.0));
+        canvas.render(&mut buffer, 320, 240);
+        assert!(buffer.iter().skip(320 * 50).take(320 * 100).all(|&pixel| pixel == background));
+
+        // Nor of its curve, whose steps keep their hierarchies
+        let trace: Vec<Point> = (0..5000).map(|i| Point::new(i as f32 * 10.0 - 25_000.0, 100.0 + (i % 2) as f32)).collect();
+        canvas.manager().set_points(trace);
+        canvas.manager().view = View::default();
+        canvas.manager().show_step(1);
+        canvas.render(&mut buffer, 320, 240);
+        assert_ne!(buffer[100 * 320 + 160], background);
+        assert_eq!(buffer[50 * 320 + 160], background);
+    }
+
+    #[test]
+    fn test_settles() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        // As if nothing happened for a while
+        let wait = |canvas: &mut EmbeddedCanvas| canvas.manager().last_activity -= IDLE_DELAY;
+
+        canvas.render(&mut buffer, 320, 240);
+        assert!(!canvas.manager().is_settled());
+        wait(&mut canvas);
+        assert!(canvas.manager().is_settled());
+
+        // Input draws frames again, and so does the halo of a new point until it fades out
+        canvas.handle_event(InputEvent::MouseMove(40.0, 200.0));
+        canvas.render(&mut buffer, 320, 240);
+        assert!(!canvas.manager().is_settled());
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+        canvas.render(&mut buffer, 320, 240);
+        wait(&mut canvas);
+        assert!(!canvas.manager().is_settled());
+        canvas.manager().overlays.clear();
+        assert!(canvas.manager().is_settled());
+
+        // The animation keeps drawing frames, until paused
+        canvas.manager().set_points(vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)]);
+        canvas.manager().start_animation();
+        canvas.manager().overlays.clear();
+        wait(&mut canvas);
+        assert!(!canvas.manager().is_settled());
+        canvas.manager().state.paused = true;
+        assert!(canvas.manager().is_settled());
+
+        // The host draws the frames of an embedded canvas, which is never idle
+        assert!(!canvas.manager().is_idle());
+    }
+
+    #[test]
+    fn test_insert_point() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let click = |canvas: &mut EmbeddedCanvas, buffer: &mut [u32], (x, y): (f32, f32)| {
+            canvas.handle_event(InputEvent::MouseMove(x, y));
+            canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+            canvas.render(buffer, 320, 240);
+            canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+            canvas.render(buffer, 320, 240);
+        };
+        for point in [(40.0, 40.0), (280.0, 40.0), (280.0, 200.0)] {
+            click(&mut canvas, &mut buffer, point);
+        }
+
+        // Alt+click near a segment splits it, on the segment, and selects the new point
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftAlt));
+        click(&mut canvas, &mut buffer, (150.0, 42.0));
+        assert_eq!(canvas.manager().points(), [
+            Point::new(40.0, 40.0),
+            Point::new(150.0, 40.0),
+            Point::new(280.0, 40.0),
+            Point::new(280.0, 200.0),
+        ]);
+        assert_eq!(canvas.manager().state.selected, Some(1));
+
+        // Away from every segment, a point is added at the end as usual
+        click(&mut canvas, &mut buffer, (40.0, 200.0));
+        assert_eq!(canvas.manager().points().len(), 5);
+        assert_eq!(canvas.manager().points()[4], Point::new(40.0, 200.0));
+        canvas.handle_event(InputEvent::KeyUp(Key::LeftAlt));
+
+        // Closed, the segment back to the first point can be split too
+        canvas.manager().set_closed(true);
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftAlt));
+        click(&mut canvas, &mut buffer, (40.0, 120.0));
+        assert_eq!(canvas.manager().points()[5], Point::new(40.0, 120.0));
+
+        // Each insertion is undone on its own
+        canvas.handle_event(InputEvent::KeyUp(Key::LeftAlt));
+        canvas.handle_event(InputEvent::KeyDown(Key::LeftCtrl));
+        canvas.handle_event(InputEvent::KeyDown(Key::Z));
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().points().len(), 5);
+    }
+
+    #[test]
+    fn test_split_at_curve() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let alt_click = |canvas: &mut EmbeddedCanvas, buffer: &mut [u32], (x, y): (f32, f32)| {
+            canvas.handle_event(InputEvent::KeyDown(Key::LeftAlt));
+            canvas.handle_event(InputEvent::MouseMove(x, y));
+            canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+            canvas.render(buffer, 320, 240);
+            canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+            canvas.handle_event(InputEvent::KeyUp(Key::LeftAlt));
+            canvas.render(buffer, 320, 240);
+        };
+        let points = vec![Point::new(40.0, 200.0), Point::new(160.0, 40.0), Point::new(280.0, 200.0)];
+        canvas.manager().set_points(points.clone());
+        canvas.manager().show_step(2);
+        canvas.render(&mut buffer, 320, 240);
+
+        // Away from the curve, nothing happens
+        alt_click(&mut canvas, &mut buffer, (160.0, 200.0));
+        assert_eq!(canvas.manager().points(), points);
+
+        // The curve's point halfway up its left side comes from the middle of the first
+        // segment, where the shape is split in two
+        canvas.manager().set_closed(true);
+        canvas.render(&mut buffer, 320, 240);
+        alt_click(&mut canvas, &mut buffer, (100.0, 120.0));
+        assert_eq!(canvas.manager().overlays.toast(), Some("Open the shape to split it"));
+        canvas.manager().set_closed(false);
+        canvas.render(&mut buffer, 320, 240);
+        alt_click(&mut canvas, &mut buffer, (100.0, 120.0));
+        assert_eq!(canvas.manager().points(), [Point::new(40.0, 200.0), Point::new(100.0, 120.0)]);
+        assert_eq!(canvas.manager().state.polylines, vec![vec![Point::new(100.0, 120.0), points[1], points[2]]]);
+        assert_eq!(canvas.manager().overlays.toast(), Some("Split the shape in two"));
+    }
+
+    #[test]
+    fn test_nudge() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+        let click = |canvas: &mut EmbeddedCanvas, buffer: &mut [u32], (x, y): (f32, f32)| {
+            canvas.handle_event(InputEvent::MouseMove(x, y));
+            canvas.handle_event(InputEvent::MouseDown(MouseButton::Left));
+            canvas.render(buffer, 320, 240);
+            canvas.handle_event(InputEvent::MouseUp(MouseButton::Left));
+            canvas.render(buffer, 320, 240);
+        };
+        let press = |canvas: &mut EmbeddedCanvas, buffer: &mut [u32], keys: &[Key]| {
+            for &key in keys {
+                canvas.handle_event(InputEvent::KeyDown(key));
+            }
+            canvas.render(buffer, 320, 240);
+            for &key in keys {
+                canvas.handle_event(InputEvent::KeyUp(key));
+            }
+            canvas.render(buffer, 320, 240);
+        };
+
+        click(&mut canvas, &mut buffer, (100.0, 100.0));
+        click(&mut canvas, &mut buffer, (200.0, 150.0));
+        assert_eq!(canvas.manager().state.selected, None);
+
+        // Clicking a point selects it, and the arrows move it a pixel, or ten with Shift
+        click(&mut canvas, &mut buffer, (100.0, 100.0));
+        assert_eq!(canvas.manager().state.selected, Some(0));
+        press(&mut canvas, &mut buffer, &[Key::Right]);
+        press(&mut canvas, &mut buffer, &[Key::LeftShift, Key::Down]);
+        assert_eq!(canvas.manager().points(), [Point::new(101.0, 110.0), Point::new(200.0, 150.0)]);
+        // Which is undone at once
+        press(&mut canvas, &mut buffer, &[Key::LeftCtrl, Key::Z]);
+        assert_eq!(canvas.manager().points()[0], Point::new(100.0, 100.0));
+
+        // Placing a point lets go of the selection
+        click(&mut canvas, &mut buffer, (250.0, 50.0));
+        assert_eq!(canvas.manager().state.selected, None);
+        press(&mut canvas, &mut buffer, &[Key::Left]);
+        assert_eq!(canvas.manager().points()[2], Point::new(250.0, 50.0));
+
+        // Deleting a point before the selected one keeps the same point selected
+        click(&mut canvas, &mut buffer, (200.0, 150.0));
+        canvas.handle_event(InputEvent::MouseMove(100.0, 100.0));
+        canvas.handle_event(InputEvent::MouseDown(MouseButton::Right));
+        canvas.render(&mut buffer, 320, 240);
+        canvas.handle_event(InputEvent::MouseUp(MouseButton::Right));
+        canvas.render(&mut buffer, 320, 240);
+        assert_eq!(canvas.manager().state.selected, Some(0));
+        assert_eq!(canvas.manager().points()[0], Point::new(200.0, 150.0));
+    }
+
+    #[test]
+    fn test_close_request() {
+        let mut canvas = EmbeddedCanvas::new();
+        let mut buffer = vec![0; 320 * 240];
+
+        canvas.handle_event(InputEvent::CloseRequested);
+        assert!(!canvas.render(&mut buffer, 320, 240));
+    }
+}
@@ -0,0 +1,65 @@
+use super::input::Input;
+
+/// The part of the UI that gets the keyboard and mouse, before the canvas does
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Focus {
+    /// Nothing is open, clicks place points
+    #[default]
+    Canvas,
+    ColorPicker,
+    /// The search for a shape takes the keyboard
+    Search,
+    /// A modal takes everything, even over the color picker
+    Modal,
+}
+
+/// Follows the focus from frame to frame. A mouse press that started on a widget belongs to
+/// it until released, so it doesn't fall through to the canvas when the widget closes
+#[derive(Default)]
+pub struct FocusTracker {
+    /// Who had the focus on the last update
+    focus: Focus,
+    /// Whether a button held down was pressed while a widget had the focus
+    pressed_on_widget: bool,
+}
+
+impl FocusTracker {
+    /// Records who has the focus this frame
+    pub fn update(&mut self, focus: Focus, input: &Input) {
+        let held = input.any_mouse_down();
+        self.pressed_on_widget = held && (focus != Focus::Canvas || self.pressed_on_widget);
+        self.focus = focus;
+    }
+
+    /// Whether the canvas gets the mouse this frame
+    pub fn canvas_has_mouse(&self) -> bool {
+        self.focus == Focus::Canvas && !self.pressed_on_widget
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::InputEvent;
+    use minifb::MouseButton;
+
+    #[test]
+    fn test_press_stays_with_widget() {
+        let (mut tracker, mut input) = (FocusTracker::default(), Input::default());
+        input.apply(InputEvent::MouseDown(MouseButton::Left));
+        tracker.update(Focus::Canvas, &input);
+        assert!(tracker.canvas_has_mouse());
+
+        // The press that closes the picker doesn't reach the canvas until released
+        tracker.update(Focus::ColorPicker, &input);
+        tracker.update(Focus::Canvas, &input);
+        assert!(!tracker.canvas_has_mouse());
+
+        input.apply(InputEvent::MouseUp(MouseButton::Left));
+        tracker.update(Focus::Canvas, &input);
+        assert!(tracker.canvas_has_mouse());
+
+        tracker.update(Focus::Modal, &input);
+        assert!(!tracker.canvas_has_mouse());
+    }
+}
@@ -0,0 +1,572 @@
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use ab_glyph::{point, Font, FontArc, FontRef, FontVec, GlyphId, PxScale, ScaleFont, VariableFont};
+use fontdb::Database;
+use rustybuzz::ttf_parser::{self, Tag};
+use rustybuzz::{Direction, UnicodeBuffer, Variation};
+use unicode_bidi::BidiInfo;
+use unicode_script::{Script, UnicodeScript};
+
+use super::theme::FontAxes;
+
+/// The bundled font, Roboto, which is variable in weight and width
+const FONT_DATA: &[u8] = include_bytes!("../../assets/Roboto-VariableFont_wdth_wght.ttf");
+
+/// Glyphs are drawn at this many offsets across a pixel, each of which is kept once drawn
+const SUBPIXEL_STEPS: f32 = 4.0;
+/// The most lines kept laid out, and glyphs kept drawn, before they are all forgotten, e.g.
+/// after many messages were shown
+const LINE_CACHE_SIZE: usize = 1024;
+const GLYPH_CACHE_SIZE: usize = 4096;
+
+/// The fonts looked through for characters the bundled font has no glyph for, e.g. Chinese,
+/// Arabic or Devanagari in localized messages, in the order they are tried
+struct Candidates {
+    database: Database,
+    faces: Vec<fontdb::ID>,
+}
+
+impl Candidates {
+    /// The system's fonts, found through fontconfig or in the system's font directories,
+    /// regular upright faces first
+    fn system() -> Self {
+        let mut database = Database::new();
+        database.load_system_fonts();
+        let mut faces: Vec<_> = database.faces().collect();
+        faces.sort_by_key(|face| face.style != fontdb::Style::Normal || face.weight != fontdb::Weight::NORMAL);
+        let faces = faces.into_iter().map(|face| face.id).collect();
+        Self { database, faces }
+    }
+
+    /// The fonts in the files, in their order. Those that can't be read are skipped
+    fn files(paths: &[PathBuf]) -> Self {
+        let mut database = Database::new();
+        for path in paths {
+            let _ = database.load_font_file(path);
+        }
+        let faces = database.faces().map(|face| face.id).collect();
+        Self { database, faces }
+    }
+
+    /// Whether the face has a glyph for the character, read without loading the whole font,
+    /// as some are tens of megabytes
+    fn covers(&self, id: fontdb::ID, c: char) -> bool {
+        self.database
+            .with_face_data(id, |data, index| ttf_parser::Face::parse(data, index).is_ok_and(|face| face.glyph_index(c).is_some()))
+            .unwrap_or(false)
+    }
+
+    fn load(&self, id: fontdb::ID) -> Option<Fallback> {
+        self.database
+            .with_face_data(id, |data, index| {
+                let font = FontVec::try_from_vec_and_index(data.to_vec(), index).ok()?;
+                Some(Fallback { id, font: FontArc::new(font), index })
+            })
+            .flatten()
+    }
+}
+
+/// A fallback font, read from its file the first time a character is found in it
+struct Fallback {
+    id: fontdb::ID,
+    font: FontArc,
+    /// Which face of its file it is
+    index: u32,
+}
+
+/// Which font a run of text is shaped with: the instance of the bundled font the text is in,
+/// or one of the fallbacks read so far
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum FontChoice {
+    Bundled,
+    Fallback(usize),
+}
+
+/// A run of text shaped on its own, in a single direction, font and script
+#[derive(Debug, PartialEq)]
+struct ShapedRun {
+    range: Range<usize>,
+    font: FontChoice,
+    rtl: bool,
+}
+
+/// A glyph laid out on a line, and where it is drawn from the left end of the line's
+/// baseline, in pixels
+struct ShapedGlyph {
+    font: FontChoice,
+    id: GlyphId,
+    x: f32,
+    y: f32,
+}
+
+/// A line of text shaped in an instance, at a size
+struct ShapedLine {
+    glyphs: Vec<ShapedGlyph>,
+    /// How far the pen moves for the line
+    advance: f32,
+    /// Where the glyphs' ink spans horizontally, in whole pixels, unless there is none
+    ink: Option<(f32, f32)>,
+}
+
+/// A glyph at a size, in an instance if it is of the bundled font, offset into a pixel
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: FontChoice,
+    id: u16,
+    size: u32,
+    axes: Option<(u32, u32)>,
+    offset: (u8, u8),
+}
+
+/// How much a glyph covers each pixel of its bounds, from the whole pixel it is drawn from
+struct GlyphBitmap {
+    left: i32,
+    top: i32,
+    width: usize,
+    coverage: Vec<f32>,
+}
+
+/// The lines laid out, by their text, with the size and the instance they are in
+type LineCache = HashMap<String, Vec<((u32, FontAxes), Rc<ShapedLine>)>>;
+type GlyphCache = HashMap<GlyphKey, Option<GlyphBitmap>>;
+
+/// The application's font, followed by fallbacks for the characters it lacks, which lay out
+/// and draw lines of text. Lines are split into runs of one direction, with the Unicode
+/// bidirectional algorithm, which are shown left to right, and each run into runs of one
+/// font and script, which are shaped on their own: letters join and reorder, and marks sit
+/// on them, as their script wants. Fallbacks are drawn as they are, whichever instance of
+/// the bundled font the text is in
+pub struct Fonts {
+    /// The regular instance of the bundled font
+    primary: FontArc,
+    /// The other instances of the bundled font drawn so far
+    instances: RefCell<Vec<(FontAxes, FontArc)>>,
+    /// The files fallbacks are looked for in, or none for the system's fonts
+    fallback_paths: Option<Vec<PathBuf>>,
+    /// Found the first time a character is missing from the bundled font, as finding the
+    /// system's fonts reads every one of them
+    candidates: OnceCell<Candidates>,
+    /// The fallbacks read so far
+    fallbacks: RefCell<Vec<Fallback>>,
+    /// The fallback each character missing from the bundled font was found in, if any, so
+    /// that the fonts are only looked through once per character
+    fallback_for: RefCell<HashMap<char, Option<usize>>>,
+    /// The lines laid out so far
+    lines: RefCell<LineCache>,
+    /// The glyphs drawn so far
+    glyphs: RefCell<GlyphCache>,
+}
+
+impl Fonts {
+    /// The bundled font, with the system's fonts as fallbacks
+    pub fn load() -> Self {
+        Self::new(None)
+    }
+
+    /// The bundled font, with the fonts at the given paths as fallbacks
+    #[cfg(test)]
+    fn with_fallbacks<P: AsRef<std::path::Path>>(paths: impl IntoIterator<Item = P>) -> Self {
+        Self::new(Some(paths.into_iter().map(|path| path.as_ref().to_path_buf()).collect()))
+    }
+
+    fn new(fallback_paths: Option<Vec<PathBuf>>) -> Self {
+        Self {
+            primary: FontArc::try_from_slice(FONT_DATA).expect("Error loading font"),
+            instances: RefCell::new(Vec::new()),
+            fallback_paths,
+            candidates: OnceCell::new(),
+            fallbacks: RefCell::new(Vec::new()),
+            fallback_for: RefCell::new(HashMap::new()),
+            lines: RefCell::new(HashMap::new()),
+            glyphs: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// The instance of the bundled font at the axes, set up the first time it is asked for
+    fn instance(&self, axes: FontAxes) -> FontArc {
+        if axes == FontAxes::REGULAR {
+            return self.primary.clone();
+        }
+        let mut instances = self.instances.borrow_mut();
+        if let Some((_, font)) = instances.iter().find(|(instance, _)| *instance == axes) {
+            return font.clone();
+        }
+        let mut font = FontRef::try_from_slice(FONT_DATA).expect("Error loading font");
+        font.set_variation(b"wght", axes.weight);
+        font.set_variation(b"wdth", axes.width);
+        let font = FontArc::new(font);
+        instances.push((axes, font.clone()));
+        font
+    }
+
+    /// How far lines of text of the given size reach above and below their baseline, the
+    /// descent being negative. They are the bundled font's, whichever fonts the glyphs are
+    /// from
+    pub fn line_metrics(&self, size: f32) -> (f32, f32) {
+        let font = self.primary.as_scaled(PxScale::from(size));
+        (font.ascent(), font.descent())
+    }
+
+    /// The first font with a glyph for the character, starting with the given instance of
+    /// the bundled font, then the fallbacks read already, then the others. That instance,
+    /// which has room for its missing glyph, if none has one
+    fn font_for(&self, c: char, primary: &FontArc) -> FontChoice {
+        if primary.glyph_id(c).0 != 0 {
+            return FontChoice::Bundled;
+        }
+        if let Some(&found) = self.fallback_for.borrow().get(&c) {
+            return found.map_or(FontChoice::Bundled, FontChoice::Fallback);
+        }
+        let found = self.find_fallback(c);
+        self.fallback_for.borrow_mut().insert(c, found);
+        found.map_or(FontChoice::Bundled, FontChoice::Fallback)
+    }
+
+    /// The fallback with a glyph for the character, reading it if it isn't yet
+    fn find_fallback(&self, c: char) -> Option<usize> {
+        let mut fallbacks = self.fallbacks.borrow_mut();
+        if let Some(found) = fallbacks.iter().position(|fallback| fallback.font.glyph_id(c).0 != 0) {
+            return Some(found);
+        }
+        let candidates = self.candidates.get_or_init(|| match &self.fallback_paths {
+            Some(paths) => Candidates::files(paths),
+            None => Candidates::system(),
+        });
+        let fallback = candidates
+            .faces
+            .iter()
+            .filter(|&&id| fallbacks.iter().all(|fallback| fallback.id != id) && candidates.covers(id, c))
+            .find_map(|&id| candidates.load(id))?;
+        fallbacks.push(fallback);
+        Some(fallbacks.len() - 1)
+    }
+
+    /// Whether the font has a glyph for the character
+    fn has_glyph(&self, font: FontChoice, c: char, primary: &FontArc) -> bool {
+        match font {
+            FontChoice::Bundled => primary.glyph_id(c).0 != 0,
+            FontChoice::Fallback(i) => self.fallbacks.borrow()[i].font.glyph_id(c).0 != 0,
+        }
+    }
+
+    /// Splits the line into the runs shaped on their own, in the order they are shown from
+    /// left to right: the runs of one direction the bidirectional algorithm reorders the
+    /// line into, split where the font or the script changes. Spaces, punctuation and marks
+    /// stay in the run around them, if its font has them
+    fn runs(&self, text: &str, primary: &FontArc) -> Vec<ShapedRun> {
+        let bidi = BidiInfo::new(text, None);
+        let mut runs = Vec::new();
+        for paragraph in &bidi.paragraphs {
+            let (levels, visual) = bidi.visual_runs(paragraph, paragraph.range.clone());
+            for range in visual {
+                let rtl = levels[range.start].is_rtl();
+                let first = runs.len();
+                let mut start = range.start;
+                let mut current: Option<(FontChoice, Script)> = None;
+                for (i, c) in text[range.clone()].char_indices() {
+                    let i = range.start + i;
+                    let script = c.script();
+                    let neutral = matches!(script, Script::Common | Script::Inherited);
+                    if let Some((font, run_script)) = current.as_mut() {
+                        let run_neutral = matches!(run_script, Script::Common | Script::Inherited);
+                        let same_script = neutral || run_neutral || *run_script == script;
+                        if same_script && (self.has_glyph(*font, c, primary) || self.font_for(c, primary) == *font) {
+                            if run_neutral && !neutral {
+                                *run_script = script;
+                            }
+                            continue;
+                        }
+                        runs.push(ShapedRun { range: start..i, font: *font, rtl });
+                        start = i;
+                    }
+                    current = Some((self.font_for(c, primary), script));
+                }
+                if let Some((font, _)) = current {
+                    runs.push(ShapedRun { range: start..range.end, font, rtl });
+                }
+                // The runs of a right-to-left run are shown from its end to its start
+                if rtl {
+                    runs[first..].reverse();
+                }
+            }
+        }
+        runs
+    }
+
+    /// The font of the choice, in the instance for the bundled font
+    fn font(&self, font: FontChoice, axes: FontAxes) -> FontArc {
+        match font {
+            FontChoice::Bundled => self.instance(axes),
+            FontChoice::Fallback(i) => self.fallbacks.borrow()[i].font.clone(),
+        }
+    }
+
+    /// The text laid out on a line in the instance, shaping each of its runs with the font
+    /// it is in. Lines are kept once laid out, e.g. to be measured and then drawn
+    fn shape(&self, text: &str, size: f32, axes: FontAxes) -> Rc<ShapedLine> {
+        let key = (size.to_bits(), axes);
+        if let Some((_, line)) = self.lines.borrow().get(text).and_then(|lines| lines.iter().find(|(laid_out, _)| *laid_out == key)) {
+            return Rc::clone(line);
+        }
+        let line = Rc::new(self.layout(text, size, axes));
+        let mut lines = self.lines.borrow_mut();
+        if lines.len() >= LINE_CACHE_SIZE {
+            lines.clear();
+        }
+        lines.entry(text.to_string()).or_default().push((key, Rc::clone(&line)));
+        line
+    }
+
+    /// Lays the text out in the instance on a line from the left end of its baseline, see
+    /// [`Self::shape`]
+    fn layout(&self, text: &str, size: f32, axes: FontAxes) -> ShapedLine {
+        let primary = self.instance(axes);
+        let scale = PxScale::from(size);
+        let mut x = 0.0;
+        let mut glyphs = Vec::new();
+        for run in self.runs(text, &primary) {
+            let font = self.font(run.font, axes);
+            let index = match run.font {
+                FontChoice::Bundled => 0,
+                FontChoice::Fallback(i) => self.fallbacks.borrow()[i].index,
+            };
+            let Some(mut face) = rustybuzz::Face::from_slice(font.font_data(), index) else {
+                continue;
+            };
+            if run.font == FontChoice::Bundled {
+                face.set_variations(&[
+                    Variation { tag: Tag::from_bytes(b"wght"), value: axes.weight },
+                    Variation { tag: Tag::from_bytes(b"wdth"), value: axes.width },
+                ]);
+            }
+            let mut buffer = UnicodeBuffer::new();
+            buffer.push_str(&text[run.range]);
+            buffer.set_direction(if run.rtl { Direction::RightToLeft } else { Direction::LeftToRight });
+            buffer.guess_segment_properties();
+            let shaped = rustybuzz::shape(&face, &[], buffer);
+
+            // The positions are in the font's units
+            let scaled = font.as_scaled(scale);
+            let (h_scale, v_scale) = (scaled.h_scale_factor(), scaled.v_scale_factor());
+            for (info, position) in shaped.glyph_infos().iter().zip(shaped.glyph_positions()) {
+                glyphs.push(ShapedGlyph {
+                    font: run.font,
+                    id: GlyphId(info.glyph_id as u16),
+                    x: x + position.x_offset as f32 * h_scale,
+                    y: -position.y_offset as f32 * v_scale,
+                });
+                x += position.x_advance as f32 * h_scale;
+            }
+        }
+
+        let mut bitmaps = self.glyphs.borrow_mut();
+        let ink = glyphs
+            .iter()
+            .filter_map(|glyph| {
+                let ((left, _), bitmap) = self.placed(&mut bitmaps, glyph, size, axes, (glyph.x, 0.0));
+                bitmap.map(|bitmap| ((left + bitmap.left) as f32, (left + bitmap.left + bitmap.width as i32) as f32))
+            })
+            .fold(None, |extents, (min, max)| {
+                Some(extents.map_or((min, max), |(left, right): (f32, f32)| (left.min(min), right.max(max))))
+            });
+        ShapedLine { glyphs, advance: x, ink }
+    }
+
+    /// The glyph of a line drawn with the left end of the line's baseline at the point: the
+    /// whole pixel it is drawn from, and how much it covers the pixels around it, unless it
+    /// has no ink, e.g. a space. Each glyph is only drawn the first time it is at its size,
+    /// instance and offset into a pixel
+    fn placed<'a>(
+        &self,
+        bitmaps: &'a mut GlyphCache,
+        glyph: &ShapedGlyph,
+        size: f32,
+        axes: FontAxes,
+        (x, y): (f32, f32),
+    ) -> ((i32, i32), Option<&'a GlyphBitmap>) {
+        let ((left, x_steps), (top, y_steps)) = (subpixel(x), subpixel(y + glyph.y));
+        // Fallbacks are drawn as they are, whatever the instance
+        let axes = (glyph.font == FontChoice::Bundled).then_some(axes);
+        let key = GlyphKey {
+            font: glyph.font,
+            id: glyph.id.0,
+            size: size.to_bits(),
+            axes: axes.map(|axes| (axes.weight.to_bits(), axes.width.to_bits())),
+            offset: (x_steps, y_steps),
+        };
+        if bitmaps.len() >= GLYPH_CACHE_SIZE && !bitmaps.contains_key(&key) {
+            bitmaps.clear();
+        }
+        let bitmap = bitmaps.entry(key).or_insert_with(|| {
+            let font = self.font(glyph.font, axes.unwrap_or(FontAxes::REGULAR));
+            let at = point(f32::from(x_steps) / SUBPIXEL_STEPS, f32::from(y_steps) / SUBPIXEL_STEPS);
+            let outline = font.outline_glyph(glyph.id.with_scale_and_position(size, at))?;
+            let bounds = outline.px_bounds();
+            let (width, height) = (bounds.width() as usize, bounds.height() as usize);
+            let mut coverage = vec![0.0; width * height];
+            outline.draw(|x, y, value| coverage[y as usize * width + x as usize] = value);
+            Some(GlyphBitmap { left: bounds.min.x as i32, top: bounds.min.y as i32, width, coverage })
+        });
+        ((left, top), bitmap.as_ref())
+    }
+
+    /// Draws a line of text in the instance with its top left corner at the given pixel,
+    /// calling `plot` with each pixel the glyphs cover and how much of it they cover, from 0
+    /// to 1
+    pub fn draw(&self, text: &str, size: f32, axes: FontAxes, (x, y): (i32, i32), mut plot: impl FnMut(i32, i32, f32)) {
+        let (ascent, _) = self.line_metrics(size);
+        let line = self.shape(text, size, axes);
+        let mut bitmaps = self.glyphs.borrow_mut();
+        for glyph in &line.glyphs {
+            let at = (x as f32 + glyph.x, y as f32 + ascent);
+            let ((left, top), Some(bitmap)) = self.placed(&mut bitmaps, glyph, size, axes, at) else {
+                continue;
+            };
+            let (left, top) = (left + bitmap.left, top + bitmap.top);
+            for (i, &coverage) in bitmap.coverage.iter().enumerate() {
+                if coverage > 0.0 {
+                    plot(left + (i % bitmap.width) as i32, top + (i / bitmap.width) as i32, coverage);
+                }
+            }
+        }
+    }
+
+    /// How far the pen moves for the text, trailing spaces included, which is where the
+    /// text after it starts
+    pub fn advance(&self, text: &str, size: f32, axes: FontAxes) -> f32 {
+        self.shape(text, size, axes).advance
+    }
+
+    /// Where the glyphs' ink spans horizontally when the text is drawn at x = 0, in whole
+    /// pixels, or none if the text has no ink, e.g. spaces
+    pub fn ink_extents(&self, text: &str, size: f32, axes: FontAxes) -> Option<(f32, f32)> {
+        self.shape(text, size, axes).ink
+    }
+}
+
+/// The whole pixel the coordinate is in, and how far into it, in steps of
+/// [`SUBPIXEL_STEPS`]
+fn subpixel(coordinate: f32) -> (i32, u8) {
+    let steps = (coordinate * SUBPIXEL_STEPS).round();
+    let whole = (steps / SUBPIXEL_STEPS).floor();
+    (whole as i32, (steps - whole * SUBPIXEL_STEPS) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let fonts = Fonts::with_fallbacks(Vec::<PathBuf>::new());
+
+        // The pen moves on by each glyph's advance
+        let line = fonts.layout("AVA tokens", 16.0, FontAxes::REGULAR);
+        assert_eq!(line.glyphs[0].x, 0.0);
+        assert!(line.glyphs.windows(2).all(|pair| pair[1].x > pair[0].x));
+        assert!(fonts.placed(&mut fonts.glyphs.borrow_mut(), &line.glyphs[3], 16.0, FontAxes::REGULAR, (0.0, 0.0)).1.is_none());
+        let advance = |text| fonts.advance(text, 16.0, FontAxes::REGULAR);
+        assert!((advance("AVA") + advance(" tokens") - advance("AVA tokens")).abs() < 1e-3);
+
+        // Characters no font has still take up room, as the missing glyph
+        let glyphs = fonts.layout("a中b", 16.0, FontAxes::REGULAR).glyphs;
+        assert_eq!(glyphs.len(), 3);
+        assert!(glyphs[2].x > glyphs[1].x);
+    }
+
+    #[test]
+    fn test_draw() {
+        let fonts = Fonts::with_fallbacks(Vec::<PathBuf>::new());
+        let (ascent, descent) = fonts.line_metrics(20.0);
+        let mut covered = Vec::new();
+        fonts.draw("Hi", 20.0, FontAxes::REGULAR, (10, 5), |x, y, coverage| {
+            if coverage > 0.5 {
+                covered.push((x, y));
+            }
+        });
+        // Between the top of the line and its bottom, and across the extents of the ink
+        let (left, right) = fonts.ink_extents("Hi", 20.0, FontAxes::REGULAR).unwrap();
+        assert!(covered.iter().all(|&(x, y)| (5..5 + (ascent - descent) as i32).contains(&y)
+            && (10 + left as i32..10 + right as i32).contains(&x)));
+        assert!(covered.len() > 20);
+        assert_eq!(fonts.ink_extents("  ", 20.0, FontAxes::REGULAR), None);
+    }
+
+    #[test]
+    fn test_instances() {
+        let fonts = Fonts::with_fallbacks(Vec::<PathBuf>::new());
+        let ink = |axes| {
+            let mut ink = 0.0;
+            fonts.draw("Hello", 20.0, axes, (0, 0), |_, _, coverage| ink += coverage);
+            ink
+        };
+        // Heavier text covers more, and narrower text is less wide
+        assert!(ink(FontAxes::REGULAR.bolder()) > ink(FontAxes::REGULAR) * 1.2);
+        let condensed = FontAxes { width: 75.0, ..FontAxes::REGULAR };
+        assert!(fonts.advance("Hello", 20.0, condensed) < fonts.advance("Hello", 20.0, FontAxes::REGULAR) * 0.9);
+        // Each instance is set up once
+        fonts.advance("Hello", 20.0, condensed);
+        assert_eq!(fonts.instances.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_shaping() {
+        let fonts = Fonts::with_fallbacks(Vec::<PathBuf>::new());
+        let advance = |text| fonts.advance(text, 16.0, FontAxes::REGULAR);
+        // Kerned
+        assert!(advance("AV") < advance("A") + advance("V"));
+        // A letter and its combining mark are drawn as the one accented letter
+        assert_eq!(fonts.layout("e\u{301}", 16.0, FontAxes::REGULAR).glyphs.len(), 1);
+    }
+
+    #[test]
+    fn test_caches() {
+        let fonts = Fonts::with_fallbacks(Vec::<PathBuf>::new());
+        // A line is laid out once, however often it is measured and drawn
+        let line = fonts.shape("Hello", 16.0, FontAxes::REGULAR);
+        fonts.draw("Hello", 16.0, FontAxes::REGULAR, (0, 0), |_, _, _| {});
+        assert!(Rc::ptr_eq(&line, &fonts.shape("Hello", 16.0, FontAxes::REGULAR)));
+        assert!(!Rc::ptr_eq(&line, &fonts.shape("Hello", 20.0, FontAxes::REGULAR)));
+
+        // And its glyphs drawn once at each offset into a pixel
+        let drawn = fonts.glyphs.borrow().len();
+        let mut coverage = Vec::new();
+        for _ in 0..2 {
+            fonts.draw("Hello", 16.0, FontAxes::REGULAR, (3, 2), |x, y, value| coverage.push((x, y, value)));
+        }
+        assert_eq!(fonts.glyphs.borrow().len(), drawn);
+        assert_eq!(coverage[..coverage.len() / 2], coverage[coverage.len() / 2..]);
+
+        assert_eq!(subpixel(2.3), (2, 1));
+        assert_eq!(subpixel(-0.25), (-1, 3));
+        assert_eq!(subpixel(2.9), (3, 0));
+    }
+
+    #[test]
+    fn test_runs() {
+        let fonts = Fonts::with_fallbacks(Vec::<PathBuf>::new());
+        let runs = |text| fonts.runs(text, &fonts.primary);
+        let run = |range, rtl| ShapedRun { range, font: FontChoice::Bundled, rtl };
+        assert_eq!(runs("ab, cd"), vec![run(0..6, false)]);
+        // A right-to-left line shows its left-to-right words in their place, on its left
+        assert_eq!(runs("אב ab"), vec![run(5..7, false), run(0..5, true)]);
+        assert_eq!(runs("ab אב"), vec![run(0..3, false), run(3..7, true)]);
+        // Greek isn't shaped with the Latin around it
+        assert_eq!(runs("a αβ"), vec![run(0..2, false), run(2..6, false)]);
+        assert!(runs("").is_empty());
+    }
+
+    #[test]
+    fn test_missing_fallbacks() {
+        // Fallbacks that can't be read are skipped
+        let fonts = Fonts::with_fallbacks(["/nonexistent/font.ttf"]);
+        assert_eq!(fonts.font_for('中', &fonts.primary), FontChoice::Bundled);
+        assert_eq!(fonts.font_for('a', &fonts.primary), FontChoice::Bundled);
+        // Looked for once
+        assert_eq!(fonts.fallback_for.borrow().get(&'中'), Some(&None));
+    }
+}
@@ -0,0 +1,39 @@
+/// A read-only view of the pixels drawn in the window
+#[derive(Clone, Copy)]
+pub struct Frame<'a> {
+    /// The pixels, row by row, as 0x00RRGGBB
+    pub pixels: &'a [u32],
+    /// The width of the frame, in pixels
+    pub width: usize,
+    /// The height of the frame, in pixels
+    pub height: usize,
+}
+
+impl<'a> Frame<'a> {
+    /// The frame as 8-bit RGBA bytes, row by row, as most image libraries expect.
+    /// The window has no transparency, so every pixel is fully opaque
+    pub fn to_rgba(self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| {
+                let [_, r, g, b] = pixel.to_be_bytes();
+                [r, g, b, 0xFF]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_rgba() {
+        let pixels = [0x00FF5555, 0x0055CCAA, 0x00000000, 0x00FFFFFF, 0x00123456, 0x00ABCDEF];
+        let frame = Frame { pixels: &pixels, width: 3, height: 2 };
+
+        let rgba = frame.to_rgba();
+        assert_eq!(rgba.len(), 6 * 4);
+        assert_eq!(&rgba[..8], &[0xFF, 0x55, 0x55, 0xFF, 0x55, 0xCC, 0xAA, 0xFF]);
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The shape control points are drawn with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Glyph {
+    Circle,
+    Square,
+    /// A diagonal cross, like an "x"
+    Cross,
+    Diamond,
+}
+
+impl Glyph {
+    /// The distance from a pixel, at the given offset from the glyph's center, to the edge of
+    /// a glyph of the given radius. It is negative inside the glyph, so that antialiasing can
+    /// cover pixels in proportion to how far inside they are
+    pub fn signed_distance(self, dx: f32, dy: f32, radius: f32) -> f32 {
+        let (ax, ay) = (dx.abs(), dy.abs());
+        match self {
+            Glyph::Circle => (dx * dx + dy * dy).sqrt() - radius,
+            // Squares and diamonds of the same radius look larger than circles
+            Glyph::Square => ax.max(ay) - radius * 0.85,
+            Glyph::Diamond => (ax + ay - radius * 1.2) * std::f32::consts::FRAC_1_SQRT_2,
+            Glyph::Cross => {
+                // Two bars along the diagonals, with their ends reaching the radius
+                let u = ((dx + dy) * std::f32::consts::FRAC_1_SQRT_2).abs();
+                let v = ((dx - dy) * std::f32::consts::FRAC_1_SQRT_2).abs();
+                let half_width = (radius * 0.3).max(1.0);
+                let bar = |along: f32, across: f32| {
+                    let (qx, qy) = (along - radius * 1.2, across - half_width);
+                    (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt() + qx.max(qy).min(0.0)
+                };
+                bar(u, v).min(bar(v, u))
+            }
+        }
+    }
+}
+
+impl FromStr for Glyph {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "circle" => Ok(Glyph::Circle),
+            "square" => Ok(Glyph::Square),
+            "cross" => Ok(Glyph::Cross),
+            "diamond" => Ok(Glyph::Diamond),
+            _ => Err(format!("unknown glyph {:?}, expected circle, square, cross or diamond", s)),
+        }
+    }
+}
+
+impl fmt::Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Glyph::Circle => "circle",
+            Glyph::Square => "square",
+            Glyph::Cross => "cross",
+            Glyph::Diamond => "diamond",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_distance() {
+        for glyph in [Glyph::Circle, Glyph::Square, Glyph::Cross, Glyph::Diamond] {
+            assert!(glyph.signed_distance(0.0, 0.0, 5.0) < 0.0, "{} center", glyph);
+            assert!(glyph.signed_distance(10.0, 0.0, 5.0) > 0.0, "{} outside", glyph);
+        }
+
+        assert_eq!(Glyph::Circle.signed_distance(3.0, 4.0, 5.0), 0.0);
+        // The corners of a square are inside, unlike a diamond's
+        assert!(Glyph::Square.signed_distance(4.0, 4.0, 5.0) < 0.0);
+        assert!(Glyph::Diamond.signed_distance(4.0, 4.0, 5.0) > 0.0);
+        // A cross is hollow between its arms
+        assert!(Glyph::Cross.signed_distance(4.0, 4.0, 5.0) < 0.0);
+        assert!(Glyph::Cross.signed_distance(5.0, 0.0, 5.0) > 0.0);
+    }
+}
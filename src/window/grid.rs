@@ -0,0 +1,113 @@
+use crate::types::Point;
+
+use super::view::View;
+
+/// The spacing of the grid, in the points' coordinates, unless changed
+pub const GRID_SPACING: f32 = 20.0;
+/// The finest and coarsest spacings the grid can be changed to
+const SPACING_RANGE: (f32, f32) = (5.0, 160.0);
+/// Below this many pixels apart, the lines would cover the canvas rather than guide the eye,
+/// so they aren't drawn
+const MIN_PIXELS_APART: f32 = 4.0;
+
+/// A square grid behind the points, which clicks can snap to, e.g. to draw symmetric control
+/// polygons. Its lines are at multiples of the spacing in the points' coordinates, so that
+/// they move and scale with the view
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grid {
+    pub spacing: f32,
+    /// Whether the lines are drawn
+    pub visible: bool,
+    /// Whether placed and dragged points go to the nearest intersection
+    pub snap: bool,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self { spacing: GRID_SPACING, visible: false, snap: false }
+    }
+}
+
+impl Grid {
+    /// The point, moved to the nearest intersection if snapping
+    pub fn snap(&self, point: Point) -> Point {
+        if !self.snap {
+            return point;
+        }
+        (point / self.spacing).map(f32::round) * self.spacing
+    }
+
+    /// Halves the spacing, down to the finest one, returning whether it changed
+    pub fn finer(&mut self) -> bool {
+        self.set_spacing(self.spacing / 2.0)
+    }
+
+    /// Doubles the spacing, up to the coarsest one, returning whether it changed
+    pub fn coarser(&mut self) -> bool {
+        self.set_spacing(self.spacing * 2.0)
+    }
+
+    fn set_spacing(&mut self, spacing: f32) -> bool {
+        let spacing = spacing.clamp(SPACING_RANGE.0, SPACING_RANGE.1);
+        let changed = spacing != self.spacing;
+        self.spacing = spacing;
+        changed
+    }
+
+    /// Where the lines cross the canvas through the view, in pixels: first the vertical
+    /// lines' x, then the horizontal lines' y. None are given if the grid is hidden or too
+    /// dense at this zoom
+    pub fn lines(&self, view: &View, (width, height): (usize, usize)) -> (Vec<f32>, Vec<f32>) {
+        let apart = self.spacing * view.scale;
+        if !self.visible || apart < MIN_PIXELS_APART {
+            return (Vec::new(), Vec::new());
+        }
+        let across = |offset: f32, extent: usize| {
+            let first = offset.rem_euclid(apart);
+            (0..)
+                .map(|i| first + i as f32 * apart)
+                .take_while(|position| *position < extent as f32)
+                .collect()
+        };
+        (across(view.offset.x, width), across(view.offset.y, height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_snap() {
+        let mut grid = Grid::default();
+        assert_eq!(grid.snap(Point::new(31.0, 9.0)), Point::new(31.0, 9.0));
+        grid.snap = true;
+        assert_eq!(grid.snap(Point::new(31.0, 9.0)), Point::new(40.0, 0.0));
+        assert_eq!(grid.snap(Point::new(-11.0, 29.0)), Point::new(-20.0, 20.0));
+
+        assert!(grid.finer());
+        assert_eq!(grid.snap(Point::new(31.0, 9.0)), Point::new(30.0, 10.0));
+        assert!(grid.finer());
+        assert!(!grid.finer());
+        assert_eq!(grid.spacing, 5.0);
+    }
+
+    #[test]
+    fn test_lines() {
+        let grid = Grid { visible: true, ..Grid::default() };
+        let (xs, ys) = grid.lines(&View::default(), (100, 50));
+        assert_eq!(xs, [0.0, 20.0, 40.0, 60.0, 80.0]);
+        assert_eq!(ys, [0.0, 20.0, 40.0]);
+
+        // The lines follow the view
+        let view = View { scale: 2.0, offset: Vector2::new(-10.0, 5.0) };
+        let (xs, ys) = grid.lines(&view, (100, 50));
+        assert_eq!(xs, [30.0, 70.0]);
+        assert_eq!(ys, [5.0, 45.0]);
+
+        let zoomed_out = View { scale: 0.1, ..View::default() };
+        assert!(grid.lines(&zoomed_out, (100, 50)).0.is_empty());
+        assert!(Grid::default().lines(&View::default(), (100, 50)).0.is_empty());
+    }
+}
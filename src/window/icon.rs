@@ -0,0 +1,73 @@
+use minifb::Window;
+
+/// The application name, as shown in the title bar and used by the desktop environment
+pub const APP_NAME: &str = "Chaikin";
+
+/// Width and height of the embedded icon, in pixels
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const ICON_SIZE: usize = 32;
+
+/// The icon as raw little endian ARGB pixels, rendered by the build script
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+static ICON_ARGB: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/icon.argb"));
+
+/// The same icon wrapped in a `.ico` file, which is what Windows knows how to load
+#[cfg(target_os = "windows")]
+static ICON_ICO: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/icon.ico"));
+
+/// Sets the embedded icon on the window, so that it shows in the title bar and taskbar.
+///
+/// How this is done depends on the platform:
+/// - Linux (X11): the pixels are handed over directly as the `_NET_WM_ICON` property.
+///   Wayland doesn't support runtime icons; there, the icon comes from the `.desktop` file
+///   in `assets/`
+/// - Windows: the executable already carries the icon as a resource, but the window
+///   itself needs it loaded from an `.ico` file, which we write to the temp directory
+/// - macOS: the dock icon comes from the app bundle, there's nothing to do at runtime
+pub fn apply(window: &mut Window) {
+    #[cfg(target_os = "linux")]
+    {
+        // minifb prefers Wayland when a compositor is available, and panics there
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            return;
+        }
+
+        // _NET_WM_ICON expects the size followed by the pixels, each as a C long
+        let mut data: Vec<u64> = vec![ICON_SIZE as u64, ICON_SIZE as u64];
+        data.extend(
+            ICON_ARGB
+                .chunks_exact(4)
+                .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]) as u64),
+        );
+        if let Ok(icon) = minifb::Icon::try_from(data.as_slice()) {
+            window.set_icon(icon);
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::ffi::OsStrExt;
+
+        let path = std::env::temp_dir().join("chaikin.ico");
+        if std::fs::write(&path, ICON_ICO).is_err() {
+            return;
+        }
+
+        // The path has to outlive the call, so we don't use Icon::from_str here
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        window.set_icon(minifb::Icon::Path(wide.as_ptr()));
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    let _ = window;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_icon_size() {
+        assert_eq!(ICON_ARGB.len(), ICON_SIZE * ICON_SIZE * 4);
+    }
+}
@@ -0,0 +1,247 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use minifb::{InputCallback, Key, KeyRepeat, MouseButton, MouseMode, Window};
+
+/// An input event, as fed by an application embedding the canvas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    /// A key was pressed
+    KeyDown(Key),
+    /// A key was released
+    KeyUp(Key),
+    /// A character was typed
+    Char(char),
+    /// The mouse moved to the given position, in canvas pixels
+    MouseMove(f32, f32),
+    /// The mouse left the canvas
+    MouseLeave,
+    /// A mouse button was pressed
+    MouseDown(MouseButton),
+    /// A mouse button was released
+    MouseUp(MouseButton),
+    /// The mouse wheel turned by the given amounts, across then along: along is positive
+    /// when turned away from the user
+    Scroll(f32, f32),
+    /// The user asked to close the canvas
+    CloseRequested,
+}
+
+/// The state of the keyboard and mouse for the current frame.
+///
+/// It is either read from the window at the start of each frame, or built up from the
+/// events fed by an application embedding the canvas
+#[derive(Default)]
+pub struct Input {
+    /// Keys held down
+    keys_down: Vec<Key>,
+    /// Keys that went down since the last frame
+    keys_pressed: Vec<Key>,
+    /// Keys that went down or repeated, while held, since the last frame
+    keys_repeated: Vec<Key>,
+    /// The characters typed since the last frame
+    text: String,
+    /// The mouse position, if it is over the canvas
+    mouse_pos: Option<(f32, f32)>,
+    /// Whether the left, middle and right buttons are held down
+    mouse_buttons: [bool; 3],
+    /// How far the wheel turned since the last frame, across then along
+    scroll: (f32, f32),
+    /// Whether the user asked to close the canvas
+    pub close_requested: bool,
+}
+
+impl Input {
+    /// Reads the state of the keyboard and mouse from the window, with the characters the
+    /// [`TextCallback`] collected since the last frame
+    pub fn from_window(window: &Window, text: String) -> Self {
+        let mouse_buttons = [MouseButton::Left, MouseButton::Middle, MouseButton::Right]
+            .map(|button| window.get_mouse_down(button));
+        // While a button is held, the position outside of the window is kept, clamped to its
+        // edges, so that drags don't stop there
+        let held = mouse_buttons.contains(&true);
+        let mouse_pos = window.get_mouse_pos(MouseMode::Discard)
+            .or_else(|| window.get_mouse_pos(MouseMode::Clamp).filter(|_| held));
+
+        Self {
+            keys_down: window.get_keys(),
+            keys_pressed: window.get_keys_pressed(KeyRepeat::No),
+            keys_repeated: window.get_keys_pressed(KeyRepeat::Yes),
+            text,
+            mouse_pos,
+            mouse_buttons,
+            scroll: window.get_scroll_wheel().unwrap_or_default(),
+            close_requested: !window.is_open(),
+        }
+    }
+
+    /// Updates the state with an event
+    pub fn apply(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::KeyDown(key) => {
+                // Hosts send the key again as it repeats
+                if !self.keys_down.contains(&key) {
+                    self.keys_down.push(key);
+                    self.keys_pressed.push(key);
+                }
+                self.keys_repeated.push(key);
+            }
+            InputEvent::KeyUp(key) => self.keys_down.retain(|k| *k != key),
+            InputEvent::Char(c) => self.text.push(c),
+            InputEvent::MouseMove(x, y) => self.mouse_pos = Some((x, y)),
+            InputEvent::MouseLeave => self.mouse_pos = None,
+            InputEvent::MouseDown(button) => self.mouse_buttons[button_index(button)] = true,
+            InputEvent::MouseUp(button) => self.mouse_buttons[button_index(button)] = false,
+            InputEvent::Scroll(x, y) => self.scroll = (self.scroll.0 + x, self.scroll.1 + y),
+            InputEvent::CloseRequested => self.close_requested = true,
+        }
+    }
+
+    /// Forgets the keys pressed during the frame that just ended, so they are only
+    /// reported once
+    pub fn end_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_repeated.clear();
+        self.text.clear();
+        self.scroll = (0.0, 0.0);
+    }
+
+    /// Whether the key is held down
+    pub fn is_key_down(&self, key: Key) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    /// Whether the key went down since the last frame
+    pub fn is_key_pressed(&self, key: Key) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    /// Whether the key went down, or repeated while held, since the last frame
+    pub fn is_key_repeated(&self, key: Key) -> bool {
+        self.keys_repeated.contains(&key)
+    }
+
+    /// The characters typed since the last frame
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Whether either of the Ctrl keys is held down
+    pub fn is_ctrl_down(&self) -> bool {
+        self.is_key_down(Key::LeftCtrl) || self.is_key_down(Key::RightCtrl)
+    }
+
+    /// Whether either of the Shift keys is held down
+    pub fn is_shift_down(&self) -> bool {
+        self.is_key_down(Key::LeftShift) || self.is_key_down(Key::RightShift)
+    }
+
+    /// Whether either of the Alt keys is held down
+    pub fn is_alt_down(&self) -> bool {
+        self.is_key_down(Key::LeftAlt) || self.is_key_down(Key::RightAlt)
+    }
+
+    /// The mouse position, if it is over the canvas
+    pub fn mouse_pos(&self) -> Option<(f32, f32)> {
+        self.mouse_pos
+    }
+
+    /// Whether any of the mouse buttons is held down
+    pub fn any_mouse_down(&self) -> bool {
+        self.mouse_buttons.contains(&true)
+    }
+
+    /// Whether the mouse button is held down
+    pub fn mouse_down(&self, button: MouseButton) -> bool {
+        self.mouse_buttons[button_index(button)]
+    }
+
+    /// How far the wheel turned since the last frame, across then along
+    pub fn scroll(&self) -> (f32, f32) {
+        self.scroll
+    }
+
+    /// Whether no key or button is held, and nothing was typed or scrolled since the last
+    /// frame
+    pub fn is_quiet(&self) -> bool {
+        self.keys_down.is_empty()
+            && self.keys_pressed.is_empty()
+            && self.text.is_empty()
+            && self.scroll == (0.0, 0.0)
+            && !self.any_mouse_down()
+    }
+}
+
+/// Collects the characters typed in the window. minifb only reports them through a
+/// callback, so they are kept here until the next frame reads them
+#[derive(Clone, Default)]
+pub struct TextCallback(Rc<RefCell<String>>);
+
+impl TextCallback {
+    /// The characters typed since the last call
+    pub fn take(&self) -> String {
+        std::mem::take(&mut self.0.borrow_mut())
+    }
+}
+
+impl InputCallback for TextCallback {
+    fn add_char(&mut self, uni_char: u32) {
+        if let Some(c) = char::from_u32(uni_char).filter(|c| !c.is_control()) {
+            self.0.borrow_mut().push(c);
+        }
+    }
+}
+
+fn button_index(button: MouseButton) -> usize {
+    match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events() {
+        let mut input = Input::default();
+        input.apply(InputEvent::KeyDown(Key::LeftCtrl));
+        input.apply(InputEvent::KeyDown(Key::R));
+        input.apply(InputEvent::MouseMove(10.0, 20.0));
+        input.apply(InputEvent::MouseDown(MouseButton::Left));
+        input.apply(InputEvent::Char('4'));
+        input.apply(InputEvent::Char('2'));
+
+        assert!(input.is_ctrl_down());
+        assert_eq!(input.text(), "42");
+        assert!(input.is_key_pressed(Key::R));
+        assert_eq!(input.mouse_pos(), Some((10.0, 20.0)));
+        assert!(input.mouse_down(MouseButton::Left));
+        assert!(!input.mouse_down(MouseButton::Right));
+
+        // Presses are only reported for one frame, held keys stay down
+        input.end_frame();
+        assert!(!input.is_key_pressed(Key::R));
+        assert!(input.is_key_down(Key::R));
+        assert_eq!(input.text(), "");
+
+        input.apply(InputEvent::KeyUp(Key::R));
+        input.apply(InputEvent::MouseLeave);
+        assert!(!input.is_key_down(Key::R));
+        assert_eq!(input.mouse_pos(), None);
+    }
+
+    #[test]
+    fn test_text_callback() {
+        let text = TextCallback::default();
+        let mut callback = text.clone();
+        callback.add_char('a' as u32);
+        callback.add_char(0x08);
+        callback.add_char('é' as u32);
+
+        assert_eq!(text.take(), "aé");
+        assert_eq!(text.take(), "");
+    }
+}
@@ -0,0 +1,94 @@
+use std::ops::Range;
+
+use crate::types::Point;
+
+use super::view::View;
+
+/// How short the segments of a curve can get on screen, in pixels, before refining it
+/// further makes no visible difference
+pub const MIN_SEGMENT_PIXELS: f32 = 1.0;
+/// How long the segments of the last step can be on screen, in pixels, before the curve
+/// shows as a polyline, past which it is refined further where it is on screen
+pub const MAX_SEGMENT_PIXELS: f32 = 4.0;
+/// The most steps the last step is refined further by, however far the view is zoomed in
+pub const MAX_EXTRA_STEPS: usize = 4;
+/// How many points the part of the curve refined goes on past the canvas on either side.
+/// The ends of a part subdivide unlike the rest of the curve, so they are kept off screen
+const MARGIN: usize = 3;
+
+/// How long the longest segment of the curve is on screen, in pixels
+pub fn longest_segment(curve: &[Point], view: &View) -> f32 {
+    curve
+        .windows(2)
+        .map(|segment| (segment[1] - segment[0]).norm())
+        .fold(0.0, f32::max)
+        * view.scale
+}
+
+/// The first step of the points whose segments are all shorter than [`MIN_SEGMENT_PIXELS`] on
+/// screen, so that the steps after it look just like it. Each step about halves the longest
+/// segment, so it is found from the points without computing any step
+pub fn subpixel_step(points: &[Point], view: &View) -> usize {
+    let longest = longest_segment(points, view);
+    if longest < MIN_SEGMENT_PIXELS {
+        return 0;
+    }
+    (longest / MIN_SEGMENT_PIXELS).log2().floor() as usize + 1
+}
+
+/// The range of the curve's points from the first segment on a canvas of the given size to
+/// the last, with [`MARGIN`] points more on either side. None if no segment is on it
+pub fn visible_range(curve: &[Point], view: &View, (width, height): (usize, usize)) -> Option<Range<usize>> {
+    let on_canvas = |i: &usize| {
+        let (start, end) = (view.screen_point(curve[*i]), view.screen_point(curve[*i + 1]));
+        start.x.max(end.x) >= 0.0
+            && start.x.min(end.x) <= width as f32
+            && start.y.max(end.y) >= 0.0
+            && start.y.min(end.y) <= height as f32
+    };
+    let segments = 0..curve.len().saturating_sub(1);
+    let first = segments.clone().find(on_canvas)?;
+    let last = segments.rev().find(on_canvas)?;
+    Some(first.saturating_sub(MARGIN)..(last + 2 + MARGIN).min(curve.len()))
+}
+
+/// How many more steps it takes for segments of the given length on screen to be at most
+/// [`MAX_SEGMENT_PIXELS`] long, each step about halving them, up to [`MAX_EXTRA_STEPS`]
+pub fn extra_steps(longest_pixels: f32) -> usize {
+    if longest_pixels <= MAX_SEGMENT_PIXELS {
+        return 0;
+    }
+    ((longest_pixels / MAX_SEGMENT_PIXELS).log2().ceil() as usize).min(MAX_EXTRA_STEPS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_segment_lengths() {
+        let curve = [Point::new(0.0, 0.0), Point::new(3.0, 4.0), Point::new(3.0, 4.5)];
+        assert_eq!(longest_segment(&curve, &View::default()), 5.0);
+        // 5 pixels, then 2.5, 1.25 and 0.625
+        assert_eq!(subpixel_step(&curve, &View::default()), 3);
+        assert_eq!(subpixel_step(&curve, &View { scale: 0.1, ..View::default() }), 0);
+
+        assert_eq!(extra_steps(3.0), 0);
+        assert_eq!(extra_steps(8.0), 1);
+        assert_eq!(extra_steps(9.0), 2);
+        assert_eq!(extra_steps(1000.0), MAX_EXTRA_STEPS);
+    }
+
+    #[test]
+    fn test_visible_range() {
+        let curve: Vec<Point> = (0..20).map(|i| Point::new(i as f32 * 10.0, 50.0)).collect();
+        // Zoomed in on the middle of the curve, from 80 to 120
+        let view = View { scale: 10.0, offset: Vector2::new(-800.0, -450.0) };
+        assert_eq!(visible_range(&curve, &view, (400, 100)), Some(4..17));
+        // Off the canvas
+        let away = View { scale: 1.0, offset: Vector2::new(0.0, 500.0) };
+        assert_eq!(visible_range(&curve, &away, (400, 100)), None);
+        assert_eq!(visible_range(&curve[..1], &View::default(), (400, 100)), None);
+    }
+}
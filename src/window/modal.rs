@@ -0,0 +1,119 @@
+use minifb::Key;
+
+use super::input::Input;
+
+/// A dialog box that takes the keyboard until it is answered or cancelled with [Escape],
+/// with the canvas dimmed behind it
+pub struct Modal {
+    /// The question, or what the text is for
+    pub title: String,
+    pub kind: ModalKind,
+}
+
+pub enum ModalKind {
+    /// A question answered with one of the given keys, e.g. [Y] or [N]
+    Choice(Vec<(Key, &'static str)>),
+    /// A line of text, confirmed with [Enter]
+    TextInput(String),
+}
+
+/// What the user did with the modal this frame
+#[derive(Clone, Debug, PartialEq)]
+pub enum ModalOutcome {
+    /// Nothing yet, the modal stays open
+    Open,
+    /// The user pressed one of the keys of a choice
+    Chose(Key),
+    /// The user confirmed the text they entered
+    Entered(String),
+    /// The user closed the modal with [Escape]
+    Cancelled,
+}
+
+impl Modal {
+    /// A question answered with one of the given keys
+    pub fn choice(title: &str, choices: Vec<(Key, &'static str)>) -> Self {
+        Self { title: title.to_string(), kind: ModalKind::Choice(choices) }
+    }
+
+    /// A yes or no question
+    pub fn confirm(title: &str) -> Self {
+        Self::choice(title, vec![(Key::Y, "Yes"), (Key::N, "No")])
+    }
+
+    /// Asks for a line of text, starting with the given one
+    pub fn text_input(title: &str, initial: &str) -> Self {
+        Self { title: title.to_string(), kind: ModalKind::TextInput(initial.to_string()) }
+    }
+
+    /// Handles this frame's keyboard input
+    pub fn handle(&mut self, input: &Input) -> ModalOutcome {
+        if input.is_key_pressed(Key::Escape) {
+            return ModalOutcome::Cancelled;
+        }
+
+        match &mut self.kind {
+            ModalKind::Choice(choices) => choices
+                .iter()
+                .find(|(key, _)| input.is_key_pressed(*key))
+                .map_or(ModalOutcome::Open, |(key, _)| ModalOutcome::Chose(*key)),
+            ModalKind::TextInput(text) => {
+                if input.is_key_pressed(Key::Enter) || input.is_key_pressed(Key::NumPadEnter) {
+                    return ModalOutcome::Entered(text.clone());
+                }
+                if input.is_key_pressed(Key::Backspace) {
+                    text.pop();
+                }
+                text.push_str(input.text());
+                ModalOutcome::Open
+            }
+        }
+    }
+
+    /// The keys the user can press, as shown under the title
+    pub fn hint(&self) -> String {
+        match &self.kind {
+            ModalKind::Choice(choices) => choices
+                .iter()
+                .map(|(key, label)| format!("[{:?}]: {}", key, label))
+                .collect::<Vec<_>>()
+                .join(" - "),
+            ModalKind::TextInput(_) => "[Enter]: OK - [Escape]: Cancel".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::InputEvent;
+
+    fn press(events: &[InputEvent]) -> Input {
+        let mut input = Input::default();
+        for event in events {
+            input.apply(*event);
+        }
+        input
+    }
+
+    #[test]
+    fn test_choice() {
+        let mut modal = Modal::confirm("Reset?");
+        assert_eq!(modal.handle(&press(&[InputEvent::KeyDown(Key::A)])), ModalOutcome::Open);
+        assert_eq!(modal.handle(&press(&[InputEvent::KeyDown(Key::N)])), ModalOutcome::Chose(Key::N));
+        assert_eq!(modal.handle(&press(&[InputEvent::KeyDown(Key::Escape)])), ModalOutcome::Cancelled);
+        assert_eq!(modal.hint(), "[Y]: Yes - [N]: No");
+    }
+
+    #[test]
+    fn test_text_input() {
+        let mut modal = Modal::text_input("Point", "1");
+        let typed = press(&[InputEvent::Char('0'), InputEvent::Char(' '), InputEvent::Char('x')]);
+        assert_eq!(modal.handle(&typed), ModalOutcome::Open);
+        assert_eq!(modal.handle(&press(&[InputEvent::KeyDown(Key::Backspace)])), ModalOutcome::Open);
+        assert_eq!(modal.handle(&press(&[InputEvent::Char('5')])), ModalOutcome::Open);
+
+        let outcome = modal.handle(&press(&[InputEvent::KeyDown(Key::Enter)]));
+        assert_eq!(outcome, ModalOutcome::Entered("10 5".to_string()));
+    }
+}
@@ -0,0 +1,288 @@
+use std::time::{Duration, Instant};
+
+use minifb::MouseButton;
+
+use super::input::Input;
+
+/// How far the mouse has to move with a button down before it counts as a drag, in pixels
+const DRAG_THRESHOLD: f32 = 3.0;
+
+/// When gestures are recognized
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureConfig {
+    /// The longest time between the presses of a double-click
+    pub double_click_time: Duration,
+    /// How far apart, in pixels, the presses of a double-click can be
+    pub double_click_distance: f32,
+    /// How long the button has to be held, without dragging, for a long press
+    pub long_press_time: Duration,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            double_click_time: Duration::from_millis(400),
+            double_click_distance: 5.0,
+            long_press_time: Duration::from_millis(600),
+        }
+    }
+}
+
+/// A gesture made with the left button
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// The button was pressed twice quickly, in about the same place
+    DoubleClick((f32, f32)),
+    /// The button was held in place for a while
+    LongPress((f32, f32)),
+}
+
+/// The state of a mouse button, followed from frame to frame
+#[derive(Clone, Copy, Debug, Default)]
+struct Button {
+    down: bool,
+    /// Whether the button went down this frame
+    pressed: bool,
+    /// Whether the button went up this frame
+    released: bool,
+    /// Where the button went down, while it is held
+    press_position: Option<(f32, f32)>,
+    /// Whether the mouse moved far enough with the button held to be dragging
+    dragging: bool,
+}
+
+/// The mouse, followed from frame to frame so that interactions can tell presses, drags and
+/// releases apart. While a button is held, the mouse stays captured: its last position is
+/// kept even if the cursor leaves the canvas
+#[derive(Default)]
+pub struct MouseState {
+    position: Option<(f32, f32)>,
+    /// How far the mouse moved since the last frame, in pixels
+    movement: (f32, f32),
+    /// How far the wheel turned along since the last frame, positive away from the user
+    scroll: f32,
+    /// The left, middle and right buttons
+    buttons: [Button; 3],
+    /// When gestures are recognized
+    pub gesture_config: GestureConfig,
+    /// The gesture recognized this frame
+    gesture: Option<Gesture>,
+    /// When and where the left button was last pressed, if it may start a double-click
+    last_press: Option<(Instant, (f32, f32))>,
+    /// When the left button went down, while it is held
+    held_since: Option<Instant>,
+    /// Whether the current press was already reported as a long press
+    long_pressed: bool,
+}
+
+impl MouseState {
+    /// Updates the state from this frame's input, which arrived at the given time
+    pub fn update(&mut self, input: &Input, now: Instant) {
+        let held = input.any_mouse_down();
+        let previous = self.position;
+        self.position = match input.mouse_pos() {
+            Some(position) => Some(position),
+            // Captured, the drag carries on from where the cursor left
+            None if held => self.position,
+            None => None,
+        };
+        self.movement = match (previous, self.position) {
+            (Some(from), Some(to)) => (to.0 - from.0, to.1 - from.1),
+            _ => (0.0, 0.0),
+        };
+        self.scroll = input.scroll().1;
+
+        for button in [MouseButton::Left, MouseButton::Middle, MouseButton::Right] {
+            let down = input.mouse_down(button);
+            let position = self.position;
+            let state = &mut self.buttons[index(button)];
+
+            state.pressed = down && !state.down;
+            state.released = !down && state.down;
+            state.down = down;
+
+            if state.pressed {
+                state.press_position = position;
+                state.dragging = false;
+            } else if !down {
+                state.press_position = None;
+                state.dragging = false;
+            } else if let (Some(start), Some(now)) = (state.press_position, position) {
+                let moved = ((now.0 - start.0).powi(2) + (now.1 - start.1).powi(2)).sqrt();
+                state.dragging |= moved >= DRAG_THRESHOLD;
+            }
+        }
+
+        self.recognize_gestures(now);
+    }
+
+    fn recognize_gestures(&mut self, now: Instant) {
+        let config = self.gesture_config;
+        let left = self.buttons[index(MouseButton::Left)];
+        self.gesture = None;
+
+        if !left.down {
+            self.held_since = None;
+            return;
+        }
+        let Some(position) = left.press_position else {
+            return;
+        };
+
+        if left.pressed {
+            self.held_since = Some(now);
+            self.long_pressed = false;
+
+            let is_double = self.last_press.is_some_and(|(time, (x, y))| {
+                let distance = ((position.0 - x).powi(2) + (position.1 - y).powi(2)).sqrt();
+                now.duration_since(time) <= config.double_click_time
+                    && distance <= config.double_click_distance
+            });
+            if is_double {
+                self.gesture = Some(Gesture::DoubleClick(position));
+                // A third press starts over, instead of being another double-click
+                self.last_press = None;
+            } else {
+                self.last_press = Some((now, position));
+            }
+            return;
+        }
+
+        let held_long = self.held_since
+            .is_some_and(|since| now.duration_since(since) >= config.long_press_time);
+        if held_long && !left.dragging && !self.long_pressed {
+            self.gesture = Some(Gesture::LongPress(position));
+            self.long_pressed = true;
+        }
+    }
+
+    /// The gesture recognized this frame, if any
+    pub fn gesture(&self) -> Option<Gesture> {
+        self.gesture
+    }
+
+    /// Where the mouse is, or where it was last seen while a button is held
+    pub fn position(&self) -> Option<(f32, f32)> {
+        self.position
+    }
+
+    pub fn is_down(&self, button: MouseButton) -> bool {
+        self.buttons[index(button)].down
+    }
+
+    /// Whether the button went down this frame
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        self.buttons[index(button)].pressed
+    }
+
+    /// Whether the button went up this frame
+    pub fn is_released(&self, button: MouseButton) -> bool {
+        self.buttons[index(button)].released
+    }
+
+    /// How far the mouse moved since the last frame, in pixels
+    pub fn movement(&self) -> (f32, f32) {
+        self.movement
+    }
+
+    /// How far the wheel turned since the last frame, positive away from the user
+    pub fn scroll(&self) -> f32 {
+        self.scroll
+    }
+
+    /// Where the drag started, if the button is held and the mouse moved since
+    pub fn drag_start(&self, button: MouseButton) -> Option<(f32, f32)> {
+        let state = &self.buttons[index(button)];
+        state.press_position.filter(|_| state.dragging)
+    }
+}
+
+fn index(button: MouseButton) -> usize {
+    match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::InputEvent;
+
+    fn frame(mouse: &mut MouseState, down: bool, (x, y): (f32, f32), now: Instant) -> Option<Gesture> {
+        let mut input = Input::default();
+        input.apply(InputEvent::MouseMove(x, y));
+        if down {
+            input.apply(InputEvent::MouseDown(MouseButton::Left));
+        }
+        mouse.update(&input, now);
+        mouse.gesture()
+    }
+
+    #[test]
+    fn test_double_click() {
+        let (mut mouse, start) = (MouseState::default(), Instant::now());
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        assert_eq!(frame(&mut mouse, true, (10.0, 10.0), at(0)), None);
+        assert_eq!(frame(&mut mouse, false, (10.0, 10.0), at(100)), None);
+        assert_eq!(frame(&mut mouse, true, (12.0, 11.0), at(200)), Some(Gesture::DoubleClick((12.0, 11.0))));
+        frame(&mut mouse, false, (12.0, 11.0), at(250));
+
+        // Too slow, then too far
+        assert_eq!(frame(&mut mouse, true, (12.0, 11.0), at(1000)), None);
+        frame(&mut mouse, false, (12.0, 11.0), at(1050));
+        assert_eq!(frame(&mut mouse, true, (40.0, 11.0), at(1100)), None);
+    }
+
+    #[test]
+    fn test_long_press() {
+        let (mut mouse, start) = (MouseState::default(), Instant::now());
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        frame(&mut mouse, true, (10.0, 10.0), at(0));
+        assert_eq!(frame(&mut mouse, true, (10.0, 10.0), at(300)), None);
+        assert_eq!(frame(&mut mouse, true, (11.0, 10.0), at(700)), Some(Gesture::LongPress((10.0, 10.0))));
+        // Reported once per press
+        assert_eq!(frame(&mut mouse, true, (11.0, 10.0), at(900)), None);
+
+        // Dragging isn't a long press
+        frame(&mut mouse, false, (11.0, 10.0), at(1000));
+        frame(&mut mouse, true, (10.0, 10.0), at(2000));
+        frame(&mut mouse, true, (50.0, 10.0), at(2100));
+        assert_eq!(frame(&mut mouse, true, (50.0, 10.0), at(3000)), None);
+    }
+
+    #[test]
+    fn test_press_drag_release() {
+        let (mut mouse, mut input) = (MouseState::default(), Input::default());
+        input.apply(InputEvent::MouseMove(10.0, 10.0));
+        input.apply(InputEvent::MouseDown(MouseButton::Left));
+        mouse.update(&input, Instant::now());
+        assert!(mouse.is_pressed(MouseButton::Left));
+        assert_eq!(mouse.drag_start(MouseButton::Left), None);
+
+        // Small moves don't count as a drag
+        input.apply(InputEvent::MouseMove(11.0, 10.0));
+        mouse.update(&input, Instant::now());
+        assert!(!mouse.is_pressed(MouseButton::Left));
+        assert_eq!(mouse.drag_start(MouseButton::Left), None);
+
+        input.apply(InputEvent::MouseMove(20.0, 10.0));
+        mouse.update(&input, Instant::now());
+        assert_eq!(mouse.drag_start(MouseButton::Left), Some((10.0, 10.0)));
+
+        // Leaving the canvas while dragging keeps the last position
+        input.apply(InputEvent::MouseLeave);
+        mouse.update(&input, Instant::now());
+        assert_eq!(mouse.position(), Some((20.0, 10.0)));
+        assert!(mouse.is_down(MouseButton::Left));
+
+        input.apply(InputEvent::MouseUp(MouseButton::Left));
+        mouse.update(&input, Instant::now());
+        assert!(mouse.is_released(MouseButton::Left));
+        assert_eq!(mouse.drag_start(MouseButton::Left), None);
+        assert_eq!(mouse.position(), None);
+    }
+}
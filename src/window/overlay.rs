@@ -0,0 +1,168 @@
+use std::time::{Duration, Instant};
+
+use crate::types::Point;
+
+/// How long the halo around a new point lasts
+const HALO_DURATION: Duration = Duration::from_millis(400);
+
+/// Something drawn over the canvas for a while: a toast message, or a short-lived effect
+/// such as a halo or a hint arrow
+pub struct Overlay {
+    pub kind: OverlayKind,
+    /// When the overlay was shown
+    started: Instant,
+    /// How long the overlay stays up, or none if it stays until dismissed
+    duration: Option<Duration>,
+}
+
+/// What an overlay draws
+#[derive(Clone, Debug, PartialEq)]
+pub enum OverlayKind {
+    /// A message at the bottom of the canvas. There is at most one toast at a time
+    Toast(String),
+    /// A ring shrinking onto a point that was just placed
+    Halo(Point),
+    /// A point briefly lit up, to draw attention to it
+    Flash(Point),
+    /// An arrow pointing at something the user should look at
+    Arrow { from: Point, to: Point },
+    /// A piece of styled text at the given position, see [`super::rich_text::parse`]
+    Label { position: Point, text: String },
+}
+
+impl Overlay {
+    /// An overlay that goes away after the given duration
+    pub fn new(kind: OverlayKind, duration: Duration) -> Self {
+        Self { kind, started: Instant::now(), duration: Some(duration) }
+    }
+
+    /// An overlay that stays up until dismissed
+    pub fn persistent(kind: OverlayKind) -> Self {
+        Self { kind, started: Instant::now(), duration: None }
+    }
+
+    /// A halo around the given point
+    pub fn halo(center: Point) -> Self {
+        Self::new(OverlayKind::Halo(center), HALO_DURATION)
+    }
+
+    /// How far along the overlay is, from 0 when it is shown to 1 when it goes away.
+    /// Persistent overlays stay at 0
+    pub fn progress(&self) -> f32 {
+        match self.duration {
+            Some(duration) => (self.started.elapsed().as_secs_f32() / duration.as_secs_f32()).min(1.0),
+            None => 0.0,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.duration.is_some_and(|duration| self.started.elapsed() >= duration)
+    }
+
+    fn is_toast(&self) -> bool {
+        matches!(self.kind, OverlayKind::Toast(_))
+    }
+}
+
+/// The overlays currently shown, in the order they are drawn
+#[derive(Default)]
+pub struct Overlays {
+    overlays: Vec<Overlay>,
+}
+
+impl Overlays {
+    /// Shows an overlay on top of the others. A toast replaces the one already shown
+    pub fn show(&mut self, overlay: Overlay) {
+        if overlay.is_toast() {
+            self.dismiss_toast();
+        }
+        self.overlays.push(overlay);
+    }
+
+    /// The message of the toast, if one is showing
+    pub fn toast(&self) -> Option<&str> {
+        self.iter().find_map(|overlay| match &overlay.kind {
+            OverlayKind::Toast(message) => Some(message.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn dismiss_toast(&mut self) {
+        self.overlays.retain(|overlay| !overlay.is_toast());
+    }
+
+    /// Hides the overlays that draw the given thing
+    pub fn dismiss(&mut self, kind: &OverlayKind) {
+        self.overlays.retain(|overlay| overlay.kind != *kind);
+    }
+
+    /// Forgets the overlays that are over. Those are never drawn anyway, this only keeps
+    /// them from piling up
+    pub fn update(&mut self) {
+        self.overlays.retain(|overlay| !overlay.is_over());
+    }
+
+    pub fn clear(&mut self) {
+        self.overlays.clear();
+    }
+
+    /// Whether any overlay changes over time, fading or going away once its time is up.
+    /// Those that stay until dismissed don't
+    pub fn is_fading(&self) -> bool {
+        self.iter().any(|overlay| overlay.duration.is_some())
+    }
+
+    /// The overlays that are still up
+    pub fn iter(&self) -> impl Iterator<Item = &Overlay> {
+        self.overlays.iter().filter(|overlay| !overlay.is_over())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress() {
+        let mut overlay = Overlay::halo(Point::new(0.0, 0.0));
+        assert!(overlay.progress() < 0.5);
+        assert!(!overlay.is_over());
+
+        overlay.started -= HALO_DURATION;
+        assert_eq!(overlay.progress(), 1.0);
+        assert!(overlay.is_over());
+
+        let mut persistent = Overlay::persistent(OverlayKind::Toast("Hello".to_string()));
+        persistent.started -= Duration::from_secs(3600);
+        assert!(!persistent.is_over());
+    }
+
+    #[test]
+    fn test_single_toast() {
+        let mut overlays = Overlays::default();
+        overlays.show(Overlay::halo(Point::new(1.0, 2.0)));
+        overlays.show(Overlay::new(OverlayKind::Toast("First".to_string()), Duration::from_secs(8)));
+        overlays.show(Overlay::persistent(OverlayKind::Toast("Second".to_string())));
+
+        assert_eq!(overlays.toast(), Some("Second"));
+        assert_eq!(overlays.iter().count(), 2);
+
+        overlays.dismiss_toast();
+        assert_eq!(overlays.toast(), None);
+        assert_eq!(overlays.iter().count(), 1);
+    }
+
+    #[test]
+    fn test_expired_overlays() {
+        let mut overlays = Overlays::default();
+        let mut expired = Overlay::new(OverlayKind::Toast("Gone".to_string()), Duration::from_secs(1));
+        expired.started -= Duration::from_secs(2);
+        overlays.show(expired);
+
+        // Hidden right away, forgotten on the next update
+        assert_eq!(overlays.toast(), None);
+        assert_eq!(overlays.overlays.len(), 1);
+        overlays.update();
+        assert!(overlays.overlays.is_empty());
+    }
+}
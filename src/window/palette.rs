@@ -0,0 +1,46 @@
+use super::theme::BlendMode;
+
+/// Colors going from one to another, to tell apart the steps of the animation, e.g. from
+/// red for the control polygon to green for the smoothest curve
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gradient {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl Gradient {
+    pub const RED_TO_GREEN: Gradient = Gradient { from: 0x00FF4040, to: 0x0040FF40 };
+
+    /// The color part of the way, from `from` at 0 to `to` at 1
+    pub fn at(&self, t: f32) -> u32 {
+        BlendMode::Normal.blend(self.to, self.from, t.clamp(0.0, 1.0))
+    }
+
+    /// The color of the step, out of the given number of steps. The step can be part of
+    /// the way to the next one, as the animation morphs between them
+    pub fn step(&self, step: f32, steps: usize) -> u32 {
+        self.at(step / steps.saturating_sub(1).max(1) as f32)
+    }
+}
+
+impl Default for Gradient {
+    fn default() -> Self {
+        Self::RED_TO_GREEN
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gradient() {
+        let gradient = Gradient { from: 0x00FF0000, to: 0x0000FF00 };
+        assert_eq!(gradient.step(0.0, 8), 0x00FF0000);
+        assert_eq!(gradient.step(7.0, 8), 0x0000FF00);
+        assert_eq!(gradient.step(3.5, 8), 0x007F7F00);
+        // Past either end, the end color
+        assert_eq!(gradient.at(1.5), 0x0000FF00);
+        assert_eq!(gradient.step(0.0, 1), 0x00FF0000);
+    }
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use minifb::MouseButton;
+
+use super::mouse::MouseState;
+
+/// The interval of [`Placement::Repeat`], unless given
+const DEFAULT_REPEAT: Duration = Duration::from_millis(150);
+/// The spacing of [`Placement::Freehand`], in pixels, unless given
+const DEFAULT_SPACING: f32 = 12.0;
+
+/// How points are placed while the left button is held
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Placement {
+    /// A single point per click
+    Click,
+    /// A point when the button goes down, then another one at each interval while it's held
+    Repeat(Duration),
+    /// Points along the mouse's path, this many pixels apart, for freehand drawing
+    Freehand(f32),
+}
+
+impl Placement {
+    /// The next policy, with its default setting, for cycling through them
+    pub fn next(self) -> Self {
+        match self {
+            Placement::Click => Placement::Repeat(DEFAULT_REPEAT),
+            Placement::Repeat(_) => Placement::Freehand(DEFAULT_SPACING),
+            Placement::Freehand(_) => Placement::Click,
+        }
+    }
+}
+
+/// Decides where points go according to a placement policy
+pub struct Placer {
+    pub placement: Placement,
+    /// When and where the last point of the current press was placed
+    last: Option<(Instant, (f32, f32))>,
+}
+
+impl Placer {
+    pub fn new(placement: Placement) -> Self {
+        Self { placement, last: None }
+    }
+
+    /// Where a point should be placed this frame, if anywhere
+    pub fn next_point(&mut self, mouse: &MouseState, now: Instant) -> Option<(f32, f32)> {
+        if !mouse.is_down(MouseButton::Left) {
+            self.last = None;
+            return None;
+        }
+        let position = mouse.position()?;
+
+        let place = match (self.last, self.placement) {
+            _ if mouse.is_pressed(MouseButton::Left) => true,
+            (None, _) | (_, Placement::Click) => false,
+            (Some((time, _)), Placement::Repeat(interval)) => now.duration_since(time) >= interval,
+            (Some((_, (x, y))), Placement::Freehand(spacing)) => {
+                ((position.0 - x).powi(2) + (position.1 - y).powi(2)).sqrt() >= spacing
+            }
+        };
+        if place {
+            self.last = Some((now, position));
+            return Some(position);
+        }
+        None
+    }
+}
+
+impl FromStr for Placement {
+    type Err = String;
+
+    /// Parses "click", "repeat" or "freehand", optionally followed by the interval in
+    /// milliseconds or the spacing in pixels, e.g. "repeat:200" or "freehand:8"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, setting) = match s.split_once(':') {
+            Some((name, setting)) => (name, Some(setting)),
+            None => (s, None),
+        };
+        let parse = |default: f32| -> Result<f32, String> {
+            let value = setting.map_or(Ok(default), |setting| {
+                setting.parse::<f32>().map_err(|e| format!("{:?}: {}", setting, e))
+            })?;
+            if value > 0.0 {
+                Ok(value)
+            } else {
+                Err(format!("the setting of {} must be positive", name))
+            }
+        };
+
+        match name {
+            "click" if setting.is_none() => Ok(Placement::Click),
+            "repeat" => {
+                let millis = parse(DEFAULT_REPEAT.as_millis() as f32)?;
+                Ok(Placement::Repeat(Duration::from_micros((millis * 1000.0).round() as u64)))
+            }
+            "freehand" => Ok(Placement::Freehand(parse(DEFAULT_SPACING)?)),
+            _ => Err(format!("unknown placement {:?}, expected click, repeat[:ms] or freehand[:px]", s)),
+        }
+    }
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Placement::Click => f.write_str("click"),
+            Placement::Repeat(interval) => write!(f, "repeat:{}", interval.as_millis()),
+            Placement::Freehand(spacing) => write!(f, "freehand:{}", spacing),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::input::Input;
+    use crate::window::InputEvent;
+
+    /// A frame with the left button held at the given position, or released
+    fn frame(placer: &mut Placer, mouse: &mut MouseState, at: Option<(f32, f32)>, now: Instant) -> Option<(f32, f32)> {
+        let mut input = Input::default();
+        if let Some((x, y)) = at {
+            input.apply(InputEvent::MouseMove(x, y));
+            input.apply(InputEvent::MouseDown(MouseButton::Left));
+        }
+        mouse.update(&input, now);
+        placer.next_point(mouse, now)
+    }
+
+    #[test]
+    fn test_click() {
+        let (mut placer, mut mouse, now) = (Placer::new(Placement::Click), MouseState::default(), Instant::now());
+        assert_eq!(frame(&mut placer, &mut mouse, Some((1.0, 1.0)), now), Some((1.0, 1.0)));
+        assert_eq!(frame(&mut placer, &mut mouse, Some((50.0, 1.0)), now + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn test_repeat() {
+        let placement = Placement::Repeat(Duration::from_millis(100));
+        let (mut placer, mut mouse, now) = (Placer::new(placement), MouseState::default(), Instant::now());
+        let at = |ms: u64| now + Duration::from_millis(ms);
+
+        assert!(frame(&mut placer, &mut mouse, Some((1.0, 1.0)), at(0)).is_some());
+        assert!(frame(&mut placer, &mut mouse, Some((2.0, 1.0)), at(50)).is_none());
+        assert_eq!(frame(&mut placer, &mut mouse, Some((3.0, 1.0)), at(120)), Some((3.0, 1.0)));
+        assert!(frame(&mut placer, &mut mouse, None, at(300)).is_none());
+    }
+
+    #[test]
+    fn test_freehand() {
+        let (mut placer, mut mouse, now) = (Placer::new(Placement::Freehand(10.0)), MouseState::default(), Instant::now());
+        assert!(frame(&mut placer, &mut mouse, Some((0.0, 0.0)), now).is_some());
+        assert!(frame(&mut placer, &mut mouse, Some((6.0, 0.0)), now).is_none());
+        assert_eq!(frame(&mut placer, &mut mouse, Some((6.0, 8.0)), now), Some((6.0, 8.0)));
+        assert!(frame(&mut placer, &mut mouse, Some((10.0, 8.0)), now).is_none());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("click".parse::<Placement>(), Ok(Placement::Click));
+        assert_eq!("repeat".parse::<Placement>(), Ok(Placement::Repeat(DEFAULT_REPEAT)));
+        assert_eq!("repeat:250".parse::<Placement>(), Ok(Placement::Repeat(Duration::from_millis(250))));
+        assert_eq!("freehand:8".parse::<Placement>(), Ok(Placement::Freehand(8.0)));
+        assert_eq!(Placement::Freehand(8.0).to_string(), "freehand:8");
+
+        assert!("click:3".parse::<Placement>().is_err());
+        assert!("freehand:0".parse::<Placement>().is_err());
+        assert!("spray".parse::<Placement>().is_err());
+    }
+}
@@ -0,0 +1,35 @@
+//! Sending documents to the system's printer, without going through another application.
+
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Queues the PDF document on the default printer with the system's print spooler: `lp` on
+/// Linux and macOS, the shell's print verb on Windows. Returns once the spooler took it
+pub fn print_pdf(path: &Path) -> io::Result<()> {
+    let status = spooler(path).status().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => io::Error::new(e.kind(), "no print spooler was found"),
+        _ => e,
+    })?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("the print spooler failed ({})", status)))
+    }
+}
+
+#[cfg(windows)]
+fn spooler(path: &Path) -> Command {
+    // Single quotes are doubled inside a PowerShell string
+    let path = path.display().to_string().replace('\'', "''");
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-Command", &format!("Start-Process -FilePath '{}' -Verb Print", path)]);
+    command
+}
+
+#[cfg(not(windows))]
+fn spooler(path: &Path) -> Command {
+    let mut command = Command::new("lp");
+    command.arg("--").arg(path);
+    command
+}
@@ -0,0 +1,532 @@
+use crate::geometry;
+use crate::types::Point;
+
+use super::dirty::{DirtyRegion, Rect};
+use super::frame::Frame;
+use super::glyph::Glyph;
+use super::theme::BlendMode;
+
+/// A buffer of pixels and the primitives everything is drawn with: pixels, lines, circles
+/// and glyphs, all antialiased. It doesn't need a window, so scripts and tests can draw with
+/// it too
+pub struct Raster {
+    /// The pixels, row by row, as 0x00RRGGBB
+    pixels: Vec<u32>,
+    width: usize,
+    height: usize,
+    /// The pixels drawn over since the raster was last filled
+    dirty: DirtyRegion,
+    /// The color of every pixel that wasn't drawn over, if they are all the same, which
+    /// they are after a fill
+    clear_color: Option<u32>,
+    /// How much of each pixel the stroke being drawn covers, 0 outside of it, see
+    /// [`Raster::draw_polyline_aa`]
+    coverage: Vec<f32>,
+    /// The pixels the stroke being drawn covers, as indices
+    covered: Vec<usize>,
+}
+
+impl Raster {
+    /// A black raster of the given size
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            pixels: vec![0; width * height],
+            width,
+            height,
+            dirty: DirtyRegion::default(),
+            clear_color: Some(0),
+            coverage: Vec::new(),
+            covered: Vec::new(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The pixels, row by row, as 0x00RRGGBB
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// The pixels, to draw into them directly. They could all be drawn over
+    pub fn pixels_mut(&mut self) -> &mut [u32] {
+        if let Some(all) = self.bounds() {
+            self.dirty.add_rect(all);
+        }
+        &mut self.pixels
+    }
+
+    /// The rectangle of every pixel, if there are any
+    fn bounds(&self) -> Option<Rect> {
+        (self.width > 0 && self.height > 0).then(|| Rect { left: 0, top: 0, right: self.width - 1, bottom: self.height - 1 })
+    }
+
+    /// A read-only view of the pixels, e.g. to save them as an image
+    pub fn frame(&self) -> Frame<'_> {
+        Frame { pixels: &self.pixels, width: self.width, height: self.height }
+    }
+
+    /// Paints every pixel with the color
+    pub fn fill(&mut self, color: u32) {
+        self.pixels.fill(color);
+        self.dirty.take();
+        self.clear_color = Some(color);
+    }
+
+    /// Paints every pixel with the color, like [`Self::fill`], by only painting the ones drawn
+    /// over since the last fill if the others are that color already
+    pub fn clear(&mut self, color: u32) {
+        if self.clear_color != Some(color) {
+            return self.fill(color);
+        }
+        if let Some(rect) = self.dirty.take() {
+            for row in rect.top..=rect.bottom {
+                let start = row * self.width;
+                self.pixels[start + rect.left..=start + rect.right].fill(color);
+            }
+        }
+    }
+
+    /// The rectangle bounding the pixels drawn over since the last fill, if any
+    pub fn dirty_bounds(&self) -> Option<Rect> {
+        self.dirty.bounds()
+    }
+
+    /// Changes the size of the raster, which is cleared to black if it did change
+    pub fn resize(&mut self, width: usize, height: usize) {
+        if (width, height) != (self.width, self.height) {
+            *self = Self::new(width, height);
+        }
+    }
+
+    /// Draws the given color at the given pixel in the buffer using linear alpha blending.
+    /// This is a common technique, that forms the basis for antialiasing techniques such as
+    /// Xiaolin Wu's line algorithm
+    /// It blends a new color (color) with an existing one in the buffer (bg) at pixel (x, y)
+    /// based on an alpha value (opacity).
+    pub fn draw_pixel_aa(&mut self, x: i32, y: i32, color: u32, alpha: f32) {
+        self.blend_pixel(x, y, color, alpha, BlendMode::Normal);
+    }
+
+    /// Combines the color with the pixel already in the buffer, with the given blend mode
+    pub fn blend_pixel(&mut self, x: i32, y: i32, color: u32, alpha: f32, mode: BlendMode) {
+        let width = self.width;
+        let height = self.height;
+        if x < 0 || x >= width as i32 || y < 0 || y >= height as i32 {
+            return;
+        }
+
+        let index = y as usize * width + x as usize;
+        self.pixels[index] = mode.blend(color, self.pixels[index], alpha);
+        self.dirty.add(x as usize, y as usize);
+    }
+
+    /// Draw a given pixel with the target color, without antialiasing
+    pub fn draw_pixel(&mut self, x: i32, y: i32, color: u32) {
+        let width = self.width;
+        let height = self.height;
+
+        if x >= 0 && x < width as i32 && y >= 0 && y < height as i32 {
+            self.pixels[y as usize * width + x as usize] = color;
+            self.dirty.add(x as usize, y as usize);
+        }
+    }
+
+    /// Draw a circle centered at the given coordinates, and radius, with the given color
+    /// with antialiasing enabled
+    pub fn draw_circle_aa(&mut self, center_x: f32, center_y: f32, radius: f32, color: u32) {
+        let width = self.width;
+        let height = self.height;
+
+        let x0 = (center_x - radius - 1.0).max(0.0) as i32;
+        let y0 = (center_y - radius - 1.0).max(0.0) as i32;
+        let x1 = (center_x + radius + 1.0).min(width as f32 - 1.0) as i32;
+        let y1 = (center_y + radius + 1.0).min(height as f32 - 1.0) as i32;
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let dx = x as f32 - center_x;
+                let dy = y as f32 - center_y;
+                let distance = (dx * dx + dy * dy).sqrt();
+
+                if distance <= radius + 1.0 {
+                    let alpha = if distance <= radius - 1.0 {
+                        1.0
+                    } else {
+                        let t = distance - (radius - 1.0);
+                        1.0 - t.min(1.0)
+                    };
+
+                    self.draw_pixel_aa(x, y, color, alpha);
+                }
+            }
+        }
+    }
+
+    /// Draws a circle outline, two pixels wide, with antialiasing and the given opacity
+    pub fn draw_ring_aa(&mut self, center_x: f32, center_y: f32, radius: f32, color: u32, opacity: f32) {
+        let reach = radius + 2.0;
+        let (x0, y0) = ((center_x - reach).floor() as i32, (center_y - reach).floor() as i32);
+        let (x1, y1) = ((center_x + reach).ceil() as i32, (center_y + reach).ceil() as i32);
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let (dx, dy) = (x as f32 - center_x, y as f32 - center_y);
+                let distance = ((dx * dx + dy * dy).sqrt() - radius).abs();
+                let alpha = (1.5 - distance).clamp(0.0, 1.0) * opacity;
+                if alpha > 0.0 {
+                    self.draw_pixel_aa(x, y, color, alpha);
+                }
+            }
+        }
+    }
+
+    /// Draws an arrow from one point to the other, with its head on the second one
+    pub fn draw_arrow(&mut self, from: Point, to: Point, color: u32, opacity: f32) {
+        let length = (to - from).norm();
+        if length == 0.0 {
+            return;
+        }
+
+        // A rough line: arrows are thin and short-lived, so Wu's algorithm isn't worth it here
+        let direction = (to - from) / length;
+        let steps = length.ceil() as i32;
+        for i in 0..=steps {
+            let p = from + direction * (i as f32);
+            self.draw_pixel_aa(p.x.round() as i32, p.y.round() as i32, color, opacity);
+        }
+
+        // The head is two short strokes back from the tip, at 30 degrees on either side
+        let (sin, cos) = (0.5f32, 0.866f32);
+        for side in [-1.0, 1.0] {
+            let back = nalgebra::Vector2::new(
+                -(direction.x * cos - side * direction.y * sin),
+                -(side * direction.x * sin + direction.y * cos),
+            );
+            for i in 0..=10 {
+                let p = to + back * (i as f32);
+                self.draw_pixel_aa(p.x.round() as i32, p.y.round() as i32, color, opacity);
+            }
+        }
+    }
+
+    /// Draws a glyph centered at the given coordinates, with antialiasing
+    pub fn draw_glyph_aa(&mut self, center_x: f32, center_y: f32, radius: f32, color: u32, glyph: Glyph) {
+        // Circles keep their own, slightly softer, antialiasing
+        if glyph == Glyph::Circle {
+            self.draw_circle_aa(center_x, center_y, radius, color);
+            return;
+        }
+
+        // All the glyphs fit in a box of 1.2 times the radius, plus a pixel for antialiasing
+        let reach = (radius * 1.2).ceil() + 1.0;
+        let (x0, y0) = ((center_x - reach).floor() as i32, (center_y - reach).floor() as i32);
+        let (x1, y1) = ((center_x + reach).ceil() as i32, (center_y + reach).ceil() as i32);
+
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                let distance = glyph.signed_distance(x as f32 - center_x, y as f32 - center_y, radius);
+                let alpha = (0.5 - distance).clamp(0.0, 1.0);
+                if alpha > 0.0 {
+                    self.draw_pixel_aa(x, y, color, alpha);
+                }
+            }
+        }
+    }
+
+    /// Draws lines between the points, and back to the first one if the path is closed,
+    /// the given number of pixels wide. Lines a pixel wide or less are Wu lines, see
+    /// [`Self::draw_line_aa`]. Wider ones are stroked as a whole, with round ends and joins:
+    /// each pixel takes the most any line covers of it, so it is blended once even where
+    /// the lines overlap, and translucent paths don't darken at their joins
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_polyline_aa(
+        &mut self,
+        points: &[Point],
+        closed: bool,
+        width: f32,
+        color: u32,
+        opacity: f32,
+        mode: BlendMode,
+    ) {
+        let closing = (closed && points.len() > 2).then(|| [points[points.len() - 1], points[0]]);
+        let segments = points.windows(2).map(|pair| [pair[0], pair[1]]).chain(closing);
+        if width <= 1.0 {
+            for [start, end] in segments {
+                self.draw_line_aa(start.x, start.y, end.x, end.y, color, opacity, mode);
+            }
+            return;
+        }
+
+        self.coverage.resize(self.width * self.height, 0.0);
+        let half = width / 2.0;
+        let reach = half + 1.0;
+        for [start, end] in segments {
+            let y0 = (start.y.min(end.y) - reach).floor().max(0.0) as usize;
+            let y1 = (start.y.max(end.y) + reach).ceil().min(self.height as f32 - 1.0);
+            if y1 < 0.0 {
+                continue;
+            }
+            // Each row is only scanned across the pixels near the segment, rather than
+            // across its whole bounding box, which is mostly empty for diagonal lines
+            for y in y0..=y1 as usize {
+                let Some((left, right)) = row_near_segment(start, end, reach, y as f32) else {
+                    continue;
+                };
+                let x0 = left.floor().max(0.0) as usize;
+                let x1 = right.ceil().min(self.width as f32 - 1.0);
+                if x1 < 0.0 {
+                    continue;
+                }
+                for x in x0..=x1 as usize {
+                    let distance = geometry::distance_to_segment(Point::new(x as f32, y as f32), start, end);
+                    let alpha = (half + 0.5 - distance).clamp(0.0, 1.0);
+                    let index = y * self.width + x;
+                    if alpha > self.coverage[index] {
+                        if self.coverage[index] == 0.0 {
+                            self.covered.push(index);
+                        }
+                        self.coverage[index] = alpha;
+                    }
+                }
+            }
+        }
+
+        let mut covered = std::mem::take(&mut self.covered);
+        for index in covered.drain(..) {
+            let (x, y) = (index % self.width, index / self.width);
+            self.blend_pixel(x as i32, y as i32, color, self.coverage[index] * opacity, mode);
+            self.coverage[index] = 0.0;
+        }
+        self.covered = covered;
+    }
+
+    /// Draws a line between the two points, with the target color and opacity blended with
+    /// the given mode, using Xiaolin Wu's line algorithm, with antialiasing enabled
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_line_aa(
+        &mut self,
+        mut x0: f32,
+        mut y0: f32,
+        mut x1: f32,
+        mut y1: f32,
+        color: u32,
+        opacity: f32,
+        mode: BlendMode,
+    ) {
+        // Determine if the line is steep
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+
+        // Make sure x0 <= x1
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let dy = y1 - y0;
+        let gradient = if dx.abs() < 1e-6 { 1.0 } else { dy / dx };
+
+        // Handle first endpoint
+        let xend = x0.round();
+        let yend = y0 + gradient * (xend - x0);
+        let xgap = 1.0 - (x0 + 0.5 - xend).abs();
+        let xpxl1 = xend as i32;
+        let ypxl1 = yend.floor() as i32;
+
+        if steep {
+            self.blend_pixel(ypxl1, xpxl1, color, (1.0 - (yend - yend.floor())) * xgap * opacity, mode);
+            self.blend_pixel(ypxl1 + 1, xpxl1, color, (yend - yend.floor()) * xgap * opacity, mode);
+        } else {
+            self.blend_pixel(xpxl1, ypxl1, color, (1.0 - (yend - yend.floor())) * xgap * opacity, mode);
+            self.blend_pixel(xpxl1, ypxl1 + 1, color, (yend - yend.floor()) * xgap * opacity, mode);
+        }
+
+        let mut intery = yend + gradient;
+
+        // Handle second endpoint
+        let xend = x1.round();
+        let yend = y1 + gradient * (xend - x1);
+        let xgap = (x1 + 0.5 - xend).abs();
+        let xpxl2 = xend as i32;
+        let ypxl2 = yend.floor() as i32;
+
+        if steep {
+            self.blend_pixel(ypxl2, xpxl2, color, (1.0 - (yend - yend.floor())) * xgap * opacity, mode);
+            self.blend_pixel(ypxl2 + 1, xpxl2, color, (yend - yend.floor()) * xgap * opacity, mode);
+        } else {
+            self.blend_pixel(xpxl2, ypxl2, color, (1.0 - (yend - yend.floor())) * xgap * opacity, mode);
+            self.blend_pixel(xpxl2, ypxl2 + 1, color, (yend - yend.floor()) * xgap * opacity, mode);
+        }
+
+        // Main loop
+        if steep {
+            for x in (xpxl1 + 1)..xpxl2 {
+                self.blend_pixel(intery.floor() as i32, x, color, (1.0 - (intery - intery.floor())) * opacity, mode);
+                self.blend_pixel(intery.floor() as i32 + 1, x, color, (intery - intery.floor()) * opacity, mode);
+                intery += gradient;
+            }
+        } else {
+            for x in (xpxl1 + 1)..xpxl2 {
+                self.blend_pixel(x, intery.floor() as i32, color, (1.0 - (intery - intery.floor())) * opacity, mode);
+                self.blend_pixel(x, intery.floor() as i32 + 1, color, (intery - intery.floor()) * opacity, mode);
+                intery += gradient;
+            }
+        }
+    }
+}
+
+/// Where the row at the height is within the distance of the segment, from its left to its
+/// right: the points near either end, and those along the segment, within the distance of
+/// the line through it. None if the row doesn't come that close
+fn row_near_segment(start: Point, end: Point, distance: f32, y: f32) -> Option<(f32, f32)> {
+    let mut span: Option<(f32, f32)> = None;
+    let mut include = |left: f32, right: f32| {
+        span = Some(span.map_or((left, right), |(min, max)| (min.min(left), max.max(right))));
+    };
+    for point in [start, end] {
+        let dy = y - point.y;
+        if dy.abs() <= distance {
+            let half = (distance * distance - dy * dy).sqrt();
+            include(point.x - half, point.x + half);
+        }
+    }
+
+    let direction = end - start;
+    let length = direction.norm();
+    if length > 0.0 {
+        // How far along the segment and across it the row's point at x is, both linear in x:
+        // a (x - start.x) has to be between the bounds
+        let dy = y - start.y;
+        let along = (direction.x, -dy * direction.y, length * length - dy * direction.y);
+        let across = (-direction.y, -distance * length - dy * direction.x, distance * length - dy * direction.x);
+        let (mut left, mut right) = (f32::NEG_INFINITY, f32::INFINITY);
+        for (a, low, high) in [along, across] {
+            if a == 0.0 {
+                if low > 0.0 || high < 0.0 {
+                    return span;
+                }
+                continue;
+            }
+            let (low, high) = (low / a, high / a);
+            left = left.max(start.x + low.min(high));
+            right = right.min(start.x + low.max(high));
+        }
+        if left <= right {
+            include(left, right);
+        }
+    }
+    span
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_pixel() {
+        let mut raster = Raster::new(4, 3);
+        raster.draw_pixel(1, 2, 0xFFFFFF);
+        // Pixels off the raster are left out
+        raster.draw_pixel(4, 0, 0xFFFFFF);
+        raster.draw_pixel(-1, 0, 0xFFFFFF);
+        assert_eq!(raster.pixels()[2 * 4 + 1], 0xFFFFFF);
+        assert_eq!(raster.pixels().iter().filter(|pixel| **pixel != 0).count(), 1);
+
+        raster.resize(2, 2);
+        assert_eq!(raster.frame().pixels, [0; 4]);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut raster = Raster::new(20, 10);
+        raster.fill(0x202020);
+        raster.draw_line_aa(2.0, 5.0, 6.0, 7.0, 0xFFFFFF, 1.0, BlendMode::Normal);
+        raster.draw_pixel(12, 1, 0xFFFFFF);
+        assert_eq!(raster.dirty_bounds(), Some(Rect { left: 2, top: 1, right: 12, bottom: 8 }));
+
+        // Only the pixels drawn over are painted again
+        raster.clear(0x202020);
+        assert!(raster.pixels().iter().all(|pixel| *pixel == 0x202020));
+        assert_eq!(raster.dirty_bounds(), None);
+
+        // A new color paints every pixel, as do pixels that could be drawn over directly
+        raster.clear(0x000000);
+        assert!(raster.pixels().iter().all(|pixel| *pixel == 0));
+        raster.pixels_mut()[0] = 0xFFFFFF;
+        assert_eq!(raster.dirty_bounds().map(|rect| (rect.width(), rect.height())), Some((20, 10)));
+        raster.clear(0x000000);
+        assert_eq!(raster.pixels()[0], 0);
+    }
+
+    #[test]
+    fn test_draw_line_aa() {
+        let mut raster = Raster::new(20, 10);
+        raster.draw_line_aa(2.0, 5.0, 17.0, 5.0, 0xFFFFFF, 1.0, BlendMode::Normal);
+        assert_eq!(raster.pixels()[5 * 20 + 10], 0xFFFFFF);
+        assert_eq!(raster.pixels()[2 * 20 + 10], 0);
+
+        // Half the opacity gives half the brightness
+        let mut faint = Raster::new(20, 10);
+        faint.draw_line_aa(2.0, 5.0, 17.0, 5.0, 0xFFFFFF, 0.5, BlendMode::Normal);
+        let channel = faint.pixels()[5 * 20 + 10] & 0xFF;
+        assert!((0x70..=0x90).contains(&channel));
+    }
+
+    #[test]
+    fn test_draw_polyline_aa() {
+        let path = [Point::new(3.0, 10.0), Point::new(16.0, 10.0), Point::new(16.0, 3.0)];
+        let lit = |raster: &Raster, x: usize, y: usize| raster.pixels()[y * 20 + x] & 0xFF;
+
+        // Three pixels wide, the line covers the rows on either side of its own
+        let mut raster = Raster::new(20, 15);
+        raster.draw_polyline_aa(&path, false, 3.0, 0xFFFFFF, 1.0, BlendMode::Normal);
+        assert!((9..=11).all(|y| lit(&raster, 8, y) == 0xFF));
+        assert_eq!(lit(&raster, 8, 12), 0);
+        assert!((15..=17).all(|x| lit(&raster, x, 6) == 0xFF));
+
+        // Translucent, the join is blended once, no brighter than the rest
+        let mut raster = Raster::new(20, 15);
+        raster.draw_polyline_aa(&path, false, 3.0, 0xFFFFFF, 0.5, BlendMode::Additive);
+        assert_eq!(lit(&raster, 16, 10), lit(&raster, 8, 10));
+
+        // Closed, the path goes back to its start
+        let mut raster = Raster::new(20, 15);
+        raster.draw_polyline_aa(&path, true, 3.0, 0xFFFFFF, 1.0, BlendMode::Normal);
+        assert_eq!(lit(&raster, 10, 6), 0xFF);
+    }
+
+    #[test]
+    fn test_row_near_segment() {
+        let segments = [
+            (Point::new(2.0, 3.0), Point::new(40.0, 31.0)),
+            (Point::new(10.0, 5.0), Point::new(10.0, 30.0)),
+            (Point::new(5.0, 12.0), Point::new(30.0, 12.0)),
+            (Point::new(8.0, 8.0), Point::new(8.0, 8.0)),
+        ];
+        for (start, end) in segments {
+            for y in 0..40 {
+                let y = y as f32;
+                let span = row_near_segment(start, end, 2.5, y);
+                // Exactly the points of the row within the distance
+                for x in (-100..500).map(|x| x as f32 / 10.0) {
+                    let near = geometry::distance_to_segment(Point::new(x, y), start, end) <= 2.5 - 1e-3;
+                    let far = geometry::distance_to_segment(Point::new(x, y), start, end) > 2.5 + 1e-3;
+                    let within = span.is_some_and(|(left, right)| left <= x && x <= right);
+                    assert!(!near || within, "{:?} {:?} at ({}, {})", start, end, x, y);
+                    assert!(!far || !within, "{:?} {:?} at ({}, {})", start, end, x, y);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+/// How a run of text is set
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Script {
+    /// On the line, at the full size
+    Normal,
+    /// Smaller and below the line, e.g. an index
+    Subscript,
+}
+
+/// How heavy a run of text is, and what it stands for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    Regular,
+    /// Emphasized, e.g. the number of a tutorial's lesson
+    Bold,
+    /// A key to press, set in bold in a box like a keycap
+    Key,
+    /// The title of a panel, larger and bold
+    Heading,
+}
+
+/// A piece of text set the same way throughout
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Run {
+    pub text: String,
+    pub script: Script,
+    pub style: Style,
+}
+
+/// How much smaller subscripts are than the text around them
+pub const SUBSCRIPT_SCALE: f32 = 0.7;
+/// How far below the line subscripts are, as a fraction of the text's size
+pub const SUBSCRIPT_DROP: f32 = 0.35;
+/// How much larger headings are than the text they are written with
+pub const HEADING_SCALE: f32 = 1.3;
+
+impl Run {
+    /// The size the run is set at, and how far below the line, in text of the given size
+    pub fn size_and_drop(&self, size: f32) -> (f32, f32) {
+        match (self.script, self.style) {
+            (Script::Subscript, _) => (size * SUBSCRIPT_SCALE, size * SUBSCRIPT_DROP),
+            (Script::Normal, Style::Heading) => (size * HEADING_SCALE, 0.0),
+            (Script::Normal, _) => (size, 0.0),
+        }
+    }
+}
+
+/// Splits styled text into runs:
+///
+/// - a subscript follows an underscore: a single character, or several in braces, e.g.
+///   "P_i" or "P_{i+1}". Underscores that aren't followed by anything are kept as they are
+/// - bold text is between double asterisks, e.g. "**1/4** Click"
+/// - keys are in brackets, e.g. "[Ctrl+S]". A bracket that isn't closed is kept as it is
+/// - a line starting with "# " is a heading
+pub fn parse(markup: &str) -> Vec<Run> {
+    let (markup, base) = match markup.strip_prefix("# ") {
+        Some(heading) => (heading, Style::Heading),
+        None => (markup, Style::Regular),
+    };
+    let mut runs = Vec::new();
+    let mut text = String::new();
+    let mut bold = false;
+    let style = |bold: bool| if bold && base == Style::Regular { Style::Bold } else { base };
+    let mut chars = markup.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let (run, script, run_style) = match (c, chars.peek().copied()) {
+            ('_', Some('{')) => {
+                chars.next();
+                (chars.by_ref().take_while(|&c| c != '}').collect(), Script::Subscript, style(bold))
+            }
+            ('_', Some(_)) => (chars.next().map(String::from).unwrap_or_default(), Script::Subscript, style(bold)),
+            ('*', Some('*')) => {
+                chars.next();
+                if !text.is_empty() {
+                    runs.push(Run { text: std::mem::take(&mut text), script: Script::Normal, style: style(bold) });
+                }
+                bold = !bold;
+                continue;
+            }
+            ('[', Some(next)) if next != ']' && chars.clone().any(|c| c == ']') => {
+                (chars.by_ref().take_while(|&c| c != ']').collect(), Script::Normal, Style::Key)
+            }
+            _ => {
+                text.push(c);
+                continue;
+            }
+        };
+
+        if !text.is_empty() {
+            runs.push(Run { text: std::mem::take(&mut text), script: Script::Normal, style: style(bold) });
+        }
+        runs.push(Run { text: run, script, style: run_style });
+    }
+
+    if !text.is_empty() {
+        runs.push(Run { text, script: Script::Normal, style: style(bold) });
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str, script: Script) -> Run {
+        styled(text, script, Style::Regular)
+    }
+
+    fn styled(text: &str, script: Script, style: Style) -> Run {
+        Run { text: text.to_string(), script, style }
+    }
+
+    #[test]
+    fn test_parse_subscripts() {
+        assert_eq!(
+            parse("Q = 0.75·P_i + 0.25·P_{i+1}"),
+            vec![
+                run("Q = 0.75·P", Script::Normal),
+                run("i", Script::Subscript),
+                run(" + 0.25·P", Script::Normal),
+                run("i+1", Script::Subscript),
+            ]
+        );
+        assert_eq!(parse("file_"), vec![run("file_", Script::Normal)]);
+        assert_eq!(parse(""), vec![]);
+    }
+
+    #[test]
+    fn test_parse_styles() {
+        assert_eq!(
+            parse("**2/4** Press [Enter] to smooth them"),
+            vec![
+                styled("2/4", Script::Normal, Style::Bold),
+                run(" Press ", Script::Normal),
+                styled("Enter", Script::Normal, Style::Key),
+                run(" to smooth them", Script::Normal),
+            ]
+        );
+        assert_eq!(parse("# Session"), vec![styled("Session", Script::Normal, Style::Heading)]);
+        // Brackets that don't hold a key are text
+        assert_eq!(parse("[] and [open"), vec![run("[] and [open", Script::Normal)]);
+        assert_eq!(
+            parse("**P_i**"),
+            vec![styled("P", Script::Normal, Style::Bold), styled("i", Script::Subscript, Style::Bold)]
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use minifb::Key;
+
+use super::input::Input;
+use crate::document;
+
+/// How many matches are listed under the query
+pub const MAX_RESULTS: usize = 8;
+/// Bonus for a character matched right after the previous one
+const CONSECUTIVE_BONUS: u32 = 5;
+/// Bonus for a character matched at the start of a word
+const WORD_START_BONUS: u32 = 3;
+
+/// A file of points, by the name of its shape
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shape {
+    /// The name in the file's metadata, or the file's name if it has none
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// What the user did with the search this frame
+#[derive(Clone, Debug, PartialEq)]
+pub enum SearchOutcome {
+    /// Still typing, the search stays open
+    Open,
+    /// The user picked the shape in the given file
+    Chose(PathBuf),
+    /// The user closed the search with [Escape]
+    Cancelled,
+}
+
+/// A quick search over the shapes next to the open file: the matches of what is typed are
+/// listed as it changes, best first, and [Enter] picks the highlighted one
+pub struct ShapeSearch {
+    shapes: Vec<Shape>,
+    pub query: String,
+    /// The highlighted match, as an index into [`Self::matches`]
+    pub selected: usize,
+}
+
+impl ShapeSearch {
+    pub fn new(shapes: Vec<Shape>) -> Self {
+        Self { shapes, query: String::new(), selected: 0 }
+    }
+
+    /// Searches the shapes in the files of points directly inside the directory. Files
+    /// that can't be read are left out
+    pub fn scan(directory: &Path) -> Self {
+        let mut paths: Vec<PathBuf> = fs::read_dir(directory)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "txt") && path.is_file())
+            .collect();
+        paths.sort();
+
+        let shapes = paths
+            .into_iter()
+            .filter_map(|path| {
+                let document = document::read_document(&path).ok()?;
+                let name = match document.metadata.name {
+                    name if !name.is_empty() => name,
+                    _ => path.file_stem()?.to_string_lossy().into_owned(),
+                };
+                Some(Shape { name, path })
+            })
+            .collect();
+        Self::new(shapes)
+    }
+
+    /// The shapes whose name matches the query, best first, up to [`MAX_RESULTS`]
+    pub fn matches(&self) -> Vec<&Shape> {
+        let mut matches: Vec<(u32, &Shape)> = self.shapes
+            .iter()
+            .filter_map(|shape| Some((fuzzy_score(&self.query, &shape.name)?, shape)))
+            .collect();
+        // Among equal scores, shorter names match more of themselves
+        matches.sort_by(|(a, first), (b, second)| {
+            b.cmp(a).then(first.name.len().cmp(&second.name.len())).then(first.name.cmp(&second.name))
+        });
+        matches.into_iter().take(MAX_RESULTS).map(|(_, shape)| shape).collect()
+    }
+
+    /// Handles this frame's keyboard input
+    pub fn handle(&mut self, input: &Input) -> SearchOutcome {
+        if input.is_key_pressed(Key::Escape) {
+            return SearchOutcome::Cancelled;
+        }
+        if input.is_key_pressed(Key::Enter) || input.is_key_pressed(Key::NumPadEnter) {
+            return match self.matches().get(self.selected) {
+                Some(shape) => SearchOutcome::Chose(shape.path.clone()),
+                None => SearchOutcome::Open,
+            };
+        }
+
+        let count = self.matches().len();
+        if input.is_key_repeated(Key::Down) && self.selected + 1 < count {
+            self.selected += 1;
+        }
+        if input.is_key_repeated(Key::Up) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if input.is_key_repeated(Key::Backspace) {
+            self.query.pop();
+            self.selected = 0;
+        }
+        if !input.text().is_empty() {
+            self.query.push_str(input.text());
+            self.selected = 0;
+        }
+        SearchOutcome::Open
+    }
+}
+
+/// How well the name matches the query, or none if it doesn't: the characters of the query
+/// have to appear in the name in order, ignoring case, and score higher when they follow
+/// each other or start words. An empty query matches every name
+pub fn fuzzy_score(query: &str, name: &str) -> Option<u32> {
+    let name: Vec<char> = name.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous = None;
+    for wanted in query.chars().flat_map(char::to_lowercase).filter(|c| !c.is_whitespace()) {
+        let found = next + name[next..].iter().position(|c| *c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += CONSECUTIVE_BONUS;
+        }
+        if found == 0 || !name[found - 1].is_alphanumeric() {
+            score += WORD_START_BONUS;
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::InputEvent;
+
+    fn shape(name: &str) -> Shape {
+        Shape { name: name.to_string(), path: PathBuf::from(format!("{}.txt", name)) }
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "Star"), Some(0));
+        assert!(fuzzy_score("sr", "Star").is_some());
+        assert_eq!(fuzzy_score("rs", "Star"), None);
+        assert_eq!(fuzzy_score("STAR", "star"), fuzzy_score("star", "star"));
+        // Consecutive characters and word starts score higher
+        assert!(fuzzy_score("sta", "Star") > fuzzy_score("sta", "Sweet tart"));
+        assert!(fuzzy_score("bs", "Big star") > fuzzy_score("bs", "Blobs"));
+    }
+
+    #[test]
+    fn test_search() {
+        let mut search = ShapeSearch::new(vec![shape("Star"), shape("Spiral"), shape("Blob")]);
+        assert_eq!(search.matches().len(), 3);
+
+        let mut input = Input::default();
+        input.apply(InputEvent::Char('s'));
+        assert_eq!(search.handle(&input), SearchOutcome::Open);
+        let names: Vec<&str> = search.matches().iter().map(|shape| shape.name.as_str()).collect();
+        assert_eq!(names, ["Star", "Spiral"]);
+
+        let mut input = Input::default();
+        input.apply(InputEvent::KeyDown(Key::Down));
+        search.handle(&input);
+        let mut input = Input::default();
+        input.apply(InputEvent::KeyDown(Key::Enter));
+        assert_eq!(search.handle(&input), SearchOutcome::Chose(PathBuf::from("Spiral.txt")));
+
+        // Nothing is picked when nothing matches
+        search.query = "xyz".to_string();
+        assert_eq!(search.handle(&input), SearchOutcome::Open);
+    }
+}
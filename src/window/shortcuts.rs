@@ -0,0 +1,399 @@
+use std::fmt;
+use std::str::FromStr;
+
+use minifb::Key;
+
+use super::input::Input;
+use crate::algorithm::Scheme;
+
+/// Something the user can ask the canvas to do from the keyboard
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Close the window, asking first if there are unsaved points
+    Quit,
+    /// Clear the points, asking first if they are unsaved
+    Reset,
+    /// Start animating the points
+    Animate,
+    /// Add a point by typing its coordinates
+    EnterPoint,
+    /// Open a file of points by typing its path
+    OpenFile,
+    /// Open the color picker
+    PickColors,
+    /// Hide the toast
+    DismissToast,
+    /// Show or hide the final curve behind the points while drawing
+    ToggleGhost,
+    /// Show or hide the curve the steps converge to, dashed behind them while animating
+    ToggleLimit,
+    /// Draw each step of the animation in its own color, or all in the line color
+    ToggleGradient,
+    /// Show or hide the steps before the current one under it while animating
+    ToggleOnionSkin,
+    /// Show or hide the control polygon under the curve while animating
+    ToggleControlPolygon,
+    /// Connect the last point back to the first, or not
+    ToggleClosed,
+    /// Only cut the corners that aren't flat yet with Chaikin's scheme, or every corner
+    ToggleAdaptive,
+    /// Draw the lines a pixel wider, back to a single pixel after the widest
+    CycleStrokeWidth,
+    /// Move the selected point by the given number of pixels, right and down, while drawing
+    Nudge { x: i32, y: i32 },
+    /// Export the control polygon and the curve at the current step as an SVG image
+    ExportSvg,
+    /// Save the points with the current settings as a session, to open again later
+    SaveSession,
+    /// Export every step of the animation as a printable PDF handout, a page per step
+    ExportHandout,
+    /// Save what the window shows as a PNG image
+    Screenshot,
+    /// Print the control polygon and the curve at the current step with the system's printer
+    Print,
+    /// Name the shape, and write notes and tags about it
+    Describe,
+    /// Show each step of the animation for half as long
+    Faster,
+    /// Show each step of the animation for twice as long
+    Slower,
+    /// Pause the animation on the current step, or resume it
+    TogglePause,
+    /// Pause the animation and show the next step
+    StepForward,
+    /// Pause the animation and show the step before
+    StepBack,
+    /// Animate one more smoothing step before starting over
+    MoreSteps,
+    /// Animate one smoothing step less before starting over
+    FewerSteps,
+    /// Show or hide the formulas of the scheme
+    ToggleFormulas,
+    /// Show or hide the interior angle at each control point
+    ToggleAngles,
+    /// Show the coordinates of the point under the mouse to the pixel, or in full
+    TogglePrecision,
+    /// Finish the polyline being drawn and start another one
+    NewPolyline,
+    /// Switch between smoothing the points and tracing their Bézier curve
+    ToggleBezier,
+    /// Scale and move the view to fit the shape in the canvas
+    FitView,
+    /// Go back to drawing the points' coordinates as the canvas' pixels, undoing zooms and pans
+    ResetView,
+    /// Search the shapes next to the open file by name, and open the chosen one
+    SearchShapes,
+    /// Smooth the points with the given scheme
+    UseScheme(Scheme),
+    /// Smooth the points with the Lane-Riesenfeld scheme one degree higher, from Chaikin's
+    CycleDegree,
+    /// Show or hide the statistics of the session
+    ToggleStats,
+    /// Start an exercise matching a target curve, or end it
+    ToggleQuiz,
+    /// Undo the last edit made to the points
+    Undo,
+    /// Redo the last edit undone
+    Redo,
+    /// Switch to the next way of placing points: per click, repeated or freehand
+    CyclePlacement,
+    /// Show or hide the grid behind the points
+    ToggleGrid,
+    /// Place and drag points onto the grid's intersections, or anywhere
+    ToggleSnap,
+    /// Halve the grid's spacing
+    FinerGrid,
+    /// Double the grid's spacing
+    CoarserGrid,
+}
+
+/// A key with the modifiers that have to be held with it, e.g. Ctrl+Shift+E
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chord {
+    pub key: Key,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+/// Whether a shortcut fires again while its key is held down
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Repeat {
+    /// Once per press
+    No,
+    /// At the keyboard's repeat rate, e.g. for nudging
+    Yes,
+}
+
+/// A chord bound to a command
+#[derive(Clone, Copy, Debug)]
+pub struct Shortcut {
+    pub chord: Chord,
+    pub command: Command,
+    pub repeat: Repeat,
+}
+
+/// The keyboard shortcuts of the canvas, resolved in one place so that chords and key repeat
+/// are handled the same way for every command
+pub struct Shortcuts {
+    shortcuts: Vec<Shortcut>,
+}
+
+impl Chord {
+    /// The key alone, without modifiers
+    pub fn key(key: Key) -> Self {
+        Self { key, ctrl: false, shift: false, alt: false }
+    }
+
+    /// The key with Ctrl held
+    pub fn ctrl(key: Key) -> Self {
+        Self { ctrl: true, ..Self::key(key) }
+    }
+
+    /// Whether the chord's key went down this frame with exactly its modifiers held, or
+    /// repeated while held if the repeat policy allows it
+    fn matches(&self, input: &Input, repeat: Repeat) -> bool {
+        let triggered = input.is_key_pressed(self.key)
+            || (repeat == Repeat::Yes && input.is_key_repeated(self.key));
+        triggered
+            && input.is_ctrl_down() == self.ctrl
+            && input.is_shift_down() == self.shift
+            && input.is_alt_down() == self.alt
+    }
+}
+
+impl Default for Shortcuts {
+    fn default() -> Self {
+        let shortcuts = [
+            (Chord::key(Key::Escape), Command::Quit),
+            (Chord::ctrl(Key::R), Command::Reset),
+            (Chord::key(Key::Enter), Command::Animate),
+            (Chord::ctrl(Key::E), Command::EnterPoint),
+            (Chord::ctrl(Key::O), Command::OpenFile),
+            (Chord::ctrl(Key::K), Command::PickColors),
+            (Chord::key(Key::Delete), Command::DismissToast),
+            (Chord::ctrl(Key::M), Command::CyclePlacement),
+            (Chord::ctrl(Key::G), Command::ToggleGhost),
+            (Chord::key(Key::I), Command::ToggleLimit),
+            (Chord::key(Key::K), Command::ToggleControlPolygon),
+            (Chord::key(Key::G), Command::ToggleGrid),
+            (Chord { shift: true, ..Chord::key(Key::G) }, Command::ToggleSnap),
+            (Chord::key(Key::LeftBracket), Command::FinerGrid),
+            (Chord::key(Key::RightBracket), Command::CoarserGrid),
+            (Chord::key(Key::C), Command::ToggleClosed),
+            (Chord::key(Key::D), Command::ToggleAdaptive),
+            (Chord::key(Key::W), Command::CycleStrokeWidth),
+            (Chord::key(Key::T), Command::ToggleGradient),
+            (Chord::key(Key::O), Command::ToggleOnionSkin),
+            (Chord::ctrl(Key::S), Command::ExportSvg),
+            (Chord { shift: true, ..Chord::ctrl(Key::S) }, Command::SaveSession),
+            (Chord::ctrl(Key::H), Command::ExportHandout),
+            (Chord::ctrl(Key::P), Command::Screenshot),
+            (Chord { shift: true, ..Chord::ctrl(Key::P) }, Command::Print),
+            (Chord::ctrl(Key::D), Command::Describe),
+            (Chord::ctrl(Key::I), Command::ToggleStats),
+            (Chord::key(Key::Equal), Command::Faster),
+            // "+" is typed with Shift on most layouts
+            (Chord { shift: true, ..Chord::key(Key::Equal) }, Command::Faster),
+            (Chord::key(Key::NumPadPlus), Command::Faster),
+            (Chord::key(Key::Minus), Command::Slower),
+            (Chord::key(Key::NumPadMinus), Command::Slower),
+            (Chord::ctrl(Key::Z), Command::Undo),
+            (Chord::ctrl(Key::Y), Command::Redo),
+            (Chord { shift: true, ..Chord::ctrl(Key::Z) }, Command::Redo),
+            (Chord::key(Key::Space), Command::TogglePause),
+            (Chord::key(Key::Up), Command::MoreSteps),
+            (Chord::key(Key::Down), Command::FewerSteps),
+            (Chord::key(Key::F), Command::ToggleFormulas),
+            (Chord::key(Key::Q), Command::ToggleQuiz),
+            (Chord::key(Key::A), Command::ToggleAngles),
+            (Chord::key(Key::P), Command::TogglePrecision),
+            (Chord::ctrl(Key::F), Command::SearchShapes),
+            (Chord::key(Key::B), Command::ToggleBezier),
+            (Chord::key(Key::N), Command::NewPolyline),
+            (Chord { shift: true, ..Chord::key(Key::F) }, Command::FitView),
+            (Chord::ctrl(Key::Key0), Command::ResetView),
+            (Chord::key(Key::Key1), Command::UseScheme(Scheme::Chaikin)),
+            (Chord::key(Key::Key2), Command::UseScheme(Scheme::CatmullRom)),
+            (Chord::key(Key::Key3), Command::UseScheme(Scheme::LaneRiesenfeld(3))),
+            (Chord::key(Key::Key4), Command::UseScheme(Scheme::FourPoint)),
+            (Chord::key(Key::L), Command::CycleDegree),
+        ];
+        let mut shortcuts = Self {
+            shortcuts: shortcuts
+                .into_iter()
+                .map(|(chord, command)| Shortcut { chord, command, repeat: Repeat::No })
+                .collect(),
+        };
+        // Holding an arrow goes through the steps
+        shortcuts.bind(Chord::key(Key::Right), Command::StepForward, Repeat::Yes);
+        shortcuts.bind(Chord::key(Key::Left), Command::StepBack, Repeat::Yes);
+        // While drawing, it nudges the selected point instead, ten pixels at a time with Shift
+        for (key, (x, y)) in [(Key::Left, (-1, 0)), (Key::Right, (1, 0)), (Key::Up, (0, -1)), (Key::Down, (0, 1))] {
+            shortcuts.bind_also(Chord::key(key), Command::Nudge { x, y }, Repeat::Yes);
+            let far = Command::Nudge { x: x * 10, y: y * 10 };
+            shortcuts.bind(Chord { shift: true, ..Chord::key(key) }, far, Repeat::Yes);
+        }
+        shortcuts
+    }
+}
+
+impl Shortcuts {
+    /// Binds the chord to the command, replacing what the chord was bound to
+    pub fn bind(&mut self, chord: Chord, command: Command, repeat: Repeat) {
+        self.shortcuts.retain(|shortcut| shortcut.chord != chord);
+        self.shortcuts.push(Shortcut { chord, command, repeat });
+    }
+
+    /// Binds the chord to the command as well as to what it is bound to already, for
+    /// commands which only apply when the others don't, e.g. while drawing rather than
+    /// animating
+    pub fn bind_also(&mut self, chord: Chord, command: Command, repeat: Repeat) {
+        self.shortcuts.push(Shortcut { chord, command, repeat });
+    }
+
+    /// The commands whose shortcut was triggered this frame, in the order they were bound
+    pub fn resolve(&self, input: &Input) -> Vec<Command> {
+        self.shortcuts
+            .iter()
+            .filter(|shortcut| shortcut.chord.matches(input, shortcut.repeat))
+            .map(|shortcut| shortcut.command)
+            .collect()
+    }
+
+    /// The chord bound to the command, if any, e.g. to show it in hints
+    pub fn chord(&self, command: Command) -> Option<Chord> {
+        self.shortcuts
+            .iter()
+            .find(|shortcut| shortcut.command == command)
+            .map(|shortcut| shortcut.chord)
+    }
+}
+
+impl FromStr for Chord {
+    type Err = String;
+
+    /// Parses chords written like "Ctrl+Shift+E", in any case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|key| !key.is_empty()).ok_or("expected a key")?;
+        let key = parse_key(key).ok_or_else(|| format!("unknown key {:?}", key))?;
+
+        let mut chord = Chord::key(key);
+        for modifier in parts {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" => chord.ctrl = true,
+                "shift" => chord.shift = true,
+                "alt" => chord.alt = true,
+                _ => return Err(format!("unknown modifier {:?}", modifier)),
+            }
+        }
+        Ok(chord)
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (held, name) in [(self.ctrl, "Ctrl+"), (self.shift, "Shift+"), (self.alt, "Alt+")] {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        write!(f, "{:?}", self.key)
+    }
+}
+
+/// Parses a key by its minifb name, e.g. "E", "Enter" or "F5", in any case
+fn parse_key(name: &str) -> Option<Key> {
+    let name = name.to_ascii_lowercase();
+    let named = [
+        Key::Escape, Key::Enter, Key::Space, Key::Tab, Key::Backspace, Key::Delete, Key::Insert,
+        Key::Home, Key::End, Key::PageUp, Key::PageDown, Key::Up, Key::Down, Key::Left, Key::Right,
+    ];
+    let letters = [
+        Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K,
+        Key::L, Key::M, Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V,
+        Key::W, Key::X, Key::Y, Key::Z,
+    ];
+    let digits = [
+        Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7,
+        Key::Key8, Key::Key9,
+    ];
+    let functions = [
+        Key::F1, Key::F2, Key::F3, Key::F4, Key::F5, Key::F6, Key::F7, Key::F8, Key::F9, Key::F10,
+        Key::F11, Key::F12,
+    ];
+
+    // Digits are written without minifb's "Key" prefix
+    let digit = name.parse::<usize>().ok().and_then(|digit| digits.get(digit).copied());
+    digit.or_else(|| {
+        named
+            .into_iter()
+            .chain(letters)
+            .chain(functions)
+            .find(|key| format!("{:?}", key).to_ascii_lowercase() == name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::InputEvent;
+
+    fn press(keys: &[Key]) -> Input {
+        let mut input = Input::default();
+        for key in keys {
+            input.apply(InputEvent::KeyDown(*key));
+        }
+        input
+    }
+
+    #[test]
+    fn test_resolve_exact_modifiers() {
+        let shortcuts = Shortcuts::default();
+        assert_eq!(shortcuts.resolve(&press(&[Key::LeftCtrl, Key::R])), vec![Command::Reset]);
+        assert_eq!(shortcuts.resolve(&press(&[Key::R])), vec![]);
+        // Another modifier held makes it another chord
+        assert_eq!(shortcuts.resolve(&press(&[Key::LeftCtrl, Key::LeftShift, Key::R])), vec![]);
+        assert_eq!(shortcuts.resolve(&press(&[Key::Enter])), vec![Command::Animate]);
+        assert_eq!(shortcuts.resolve(&press(&[Key::LeftCtrl, Key::LeftShift, Key::Z])), vec![Command::Redo]);
+        // An arrow steps through the animation or nudges the selected point, whichever applies
+        assert_eq!(shortcuts.resolve(&press(&[Key::Right])), vec![Command::StepForward, Command::Nudge { x: 1, y: 0 }]);
+        assert_eq!(shortcuts.resolve(&press(&[Key::LeftShift, Key::Up])), vec![Command::Nudge { x: 0, y: -10 }]);
+    }
+
+    #[test]
+    fn test_repeat_policy() {
+        let mut shortcuts = Shortcuts::default();
+        let mut input = press(&[Key::Delete]);
+        input.end_frame();
+        // The host sends the key again while it is held
+        input.apply(InputEvent::KeyDown(Key::Delete));
+        assert_eq!(shortcuts.resolve(&input), vec![]);
+
+        shortcuts.bind(Chord::key(Key::Delete), Command::DismissToast, Repeat::Yes);
+        assert_eq!(shortcuts.resolve(&input), vec![Command::DismissToast]);
+    }
+
+    #[test]
+    fn test_parse_chord() {
+        let chord: Chord = "Ctrl+Shift+E".parse().unwrap();
+        assert_eq!(chord, Chord { key: Key::E, ctrl: true, shift: true, alt: false });
+        assert_eq!(chord.to_string(), "Ctrl+Shift+E");
+        assert_eq!("alt + f5".parse::<Chord>().unwrap(), Chord { alt: true, ..Chord::key(Key::F5) });
+        assert_eq!("3".parse::<Chord>().unwrap(), Chord::key(Key::Key3));
+
+        assert!("Ctrl+".parse::<Chord>().is_err());
+        assert!("Hyper+E".parse::<Chord>().is_err());
+        assert!("Ctrl+Nope".parse::<Chord>().is_err());
+    }
+
+    #[test]
+    fn test_rebind() {
+        let mut shortcuts = Shortcuts::default();
+        shortcuts.bind(Chord::ctrl(Key::N), Command::Reset, Repeat::No);
+        assert_eq!(shortcuts.resolve(&press(&[Key::LeftCtrl, Key::N])), vec![Command::Reset]);
+        assert_eq!(shortcuts.chord(Command::Reset), Some(Chord::ctrl(Key::R)));
+        assert_eq!(shortcuts.chord(Command::Animate), Some(Chord::key(Key::Enter)));
+    }
+}
@@ -0,0 +1,238 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::glyph::Glyph;
+
+/// The colors the canvas is drawn with, as 0x00RRGGBB, and how lines are blended
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    /// The control points
+    pub point: u32,
+    /// The shape of the control points
+    pub point_glyph: Glyph,
+    /// The control polygon and the smoothed curve
+    pub line: u32,
+    /// The control polygon, drawn faintly under the curve while animating
+    pub polygon: u32,
+    /// What the canvas is filled with before drawing
+    pub background: u32,
+    /// The opacity of the lines, between 0 and 1
+    pub stroke_opacity: f32,
+    /// How wide the lines are, in pixels, within [`STROKE_WIDTH_RANGE`]
+    pub stroke_width: f32,
+    /// How the lines are combined with what is under them
+    pub blend_mode: BlendMode,
+    /// The instance of the font text is written in
+    pub text_font: FontAxes,
+    /// The instance of the font titles are written in, e.g. the headings of panels
+    pub title_font: FontAxes,
+}
+
+/// The widths the lines can be drawn at, in pixels. One pixel wide lines are hard to see on
+/// high-density displays
+pub const STROKE_WIDTH_RANGE: (f32, f32) = (1.0, 5.0);
+
+/// The weights the bundled font goes from and to, thin to black
+pub const WEIGHT_RANGE: (f32, f32) = (100.0, 900.0);
+/// The widths the bundled font goes from and to, in percent of its normal width
+pub const WIDTH_RANGE: (f32, f32) = (75.0, 100.0);
+/// How much heavier bold text is than the text around it
+const BOLDER: f32 = 300.0;
+
+/// Where on its axes an instance of the bundled variable font is, written "weight[,width]",
+/// e.g. "600" or "700,87.5"
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontAxes {
+    /// From 100 (thin) to 900 (black), 400 being regular and 700 bold
+    pub weight: f32,
+    /// From 75 (condensed) to 100 (normal), in percent
+    pub width: f32,
+}
+
+impl FontAxes {
+    pub const REGULAR: FontAxes = FontAxes { weight: 400.0, width: 100.0 };
+    pub const SEMIBOLD: FontAxes = FontAxes { weight: 600.0, width: 100.0 };
+
+    /// The same instance, heavier, for bold text
+    pub fn bolder(self) -> Self {
+        Self { weight: (self.weight + BOLDER).min(WEIGHT_RANGE.1), ..self }
+    }
+}
+
+/// How a stroke is combined with the pixels under it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    /// The stroke covers what is under it, in proportion to its opacity
+    Normal,
+    /// The stroke is added to what is under it, so overlapping strokes get brighter.
+    /// This looks best on dark backgrounds
+    Additive,
+}
+
+/// One of the colors of a [`Theme`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThemeColor {
+    Point,
+    Line,
+    Polygon,
+    Background,
+}
+
+impl Default for Theme {
+    /// Red points and blue-green lines on black, with a grey control polygon
+    fn default() -> Self {
+        Self {
+            point: 0x00FF5555,
+            point_glyph: Glyph::Circle,
+            line: 0x0055CCAA,
+            polygon: 0x00AAAAAA,
+            background: 0x00000000,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            blend_mode: BlendMode::Normal,
+            text_font: FontAxes::REGULAR,
+            title_font: FontAxes::SEMIBOLD,
+        }
+    }
+}
+
+impl Theme {
+    pub fn get(&self, color: ThemeColor) -> u32 {
+        match color {
+            ThemeColor::Point => self.point,
+            ThemeColor::Line => self.line,
+            ThemeColor::Polygon => self.polygon,
+            ThemeColor::Background => self.background,
+        }
+    }
+
+    /// The theme with the lines a pixel wider, back to the thinnest after the widest
+    pub fn with_next_stroke_width(self) -> Self {
+        let (min, max) = STROKE_WIDTH_RANGE;
+        let stroke_width = if self.stroke_width + 1.0 > max { min } else { (self.stroke_width + 1.0).floor() };
+        Self { stroke_width, ..self }
+    }
+
+    pub fn set(&mut self, color: ThemeColor, value: u32) {
+        match color {
+            ThemeColor::Point => self.point = value,
+            ThemeColor::Line => self.line = value,
+            ThemeColor::Polygon => self.polygon = value,
+            ThemeColor::Background => self.background = value,
+        }
+    }
+}
+
+impl ThemeColor {
+    /// The color after this one, wrapping around
+    pub fn next(self) -> Self {
+        match self {
+            ThemeColor::Point => ThemeColor::Line,
+            ThemeColor::Line => ThemeColor::Polygon,
+            ThemeColor::Polygon => ThemeColor::Background,
+            ThemeColor::Background => ThemeColor::Point,
+        }
+    }
+
+    /// The name shown to the user
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeColor::Point => "Points",
+            ThemeColor::Line => "Lines",
+            ThemeColor::Polygon => "Control polygon",
+            ThemeColor::Background => "Background",
+        }
+    }
+}
+
+impl BlendMode {
+    /// Blends the color over the background, with the given opacity
+    pub fn blend(self, color: u32, background: u32, alpha: f32) -> u32 {
+        let channel = |c: u32, shift: u32| ((c >> shift) & 0xFF) as f32;
+        let mix = |shift: u32| {
+            let (c, bg) = (channel(color, shift), channel(background, shift));
+            let mixed = match self {
+                BlendMode::Normal => c * alpha + bg * (1.0 - alpha),
+                BlendMode::Additive => bg + c * alpha,
+            };
+            (mixed as u32).min(0xFF) << shift
+        };
+        mix(16) | mix(8) | mix(0)
+    }
+}
+
+impl FromStr for BlendMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(BlendMode::Normal),
+            "additive" => Ok(BlendMode::Additive),
+            _ => Err(format!("unknown blend mode {:?}, expected normal or additive", s)),
+        }
+    }
+}
+
+impl fmt::Display for BlendMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            BlendMode::Normal => "normal",
+            BlendMode::Additive => "additive",
+        })
+    }
+}
+
+impl FromStr for FontAxes {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (weight, width) = s.split_once(',').unwrap_or((s, "100"));
+        let parse = |value: &str, name: &str, (min, max): (f32, f32)| match value.trim().parse::<f32>() {
+            Ok(value) if (min..=max).contains(&value) => Ok(value),
+            _ => Err(format!("the {} must be a number from {} to {}, not {:?}", name, min, max, value)),
+        };
+        Ok(FontAxes { weight: parse(weight, "weight", WEIGHT_RANGE)?, width: parse(width, "width", WIDTH_RANGE)? })
+    }
+}
+
+impl fmt::Display for FontAxes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.weight, self.width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blend() {
+        assert_eq!(BlendMode::Normal.blend(0x00FF0000, 0x000000FF, 0.5), 0x007F007F);
+        assert_eq!(BlendMode::Normal.blend(0x00FF0000, 0x000000FF, 1.0), 0x00FF0000);
+        // Additive blending saturates instead of covering
+        assert_eq!(BlendMode::Additive.blend(0x00808080, 0x00C00010, 1.0), 0x00FF8090);
+        assert_eq!(BlendMode::Additive.blend(0x00FF0000, 0x000000FF, 0.5), 0x007F00FF);
+    }
+
+    #[test]
+    fn test_font_axes() {
+        assert_eq!("600".parse(), Ok(FontAxes::SEMIBOLD));
+        assert_eq!("700, 87.5".parse(), Ok(FontAxes { weight: 700.0, width: 87.5 }));
+        assert!("1000".parse::<FontAxes>().is_err());
+        assert!("400,50".parse::<FontAxes>().is_err());
+        assert!("bold".parse::<FontAxes>().is_err());
+        assert_eq!(FontAxes::REGULAR.to_string().parse(), Ok(FontAxes::REGULAR));
+        assert_eq!(FontAxes::SEMIBOLD.bolder().weight, 900.0);
+    }
+
+    #[test]
+    fn test_stroke_widths() {
+        let widths: Vec<f32> = std::iter::successors(Some(Theme::default()), |theme| Some(theme.with_next_stroke_width()))
+            .map(|theme| theme.stroke_width)
+            .take(6)
+            .collect();
+        assert_eq!(widths, [1.0, 2.0, 3.0, 4.0, 5.0, 1.0]);
+        let theme = Theme { stroke_width: 2.5, ..Theme::default() };
+        assert_eq!(theme.with_next_stroke_width().stroke_width, 3.0);
+    }
+}
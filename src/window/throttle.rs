@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+
+/// Limits how often an expensive value, such as a preview of the curve, is recomputed while
+/// its input keeps changing, e.g. on every mouse move.
+///
+/// The value is recomputed when its input changes, but at most once per interval: in
+/// between, the last value is kept. Once the input stops changing, the value catches up
+/// with it an interval later at the most
+pub struct Throttle<K, V> {
+    interval: Duration,
+    /// The input the value was computed from, the value and when it was computed
+    cached: Option<(K, V, Instant)>,
+}
+
+impl<K: PartialEq, V> Throttle<K, V> {
+    pub fn new(interval: Duration) -> Self {
+        Self { interval, cached: None }
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// The value for the input, computed again if the input changed and the interval has
+    /// passed since the last time, or the last value otherwise
+    pub fn get(&mut self, input: K, now: Instant, compute: impl FnOnce(&K) -> V) -> &V {
+        let stale = match &self.cached {
+            None => true,
+            Some((cached, _, computed)) => {
+                *cached != input && now.duration_since(*computed) >= self.interval
+            }
+        };
+        if stale {
+            let value = compute(&input);
+            self.cached = Some((input, value, now));
+        }
+        &self.cached.as_ref().expect("computed above").1
+    }
+
+    /// Forgets the value, e.g. when the way it's computed changes
+    pub fn clear(&mut self) {
+        self.cached = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle() {
+        let mut throttle = Throttle::new(Duration::from_millis(50));
+        let now = Instant::now();
+        let at = |ms: u64| now + Duration::from_millis(ms);
+        let mut computed = 0;
+        let mut get = |throttle: &mut Throttle<u32, u32>, input: u32, ms: u64| {
+            *throttle.get(input, at(ms), |input| {
+                computed += 1;
+                input * 10
+            })
+        };
+
+        assert_eq!(get(&mut throttle, 1, 0), 10);
+        // Moving again right away keeps the last value
+        assert_eq!(get(&mut throttle, 2, 10), 10);
+        assert_eq!(get(&mut throttle, 3, 20), 10);
+        // It catches up once the interval has passed, even if the input settled
+        assert_eq!(get(&mut throttle, 3, 60), 30);
+        assert_eq!(get(&mut throttle, 3, 500), 30);
+
+        throttle.clear();
+        assert_eq!(get(&mut throttle, 3, 510), 30);
+        assert_eq!(computed, 3);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::types::Point;
+
+use super::overlay::OverlayKind;
+
+/// How many points the tutorial asks for, enough for Chaikin's scheme to show a curve
+const TUTORIAL_POINTS: usize = 3;
+/// How far the hints are from what they point at
+const HINT_OFFSET: f32 = 40.0;
+
+/// One thing the tutorial teaches, in the order it teaches them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lesson {
+    PlacePoints,
+    Animate,
+    ChangeSpeed,
+    Export,
+}
+
+/// Walks a new user through the canvas, one lesson at a time. A lesson is over once the
+/// user did what it asks
+pub struct Tutorial {
+    /// The lesson being taught, or none once they are all over
+    lesson: Option<Lesson>,
+}
+
+impl Lesson {
+    /// The lesson after this one, if there is one
+    fn next(self) -> Option<Lesson> {
+        match self {
+            Lesson::PlacePoints => Some(Lesson::Animate),
+            Lesson::Animate => Some(Lesson::ChangeSpeed),
+            Lesson::ChangeSpeed => Some(Lesson::Export),
+            Lesson::Export => None,
+        }
+    }
+
+    /// What the user is asked to do, as styled text with the keys to press
+    pub fn hint(self) -> String {
+        match self {
+            Lesson::PlacePoints => format!("**1/4** Click to place {} points", TUTORIAL_POINTS),
+            Lesson::Animate => "**2/4** Press [Enter] to smooth them".to_string(),
+            Lesson::ChangeSpeed => "**3/4** Press [+] or [-] to change the speed".to_string(),
+            Lesson::Export => "**4/4** Press [Ctrl+S] to export the curve".to_string(),
+        }
+    }
+
+    /// Whether the points are enough to go on, for the lesson that asks for them
+    pub fn has_enough_points(points: &[Point]) -> bool {
+        points.len() >= TUTORIAL_POINTS
+    }
+
+    /// The overlays that show the hint, next to what it is about: the last point once there
+    /// is one to animate, or the top of the canvas
+    pub fn overlays(self, points: &[Point], width: usize) -> Vec<OverlayKind> {
+        let text = self.hint();
+        let top = Point::new(width as f32 / 2.0 - 120.0, 20.0);
+        match (self, points.last()) {
+            (Lesson::Animate, Some(last)) => {
+                // Above the point, or below it when it is near the top
+                let above = last.y > HINT_OFFSET * 2.0;
+                let dy = if above { -HINT_OFFSET } else { HINT_OFFSET };
+                let position = Point::new(last.x + HINT_OFFSET / 2.0, last.y + dy);
+                let tip = Point::new(last.x, last.y + dy.signum() * 8.0);
+                vec![
+                    OverlayKind::Arrow { from: position, to: tip },
+                    OverlayKind::Label { position, text },
+                ]
+            }
+            _ => vec![OverlayKind::Label { position: top, text }],
+        }
+    }
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Self { lesson: Some(Lesson::PlacePoints) }
+    }
+}
+
+impl Tutorial {
+    /// The lesson being taught, or none once they are all over
+    pub fn lesson(&self) -> Option<Lesson> {
+        self.lesson
+    }
+
+    /// Ends the lesson if it is the one being taught, returning whether it was
+    pub fn complete(&mut self, lesson: Lesson) -> bool {
+        if self.lesson != Some(lesson) {
+            return false;
+        }
+        self.lesson = lesson.next();
+        true
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.lesson.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lessons_in_order() {
+        let mut tutorial = Tutorial::default();
+        // Doing something ahead of time doesn't skip lessons
+        assert!(!tutorial.complete(Lesson::Export));
+        for lesson in [Lesson::PlacePoints, Lesson::Animate, Lesson::ChangeSpeed, Lesson::Export] {
+            assert_eq!(tutorial.lesson(), Some(lesson));
+            assert!(tutorial.complete(lesson));
+        }
+        assert!(tutorial.is_over());
+    }
+
+    #[test]
+    fn test_hint_next_to_last_point() {
+        let points = [Point::new(100.0, 20.0)];
+        let overlays = Lesson::Animate.overlays(&points, 800);
+        // Near the top, the hint goes below the point
+        let Some(OverlayKind::Label { position, .. }) = overlays.last() else {
+            panic!("expected a label, got {:?}", overlays);
+        };
+        assert!(position.y > 20.0);
+        assert!(matches!(overlays[0], OverlayKind::Arrow { .. }));
+
+        assert_eq!(Lesson::Export.overlays(&points, 800).len(), 1);
+    }
+}
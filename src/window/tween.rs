@@ -0,0 +1,140 @@
+use crate::algorithm::resample_by_arclength;
+use crate::types::Point;
+
+/// The polyline part of the way from one polyline to another, from `from` at 0 to `to` at 1,
+/// e.g. to morph a step of the animation into the next one. Both are resampled to the same
+/// number of points, evenly along their points, and blended point by point. The count is
+/// chosen so that every point of `from` is kept, which makes 0 look exactly like it. Closed
+/// polylines are morphed along the segments back to their first points too
+pub fn between(from: &[Point], to: &[Point], t: f32, closed: bool) -> Vec<Point> {
+    if closed && from.len() >= 2 && to.len() >= 2 {
+        return open_loop(between(&closing(from), &closing(to), t, false));
+    }
+    let (n, m) = (from.len(), to.len());
+    if n < 2 || m < 2 {
+        return if t < 1.0 { from.to_vec() } else { to.to_vec() };
+    }
+
+    let count = (n - 1) * (m - 1).div_ceil(n - 1) + 1;
+    resample(from, count)
+        .into_iter()
+        .zip(resample(to, count))
+        .map(|(a, b)| a + (b - a) * t)
+        .collect()
+}
+
+/// Like [`between`], but with both polylines resampled evenly along their length, to as many
+/// points as the longer one has, so that the points keep the same spacing all along the
+/// morph rather than bunching up at the corners. 0 then only looks like `from` up to the
+/// corners its points cut. Closed polylines are resampled along their whole loops, the
+/// segments back to their first points included
+pub fn between_by_arclength(from: &[Point], to: &[Point], t: f32, closed: bool) -> Vec<Point> {
+    if closed && from.len() >= 2 && to.len() >= 2 {
+        return open_loop(between_by_arclength(&closing(from), &closing(to), t, false));
+    }
+    let count = from.len().max(to.len());
+    if from.len() < 2 || to.len() < 2 {
+        return if t < 1.0 { from.to_vec() } else { to.to_vec() };
+    }
+
+    resample_by_arclength(from, count)
+        .into_iter()
+        .zip(resample_by_arclength(to, count))
+        .map(|(a, b)| a + (b - a) * t)
+        .collect()
+}
+
+/// The closed polyline as an open one, back to its first point
+fn closing(points: &[Point]) -> Vec<Point> {
+    let mut closing = points.to_vec();
+    closing.extend(points.first());
+    closing
+}
+
+/// The open polyline a closed one was morphed as, see [`closing`], without the point it
+/// ends on, which is its first one
+fn open_loop(mut points: Vec<Point>) -> Vec<Point> {
+    points.pop();
+    points
+}
+
+/// The polyline with `count` points, spread evenly along the points of the given one rather
+/// than along its length. Needs at least two points and a count of at least two
+fn resample(points: &[Point], count: usize) -> Vec<Point> {
+    let (segments, intervals) = (points.len() - 1, count - 1);
+    (0..count)
+        .map(|k| {
+            // Integers keep the points that land exactly on one in place
+            let position = k * segments;
+            let (i, remainder) = (position / intervals, position % intervals);
+            if remainder == 0 {
+                return points[i];
+            }
+            let t = remainder as f32 / intervals as f32;
+            points[i] + (points[i + 1] - points[i]) * t
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_between() {
+        let from = [Point::new(0.0, 0.0), Point::new(10.0, 10.0), Point::new(20.0, 0.0)];
+        let to = [Point::new(0.0, 0.0), Point::new(5.0, 0.0), Point::new(10.0, 0.0), Point::new(15.0, 0.0), Point::new(20.0, 0.0)];
+
+        // Every point of the first polyline is kept, with the others on its segments
+        let start = between(&from, &to, 0.0, false);
+        assert_eq!(start.len(), 5);
+        assert_eq!(start[0], from[0]);
+        assert_eq!(start[2], from[1]);
+        assert_eq!(start[4], from[2]);
+        assert_eq!(start[1], Point::new(5.0, 5.0));
+
+        assert_eq!(between(&from, &to, 1.0, false), to);
+        assert_eq!(between(&from, &to, 0.5, false)[2], Point::new(10.0, 5.0));
+
+        // Closed, the triangle's points are kept, with others on the segment back to the first
+        let loop_start = between(&from, &to[..4], 0.0, true);
+        assert_eq!(loop_start.len(), 6);
+        assert_eq!([loop_start[0], loop_start[2], loop_start[4]], from);
+        assert_eq!(loop_start[5], Point::new(10.0, 0.0));
+    }
+
+    #[test]
+    fn test_between_by_arclength() {
+        // A long side of two points and a short one of three, then a straight line
+        let from = [Point::new(0.0, 0.0), Point::new(8.0, 0.0), Point::new(8.0, 2.0), Point::new(8.0, 4.0)];
+        let to = [Point::new(0.0, 0.0), Point::new(12.0, 0.0)];
+
+        let start = between_by_arclength(&from, &to, 0.0, false);
+        assert_eq!(start, vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(8.0, 0.0), Point::new(8.0, 4.0)]);
+        assert_eq!(between_by_arclength(&from, &to, 1.0, false), vec![
+            Point::new(0.0, 0.0),
+            Point::new(4.0, 0.0),
+            Point::new(8.0, 0.0),
+            Point::new(12.0, 0.0),
+        ]);
+        assert_eq!(between_by_arclength(&from[..1], &to, 0.5, false), from[..1]);
+
+        // Closed, the points are as far apart around the whole loop
+        let square = [Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(4.0, 4.0), Point::new(0.0, 4.0)];
+        let corners = [Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(4.0, 2.0), Point::new(4.0, 4.0), Point::new(0.0, 4.0)];
+        let start = between_by_arclength(&corners, &square, 0.0, true);
+        let expected = [Point::new(0.0, 0.0), Point::new(3.2, 0.0), Point::new(4.0, 2.4), Point::new(2.4, 4.0), Point::new(0.0, 3.2)];
+        assert_eq!(start.len(), expected.len());
+        assert!(start.iter().zip(expected).all(|(point, expected)| (point - expected).norm() < 1e-4), "{:?}", start);
+    }
+
+    #[test]
+    fn test_between_too_few_points() {
+        let point = [Point::new(1.0, 2.0)];
+        let line = [Point::new(0.0, 0.0), Point::new(4.0, 0.0)];
+        assert_eq!(between(&point, &line, 0.5, false), point);
+        assert_eq!(between(&point, &line, 1.0, false), line);
+        assert_eq!(between(&point, &line, 0.5, true), point);
+        assert!(between_by_arclength(&[], &line, 0.5, true).is_empty());
+    }
+}
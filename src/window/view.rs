@@ -0,0 +1,174 @@
+use nalgebra::Vector2;
+
+use crate::types::Point;
+
+/// The smallest part of the canvas, across, that a shape can be drawn over and still be
+/// worked on comfortably
+const LEGIBLE_FRACTION: f32 = 0.1;
+/// How far out and in the view can be zoomed, in pixels per unit. Views fitted to points in
+/// other units can be further, and are only zoomed back toward these
+const SCALE_RANGE: (f32, f32) = (0.05, 64.0);
+
+/// How the points are placed on the canvas: scaled about the origin, then moved. The points
+/// keep their own coordinates, only the drawing and the mouse go through the view
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+    /// Pixels per unit of the points' coordinates
+    pub scale: f32,
+    /// Where the origin of the points is drawn, in pixels
+    pub offset: Vector2<f32>,
+}
+
+impl Default for View {
+    /// The points' coordinates are the canvas' pixels
+    fn default() -> Self {
+        Self { scale: 1.0, offset: Vector2::zeros() }
+    }
+}
+
+impl View {
+    /// Where the point is drawn on the canvas
+    pub fn screen_point(&self, point: Point) -> Point {
+        point * self.scale + self.offset
+    }
+
+    /// The point drawn at the given pixel, e.g. under the mouse
+    pub fn point_at_pixel(&self, x: f32, y: f32) -> Point {
+        (Point::new(x, y) - self.offset) / self.scale
+    }
+
+    /// Scales the view by the factor about the given pixel, which stays over the same point,
+    /// e.g. the one under the mouse
+    pub fn zoom_at(&mut self, factor: f32, (x, y): (f32, f32)) {
+        let anchor = self.point_at_pixel(x, y);
+        let (min, max) = (SCALE_RANGE.0.min(self.scale), SCALE_RANGE.1.max(self.scale));
+        self.scale = (self.scale * factor).clamp(min, max);
+        self.offset = Vector2::new(x, y) - anchor.coords * self.scale;
+    }
+
+    /// Moves the view by the given number of pixels
+    pub fn pan(&mut self, (dx, dy): (f32, f32)) {
+        self.offset += Vector2::new(dx, dy);
+    }
+
+    /// Whether all the points are drawn inside a canvas of the given size, over at least a
+    /// tenth of it across. Points from other tools, e.g. GIS or CAD exports, often aren't
+    pub fn shows(&self, points: &[Point], (width, height): (usize, usize)) -> bool {
+        let Some((min, max)) = bounds(points) else {
+            return true;
+        };
+        let (min, max) = (self.screen_point(min), self.screen_point(max));
+        let inside = min.x >= 0.0 && min.y >= 0.0 && max.x <= width as f32 && max.y <= height as f32;
+        let across = ((max.x - min.x) / width as f32).max((max.y - min.y) / height as f32);
+        inside && (points.len() < 2 || across >= LEGIBLE_FRACTION)
+    }
+
+    /// The view that shows all the points as large as possible in a canvas of the given
+    /// size, with at least `padding` pixels around them, or none if there are no points.
+    /// A lone point, or points on a line, are only scaled to fit along the sides they span
+    pub fn fit(points: &[Point], (width, height): (usize, usize), padding: f32) -> Option<Self> {
+        let (min, max) = bounds(points)?;
+        let (room_x, room_y) = ((width as f32 - 2.0 * padding).max(1.0), (height as f32 - 2.0 * padding).max(1.0));
+        let size = max - min;
+        let scale = [(room_x, size.x), (room_y, size.y)]
+            .into_iter()
+            .filter(|(_, extent)| *extent > f32::EPSILON)
+            .map(|(room, extent)| room / extent)
+            .reduce(f32::min)
+            .unwrap_or(1.0);
+
+        let center = nalgebra::center(&min, &max);
+        let screen_center = Vector2::new(width as f32 / 2.0, height as f32 / 2.0);
+        Some(Self { scale, offset: screen_center - center.coords * scale })
+    }
+
+    /// How far to move the points, in their own coordinates, for their bounding box to be
+    /// drawn in the middle of a canvas of the given size, by whole pixels so that they stay
+    /// as sharp, or none if there are no points
+    pub fn centering(&self, points: &[Point], (width, height): (usize, usize)) -> Option<Vector2<f32>> {
+        let (min, max) = bounds(points)?;
+        let center = self.screen_point(nalgebra::center(&min, &max));
+        let pixels = (Point::new(width as f32 / 2.0, height as f32 / 2.0) - center).map(f32::round);
+        Some(pixels / self.scale)
+    }
+}
+
+
+/// The corners of the smallest box around the points, or none if there are no points
+fn bounds(points: &[Point]) -> Option<(Point, Point)> {
+    let first = *points.first()?;
+    Some(points.iter().fold((first, first), |(min, max), point| {
+        (Point::new(min.x.min(point.x), min.y.min(point.y)), Point::new(max.x.max(point.x), max.y.max(point.y)))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        let points = [Point::new(1000.0, 2000.0), Point::new(1100.0, 2050.0)];
+        let view = View::fit(&points, (800, 600), 50.0).unwrap();
+        // The width is what limits the scale
+        assert_eq!(view.scale, 7.0);
+        assert_eq!(view.screen_point(points[0]), Point::new(50.0, 125.0));
+        assert_eq!(view.screen_point(points[1]), Point::new(750.0, 475.0));
+        assert_eq!(view.point_at_pixel(50.0, 125.0), points[0]);
+
+        // A lone point is only moved to the middle
+        let view = View::fit(&points[..1], (800, 600), 50.0).unwrap();
+        assert_eq!(view.screen_point(points[0]), Point::new(400.0, 300.0));
+        assert!(View::fit(&[], (800, 600), 50.0).is_none());
+    }
+
+    #[test]
+    fn test_centering() {
+        let points = [Point::new(10.0, 10.0), Point::new(30.0, 20.0)];
+        assert_eq!(View::default().centering(&points, (100, 100)), Some(Vector2::new(30.0, 35.0)));
+
+        // Zoomed and panned, the points are moved to the middle of what is drawn
+        let view = View { scale: 2.0, offset: Vector2::new(-20.0, 10.0) };
+        let offset = view.centering(&points, (100, 100)).unwrap();
+        let center = view.screen_point(nalgebra::center(&points[0], &points[1]) + offset);
+        assert_eq!(center, Point::new(50.0, 50.0));
+        assert_eq!(view.centering(&[], (100, 100)), None);
+    }
+
+    #[test]
+    fn test_shows() {
+        let view = View::default();
+        let drawn = [Point::new(100.0, 100.0), Point::new(300.0, 200.0)];
+        assert!(view.shows(&drawn, (800, 600)));
+        assert!(view.shows(&drawn[..1], (800, 600)));
+        // Outside the canvas, or in a corner of it
+        assert!(!view.shows(&[Point::new(100.0, 100.0), Point::new(900.0, 200.0)], (800, 600)));
+        assert!(!view.shows(&[Point::new(0.1, 0.2), Point::new(0.9, 0.5)], (800, 600)));
+
+        let fitted = View::fit(&[Point::new(0.1, 0.2), Point::new(0.9, 0.5)], (800, 600), 40.0).unwrap();
+        assert!(fitted.shows(&[Point::new(0.1, 0.2), Point::new(0.9, 0.5)], (800, 600)));
+    }
+
+    #[test]
+    fn test_zoom_and_pan() {
+        let mut view = View::default();
+        // The point under the mouse stays there
+        view.zoom_at(2.0, (100.0, 50.0));
+        assert_eq!(view.scale, 2.0);
+        assert_eq!(view.screen_point(Point::new(100.0, 50.0)), Point::new(100.0, 50.0));
+        assert_eq!(view.screen_point(Point::new(0.0, 0.0)), Point::new(-100.0, -50.0));
+
+        view.pan((30.0, -10.0));
+        assert_eq!(view.point_at_pixel(130.0, 40.0), Point::new(100.0, 50.0));
+
+        view.zoom_at(1000.0, (0.0, 0.0));
+        assert_eq!(view.scale, SCALE_RANGE.1);
+        // Fitted beyond the range, the view can still zoom back toward it
+        let mut fitted = View { scale: 1e5, ..View::default() };
+        fitted.zoom_at(0.5, (0.0, 0.0));
+        assert_eq!(fitted.scale, 5e4);
+        // But not further away from it
+        fitted.zoom_at(4.0, (0.0, 0.0));
+        assert_eq!(fitted.scale, 5e4);
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+/// A computation running on a background thread, so that the window keeps drawing and
+/// handling input while it runs
+pub struct Job<T> {
+    result: Receiver<T>,
+    cancel: CancelToken,
+    progress: Progress,
+    /// When the job was started, e.g. to animate a spinner
+    pub started: Instant,
+}
+
+/// Tells a job that its result isn't wanted anymore. Jobs check it between the parts of
+/// their work, e.g. between steps, and give up as soon as they see it
+#[derive(Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// How much of its work a job has done, e.g. steps rendered or frames encoded, for a
+/// progress bar
+#[derive(Clone, Default)]
+pub struct Progress {
+    done: Arc<AtomicUsize>,
+    total: Arc<AtomicUsize>,
+}
+
+impl Progress {
+    /// Sets how much work there is in all, in whatever unit the job counts it
+    pub fn set_total(&self, total: usize) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Records that some more of the work is done
+    pub fn advance(&self, done: usize) {
+        self.done.fetch_add(done, Ordering::Relaxed);
+    }
+
+    /// The part of the work done, between 0 and 1, or none until the job says how much
+    /// there is
+    pub fn fraction(&self) -> Option<f32> {
+        let total = self.total.load(Ordering::Relaxed);
+        let done = self.done.load(Ordering::Relaxed);
+        (total > 0).then(|| (done as f32 / total as f32).min(1.0))
+    }
+}
+
+impl<T: Send + 'static> Job<T> {
+    /// Starts computing on a new thread. The computation reports its progress as it goes,
+    /// and returns none when it sees that it was cancelled, and its partial result is
+    /// discarded
+    pub fn spawn(compute: impl FnOnce(&CancelToken, &Progress) -> Option<T> + Send + 'static) -> Self {
+        let (sender, result) = mpsc::channel();
+        let (cancel, progress) = (CancelToken::default(), Progress::default());
+        let (token, reporter) = (cancel.clone(), progress.clone());
+        thread::spawn(move || {
+            if let Some(result) = compute(&token, &reporter) {
+                // The job may have been dropped in the meantime, then nobody wants the result
+                let _ = sender.send(result);
+            }
+        });
+        Self { result, cancel, progress, started: Instant::now() }
+    }
+}
+
+impl<T> Job<T> {
+    /// The result, once the job is done
+    ///
+    /// # Panics
+    ///
+    /// If the computation panicked
+    pub fn poll(&self) -> Option<T> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => panic!("a background job panicked"),
+        }
+    }
+
+    /// The part of the work done so far, if the job said how much there is
+    pub fn progress(&self) -> Option<f32> {
+        self.progress.fraction()
+    }
+
+    /// Stops the job as soon as it checks its token, discarding what it computed so far
+    pub fn cancel(self) {
+        self.cancel.cancel();
+    }
+}
+
+impl<T> Drop for Job<T> {
+    /// A job nobody holds anymore is of no use, e.g. one for a step that isn't shown anymore
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_job() {
+        let (go, wait) = mpsc::channel::<()>();
+        let job = Job::spawn(move |_, progress| {
+            progress.set_total(4);
+            progress.advance(1);
+            wait.recv().unwrap();
+            progress.advance(3);
+            Some(42)
+        });
+        while job.progress().is_none() {
+            thread::yield_now();
+        }
+        assert_eq!(job.progress(), Some(0.25));
+        assert_eq!(job.poll(), None);
+
+        go.send(()).unwrap();
+        let result = loop {
+            if let Some(result) = job.poll() {
+                break result;
+            }
+            thread::yield_now();
+        };
+        assert_eq!(result, 42);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let (stopped, wait) = mpsc::channel();
+        let job: Job<()> = Job::spawn(move |token, _| {
+            while !token.is_cancelled() {
+                thread::yield_now();
+            }
+            stopped.send(()).unwrap();
+            None
+        });
+        job.cancel();
+        assert!(wait.recv().is_ok());
+    }
+}